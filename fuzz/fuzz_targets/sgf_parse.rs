@@ -0,0 +1,16 @@
+#![no_main]
+
+use go3d::game::sgf;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    // Anything the parser accepts must survive a write/parse round trip
+    if let Ok(record) = sgf::parse(input) {
+        let written = sgf::write(&record);
+        assert_eq!(sgf::parse(&written).as_ref(), Ok(&record));
+    }
+});
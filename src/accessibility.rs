@@ -0,0 +1,402 @@
+// Screen-reader support: spoken announcements of moves, captures and the side
+// to move (an ARIA live region in browsers, the terminal plus text-to-speech
+// behind the `speech` feature on native), and a small text command language so
+// the game can be played without the 3D view.
+//
+// Points are written column letter, row number, layer: "d4-2" is x=3, y=3, z=1.
+use crate::game::{
+    format_thinking, Axis, GameRules, GameSession, MoveError, RecordError, SessionEvent, StoneColor, Symmetry, ViewState,
+};
+use std::fmt;
+
+type Point = (u8, u8, u8);
+
+pub const HELP: &str =
+    "Commands: play [B|W] d4-2, pass, undo, look d4-2, status, moves, back, forward, return, resume, rotate x|y|z, mirror x|y|z, help";
+
+pub fn color_name(color: StoneColor) -> &'static str {
+    color.name()
+}
+
+pub fn format_point((x, y, z): Point) -> String {
+    format!("{}{}-{}", (b'a' + x) as char, y as u32 + 1, z as u32 + 1)
+}
+
+pub fn parse_point(text: &str, board_size: usize) -> Result<Point, CommandError> {
+    let bad = || CommandError::BadPoint(text.to_string());
+    let text = text.to_ascii_lowercase();
+    let (column_row, layer) = text.split_once('-').ok_or_else(bad)?;
+
+    let mut chars = column_row.chars();
+    let column = chars.next().filter(char::is_ascii_lowercase).ok_or_else(bad)?;
+    let row: usize = chars.as_str().parse().map_err(|_| bad())?;
+    let layer: usize = layer.parse().map_err(|_| bad())?;
+
+    let x = (column as u8 - b'a') as usize;
+    let in_range = |n: usize| (1..=board_size).contains(&n);
+    if x >= board_size || !in_range(row) || !in_range(layer) {
+        return Err(CommandError::OffBoard(text.to_string()));
+    }
+    Ok((x as u8, (row - 1) as u8, (layer - 1) as u8))
+}
+
+// "Black to move, move 7. Captured: 2 black, 0 white."
+pub fn describe_status(rules: &GameRules) -> String {
+    let board = rules.board();
+    let captured: Vec<String> = StoneColor::players(rules.players())
+        .iter()
+        .map(|&color| format!("{} {}", board.get_captured(color), color_name(color).to_lowercase()))
+        .collect();
+    format!(
+        "{} to move, move {}. Captured: {}.",
+        color_name(rules.current_player()),
+        rules.moves().len() + 1,
+        captured.join(", "),
+    )
+}
+
+// "1. Black d4-2 (3.2 s)", one move per line
+pub fn describe_moves(rules: &GameRules) -> String {
+    if rules.moves().is_empty() {
+        return "No moves yet.".to_string();
+    }
+    let lines: Vec<String> = rules
+        .moves()
+        .iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let play = record.position.map_or_else(|| "passes".to_string(), format_point);
+            let mut line = format!("{}. {} {}", index + 1, color_name(record.color), play);
+            if let Some(thinking) = record.thinking {
+                line.push_str(&format!(" ({})", format_thinking(thinking)));
+            }
+            line
+        })
+        .collect();
+    lines.join("\n")
+}
+
+// "Now reviewing move 3 of 10. White to move, move 4. ..."
+fn describe_view(session: &GameSession) -> String {
+    let view = session.view();
+    format!("Now {}. {}", view, describe_status(&session.read()))
+}
+
+// Who has won outright and how, once the variant's victory condition is met
+pub fn describe_win(rules: &GameRules) -> Option<String> {
+    let winner = rules.winner()?;
+    let condition = rules.victory_condition()?;
+    Some(format!("{} {} and wins.", color_name(winner), condition.describe_win()))
+}
+
+// What a screen reader should say after `event`; `rules` is the state after it
+pub fn announce(event: &SessionEvent, rules: &GameRules) -> Option<String> {
+    let to_move = describe_win(rules).unwrap_or_else(|| format!("{} to move.", color_name(rules.current_player())));
+    let text = match event {
+        SessionEvent::MovePlayed { color, position, captured: 0 } => {
+            format!("{} {}. {}", color_name(*color), format_point(*position), to_move)
+        }
+        SessionEvent::MovePlayed { color, position, captured } => format!(
+            "{} {}, captures {} {}. {}",
+            color_name(*color),
+            format_point(*position),
+            captured,
+            if *captured == 1 { "stone" } else { "stones" },
+            to_move,
+        ),
+        SessionEvent::Passed { color } => format!("{} passes. {}", color_name(*color), to_move),
+        SessionEvent::Undone => format!("Move taken back. {}", to_move),
+        SessionEvent::Reset => format!("Board cleared. {}", to_move),
+        SessionEvent::Changed => return None,
+    };
+    Some(text)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    // The color is optional and only checked against the side to move
+    Play { color: Option<StoneColor>, point: Point },
+    Pass,
+    Undo,
+    Look(Point),
+    Status,
+    // The moves so far, with how long each took
+    Moves,
+    // Reviewing: step through the game, go back to it, or play on from the position shown
+    Back,
+    Forward,
+    Return,
+    Resume,
+    // Turns the whole position a quarter turn about the axis, or flips it
+    Transform(Symmetry),
+    Help,
+}
+
+impl Command {
+    pub fn parse(line: &str, board_size: usize) -> Result<Self, CommandError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((verb, args)) = words.split_first() else {
+            return Err(CommandError::Empty);
+        };
+
+        match (verb.to_ascii_lowercase().as_str(), args) {
+            ("play" | "p", [point]) => Ok(Command::Play {
+                color: None,
+                point: parse_point(point, board_size)?,
+            }),
+            ("play" | "p", [color, point]) => {
+                let color = match color.to_ascii_lowercase().as_str() {
+                    "b" | "black" => StoneColor::Black,
+                    "w" | "white" => StoneColor::White,
+                    "r" | "red" => StoneColor::Red,
+                    "g" | "green" => StoneColor::Green,
+                    _ => return Err(CommandError::Unknown(line.trim().to_string())),
+                };
+                Ok(Command::Play {
+                    color: Some(color),
+                    point: parse_point(point, board_size)?,
+                })
+            }
+            ("pass", []) => Ok(Command::Pass),
+            ("undo", []) => Ok(Command::Undo),
+            ("look" | "l", [point]) => Ok(Command::Look(parse_point(point, board_size)?)),
+            ("status" | "s", []) => Ok(Command::Status),
+            ("moves" | "m", []) => Ok(Command::Moves),
+            ("back", []) => Ok(Command::Back),
+            ("forward", []) => Ok(Command::Forward),
+            ("return", []) => Ok(Command::Return),
+            ("resume", []) => Ok(Command::Resume),
+            ("rotate" | "mirror", [axis]) => {
+                let axis: Axis = axis.parse().map_err(|_| CommandError::Unknown(line.trim().to_string()))?;
+                Ok(Command::Transform(if verb.eq_ignore_ascii_case("rotate") {
+                    Symmetry::Rotate(axis)
+                } else {
+                    Symmetry::Reflect(axis)
+                }))
+            }
+            ("help" | "?", []) => Ok(Command::Help),
+            _ => Err(CommandError::Unknown(line.trim().to_string())),
+        }
+    }
+
+    // Moves are announced through the session events, so only queries reply here
+    pub fn execute(&self, session: &GameSession) -> Result<Option<String>, CommandError> {
+        match *self {
+            Command::Play { color, point: (x, y, z) } => {
+                let to_move = session.read().current_player();
+                if color.is_some_and(|color| color != to_move) {
+                    return Err(CommandError::WrongTurn(to_move));
+                }
+                session.read().check_move(x, y, z).map_err(CommandError::Illegal)?;
+                session.make_move(x, y, z);
+                Ok(None)
+            }
+            Command::Pass => {
+                session.pass();
+                Ok(None)
+            }
+            Command::Undo => {
+                if !session.undo() {
+                    return Err(CommandError::NothingToUndo);
+                }
+                Ok(None)
+            }
+            Command::Look(point) => {
+                let stone = session.read().board().get_stone(point);
+                Ok(Some(match stone {
+                    Some(color) => format!("{}: {} stone", format_point(point), color_name(color)),
+                    None => format!("{}: empty", format_point(point)),
+                }))
+            }
+            Command::Status => Ok(Some(describe_status(&session.read()))),
+            Command::Moves => Ok(Some(describe_moves(&session.read()))),
+            Command::Back | Command::Forward => {
+                let moved = if *self == Command::Back {
+                    session.step_back()
+                } else {
+                    session.step_forward()
+                };
+                if !moved {
+                    return Err(CommandError::NoMoreMoves);
+                }
+                Ok(Some(describe_view(session)))
+            }
+            Command::Return | Command::Resume => {
+                let left = if *self == Command::Return {
+                    session.return_to_game()
+                } else {
+                    session.play_from_here()
+                };
+                if !left {
+                    return Err(CommandError::NotReviewing);
+                }
+                Ok(Some(describe_view(session)))
+            }
+            Command::Transform(symmetry) => {
+                // The game is rebuilt from its record, which the review would cut short
+                if session.view() != ViewState::Live {
+                    return Err(CommandError::Reviewing);
+                }
+                session.transform(symmetry).map_err(CommandError::NotSymmetric)?;
+                Ok(Some(format!("Position turned: {}", symmetry)))
+            }
+            Command::Help => Ok(Some(HELP.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    Empty,
+    Unknown(String),
+    BadPoint(String),
+    OffBoard(String),
+    WrongTurn(StoneColor),
+    Illegal(MoveError),
+    NothingToUndo,
+    NoMoreMoves,
+    NotReviewing,
+    Reviewing,
+    NotSymmetric(RecordError),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Empty => f.write_str("no command given"),
+            CommandError::Unknown(line) => write!(f, "unknown command '{}'. {}", line, HELP),
+            CommandError::BadPoint(point) => {
+                write!(f, "can't read point '{}', expected column, row and layer like d4-2", point)
+            }
+            CommandError::OffBoard(point) => write!(f, "{} is outside the board", point),
+            CommandError::WrongTurn(to_move) => write!(f, "it is {}'s turn", color_name(*to_move)),
+            CommandError::Illegal(error) => write!(f, "illegal move: {}", error),
+            CommandError::NothingToUndo => f.write_str("nothing to undo"),
+            CommandError::NoMoreMoves => f.write_str("no move to step to"),
+            CommandError::NotReviewing => f.write_str("not reviewing; use back to look at earlier moves"),
+            CommandError::Reviewing => f.write_str("reviewing; use return to go back to the game first"),
+            CommandError::NotSymmetric(error) => write!(f, "the turned game can't be played under this variant: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+// Lines typed on stdin, for playing from a terminal or screen reader
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_commands_from_stdin() -> std::sync::mpsc::Receiver<String> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+pub struct Announcer {
+    enabled: bool,
+    output: Output,
+}
+
+impl Announcer {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            output: Output::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn toggle(&mut self) -> bool {
+        self.enabled = !self.enabled;
+        self.enabled
+    }
+
+    pub fn say(&mut self, text: &str) {
+        if self.enabled {
+            self.output.say(text);
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &SessionEvent, rules: &GameRules) {
+        if let Some(text) = announce(event, rules) {
+            self.say(&text);
+        }
+    }
+}
+
+// Native: the terminal is what screen readers already follow; text-to-speech
+// is added on top when the `speech` feature is enabled.
+#[cfg(not(target_arch = "wasm32"))]
+struct Output {
+    #[cfg(feature = "speech")]
+    tts: Option<tts::Tts>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Output {
+    fn new() -> Self {
+        Self {
+            #[cfg(feature = "speech")]
+            tts: tts::Tts::default()
+                .map_err(|error| log::warn!("text-to-speech unavailable: {}", error))
+                .ok(),
+        }
+    }
+
+    fn say(&mut self, text: &str) {
+        println!("{}", text);
+        #[cfg(feature = "speech")]
+        if let Some(tts) = self.tts.as_mut() {
+            // Interrupt: the newest position matters more than a stale one
+            if let Err(error) = tts.speak(text, true) {
+                log::warn!("couldn't speak announcement: {}", error);
+            }
+        }
+    }
+}
+
+// Browsers: a visually hidden polite live region, created on first use
+#[cfg(target_arch = "wasm32")]
+struct Output {
+    region: Option<web_sys::Element>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Output {
+    const STYLE: &'static str =
+        "position:absolute;width:1px;height:1px;margin:-1px;overflow:hidden;clip:rect(0 0 0 0);white-space:nowrap";
+
+    fn new() -> Self {
+        Self { region: None }
+    }
+
+    fn live_region() -> Option<web_sys::Element> {
+        let document = web_sys::window()?.document()?;
+        let region = document.create_element("div").ok()?;
+        region.set_attribute("role", "status").ok()?;
+        region.set_attribute("aria-live", "polite").ok()?;
+        region.set_attribute("aria-atomic", "true").ok()?;
+        region.set_attribute("style", Self::STYLE).ok()?;
+        document.body()?.append_child(&region).ok()?;
+        Some(region)
+    }
+
+    fn say(&mut self, text: &str) {
+        if self.region.is_none() {
+            self.region = Self::live_region();
+        }
+        if let Some(region) = &self.region {
+            // Clear first so repeating the same sentence is still announced
+            region.set_text_content(None);
+            region.set_text_content(Some(text));
+        }
+    }
+}
\ No newline at end of file
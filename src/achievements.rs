@@ -0,0 +1,141 @@
+// Milestones unlocked by local players, checked against session events as the
+// game is played and at its end. Unlocked achievements are stored with the
+// player's profile.
+use crate::game::{GameRules, SessionEvent, StoneColor};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+// Smallest board that counts as a full-size game
+pub const BIG_BOARD: usize = 9;
+pub const VETERAN_GAMES: u32 = 100;
+// A group with this many liberties can no longer be chased down a ladder
+const ESCAPE_LIBERTIES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Achievement {
+    FirstCapture,
+    LadderEscape,
+    BigBoardWin,
+    Veteran,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::FirstCapture,
+        Achievement::LadderEscape,
+        Achievement::BigBoardWin,
+        Achievement::Veteran,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::FirstCapture => "First blood",
+            Achievement::LadderEscape => "Way out",
+            Achievement::BigBoardWin => "Full size",
+            Achievement::Veteran => "Veteran",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::FirstCapture => "Capture an enemy stone",
+            Achievement::LadderEscape => "Extend a group out of atari to three or more liberties",
+            Achievement::BigBoardWin => "Win a game on a 9³ or larger board",
+            Achievement::Veteran => "Finish 100 games",
+        }
+    }
+
+    // Stable identifier used in the profile store
+    fn id(self) -> &'static str {
+        match self {
+            Achievement::FirstCapture => "first-capture",
+            Achievement::LadderEscape => "ladder-escape",
+            Achievement::BigBoardWin => "big-board-win",
+            Achievement::Veteran => "veteran",
+        }
+    }
+}
+
+impl fmt::Display for Achievement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for Achievement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Achievement::ALL
+            .into_iter()
+            .find(|achievement| achievement.id() == s)
+            .ok_or_else(|| format!("unknown achievement '{}'", s))
+    }
+}
+
+// Achievements `player` earned with `event`; `rules` is the state after it
+pub fn check_event(event: &SessionEvent, rules: &GameRules, player: StoneColor) -> Vec<Achievement> {
+    let SessionEvent::MovePlayed { color, position, captured } = *event else {
+        return Vec::new();
+    };
+    if color != player {
+        return Vec::new();
+    }
+
+    let mut earned = Vec::new();
+    if captured > 0 {
+        earned.push(Achievement::FirstCapture);
+    }
+    // Captures change the surroundings too much to reconstruct the position
+    // before the move, so only plain extensions count as escapes
+    if captured == 0 && escaped_atari(rules, position) {
+        earned.push(Achievement::LadderEscape);
+    }
+    earned
+}
+
+fn escaped_atari(rules: &GameRules, position: (u8, u8, u8)) -> bool {
+    let board = rules.board();
+    let Some(color) = board.get_stone(position) else {
+        return false;
+    };
+    let Some(group) = board.get_group(position) else {
+        return false;
+    };
+    if board.get_liberties(&group).len() < ESCAPE_LIBERTIES {
+        return false;
+    }
+
+    // Before the move, some friendly neighbour's group had the played point
+    // as its last liberty
+    let mut before = board.clone();
+    before.remove_stone(position);
+    let mut seen = HashSet::new();
+    board
+        .get_neighbors(position)
+        .into_iter()
+        .filter(|&neighbor| before.get_stone(neighbor) == Some(color))
+        .any(|neighbor| {
+            if seen.contains(&neighbor) {
+                return false;
+            }
+            let Some(old_group) = before.get_group(neighbor) else {
+                return false;
+            };
+            seen.extend(old_group.iter().copied());
+            before.get_liberties(&old_group).len() == 1
+        })
+}
+
+// Achievements for finishing a game; `games_played` includes this one
+pub fn check_game_end(board_size: usize, won: bool, games_played: u32) -> Vec<Achievement> {
+    let mut earned = Vec::new();
+    if won && board_size >= BIG_BOARD {
+        earned.push(Achievement::BigBoardWin);
+    }
+    if games_played >= VETERAN_GAMES {
+        earned.push(Achievement::Veteran);
+    }
+    earned
+}
\ No newline at end of file
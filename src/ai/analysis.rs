@@ -0,0 +1,64 @@
+// Batch analysis of a game record: the estimated final score after every move
+// and the moves where the game swung. The estimate is the score if the game
+// ended in that position, which is crude early on but cheap enough to run over
+// a whole record at once.
+use crate::game::{GameRecord, GameRules, RecordError, StoneColor};
+use crate::settings::GameSettings;
+
+// Points lost over a move and the reply before the move is flagged
+pub const BLUNDER_THRESHOLD: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blunder {
+    pub move_number: usize, // 1-based
+    pub color: StoneColor,
+    pub loss: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GameAnalysis {
+    // Black's lead (negative when White leads); index 0 is the empty board,
+    // index n the position after move n
+    pub scores: Vec<f32>,
+    pub blunders: Vec<Blunder>,
+}
+
+// Black's lead if the game ended now, komi included
+pub fn estimate_score(rules: &GameRules, settings: &GameSettings) -> f32 {
+    let (black, white) = settings.score(rules);
+    black - white
+}
+
+pub fn analyze(record: &GameRecord, settings: &GameSettings) -> Result<GameAnalysis, RecordError> {
+    let settings = GameSettings {
+        komi: record.komi,
+        ..settings.clone()
+    };
+    let mut scores = Vec::with_capacity(record.moves.len() + 1);
+    record.replay_each(|rules| scores.push(estimate_score(rules, &settings)))?;
+
+    // Judge each move after the opponent's answer, so a move that leaves a
+    // group to be captured is blamed rather than the capture itself
+    let blunders = record
+        .moves
+        .iter()
+        .enumerate()
+        .filter_map(|(index, played)| {
+            let before = scores[index];
+            let after = scores[(index + 2).min(scores.len() - 1)];
+            // The estimate is Black's lead over White, so it can't blame the extra colors
+            let loss = match played.color {
+                StoneColor::Black => before - after,
+                StoneColor::White => after - before,
+                StoneColor::Red | StoneColor::Green => 0.0,
+            };
+            (loss >= BLUNDER_THRESHOLD).then_some(Blunder {
+                move_number: index + 1,
+                color: played.color,
+                loss,
+            })
+        })
+        .collect();
+
+    Ok(GameAnalysis { scores, blunders })
+}
\ No newline at end of file
@@ -0,0 +1,98 @@
+use super::Engine;
+use crate::api::{Game, Move, Position};
+use crate::game::StoneColor;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+
+// One-ply search: prefer moves that capture the most stones, then moves that
+// leave the new stone's group with the most liberties
+#[derive(Debug)]
+pub struct GreedyEngine {
+    // Breaks ties between equally good moves
+    rng: SmallRng,
+}
+
+impl Default for GreedyEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GreedyEngine {
+    // Softmax temperature for policy(), in evaluation units (one liberty)
+    const TEMPERATURE: f32 = 4.0;
+
+    pub fn new() -> Self {
+        Self { rng: super::rng(None) }
+    }
+
+    fn evaluate_move(game: &Game, position: Position) -> Option<i64> {
+        let color = game.to_move();
+        let mut after = game.clone();
+        after.play(Move::Place(position)).ok()?;
+        Some(Self::evaluate(&after, color))
+    }
+
+    fn evaluate(game: &Game, color: StoneColor) -> i64 {
+        let rules = game.rules();
+        // Stones taken from every other player, however many there are
+        let captured: i64 = StoneColor::players(rules.players())
+            .iter()
+            .filter(|&&other| other != color)
+            .map(|&other| rules.board().get_captured(other) as i64)
+            .sum();
+
+        let liberties = rules
+            .moves()
+            .last()
+            .and_then(|last| last.position)
+            .and_then(|pos| rules.board().get_group(pos))
+            .map(|group| rules.board().get_liberties(&group).len() as i64)
+            .unwrap_or(0);
+
+        captured * 100 + liberties
+    }
+}
+
+impl Engine for GreedyEngine {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn select_move(&mut self, game: &Game) -> Move {
+        let mut candidates = game.legal_moves();
+        // Shuffle first so ties are broken randomly rather than by scan order
+        candidates.shuffle(&mut self.rng);
+
+        candidates
+            .into_iter()
+            .max_by_key(|&position| Self::evaluate_move(game, position).unwrap_or(i64::MIN))
+            .map(Move::Place)
+            .unwrap_or(Move::Pass)
+    }
+
+    fn policy(&mut self, game: &Game) -> Vec<(Position, f32)> {
+        let scored: Vec<(Position, i64)> = game
+            .legal_moves()
+            .into_iter()
+            .filter_map(|position| Some((position, Self::evaluate_move(game, position)?)))
+            .collect();
+        let Some(best) = scored.iter().map(|&(_, score)| score).max() else {
+            return Vec::new();
+        };
+
+        let weights: Vec<(Position, f32)> = scored
+            .into_iter()
+            .map(|(position, score)| (position, ((score - best) as f32 / Self::TEMPERATURE).exp()))
+            .collect();
+        let total: f32 = weights.iter().map(|&(_, weight)| weight).sum();
+        weights
+            .into_iter()
+            .map(|(position, weight)| (position, weight / total))
+            .collect()
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = super::rng(Some(seed));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+// Influence ("moyo") estimate after Bouzy's dilation/erosion algorithm,
+// carried over to the 3D lattice: every stone starts as a strong source, a
+// few dilations spread its influence over the empty points it faces
+// unopposed, and erosions then wear away whatever a contested boundary can't
+// hold. What's left outlines each side's frameworks.
+//
+// Black counts positive and White negative, like the score estimate; the
+// third and fourth colors of larger games take no part.
+use crate::game::{Board, StoneColor};
+use std::collections::HashMap;
+
+type Position = (u8, u8, u8);
+
+// Bouzy's moyo setting: 5 dilations and 10 erosions keep the frameworks a
+// group of stones faces, where 21 erosions would cut them back to secure
+// territory
+pub const DILATIONS: usize = 5;
+pub const EROSIONS: usize = 10;
+const STONE: i32 = 128;
+
+// Influence on every point: above zero leans Black, below zero White
+pub fn influence(board: &Board) -> HashMap<Position, i32> {
+    let mut field: HashMap<Position, i32> = board
+        .positions()
+        .map(|position| {
+            let value = match board.get_stone(position) {
+                Some(StoneColor::Black) => STONE,
+                Some(StoneColor::White) => -STONE,
+                _ => 0,
+            };
+            (position, value)
+        })
+        .collect();
+    let neighbors: HashMap<Position, Vec<Position>> = field
+        .keys()
+        .map(|&position| (position, board.get_neighbors(position)))
+        .collect();
+
+    for _ in 0..DILATIONS {
+        field = dilate(&field, &neighbors);
+    }
+    for _ in 0..EROSIONS {
+        field = erode(&field, &neighbors);
+    }
+    field
+}
+
+// A point grows by one for each neighbor of its own sign, as long as none
+// of them leans the other way
+fn dilate(field: &HashMap<Position, i32>, neighbors: &HashMap<Position, Vec<Position>>) -> HashMap<Position, i32> {
+    field
+        .iter()
+        .map(|(&position, &value)| {
+            let around = neighbors[&position].iter().map(|neighbor| field[neighbor]);
+            let (positive, negative) = around.fold((0, 0), |(positive, negative), neighbor| {
+                (positive + i32::from(neighbor > 0), negative + i32::from(neighbor < 0))
+            });
+            let value = if value >= 0 && negative == 0 {
+                value + positive
+            } else if value <= 0 && positive == 0 {
+                value - negative
+            } else {
+                value
+            };
+            (position, value)
+        })
+        .collect()
+}
+
+// A point shrinks towards zero by one for each neighbor that doesn't share
+// its sign, and stops there
+fn erode(field: &HashMap<Position, i32>, neighbors: &HashMap<Position, Vec<Position>>) -> HashMap<Position, i32> {
+    field
+        .iter()
+        .map(|(&position, &value)| {
+            let around = neighbors[&position].iter().map(|neighbor| field[neighbor]);
+            let value = match value.signum() {
+                1 => (value - around.filter(|&neighbor| neighbor <= 0).count() as i32).max(0),
+                -1 => (value + around.filter(|&neighbor| neighbor >= 0).count() as i32).min(0),
+                _ => 0,
+            };
+            (position, value)
+        })
+        .collect()
+}
@@ -0,0 +1,304 @@
+use super::Engine;
+use crate::api::{Game, Move, Position};
+use crate::game::{zobrist, PositionTable, StoneColor};
+use crate::settings::GameSettings;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::sync::Mutex;
+
+// Monte Carlo tree search with random playouts. Natively the playouts run in
+// parallel on a rayon pool sharing one tree; selection adds a virtual loss to
+// every node on the chosen path so concurrent workers spread out instead of
+// piling onto the same line. wasm has no threads, so it searches serially.
+// Each playout draws from its own generator, seeded from the engine's by its
+// number, so a seeded search repeats itself exactly on one thread; on more,
+// the order playouts finish in still varies.
+// Positions reached by different move orders share one node, found through a
+// table keyed by the same Zobrist hashes the rules use for superko.
+pub struct MctsEngine {
+    playouts: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool: Option<rayon::ThreadPool>,
+    rng: SmallRng,
+    // Komi and the scoring rule that decide who won a playout
+    scoring: GameSettings,
+}
+
+impl MctsEngine {
+    pub const DEFAULT_PLAYOUTS: usize = 500;
+    // UCT exploration constant
+    const EXPLORATION: f32 = 1.4;
+    // Random picks tried before falling back to scanning every legal move
+    const RANDOM_ATTEMPTS: usize = 16;
+
+    // `threads` of 0 uses rayon's default of one per core; playouts are
+    // scored as `scoring` scores the game itself
+    pub fn new(playouts: usize, threads: usize, scoring: GameSettings) -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let _ = threads;
+
+        Self {
+            playouts: playouts.max(1),
+            #[cfg(not(target_arch = "wasm32"))]
+            pool: (threads > 0)
+                .then(|| rayon::ThreadPoolBuilder::new().num_threads(threads).build())
+                .and_then(|pool| {
+                    pool.map_err(|error| log::warn!("couldn't start search threads: {}", error))
+                        .ok()
+                }),
+            rng: super::rng(None),
+            scoring,
+        }
+    }
+
+    fn search(&mut self, game: &Game) -> Tree {
+        let tree = Mutex::new(Tree::new(game));
+        let seed: u64 = self.rng.gen();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            use rayon::prelude::*;
+            let run = || (0..self.playouts).into_par_iter().for_each(|number| iterate(&tree, game, &self.scoring, seed, number));
+            match &self.pool {
+                Some(pool) => pool.install(run),
+                None => run(),
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        for number in 0..self.playouts {
+            iterate(&tree, game, &self.scoring, seed, number);
+        }
+
+        tree.into_inner().expect("search tree poisoned")
+    }
+}
+
+impl Engine for MctsEngine {
+    fn name(&self) -> &str {
+        "mcts"
+    }
+
+    fn select_move(&mut self, game: &Game) -> Move {
+        let tree = self.search(game);
+        tree.nodes[0]
+            .children
+            .iter()
+            .max_by_key(|&&(_, child)| tree.nodes[child].visits)
+            .map_or(Move::Pass, |&(mv, _)| mv)
+    }
+
+    // Share of root visits per placement
+    fn policy(&mut self, game: &Game) -> Vec<(Position, f32)> {
+        let tree = self.search(game);
+        let root = &tree.nodes[0];
+        let total: u32 = root.children.iter().map(|&(_, child)| tree.nodes[child].visits).sum();
+        root.children
+            .iter()
+            .filter_map(|&(mv, child)| match mv {
+                Move::Place(position) => Some((position, tree.nodes[child].visits as f32 / total.max(1) as f32)),
+                Move::Pass => None,
+            })
+            .collect()
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = super::rng(Some(seed));
+    }
+}
+
+struct Node {
+    // The player who moved into this position; wins are counted for them
+    color: StoneColor,
+    // Each move from here and the node it leads to, which may be shared
+    children: Vec<(Move, usize)>,
+    // Moves not yet expanded; None until the node is first reached
+    untried: Option<Vec<Move>>,
+    visits: u32,
+    wins: f32,
+    virtual_loss: u32,
+}
+
+impl Node {
+    fn uct(&self, parent_visits: f32) -> f32 {
+        // In-flight playouts count as visits that were lost
+        let visits = (self.visits + self.virtual_loss) as f32;
+        if visits == 0.0 {
+            return f32::INFINITY;
+        }
+        self.wins / visits + MctsEngine::EXPLORATION * (parent_visits.ln() / visits).sqrt()
+    }
+}
+
+impl Node {
+    fn new(color: StoneColor) -> Self {
+        Self {
+            color,
+            children: Vec::new(),
+            untried: None,
+            visits: 0,
+            wins: 0.0,
+            virtual_loss: 0,
+        }
+    }
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+    // Node index per position and side to move
+    transpositions: PositionTable<usize>,
+}
+
+// The stones and whose turn it is
+fn position_key(game: &Game) -> u64 {
+    game.rules().board().hash() ^ zobrist::to_move_key(game.to_move())
+}
+
+impl Tree {
+    fn new(game: &Game) -> Self {
+        let mut transpositions = PositionTable::new();
+        transpositions.insert(position_key(game), 0);
+        Self {
+            nodes: vec![Node::new(game.to_move().previous(game.rules().players()))],
+            transpositions,
+        }
+    }
+
+    // Walks down by UCT, expands one new child and returns the path with the
+    // position it leads to
+    fn select_and_expand(&mut self, root_game: &Game, rng: &mut impl Rng) -> (Vec<usize>, Game) {
+        let mut game = root_game.clone();
+        let mut path = vec![0];
+        let mut current = 0;
+
+        loop {
+            // A shared node's moves were found along another move order, so
+            // superko may forbid some of them here; the search stops short then
+            let untried = self.nodes[current].untried.get_or_insert_with(|| candidate_moves(&game, rng));
+            if let Some(mv) = untried.pop() {
+                let color = game.to_move();
+                if game.play(mv).is_err() {
+                    untried.push(mv);
+                    break;
+                }
+                // Passes get their own nodes: two of them end the game, which
+                // the position alone doesn't show
+                let key = position_key(&game);
+                let child = match self.transpositions.get(key) {
+                    Some(&child) if mv != Move::Pass => child,
+                    _ => {
+                        let child = self.nodes.len();
+                        self.nodes.push(Node::new(color));
+                        if mv != Move::Pass {
+                            self.transpositions.insert(key, child);
+                        }
+                        child
+                    }
+                };
+                self.nodes[current].children.push((mv, child));
+                path.push(child);
+                break;
+            }
+
+            let node = &self.nodes[current];
+            let parent_visits = (node.visits + node.virtual_loss).max(1) as f32;
+            let Some(&(mv, next)) = node.children.iter().max_by(|&&(_, a), &&(_, b)| {
+                self.nodes[a].uct(parent_visits).total_cmp(&self.nodes[b].uct(parent_visits))
+            }) else {
+                break; // terminal: no moves at all
+            };
+            if game.play(mv).is_err() {
+                break;
+            }
+            path.push(next);
+            current = next;
+        }
+
+        for &index in &path {
+            self.nodes[index].virtual_loss += 1;
+        }
+        (path, game)
+    }
+
+    fn backpropagate(&mut self, path: &[usize], winner: StoneColor) {
+        for &index in path {
+            let node = &mut self.nodes[index];
+            node.virtual_loss -= 1;
+            node.visits += 1;
+            if node.color == winner {
+                node.wins += 1.0;
+            }
+        }
+    }
+}
+
+fn iterate(tree: &Mutex<Tree>, root_game: &Game, scoring: &GameSettings, seed: u64, number: usize) {
+    let mut rng = SmallRng::seed_from_u64(seed.wrapping_add(number as u64));
+    let (path, game) = tree.lock().expect("search tree poisoned").select_and_expand(root_game, &mut rng);
+    // The playout is the expensive part and runs without the lock
+    let winner = playout(game, scoring, &mut rng);
+    tree.lock().expect("search tree poisoned").backpropagate(&path, winner);
+}
+
+// Every placement in random order, or a pass when there are none. Passing is
+// left out otherwise so the search doesn't waste playouts on it early on.
+fn candidate_moves(game: &Game, rng: &mut impl Rng) -> Vec<Move> {
+    let mut moves: Vec<Move> = game.legal_moves().into_iter().map(Move::Place).collect();
+    if moves.is_empty() {
+        moves.push(Move::Pass);
+    }
+    moves.shuffle(rng);
+    moves
+}
+
+// Plays random non-eye-filling moves until every player passes in turn, then
+// scores; an outright win under the variant ends the playout early. The
+// highest score wins, the later player in turn order on a tie.
+fn playout(mut game: Game, scoring: &GameSettings, rng: &mut impl Rng) -> StoneColor {
+    let move_limit = game.size().pow(3) * 2;
+    for _ in 0..move_limit {
+        let mv = random_move(&game, rng);
+        game.play(mv).expect("random moves are legal");
+        if game.is_over() {
+            break;
+        }
+    }
+    if let Some(winner) = game.rules().winner() {
+        return winner;
+    }
+
+    scoring
+        .scores(game.rules())
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(StoneColor::White, |(color, _)| color)
+}
+
+fn random_move(game: &Game, rng: &mut impl Rng) -> Move {
+    let size = game.size() as u8;
+    let color = game.to_move();
+    let playable = |position: Position| game.is_legal(position) && !is_own_eye(game, position, color);
+
+    for _ in 0..MctsEngine::RANDOM_ATTEMPTS {
+        let position = Position::new(rng.gen_range(0..size), rng.gen_range(0..size), rng.gen_range(0..size));
+        if playable(position) {
+            return Move::Place(position);
+        }
+    }
+    game.legal_moves()
+        .into_iter()
+        .filter(|&position| !is_own_eye(game, position, color))
+        .collect::<Vec<_>>()
+        .choose(rng)
+        .map_or(Move::Pass, |&position| Move::Place(position))
+}
+
+// An empty point whose neighbours are all `color`; filling it only helps the
+// opponent, and skipping it lets random games end
+fn is_own_eye(game: &Game, position: Position, color: StoneColor) -> bool {
+    let board = game.rules().board();
+    board
+        .get_neighbors(position.into())
+        .into_iter()
+        .all(|neighbor| board.get_stone(neighbor) == Some(color))
+}
\ No newline at end of file
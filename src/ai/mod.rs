@@ -0,0 +1,42 @@
+pub mod analysis;
+pub mod greedy;
+pub mod influence;
+pub mod mcts;
+pub mod patterns;
+pub mod random;
+
+pub use greedy::GreedyEngine;
+pub use mcts::MctsEngine;
+pub use random::RandomEngine;
+
+use crate::api::{Game, Move, Position};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+// The random source engines draw from: fresh from the system unless a seed
+// is given, so that engines seeded alike choose alike
+pub fn rng(seed: Option<u64>) -> SmallRng {
+    match seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_entropy(),
+    }
+}
+
+pub trait Engine: Send {
+    fn name(&self) -> &str;
+
+    fn select_move(&mut self, game: &Game) -> Move;
+
+    // How strongly the engine favours each legal placement, as weights summing
+    // to 1 (priors or search visit shares). Engines without an opinion spread
+    // the weight evenly.
+    fn policy(&mut self, game: &Game) -> Vec<(Position, f32)> {
+        let moves = game.legal_moves();
+        let weight = 1.0 / moves.len().max(1) as f32;
+        moves.into_iter().map(|position| (position, weight)).collect()
+    }
+
+    // Restarts the engine's random choices from `seed`, so the same moves
+    // against it get the same replies. Engines without randomness ignore it.
+    fn reseed(&mut self, _seed: u64) {}
+}
\ No newline at end of file
@@ -0,0 +1,248 @@
+// Joseki-style pattern matching: a small built-in library of local shapes in
+// the corners, along the edges and on the faces of the cube, each with a named
+// continuation for the side to move.
+//
+// A pattern covers a 3x3x3 box in its own frame. The box is laid over the
+// board in every orientation its region allows (8 corners, 12 edges, 6 faces,
+// each turned and mirrored), and its contents hashed in the pattern's frame
+// with the stones seen as the side to move's or someone else's; a pattern
+// matches where the hashes agree, whichever way round the shape was played.
+use crate::game::zobrist::mix;
+use crate::game::GameRules;
+use std::collections::HashMap;
+
+type Position = (u8, u8, u8);
+// A point in a pattern's box, each coordinate 0..3
+type Local = (u8, u8, u8);
+
+const BOX: u8 = 3;
+
+// Where on the cube a pattern's box sits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Region {
+    // Against three walls; (0, 0, 0) is the board's corner point
+    Corner,
+    // Against two walls and halfway along the edge between them, which runs
+    // along the box's first axis
+    Edge,
+    // Against one wall, in the middle of the face; the third axis points inwards
+    Face,
+}
+
+impl Region {
+    const ALL: [Region; 3] = [Region::Corner, Region::Edge, Region::Face];
+
+    // Whether the box is centred along its local `axis` rather than set against a wall
+    fn centred(self, axis: usize) -> bool {
+        match self {
+            Region::Corner => false,
+            Region::Edge => axis == 0,
+            Region::Face => axis < 2,
+        }
+    }
+}
+
+pub struct Pattern {
+    pub name: &'static str,
+    pub region: Region,
+    // Stones of the side to move, then anyone else's; the rest of the box is empty
+    pub own: &'static [Local],
+    pub other: &'static [Local],
+    pub continuation: Local,
+}
+
+impl Pattern {
+    fn hash(&self) -> u64 {
+        let own = self.own.iter().map(|&local| cell_key(local, false));
+        let other = self.other.iter().map(|&local| cell_key(local, true));
+        own.chain(other).fold(0, |hash, key| hash ^ key)
+    }
+}
+
+const BUILTIN: &[Pattern] = &[
+    Pattern {
+        name: "Corner: take the 2-2-2 point",
+        region: Region::Corner,
+        own: &[],
+        other: &[],
+        continuation: (1, 1, 1),
+    },
+    Pattern {
+        name: "Corner: enclose along the long diagonal",
+        region: Region::Corner,
+        own: &[(1, 1, 1)],
+        other: &[],
+        continuation: (2, 2, 2),
+    },
+    Pattern {
+        name: "Corner: attach to the 2-2-2 stone",
+        region: Region::Corner,
+        own: &[],
+        other: &[(1, 1, 1)],
+        continuation: (2, 1, 1),
+    },
+    Pattern {
+        name: "Corner: hane after the attachment",
+        region: Region::Corner,
+        own: &[(1, 1, 1)],
+        other: &[(2, 1, 1)],
+        continuation: (2, 2, 1),
+    },
+    Pattern {
+        name: "Edge: extend along the edge",
+        region: Region::Edge,
+        own: &[(1, 1, 1)],
+        other: &[],
+        continuation: (2, 1, 1),
+    },
+    Pattern {
+        name: "Edge: hane against the contact",
+        region: Region::Edge,
+        own: &[(1, 1, 1)],
+        other: &[(1, 2, 1)],
+        continuation: (2, 2, 1),
+    },
+    Pattern {
+        name: "Face: take the point under the centre",
+        region: Region::Face,
+        own: &[],
+        other: &[],
+        continuation: (1, 1, 1),
+    },
+    Pattern {
+        name: "Face: undercut from the surface",
+        region: Region::Face,
+        own: &[],
+        other: &[(1, 1, 1)],
+        continuation: (1, 1, 0),
+    },
+];
+
+fn cell_key((u, v, w): Local, other: bool) -> u64 {
+    mix(u64::from(u) | u64::from(v) << 8 | u64::from(w) << 16 | u64::from(other) << 24 | 0x7a << 32)
+}
+
+// How a box lies on the board: local axis i runs along board axis `axes[i]`,
+// from `origin` in the direction `step` (both indexed by board axis)
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    axes: [usize; 3],
+    origin: [i32; 3],
+    step: [i32; 3],
+    size: i32,
+}
+
+impl Placement {
+    fn board_point(&self, (u, v, w): Local) -> Option<Position> {
+        let mut point = self.origin;
+        for (axis, local) in [u, v, w].into_iter().enumerate() {
+            let board_axis = self.axes[axis];
+            point[board_axis] += self.step[board_axis] * i32::from(local);
+        }
+        let on_board = |n: i32| (0..self.size).contains(&n);
+        point
+            .iter()
+            .all(|&n| on_board(n))
+            .then(|| (point[0] as u8, point[1] as u8, point[2] as u8))
+    }
+}
+
+const AXIS_ORDERS: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+
+// Every way of laying a box for `region` on a board of `size`; none when the
+// board is smaller than a box
+fn placements(region: Region, size: usize) -> Vec<Placement> {
+    if size < usize::from(BOX) {
+        return Vec::new();
+    }
+    let size = size as i32;
+    let middle = (size - 1) / 2;
+    let mut placements = Vec::new();
+    for axes in AXIS_ORDERS {
+        for signs in 0..8 {
+            let mut origin = [0; 3];
+            let mut step = [0; 3];
+            for (axis, &board_axis) in axes.iter().enumerate() {
+                let forwards = signs & (1 << axis) == 0;
+                step[board_axis] = if forwards { 1 } else { -1 };
+                origin[board_axis] = match (region.centred(axis), forwards) {
+                    // Mirror images of each other, so even sizes stay symmetric
+                    (true, true) => middle - 1,
+                    (true, false) => size - middle,
+                    (false, true) => 0,
+                    (false, false) => size - 1,
+                };
+            }
+            placements.push(Placement { axes, origin, step, size });
+        }
+    }
+    placements
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub name: &'static str,
+    // Where the side to move would play the continuation
+    pub continuation: Position,
+}
+
+pub struct PatternLibrary {
+    patterns: &'static [Pattern],
+    // Indices into `patterns` by region and box hash
+    index: HashMap<(Region, u64), Vec<usize>>,
+}
+
+impl PatternLibrary {
+    pub fn builtin() -> Self {
+        Self::new(BUILTIN)
+    }
+
+    pub fn new(patterns: &'static [Pattern]) -> Self {
+        let mut index: HashMap<(Region, u64), Vec<usize>> = HashMap::new();
+        for (i, pattern) in patterns.iter().enumerate() {
+            index.entry((pattern.region, pattern.hash())).or_default().push(i);
+        }
+        Self { patterns, index }
+    }
+
+    // Named continuations for the side to move, legal ones only, in name order
+    pub fn matches(&self, rules: &GameRules) -> Vec<PatternMatch> {
+        let board = rules.board();
+        let to_move = rules.current_player();
+        let mut found: Vec<PatternMatch> = Vec::new();
+        for region in Region::ALL {
+            for placement in placements(region, board.size()) {
+                let mut hash = 0;
+                for local in local_points() {
+                    let stone = placement.board_point(local).and_then(|point| board.get_stone(point));
+                    if let Some(color) = stone {
+                        hash ^= cell_key(local, color != to_move);
+                    }
+                }
+                let Some(candidates) = self.index.get(&(region, hash)) else {
+                    continue;
+                };
+                for &i in candidates {
+                    let pattern = &self.patterns[i];
+                    let Some(continuation) = placement.board_point(pattern.continuation) else {
+                        continue;
+                    };
+                    let (x, y, z) = continuation;
+                    let found_match = PatternMatch {
+                        name: pattern.name,
+                        continuation,
+                    };
+                    if rules.is_legal_move(x, y, z) && !found.contains(&found_match) {
+                        found.push(found_match);
+                    }
+                }
+            }
+        }
+        found.sort_by_key(|found| (found.name, found.continuation));
+        found
+    }
+}
+
+fn local_points() -> impl Iterator<Item = Local> {
+    (0..BOX).flat_map(|u| (0..BOX).flat_map(move |v| (0..BOX).map(move |w| (u, v, w))))
+}
@@ -0,0 +1,39 @@
+use super::Engine;
+use crate::api::{Game, Move};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+
+// Plays a uniformly random legal move, passing when none remain
+#[derive(Debug)]
+pub struct RandomEngine {
+    rng: SmallRng,
+}
+
+impl Default for RandomEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RandomEngine {
+    pub fn new() -> Self {
+        Self { rng: super::rng(None) }
+    }
+}
+
+impl Engine for RandomEngine {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn select_move(&mut self, game: &Game) -> Move {
+        match game.legal_moves().choose(&mut self.rng) {
+            Some(&position) => Move::Place(position),
+            None => Move::Pass,
+        }
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = super::rng(Some(seed));
+    }
+}
@@ -0,0 +1,219 @@
+//! Stable entry point for using go3d as a rules/AI library.
+//!
+//! Everything here is covered by semver. The other modules are implementation
+//! details of the bundled frontends, public only with the `frontends` feature.
+
+use crate::game::{GameRules, MoveError, MoveRecord, RulesObserver, StoneColor};
+use std::fmt;
+use std::sync::Arc;
+
+pub use crate::game::ObserverId;
+pub use crate::settings::{GameSettings, ScoringRule};
+
+/// An intersection of the cubic lattice, each coordinate in `0..size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    pub x: u8,
+    pub y: u8,
+    pub z: u8,
+}
+
+impl Position {
+    pub const fn new(x: u8, y: u8, z: u8) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<(u8, u8, u8)> for Position {
+    fn from((x, y, z): (u8, u8, u8)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<Position> for (u8, u8, u8) {
+    fn from(position: Position) -> Self {
+        (position.x, position.y, position.z)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// A single turn: either a stone placement or a pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Place(Position),
+    Pass,
+}
+
+/// What the last move changed, for updating a copy of the board without
+/// comparing whole boards. Empty after a pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardDiff {
+    /// The stone the move placed.
+    pub placed: Vec<(Position, StoneColor)>,
+    /// The stones it captured, in position order.
+    pub removed: Vec<(Position, StoneColor)>,
+}
+
+impl BoardDiff {
+    pub fn is_empty(&self) -> bool {
+        self.placed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Follows a game as it is played, e.g. to drive another GUI from a [`Game`].
+///
+/// Register with [`Game::observe`]. Callbacks run inside [`Game::play`], after
+/// the board has been updated; every method has an empty default.
+pub trait GameObserver: Send + Sync {
+    /// `color` played `mv`.
+    fn on_move(&self, _color: StoneColor, _mv: Move) {}
+
+    /// `color`'s `stones` were just captured, reported before the capturing move.
+    fn on_capture(&self, _color: StoneColor, _stones: &[Position]) {}
+
+    /// Two passes in a row ended the game; `territory` is as [`Game::territory`] reports it.
+    fn on_game_end(&self, _territory: (usize, usize)) {}
+}
+
+struct ObserverAdapter<O>(O);
+
+impl<O: GameObserver> RulesObserver for ObserverAdapter<O> {
+    fn on_move(&self, _rules: &GameRules, record: &MoveRecord, _captured: &[(u8, u8, u8)]) {
+        let mv = match record.position {
+            Some(position) => Move::Place(position.into()),
+            None => Move::Pass,
+        };
+        self.0.on_move(record.color, mv);
+    }
+
+    fn on_capture(&self, _rules: &GameRules, color: StoneColor, stones: &[(u8, u8, u8)]) {
+        let stones: Vec<Position> = stones.iter().map(|&position| position.into()).collect();
+        self.0.on_capture(color, &stones);
+    }
+
+    fn on_game_end(&self, rules: &GameRules) {
+        self.0.on_game_end(rules.get_territory_score());
+    }
+}
+
+/// A game of 3D Go with full rule enforcement (captures, suicide, ko).
+#[derive(Debug, Clone)]
+pub struct Game {
+    rules: GameRules,
+}
+
+impl Game {
+    /// Creates an empty `size`×`size`×`size` board with Black to move.
+    pub fn new(size: usize) -> Self {
+        Self {
+            rules: GameRules::new(size),
+        }
+    }
+
+    pub(crate) fn rules(&self) -> &GameRules {
+        &self.rules
+    }
+
+    pub fn size(&self) -> usize {
+        self.rules.board().size()
+    }
+
+    /// The color whose turn it is.
+    pub fn to_move(&self) -> StoneColor {
+        self.rules.current_player()
+    }
+
+    pub fn stone_at(&self, position: Position) -> Option<StoneColor> {
+        self.rules.board().get_stone(position.into())
+    }
+
+    /// All stones on the board, in no particular order.
+    pub fn stones(&self) -> impl Iterator<Item = (Position, StoneColor)> + '_ {
+        self.rules
+            .board()
+            .get_all_stones()
+            .map(|(&position, &color)| (position.into(), color))
+    }
+
+    pub fn is_legal(&self, position: Position) -> bool {
+        self.rules.is_legal_move(position.x, position.y, position.z)
+    }
+
+    /// Every placement the side to move may currently make.
+    pub fn legal_moves(&self) -> Vec<Position> {
+        self.rules.legal_moves().into_iter().map(Position::from).collect()
+    }
+
+    /// Plays `mv` for the side to move, or explains why it is illegal.
+    pub fn play(&mut self, mv: Move) -> Result<(), MoveError> {
+        match mv {
+            Move::Place(position) => {
+                self.rules.check_move(position.x, position.y, position.z)?;
+                self.rules.make_move(position.x, position.y, position.z);
+            }
+            Move::Pass => self.rules.pass(),
+        }
+        Ok(())
+    }
+
+    /// Takes back the last move or pass; returns false at the start of the game.
+    pub fn undo(&mut self) -> bool {
+        self.rules.undo()
+    }
+
+    /// What the last move or pass changed on the board.
+    pub fn last_move_diff(&self) -> BoardDiff {
+        let diff = self.rules.diff_last_move();
+        let convert = |stones: Vec<((u8, u8, u8), StoneColor)>| {
+            stones.into_iter().map(|(position, color)| (position.into(), color)).collect()
+        };
+        BoardDiff {
+            placed: convert(diff.placed),
+            removed: convert(diff.removed),
+        }
+    }
+
+    /// Number of `color` stones that have been captured so far.
+    pub fn captures(&self, color: StoneColor) -> usize {
+        self.rules.board().get_captured(color)
+    }
+
+    /// Calls `observer` for every move from now on. A clone of the game starts
+    /// without observers.
+    pub fn observe(&mut self, observer: impl GameObserver + 'static) -> ObserverId {
+        self.rules.add_observer(Arc::new(ObserverAdapter(observer)))
+    }
+
+    /// Stops an observer; returns false if it was already removed.
+    pub fn unobserve(&mut self, id: ObserverId) -> bool {
+        self.rules.remove_observer(id)
+    }
+
+    /// Whether two passes in a row have ended the game.
+    pub fn is_over(&self) -> bool {
+        self.rules.is_over()
+    }
+
+    /// Empty points enclosed by a single color, as `(black, white)`.
+    pub fn territory(&self) -> (usize, usize) {
+        self.rules.get_territory_score()
+    }
+}
+
+// Conversions for the bundled frontends, which drive the internal rules directly
+impl From<GameRules> for Game {
+    fn from(rules: GameRules) -> Self {
+        Self { rules }
+    }
+}
+
+impl From<Game> for GameRules {
+    fn from(game: Game) -> Self {
+        game.rules
+    }
+}
\ No newline at end of file
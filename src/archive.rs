@@ -0,0 +1,156 @@
+// Finished games, kept as SGF-3D records with their players and result
+// through their own storage backend, for browsing and reopening in review.
+// Entries are numbered as they're added, so the newest sorts last.
+use crate::game::{sgf, GameRecord, StoneColor};
+use crate::storage::{Backend, StorageError};
+use std::fmt;
+use std::str::FromStr;
+
+const PREFIX: &str = "game";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultFilter {
+    Won(StoneColor),
+    Draw,
+}
+
+impl FromStr for ResultFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("draw") {
+            return Ok(ResultFilter::Draw);
+        }
+        StoneColor::ALL
+            .into_iter()
+            .find(|color| color.name().eq_ignore_ascii_case(s))
+            .map(ResultFilter::Won)
+            .ok_or_else(|| format!("unknown result '{}', expected black, white, red, green or draw", s))
+    }
+}
+
+impl fmt::Display for ResultFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResultFilter::Won(color) => f.write_str(&color.name().to_lowercase()),
+            ResultFilter::Draw => f.write_str("draw"),
+        }
+    }
+}
+
+// Which games to show; every field left as None lets all of them through
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchiveFilter {
+    // Either player, ignoring case
+    pub player: Option<String>,
+    pub board_size: Option<usize>,
+    pub result: Option<ResultFilter>,
+}
+
+impl ArchiveFilter {
+    pub fn matches(&self, record: &GameRecord) -> bool {
+        let player = self.player.as_ref().is_none_or(|wanted| {
+            [&record.black_player, &record.white_player]
+                .into_iter()
+                .flatten()
+                .any(|name| name.eq_ignore_ascii_case(wanted))
+        });
+        let size = self.board_size.is_none_or(|size| size == record.board_size);
+        let result = self.result.is_none_or(|wanted| match record.result {
+            Some(result) => match wanted {
+                ResultFilter::Won(color) => result.winner() == Some(color),
+                ResultFilter::Draw => result.winner().is_none(),
+            },
+            None => false,
+        });
+        player && size && result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    pub slot: String,
+    pub record: GameRecord,
+}
+
+impl ArchiveEntry {
+    // e.g. "5³ board, 64 moves, Ann vs AI level 2, W+4.5"
+    pub fn describe(&self) -> String {
+        let record = &self.record;
+        let name = |name: &Option<String>, color: StoneColor| name.clone().unwrap_or_else(|| color.name().to_string());
+        let result = record.result.map_or("unfinished".to_string(), |result| result.to_string());
+        format!(
+            "{}³ board, {} moves, {} vs {}, {}",
+            record.board_size,
+            record.moves.len(),
+            name(&record.black_player, StoneColor::Black),
+            name(&record.white_player, StoneColor::White),
+            result
+        )
+    }
+}
+
+pub struct Archive {
+    backend: Box<dyn Backend>,
+}
+
+impl Archive {
+    pub fn new(backend: Box<dyn Backend>) -> Self {
+        Self { backend }
+    }
+
+    // Adds a game under the next free number and returns its slot
+    pub fn add(&mut self, record: &GameRecord) -> Result<String, StorageError> {
+        let next = self
+            .backend
+            .list()?
+            .iter()
+            .filter_map(|slot| slot.strip_prefix(PREFIX)?.parse::<u32>().ok())
+            .max()
+            .map_or(1, |last| last + 1);
+        let slot = format!("{}{:05}", PREFIX, next);
+        self.backend.save(&slot, &sgf::write(record))?;
+        Ok(slot)
+    }
+
+    // The games `filter` lets through, newest first. Entries that can't be
+    // read or replayed are skipped rather than hiding the rest.
+    pub fn entries(&self, filter: &ArchiveFilter) -> Result<Vec<ArchiveEntry>, StorageError> {
+        let mut slots: Vec<String> = self
+            .backend
+            .list()?
+            .into_iter()
+            .filter(|slot| slot.starts_with(PREFIX))
+            .collect();
+        slots.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut entries = Vec::new();
+        for slot in slots {
+            let Some(data) = self.backend.load(&slot)? else {
+                continue;
+            };
+            let record = sgf::parse(&data)
+                .map_err(|error| error.to_string())
+                .and_then(|record| record.replay().map(|_| record).map_err(|error| error.to_string()));
+            match record {
+                Ok(record) if filter.matches(&record) => entries.push(ArchiveEntry { slot, record }),
+                Ok(_) => {}
+                Err(error) => log::warn!("skipping archived game '{}': {}", slot, error),
+            }
+        }
+        Ok(entries)
+    }
+
+    // The archive screen: one numbered line per game, newest first
+    pub fn describe(&self, filter: &ArchiveFilter) -> Vec<String> {
+        match self.entries(filter) {
+            Ok(entries) if entries.is_empty() => vec!["No archived games".to_string()],
+            Ok(entries) => entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| format!("{:>3}: {}", index + 1, entry.describe()))
+                .collect(),
+            Err(error) => vec![error.to_string()],
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// Attract mode for the web page: after a spell with nobody at the controls,
+// a bundled game plays itself on a slowly turning board under a "click to
+// play" banner. The first key, click or touch puts the live game back as it
+// was, camera included, and is otherwise ignored.
+use crate::game::{sgf, Annotations, CameraPath, GameRecord, GameRules, GameSession};
+use crate::render::CameraPreset;
+use std::time::Duration;
+
+// A lively game on the 4x4x4 board, with plenty of captures
+const DEMO_GAME: &str = include_str!("attract.sgf");
+
+pub const BANNER: &str = "CLICK TO PLAY";
+pub const DEFAULT_IDLE: Duration = Duration::from_secs(60);
+// A move every so often, and a pause on the final position before the game
+// starts over
+const MOVE_SECONDS: f32 = 1.2;
+const FINAL_SECONDS: f32 = 6.0;
+// Radians per second about the vertical axis
+const SPIN: f32 = 0.15;
+
+// Everything the demo replaces, to put back when someone returns
+struct Saved {
+    rules: GameRules,
+    annotations: Annotations,
+    camera_path: CameraPath,
+    camera: CameraPreset,
+}
+
+struct Demo {
+    saved: Saved,
+    moves_played: usize,
+    until_next: f32,
+}
+
+pub struct AttractMode {
+    record: GameRecord,
+    // How long to wait for input before the demo starts; None never starts it
+    idle_after: Option<Duration>,
+    idle: f32,
+    demo: Option<Demo>,
+}
+
+impl AttractMode {
+    pub fn new(idle_after: Option<Duration>) -> Self {
+        let record = sgf::parse(DEMO_GAME).expect("the bundled demo game parses");
+        Self {
+            record,
+            idle_after,
+            idle: 0.0,
+            demo: None,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.demo.is_some()
+    }
+
+    pub fn board_size(&self) -> usize {
+        self.record.board_size
+    }
+
+    // Someone's about, but not asking to play: starts the wait over
+    pub fn activity(&mut self) {
+        self.idle = 0.0;
+    }
+
+    // Someone's here: starts the wait over, and ends the demo if it was
+    // playing, returning the camera to put back. The input that ended the
+    // demo shouldn't be acted on.
+    pub fn input(&mut self, session: &GameSession) -> Option<CameraPreset> {
+        self.idle = 0.0;
+        let saved = self.demo.take()?.saved;
+        session.update(|rules| rules.replace(saved.rules));
+        session.set_annotations(saved.annotations);
+        session.set_camera_path(saved.camera_path);
+        Some(saved.camera)
+    }
+
+    // Advances the wait or the demo by `dt` seconds of real time. While the
+    // demo plays it returns where to put the camera, turned a little from
+    // `camera`; `center` is the middle of the demo board, where it orbits.
+    pub fn update(&mut self, dt: f32, session: &GameSession, camera: CameraPreset, center: glam::Vec3) -> Option<CameraPreset> {
+        match &mut self.demo {
+            None => {
+                let idle_after = self.idle_after?;
+                self.idle += dt;
+                if self.idle < idle_after.as_secs_f32() {
+                    return None;
+                }
+                self.demo = Some(Demo {
+                    saved: Saved {
+                        rules: session.snapshot(),
+                        annotations: session.annotations().clone(),
+                        camera_path: session.camera_path(),
+                        camera,
+                    },
+                    moves_played: 0,
+                    until_next: MOVE_SECONDS,
+                });
+                self.restart(session);
+                Some(CameraPreset { center, ..camera })
+            }
+            Some(demo) => {
+                demo.until_next -= dt;
+                if demo.until_next <= 0.0 {
+                    match self.record.moves.get(demo.moves_played) {
+                        Some(mv) => {
+                            match mv.position {
+                                Some((x, y, z)) => {
+                                    session.make_move(x, y, z);
+                                }
+                                None => session.pass(),
+                            }
+                            demo.moves_played += 1;
+                            demo.until_next = if demo.moves_played == self.record.moves.len() {
+                                FINAL_SECONDS
+                            } else {
+                                MOVE_SECONDS
+                            };
+                        }
+                        None => {
+                            demo.moves_played = 0;
+                            demo.until_next = MOVE_SECONDS;
+                            self.restart(session);
+                        }
+                    }
+                }
+                Some(CameraPreset {
+                    angle_x: camera.angle_x + SPIN * dt,
+                    ..camera
+                })
+            }
+        }
+    }
+
+    // The demo's empty board, ready for its first move
+    fn restart(&self, session: &GameSession) {
+        let start = GameRecord {
+            moves: Vec::new(),
+            ..self.record.clone()
+        };
+        if let Err(error) = session.load(&start) {
+            log::warn!("cannot start the demo game: {}", error);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// Sound effects for game events: rodio on native (behind the `audio` feature),
+// WebAudio in the browser, silence otherwise.
+mod synth;
+
+use crate::game::SessionEvent;
+use crate::settings::AudioSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Stone,
+    Capture,
+    Pass,
+}
+
+impl Sound {
+    // Sounds a session event should trigger, in playback order
+    pub fn for_event(event: &SessionEvent) -> &'static [Sound] {
+        match event {
+            SessionEvent::MovePlayed { captured: 0, .. } => &[Sound::Stone],
+            SessionEvent::MovePlayed { .. } => &[Sound::Capture],
+            SessionEvent::Passed { .. } => &[Sound::Pass],
+            _ => &[],
+        }
+    }
+}
+
+pub struct AudioPlayer {
+    settings: AudioSettings,
+    output: Output,
+}
+
+impl AudioPlayer {
+    pub fn new(settings: AudioSettings) -> Self {
+        Self {
+            settings,
+            output: Output::new(),
+        }
+    }
+
+    pub fn settings(&self) -> AudioSettings {
+        self.settings
+    }
+
+    pub fn toggle_mute(&mut self) -> bool {
+        self.settings.muted = !self.settings.muted;
+        self.settings.muted
+    }
+
+    pub fn adjust_volume(&mut self, delta: f32) -> f32 {
+        self.settings.adjust_volume(delta);
+        self.settings.volume
+    }
+
+    pub fn play(&mut self, sound: Sound) {
+        let gain = self.settings.gain();
+        if gain > 0.0 {
+            self.output.play(synth::render(sound, gain));
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &SessionEvent) {
+        for &sound in Sound::for_event(event) {
+            self.play(sound);
+        }
+    }
+}
+
+#[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+struct Output {
+    // The stream must stay alive for the handle to keep playing
+    device: Option<(rodio::OutputStream, rodio::OutputStreamHandle)>,
+}
+
+#[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+impl Output {
+    fn new() -> Self {
+        let device = rodio::OutputStream::try_default()
+            .map_err(|error| log::warn!("no audio output: {}", error))
+            .ok();
+        Self { device }
+    }
+
+    fn play(&mut self, samples: Vec<f32>) {
+        if let Some((_, handle)) = &self.device {
+            let source = rodio::buffer::SamplesBuffer::new(1, synth::SAMPLE_RATE, samples);
+            if let Err(error) = handle.play_raw(source) {
+                log::warn!("couldn't play sound: {}", error);
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+struct Output {
+    // Created on first use: browsers only allow audio after a user gesture
+    context: Option<web_sys::AudioContext>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Output {
+    fn new() -> Self {
+        Self { context: None }
+    }
+
+    fn play(&mut self, samples: Vec<f32>) {
+        if self.context.is_none() {
+            self.context = web_sys::AudioContext::new().ok();
+        }
+        let Some(context) = &self.context else {
+            return;
+        };
+
+        let result = context
+            .create_buffer(1, samples.len() as u32, synth::SAMPLE_RATE as f32)
+            .and_then(|buffer| {
+                buffer.copy_to_channel(&samples, 0)?;
+                let source = context.create_buffer_source()?;
+                source.set_buffer(Some(&buffer));
+                source.connect_with_audio_node(&context.destination())?;
+                source.start()
+            });
+        if let Err(error) = result {
+            log::warn!("couldn't play sound: {:?}", error);
+        }
+    }
+}
+
+#[cfg(all(not(feature = "audio"), not(target_arch = "wasm32")))]
+struct Output;
+
+#[cfg(all(not(feature = "audio"), not(target_arch = "wasm32")))]
+impl Output {
+    fn new() -> Self {
+        Output
+    }
+
+    fn play(&mut self, _samples: Vec<f32>) {}
+}
\ No newline at end of file
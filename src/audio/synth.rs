@@ -0,0 +1,100 @@
+// Procedural sound effects, so neither build has to ship audio assets
+use super::Sound;
+use std::f32::consts::TAU;
+
+pub const SAMPLE_RATE: u32 = 44_100;
+
+struct Tone {
+    start: f32, // seconds
+    duration: f32,
+    frequency: f32,
+    end_frequency: f32, // linear sweep towards this
+    decay: f32,         // exponential envelope time constant
+    level: f32,
+    noise: bool,
+}
+
+impl Tone {
+    const fn new(start: f32, duration: f32, frequency: f32, decay: f32, level: f32) -> Self {
+        Self {
+            start,
+            duration,
+            frequency,
+            end_frequency: frequency,
+            decay,
+            level,
+            noise: false,
+        }
+    }
+
+    const fn sweep_to(mut self, end_frequency: f32) -> Self {
+        self.end_frequency = end_frequency;
+        self
+    }
+
+    const fn noise(mut self) -> Self {
+        self.noise = true;
+        self
+    }
+}
+
+fn tones(sound: Sound) -> &'static [Tone] {
+    const STONE: &[Tone] = &[
+        Tone::new(0.0, 0.06, 2200.0, 0.008, 0.5),
+        Tone::new(0.0, 0.06, 3300.0, 0.006, 0.3),
+        Tone::new(0.0, 0.02, 0.0, 0.003, 0.4).noise(),
+    ];
+    const CAPTURE: &[Tone] = &[
+        Tone::new(0.0, 0.05, 2200.0, 0.008, 0.4),
+        Tone::new(0.07, 0.05, 2000.0, 0.008, 0.4),
+        Tone::new(0.05, 0.25, 660.0, 0.12, 0.5).sweep_to(330.0),
+    ];
+    const PASS: &[Tone] = &[
+        Tone::new(0.0, 0.15, 440.0, 0.08, 0.4),
+        Tone::new(0.15, 0.2, 330.0, 0.1, 0.4),
+    ];
+
+    match sound {
+        Sound::Stone => STONE,
+        Sound::Capture => CAPTURE,
+        Sound::Pass => PASS,
+    }
+}
+
+// Mono samples in -1.0..=1.0, already scaled by `gain`
+pub fn render(sound: Sound, gain: f32) -> Vec<f32> {
+    let tones = tones(sound);
+    let length = tones
+        .iter()
+        .map(|tone| tone.start + tone.duration)
+        .fold(0.0, f32::max);
+    let mut samples = vec![0.0; (length * SAMPLE_RATE as f32).ceil() as usize];
+
+    // Deterministic noise keeps every click identical
+    let mut seed: u32 = 0x9E37_79B9;
+    for tone in tones {
+        let first = (tone.start * SAMPLE_RATE as f32) as usize;
+        let count = (tone.duration * SAMPLE_RATE as f32) as usize;
+        let mut phase = 0.0;
+
+        for (i, sample) in samples.iter_mut().skip(first).take(count).enumerate() {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let envelope = (-t / tone.decay).exp() * tone.level;
+            let value = if tone.noise {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1 << 23) as f32 - 1.0
+            } else {
+                let progress = t / tone.duration;
+                let frequency = tone.frequency + (tone.end_frequency - tone.frequency) * progress;
+                phase += TAU * frequency / SAMPLE_RATE as f32;
+                phase.sin()
+            };
+            *sample += value * envelope;
+        }
+    }
+
+    for sample in &mut samples {
+        *sample = (*sample * gain).clamp(-1.0, 1.0);
+    }
+    samples
+}
\ No newline at end of file
@@ -0,0 +1,104 @@
+// Measures how strong each AI level plays, so the game can show levels as
+// approximate ranks (see src/calibration.rs for the method). Results are saved
+// where the game looks for them.
+use clap::Parser;
+use go3d::ai::RandomEngine;
+use go3d::calibration::{self, Calibration, Rank};
+use go3d::game::StoneColor;
+use go3d::profiles::Outcome;
+use go3d::settings::{AiBudget, AiSettings, GameSettings};
+use go3d::storage;
+use go3d::tournament;
+
+/// Rank the AI levels by the handicap each can give the random engine
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// Board sizes to measure on
+    #[arg(long, value_delimiter = ',', default_values_t = [3, 4, 5], value_parser = clap::value_parser!(u8).range(GameSettings::MIN_BOARD_SIZE as i64..=GameSettings::MAX_BOARD_SIZE as i64))]
+    sizes: Vec<u8>,
+
+    /// AI levels to measure (default: all above the random baseline)
+    #[arg(long, value_delimiter = ',', value_parser = clap::value_parser!(u8).range(1..=AiSettings::MAX_LEVEL as i64))]
+    levels: Vec<u8>,
+
+    /// Games per handicap step; more gives steadier ranks
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    games: u32,
+
+    /// Search threads for level 2 (default: one per core)
+    #[arg(long, default_value_t = 0, hide_default_value = true)]
+    threads: usize,
+
+    /// Print the ranks without saving them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+// Share of `games` White wins giving Black `handicap` stones
+fn white_win_rate(ai: &AiSettings, settings: &GameSettings, handicap: usize, games: u32) -> f32 {
+    let mut wins = 0;
+    for _ in 0..games {
+        let mut start = settings.new_game();
+        calibration::place_handicap(&mut start, handicap);
+        let mut baseline = RandomEngine::new();
+        let mut engine = ai.create_engine(settings);
+        let result = tournament::play_match_from(start, &mut baseline, &mut *engine, settings, rand::random(), |_, _, _| {});
+        if result.outcome == Outcome::Win(StoneColor::White) {
+            wins += 1;
+        }
+    }
+    wins as f32 / games as f32
+}
+
+fn main() {
+    env_logger::init();
+    let args = Args::parse();
+    let levels = if args.levels.is_empty() {
+        (1..=AiSettings::MAX_LEVEL).collect()
+    } else {
+        args.levels.clone()
+    };
+
+    let mut backend = storage::profile_backend();
+    let mut calibration = Calibration::load(&*backend);
+    for &size in &args.sizes {
+        let settings = GameSettings {
+            board_size: size as usize,
+            ..GameSettings::default()
+        };
+        let most_stones = calibration::handicap_points(settings.board_size).len();
+        for &level in &levels {
+            let ai = AiSettings {
+                enabled: true,
+                level,
+                threads: args.threads,
+                // Ranks measured on battery power would be too low for good
+                budget: AiBudget::Full,
+                ..AiSettings::default()
+            };
+            // The largest handicap still won at least half the time
+            let mut given = None;
+            for handicap in 0..=most_stones {
+                let rate = white_win_rate(&ai, &settings, handicap, args.games);
+                println!("{}³, level {}, {} stones: White won {:.0}%", size, level, handicap, rate * 100.0);
+                if rate < 0.5 {
+                    break;
+                }
+                given = Some(handicap);
+            }
+            let rank = given.map_or(Rank::BASELINE, Rank::from_handicap);
+            println!("{}³, level {}: about {}", size, level, rank);
+            calibration.set(level, settings.board_size, rank);
+        }
+    }
+
+    if args.dry_run {
+        return;
+    }
+    if let Err(error) = calibration.save(&mut *backend) {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+    println!("Saved; the game now shows these ranks next to the AI levels");
+}
\ No newline at end of file
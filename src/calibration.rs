@@ -0,0 +1,200 @@
+// Approximate ranks for the AI levels, so a level can be offered as "about 24
+// kyu" instead of a bare number. The calibrate binary measures them: each level
+// takes White against the random engine, a fixed baseline counted as 30 kyu,
+// and gives it more and more handicap stones until it stops winning most
+// games. Every stone it can still give counts as one rank. Results are kept per
+// board size as `level@size=rank` lines next to the player profiles, after a
+// `version=N` line.
+use crate::game::GameRules;
+use crate::storage::{self, Backend, StorageError};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+type Position = (u8, u8, u8);
+
+const SLOT: &str = "calibration";
+pub const FORMAT_VERSION: u32 = 2;
+pub const BASELINE_KYU: i32 = 30;
+pub const MAX_HANDICAP: usize = 9;
+
+// Kyu count down to 1 kyu, then dan count up from 1 dan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rank {
+    // 1 kyu is 1, 1 dan is 0, 2 dan is -1
+    kyu: i32,
+}
+
+impl Rank {
+    pub const BASELINE: Rank = Rank { kyu: BASELINE_KYU };
+
+    // The rank of a player who can just give the baseline `stones` handicap
+    pub fn from_handicap(stones: usize) -> Self {
+        Self {
+            kyu: BASELINE_KYU - stones as i32,
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kyu > 0 {
+            write!(f, "{} kyu", self.kyu)
+        } else {
+            write!(f, "{} dan", 1 - self.kyu)
+        }
+    }
+}
+
+// "24k" or "2d"
+impl FromStr for Rank {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || format!("'{}' is not a rank (expected e.g. 24k or 2d)", s);
+        let number = |text: &str| text.parse::<i32>().ok().filter(|n| *n >= 1).ok_or_else(bad);
+        let s = s.trim();
+        if let Some(kyu) = s.strip_suffix('k') {
+            Ok(Rank { kyu: number(kyu)? })
+        } else if let Some(dan) = s.strip_suffix('d') {
+            Ok(Rank { kyu: 1 - number(dan)? })
+        } else {
+            Err(bad())
+        }
+    }
+}
+
+fn short(rank: Rank) -> String {
+    if rank.kyu > 0 {
+        format!("{}k", rank.kyu)
+    } else {
+        format!("{}d", 1 - rank.kyu)
+    }
+}
+
+// Where handicap stones go: the center on odd boards, then the corners of a
+// cube set a quarter of the board in from the edges
+pub fn handicap_points(board_size: usize) -> Vec<Position> {
+    let low = (board_size / 4) as u8;
+    let high = (board_size - 1) as u8 - low;
+    let mut points = Vec::new();
+    if board_size % 2 == 1 {
+        let middle = (board_size / 2) as u8;
+        points.push((middle, middle, middle));
+    }
+    for x in [low, high] {
+        for y in [low, high] {
+            for z in [low, high] {
+                if !points.contains(&(x, y, z)) {
+                    points.push((x, y, z));
+                }
+            }
+        }
+    }
+    points.truncate(MAX_HANDICAP);
+    points
+}
+
+// Black places `stones` handicap stones, White passing in between so the
+// record replays like any other game; White moves next
+pub fn place_handicap(rules: &mut GameRules, stones: usize) {
+    for (x, y, z) in handicap_points(rules.board().size()).into_iter().take(stones) {
+        rules.make_move(x, y, z);
+        rules.pass();
+    }
+}
+
+// Brings older calibration lines up to the current format one version at a
+// time
+fn migrate(lines: Vec<String>, version: u32) -> Vec<String> {
+    let mut lines = lines;
+    for from in version..FORMAT_VERSION {
+        lines = match from {
+            // Ranks have been `level@size=rank` lines from the start
+            1 => lines,
+            _ => unreachable!("no migration from calibration version {}", from),
+        };
+    }
+    lines
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Calibration {
+    // By (level, board size)
+    ranks: BTreeMap<(u8, usize), Rank>,
+}
+
+impl Calibration {
+    // Nothing measured yet, or nothing readable, leaves the levels unranked
+    pub fn load(backend: &dyn Backend) -> Self {
+        match backend.load(SLOT) {
+            Ok(Some(data)) => Self::parse(&data),
+            Ok(None) => Self::default(),
+            Err(error) => {
+                log::warn!("{}; AI levels are shown without ranks", error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, backend: &mut dyn Backend) -> Result<(), StorageError> {
+        let mut lines = vec![storage::version_line(FORMAT_VERSION)];
+        lines.extend(
+            self.ranks
+                .iter()
+                .map(|(&(level, size), &rank)| format!("{}@{}={}", level, size, short(rank))),
+        );
+        backend.save(SLOT, &lines.join("\n"))
+    }
+
+    // Ranks from a newer build are left unread, as if nothing were measured
+    fn parse(data: &str) -> Self {
+        let mut calibration = Self::default();
+        let (version, lines) = match storage::split_version(data, FORMAT_VERSION) {
+            Ok(split) => split,
+            Err(error) => {
+                log::warn!("{}; AI levels are shown without ranks", error);
+                return calibration;
+            }
+        };
+        for line in migrate(lines, version).iter().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let read = line.split_once('=').and_then(|(key, rank)| {
+                let (level, size) = key.trim().split_once('@')?;
+                let key = (level.parse().ok()?, size.parse().ok()?);
+                calibration.ranks.insert(key, rank.parse().ok()?);
+                Some(())
+            });
+            if read.is_none() {
+                log::warn!("skipping unreadable calibration line '{}'", line);
+            }
+        }
+        calibration
+    }
+
+    pub fn set(&mut self, level: u8, board_size: usize, rank: Rank) {
+        self.ranks.insert((level, board_size), rank);
+    }
+
+    // Measured on this board size, or else on the nearest size measured; level
+    // 0 is the baseline itself
+    pub fn rank(&self, level: u8, board_size: usize) -> Option<(Rank, usize)> {
+        if level == 0 {
+            return Some((Rank::BASELINE, board_size));
+        }
+        self.ranks
+            .iter()
+            .filter(|(&(measured_level, _), _)| measured_level == level)
+            .min_by_key(|(&(_, size), _)| size.abs_diff(board_size))
+            .map(|(&(_, size), &rank)| (rank, size))
+    }
+
+    // "AI level 1 (about 24 kyu)", naming the board size measured on if it
+    // isn't the one being played
+    pub fn label(&self, level: u8, board_size: usize) -> String {
+        match self.rank(level, board_size) {
+            Some((rank, size)) if size == board_size => format!("AI level {} (about {})", level, rank),
+            Some((rank, size)) => format!("AI level {} (about {} on {}³ boards)", level, rank, size),
+            None => format!("AI level {}", level),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+// Crash reports for native builds. A panic writes the message, a backtrace,
+// the settings in use and the game so far to the crash storage; the record
+// leaves out player names and annotations, which are the user's own. The
+// next launch offers to copy the newest report to the clipboard, ready to
+// paste into a bug report.
+use crate::game::{sgf, Annotations, GameRecord};
+use crate::storage::{self, Backend, StorageError};
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Names the report the next launch hasn't offered yet
+const PENDING: &str = "pending";
+const PREFIX: &str = "crash-";
+
+// Where the game stood, as of the last move
+struct Context {
+    settings: String,
+    record: Option<String>,
+}
+
+static CONTEXT: Mutex<Context> = Mutex::new(Context {
+    settings: String::new(),
+    record: None,
+});
+
+// Describes the settings in use for the report
+pub fn set_settings(settings: String) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.settings = settings;
+    }
+}
+
+// Keeps the game as it stands for the report, anonymized
+pub fn set_game(record: &GameRecord) {
+    let record = GameRecord {
+        annotations: Annotations::default(),
+        black_player: None,
+        white_player: None,
+        ..record.clone()
+    };
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.record = Some(sgf::write(&record));
+    }
+}
+
+// Writes a report on every panic, after the usual message
+pub fn install() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        let report = report(info);
+        match save_report(&mut *storage::crash_backend(), &report) {
+            Ok(slot) => eprintln!("A crash report was saved as {}; the next launch offers to copy it", slot),
+            Err(error) => eprintln!("couldn't save a crash report: {}", error),
+        }
+    }));
+}
+
+fn report(info: &PanicHookInfo<'_>) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "(no message)".to_string());
+    let location = info.location().map_or("unknown".to_string(), |location| location.to_string());
+    // The panic may have struck while the context was being updated
+    let (settings, record) = match CONTEXT.try_lock() {
+        Ok(context) => (context.settings.clone(), context.record.clone()),
+        Err(_) => (String::new(), None),
+    };
+    format!(
+        "go3d {} crash report ({} {})\n\npanic at {}: {}\n\nbacktrace:\n{}\n\nsettings:\n{}\n\ngame (player names and annotations left out):\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        location,
+        message,
+        std::backtrace::Backtrace::force_capture(),
+        settings,
+        record.as_deref().unwrap_or("(no game yet)")
+    )
+}
+
+// Saves a report under the time it was written and marks it for the next
+// launch to offer; returns its slot
+pub fn save_report(backend: &mut dyn Backend, report: &str) -> Result<String, StorageError> {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let slot = format!("{}{}", PREFIX, seconds);
+    backend.save(&slot, report)?;
+    backend.save(PENDING, &slot)?;
+    Ok(slot)
+}
+
+// The report not yet offered, with its slot; each one is offered once
+pub fn take_pending(backend: &mut dyn Backend) -> Result<Option<(String, String)>, StorageError> {
+    let Some(slot) = backend.load(PENDING)? else {
+        return Ok(None);
+    };
+    backend.delete(PENDING)?;
+    let slot = slot.trim().to_string();
+    Ok(backend.load(&slot)?.map(|report| (slot, report)))
+}
+
+// Through the platform's clipboard tool, the first one that's installed
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    const TOOLS: &[(&str, &[&str])] = &[
+        ("pbcopy", &[]),
+        ("clip", &[]),
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+    for (program, args) in TOOLS {
+        let Ok(mut child) = Command::new(program).args(*args).stdin(Stdio::piped()).spawn() else {
+            continue;
+        };
+        let written = child.stdin.take().is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        if child.wait().is_ok_and(|status| status.success()) && written {
+            return Ok(());
+        }
+    }
+    Err("no clipboard tool found (pbcopy, clip, wl-copy, xclip or xsel)".to_string())
+}
@@ -0,0 +1,169 @@
+// Open/save for game records and exports. Native builds use the platform's file
+// dialogs (with the `dialogs` feature); the browser build downloads and uploads
+// through temporary <a download> and <input type="file"> elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Game,
+    Gltf,
+    Booklet,
+}
+
+impl FileKind {
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    fn description(self) -> &'static str {
+        match self {
+            FileKind::Game => "SGF-3D game record",
+            FileKind::Gltf => "glTF scene",
+            FileKind::Booklet => "PDF booklet",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            FileKind::Game => "sgf",
+            FileKind::Gltf => "gltf",
+            FileKind::Booklet => "pdf",
+        }
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    fn mime_type(self) -> &'static str {
+        match self {
+            FileKind::Game => "application/x-go-sgf",
+            FileKind::Gltf => "model/gltf+json",
+            FileKind::Booklet => "application/pdf",
+        }
+    }
+
+    fn default_file_name(self) -> String {
+        match self {
+            FileKind::Game => format!("3dgo-game.{}", self.extension()),
+            FileKind::Gltf => format!("3dgo-board.{}", self.extension()),
+            FileKind::Booklet => format!("3dgo-booklet.{}", self.extension()),
+        }
+    }
+}
+
+#[cfg(all(feature = "dialogs", not(target_arch = "wasm32")))]
+mod platform {
+    use super::FileKind;
+
+    fn dialog(kind: FileKind) -> rfd::FileDialog {
+        rfd::FileDialog::new().add_filter(kind.description(), &[kind.extension()])
+    }
+
+    // Returns where the file went, or None if the user cancelled
+    pub fn save_file(kind: FileKind, contents: &str) -> Result<Option<String>, String> {
+        let Some(path) = dialog(kind).set_file_name(kind.default_file_name()).save_file() else {
+            return Ok(None);
+        };
+        std::fs::write(&path, contents)
+            .map(|_| Some(path.display().to_string()))
+            .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    }
+
+    pub fn open_file(kind: FileKind, on_loaded: impl FnOnce(String) + 'static) -> Result<(), String> {
+        let Some(path) = dialog(kind).pick_file() else {
+            return Ok(());
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        on_loaded(text);
+        Ok(())
+    }
+}
+
+#[cfg(all(not(feature = "dialogs"), not(target_arch = "wasm32")))]
+mod platform {
+    use super::FileKind;
+
+    // Without dialogs, saves land in the working directory
+    pub fn save_file(kind: FileKind, contents: &str) -> Result<Option<String>, String> {
+        let path = kind.default_file_name();
+        std::fs::write(&path, contents)
+            .map(|_| Some(path.clone()))
+            .map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    pub fn open_file(kind: FileKind, _on_loaded: impl FnOnce(String) + 'static) -> Result<(), String> {
+        Err(format!(
+            "opening a {} needs the `dialogs` feature; use --load instead",
+            kind.description()
+        ))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use super::FileKind;
+    use wasm_bindgen::prelude::*;
+    use web_sys::{Blob, BlobPropertyBag, FileReader, HtmlAnchorElement, HtmlInputElement, Url};
+
+    fn js_error(error: JsValue) -> String {
+        format!("{:?}", error)
+    }
+
+    fn document() -> Result<web_sys::Document, String> {
+        web_sys::window()
+            .and_then(|win| win.document())
+            .ok_or_else(|| "no document".to_string())
+    }
+
+    // Starts a download; the browser decides where the file goes
+    pub fn save_file(kind: FileKind, contents: &str) -> Result<Option<String>, String> {
+        let options = BlobPropertyBag::new();
+        options.set_type(kind.mime_type());
+        let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+        let blob = Blob::new_with_str_sequence_and_options(&parts, &options).map_err(js_error)?;
+        let url = Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+        let anchor: HtmlAnchorElement = document()?
+            .create_element("a")
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| "couldn't create download link".to_string())?;
+        let file_name = kind.default_file_name();
+        anchor.set_href(&url);
+        anchor.set_download(&file_name);
+        anchor.click();
+        Url::revoke_object_url(&url).map_err(js_error)?;
+        Ok(Some(file_name))
+    }
+
+    // Shows the browser's file picker; `on_loaded` runs once the file has been read
+    pub fn open_file(kind: FileKind, on_loaded: impl FnOnce(String) + 'static) -> Result<(), String> {
+        let input: HtmlInputElement = document()?
+            .create_element("input")
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| "couldn't create file input".to_string())?;
+        input.set_type("file");
+        input.set_accept(&format!(".{}", kind.extension()));
+
+        let target = input.clone();
+        let on_change = Closure::once_into_js(move || {
+            let Some(file) = target.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let Ok(reader) = FileReader::new() else {
+                return;
+            };
+            let source = reader.clone();
+            let on_load = Closure::once_into_js(move || {
+                match source.result().ok().and_then(|result| result.as_string()) {
+                    Some(text) => on_loaded(text),
+                    None => log::warn!("couldn't read the selected file"),
+                }
+            });
+            reader.set_onload(Some(on_load.unchecked_ref()));
+            if let Err(error) = reader.read_as_text(&file) {
+                log::warn!("couldn't read the selected file: {:?}", error);
+            }
+        });
+        input.set_onchange(Some(on_change.unchecked_ref()));
+        input.click();
+        Ok(())
+    }
+}
+
+pub use platform::{open_file, save_file};
\ No newline at end of file
@@ -0,0 +1,234 @@
+// Named AI configurations, so an experiment can be run again with the same
+// engine: which engine plays and, for MCTS, its playouts and threads. Kept as
+// `key=value` lines through their own storage backend, one slot per name,
+// and read strictly: a line that can't be understood, or a configuration
+// from a newer build, refuses the whole configuration rather than letting it
+// play with something else.
+use crate::settings::AiSettings;
+use crate::storage::{self, Backend, StorageError, VersionError};
+use std::fmt;
+
+// Indexed by AI level
+const ENGINES: [&str; AiSettings::MAX_LEVEL as usize + 1] = ["random", "greedy", "mcts"];
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineConfigError {
+    BadName(String),
+    // The line number and the line itself
+    BadLine(usize, String),
+    UnknownEngine(String),
+    NotFound(String),
+    Version(VersionError),
+    Storage(String),
+}
+
+impl fmt::Display for EngineConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineConfigError::BadName(name) => {
+                write!(f, "engine name '{}' must be 1-32 letters, digits, '-' or '_'", name)
+            }
+            EngineConfigError::BadLine(number, line) => write!(f, "line {}: cannot read '{}'", number, line),
+            EngineConfigError::UnknownEngine(engine) => {
+                write!(f, "unknown engine '{}', expected {}", engine, ENGINES.join(", "))
+            }
+            EngineConfigError::NotFound(name) => write!(f, "no saved engine named '{}' (--engines lists them)", name),
+            EngineConfigError::Version(error) => write!(f, "engine configuration: {}", error),
+            EngineConfigError::Storage(reason) => write!(f, "couldn't reach saved engines: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for EngineConfigError {}
+
+impl From<StorageError> for EngineConfigError {
+    fn from(error: StorageError) -> Self {
+        EngineConfigError::Storage(error.to_string())
+    }
+}
+
+impl From<VersionError> for EngineConfigError {
+    fn from(error: VersionError) -> Self {
+        EngineConfigError::Version(error)
+    }
+}
+
+// Brings older configuration lines up to the current format one version at a
+// time
+fn migrate(lines: Vec<String>, version: u32) -> Vec<String> {
+    let mut lines = lines;
+    for from in version..FORMAT_VERSION {
+        lines = match from {
+            // Name, engine, playouts and threads are all there has been
+            1 => lines,
+            _ => unreachable!("no migration from engine configuration version {}", from),
+        };
+    }
+    lines
+}
+
+// Names double as storage slots, and so as file names
+pub fn validate_name(name: &str) -> Result<(), EngineConfigError> {
+    let length = name.chars().count();
+    if !(1..=32).contains(&length) || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(EngineConfigError::BadName(name.to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    pub name: String,
+    // The AI level of the engine: 0 random, 1 greedy, 2 MCTS
+    pub level: u8,
+    pub playouts: usize,
+    // 0 means one per core
+    pub threads: usize,
+}
+
+impl EngineConfig {
+    pub fn new(name: &str, ai: &AiSettings) -> Result<Self, EngineConfigError> {
+        validate_name(name)?;
+        Ok(Self {
+            name: name.to_string(),
+            level: ai.level.min(AiSettings::MAX_LEVEL),
+            playouts: ai.playouts,
+            threads: ai.threads,
+        })
+    }
+
+    pub fn engine(&self) -> &'static str {
+        ENGINES[self.level as usize]
+    }
+
+    // Ready to play, on the default side
+    pub fn ai_settings(&self) -> AiSettings {
+        AiSettings {
+            enabled: true,
+            level: self.level,
+            playouts: self.playouts,
+            threads: self.threads,
+            ..AiSettings::default()
+        }
+    }
+
+    // e.g. "deep: mcts, 5000 playouts, 4 threads"
+    pub fn describe(&self) -> String {
+        if self.level < AiSettings::MAX_LEVEL {
+            return format!("{}: {}", self.name, self.engine());
+        }
+        let threads = match self.threads {
+            0 => "a thread per core".to_string(),
+            1 => "1 thread".to_string(),
+            threads => format!("{} threads", threads),
+        };
+        format!("{}: {}, {} playouts, {}", self.name, self.engine(), self.playouts, threads)
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "{}\nname={}\nengine={}\nplayouts={}\nthreads={}\n",
+            storage::version_line(FORMAT_VERSION),
+            self.name,
+            self.engine(),
+            self.playouts,
+            self.threads
+        )
+    }
+
+    // `name` is used when the text doesn't carry one of its own. Blank lines
+    // and lines starting with '#' are skipped; settings left out keep the
+    // defaults of `AiSettings`.
+    pub fn parse(text: &str, name: &str) -> Result<Self, EngineConfigError> {
+        let defaults = AiSettings::default();
+        let mut config = Self {
+            name: name.to_string(),
+            level: defaults.level,
+            playouts: defaults.playouts,
+            threads: defaults.threads,
+        };
+        let (version, lines) = storage::split_version(text, FORMAT_VERSION)?;
+        for (index, line) in migrate(lines, version).iter().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad_line = || EngineConfigError::BadLine(index + 1, line.to_string());
+            let (key, value) = line.split_once('=').ok_or_else(bad_line)?;
+            let value = value.trim();
+            match key.trim() {
+                "name" => config.name = value.to_string(),
+                "engine" => {
+                    let level = ENGINES
+                        .iter()
+                        .position(|engine| engine.eq_ignore_ascii_case(value))
+                        .ok_or_else(|| EngineConfigError::UnknownEngine(value.to_string()))?;
+                    config.level = level as u8;
+                }
+                "playouts" => config.playouts = value.parse().ok().filter(|&playouts| playouts > 0).ok_or_else(bad_line)?,
+                "threads" => config.threads = value.parse().map_err(|_| bad_line())?,
+                _ => return Err(bad_line()),
+            }
+        }
+        validate_name(&config.name)?;
+        Ok(config)
+    }
+}
+
+pub struct EngineConfigs {
+    backend: Box<dyn Backend>,
+}
+
+impl EngineConfigs {
+    pub fn new(backend: Box<dyn Backend>) -> Self {
+        Self { backend }
+    }
+
+    // Every saved configuration, by name. One that can't be read is skipped
+    // with a warning rather than hiding the rest.
+    pub fn list(&self) -> Result<Vec<EngineConfig>, EngineConfigError> {
+        let mut names = self.backend.list()?;
+        names.sort_unstable();
+        let mut configs = Vec::new();
+        for name in names {
+            match self.get(&name) {
+                Ok(config) => configs.push(config),
+                Err(error) => log::warn!("skipping saved engine '{}': {}", name, error),
+            }
+        }
+        Ok(configs)
+    }
+
+    pub fn get(&self, name: &str) -> Result<EngineConfig, EngineConfigError> {
+        validate_name(name)?;
+        let text = self
+            .backend
+            .load(name)?
+            .ok_or_else(|| EngineConfigError::NotFound(name.to_string()))?;
+        // The slot names the configuration, whatever the text inside says
+        Ok(EngineConfig {
+            name: name.to_string(),
+            ..EngineConfig::parse(&text, name)?
+        })
+    }
+
+    // Replaces any configuration of the same name
+    pub fn save(&mut self, config: &EngineConfig) -> Result<(), EngineConfigError> {
+        validate_name(&config.name)?;
+        self.backend.save(&config.name, &config.to_text())?;
+        Ok(())
+    }
+
+    // Saves a configuration written elsewhere, named by its `name` line or
+    // else `name`
+    pub fn import(&mut self, text: &str, name: &str) -> Result<EngineConfig, EngineConfigError> {
+        let config = EngineConfig::parse(text, name)?;
+        self.save(&config)?;
+        Ok(config)
+    }
+
+    pub fn export(&self, name: &str) -> Result<String, EngineConfigError> {
+        Ok(self.get(name)?.to_text())
+    }
+}
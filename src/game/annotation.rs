@@ -0,0 +1,152 @@
+// Review and teaching markup on intersections: letter labels, triangles,
+// squares and colored markers. Like SGF markup, each annotation belongs to
+// one node of the game, i.e. it shows while that many moves have been played.
+use super::Symmetry;
+use std::fmt;
+use std::str::FromStr;
+
+type Position = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerColor {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+}
+
+impl MarkerColor {
+    pub const ALL: [MarkerColor; 4] = [MarkerColor::Red, MarkerColor::Green, MarkerColor::Blue, MarkerColor::Yellow];
+
+    pub fn rgba(self) -> [f32; 4] {
+        match self {
+            MarkerColor::Red => [0.9, 0.2, 0.15, 1.0],
+            MarkerColor::Green => [0.2, 0.8, 0.3, 1.0],
+            MarkerColor::Blue => [0.2, 0.45, 0.95, 1.0],
+            MarkerColor::Yellow => [0.95, 0.85, 0.15, 1.0],
+        }
+    }
+}
+
+impl FromStr for MarkerColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "red" => Ok(MarkerColor::Red),
+            "green" => Ok(MarkerColor::Green),
+            "blue" => Ok(MarkerColor::Blue),
+            "yellow" => Ok(MarkerColor::Yellow),
+            other => Err(format!("unknown marker color '{}'", other)),
+        }
+    }
+}
+
+impl fmt::Display for MarkerColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkerColor::Red => f.write_str("red"),
+            MarkerColor::Green => f.write_str("green"),
+            MarkerColor::Blue => f.write_str("blue"),
+            MarkerColor::Yellow => f.write_str("yellow"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    // An uppercase letter, A to Z
+    Label(char),
+    Triangle,
+    Square,
+    Marker(MarkerColor),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Annotation {
+    pub move_number: usize,
+    pub position: Position,
+    pub mark: Mark,
+}
+
+// At most one mark per intersection and node
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Annotations {
+    marks: Vec<Annotation>,
+}
+
+impl Annotations {
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Annotation> {
+        self.marks.iter()
+    }
+
+    // The marks shown after `move_number` moves
+    pub fn at(&self, move_number: usize) -> impl Iterator<Item = &Annotation> {
+        self.marks.iter().filter(move |annotation| annotation.move_number == move_number)
+    }
+
+    // Replaces whatever mark the point had in that node
+    pub fn insert(&mut self, annotation: Annotation) {
+        self.remove(annotation.move_number, annotation.position);
+        self.marks.push(annotation);
+    }
+
+    pub fn remove(&mut self, move_number: usize, position: Position) -> Option<Mark> {
+        let index = self
+            .marks
+            .iter()
+            .position(|a| a.move_number == move_number && a.position == position)?;
+        Some(self.marks.remove(index).mark)
+    }
+
+    // Placing the same kind of mark again takes it off, so the tools work as
+    // toggles; a label tool places the next free letter. Returns the mark now
+    // on the point, if any.
+    pub fn toggle(&mut self, move_number: usize, position: Position, mark: Mark) -> Option<Mark> {
+        let removed = self.remove(move_number, position);
+        let same_kind = match (removed, mark) {
+            (Some(Mark::Label(_)), Mark::Label(_)) => true,
+            (Some(old), new) => old == new,
+            (None, _) => false,
+        };
+        if same_kind {
+            return None;
+        }
+
+        let mark = match mark {
+            Mark::Label(_) => Mark::Label(self.next_label(move_number)?),
+            other => other,
+        };
+        self.marks.push(Annotation {
+            move_number,
+            position,
+            mark,
+        });
+        Some(mark)
+    }
+
+    // First letter not yet used as a label in that node
+    pub fn next_label(&self, move_number: usize) -> Option<char> {
+        ('A'..='Z').find(|&letter| !self.at(move_number).any(|a| a.mark == Mark::Label(letter)))
+    }
+
+    // Forgets the nodes after `move_number`, e.g. when a new move replaces them
+    pub fn truncate(&mut self, move_number: usize) {
+        self.marks.retain(|annotation| annotation.move_number <= move_number);
+    }
+
+    pub fn clear(&mut self) {
+        self.marks.clear();
+    }
+
+    // Moves every mark to where `symmetry` takes its point
+    pub fn transform(&mut self, symmetry: Symmetry, size: usize) {
+        for annotation in &mut self.marks {
+            annotation.position = symmetry.apply(annotation.position, size);
+        }
+    }
+}
\ No newline at end of file
@@ -1,5 +1,8 @@
-use super::{Stone, StoneColor};
+use super::topology::{Lattice, Topology};
+use super::zobrist;
+use super::StoneColor;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 pub const BOARD_SIZE: usize = 3;
 
@@ -9,8 +12,11 @@ type Position = (u8, u8, u8);
 pub struct Board {
     stones: HashMap<Position, StoneColor>,
     size: usize,
-    captured_black: usize,
-    captured_white: usize,
+    // Stones of each color taken off the board, by StoneColor::index
+    captured: [usize; StoneColor::MAX_PLAYERS as usize],
+    // Zobrist hash of the stones, kept up to date as they change
+    hash: u64,
+    topology: Arc<dyn Topology>,
 }
 
 impl Default for Board {
@@ -21,14 +27,24 @@ impl Default for Board {
 
 impl Board {
     pub fn new(size: usize) -> Self {
+        Self::with_topology(size, Arc::new(Lattice))
+    }
+
+    // An empty board of the points `topology` keeps within the size³ cube
+    pub fn with_topology(size: usize, topology: Arc<dyn Topology>) -> Self {
         Self {
             stones: HashMap::new(),
             size,
-            captured_black: 0,
-            captured_white: 0,
+            captured: [0; StoneColor::MAX_PLAYERS as usize],
+            hash: 0,
+            topology,
         }
     }
 
+    pub fn topology(&self) -> &dyn Topology {
+        &*self.topology
+    }
+
     pub fn new_with_dodecahedron(size: usize) -> Self {
         let mut board = Self::new(size);
         board.place_dodecahedron();
@@ -68,7 +84,7 @@ impl Board {
         ];
 
         // Place black stones at scaled dodecahedron vertices
-        for (i, &(x, y, z)) in vertices.iter().enumerate() {
+        for &(x, y, z) in vertices.iter() {
             let pos_x = (center + x * radius) as u8;
             let pos_y = (center + y * radius) as u8;  
             let pos_z = (center + z * radius) as u8;
@@ -99,8 +115,8 @@ impl Board {
 
     pub fn clear(&mut self) {
         self.stones.clear();
-        self.captured_black = 0;
-        self.captured_white = 0;
+        self.hash = 0;
+        self.captured = [0; StoneColor::MAX_PLAYERS as usize];
     }
 
     pub fn reset_with_dodecahedron(&mut self) {
@@ -146,7 +162,7 @@ impl Board {
         let mut layers_y = std::collections::HashSet::new();
         let mut layers_z = std::collections::HashSet::new();
         
-        for ((x, y, z), _) in &self.stones {
+        for (x, y, z) in self.stones.keys() {
             layers_x.insert(*x);
             layers_y.insert(*y);
             layers_z.insert(*z);
@@ -164,10 +180,7 @@ impl Board {
                 print!("  ");
                 for x in 0..3 {
                     if let Some(color) = self.get_stone((x, y, z)) {
-                        match color {
-                            StoneColor::Black => print!("B "),
-                            StoneColor::White => print!("W "),
-                        }
+                        print!("{} ", color.letter());
                     } else {
                         print!(". ");
                     }
@@ -182,7 +195,10 @@ impl Board {
     }
 
     pub fn is_valid_position(&self, x: u8, y: u8, z: u8) -> bool {
-        (x as usize) < self.size && (y as usize) < self.size && (z as usize) < self.size
+        (x as usize) < self.size
+            && (y as usize) < self.size
+            && (z as usize) < self.size
+            && self.topology.contains(self.size, (x, y, z))
     }
 
     pub fn get_stone(&self, pos: Position) -> Option<StoneColor> {
@@ -197,37 +213,23 @@ impl Board {
         }
 
         self.stones.insert(pos, color);
+        self.hash ^= zobrist::stone_key(pos, color);
         true
     }
 
     pub fn remove_stone(&mut self, pos: Position) -> Option<StoneColor> {
-        self.stones.remove(&pos)
+        let color = self.stones.remove(&pos)?;
+        self.hash ^= zobrist::stone_key(pos, color);
+        Some(color)
     }
 
-    pub fn get_neighbors(&self, pos: Position) -> Vec<Position> {
-        let (x, y, z) = pos;
-        let mut neighbors = Vec::new();
-        
-        let directions = [
-            (-1, 0, 0), (1, 0, 0),   // x-axis
-            (0, -1, 0), (0, 1, 0),   // y-axis  
-            (0, 0, -1), (0, 0, 1),   // z-axis
-        ];
-
-        for (dx, dy, dz) in directions {
-            let nx = x as i8 + dx;
-            let ny = y as i8 + dy;
-            let nz = z as i8 + dz;
-
-            if nx >= 0 && ny >= 0 && nz >= 0 {
-                let (nx, ny, nz) = (nx as u8, ny as u8, nz as u8);
-                if self.is_valid_position(nx, ny, nz) {
-                    neighbors.push((nx, ny, nz));
-                }
-            }
-        }
+    // Equal for boards with the same stones, whatever the captures
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
 
-        neighbors
+    pub fn get_neighbors(&self, pos: Position) -> Vec<Position> {
+        self.topology.neighbors(self.size, pos)
     }
 
     pub fn get_group(&self, pos: Position) -> Option<HashSet<Position>> {
@@ -270,6 +272,32 @@ impl Board {
         liberties
     }
 
+    // Every group on the board with its number of liberties, each found once
+    pub fn groups(&self) -> Vec<(HashSet<Position>, usize)> {
+        let mut seen = HashSet::new();
+        let mut groups = Vec::new();
+        for &position in self.stones.keys() {
+            if seen.contains(&position) {
+                continue;
+            }
+            let Some(group) = self.get_group(position) else {
+                continue;
+            };
+            let liberties = self.get_liberties(&group).len();
+            seen.extend(group.iter().copied());
+            groups.push((group, liberties));
+        }
+        groups
+    }
+
+    // The liberties of every stone's group, each group counted once
+    pub fn liberty_counts(&self) -> HashMap<Position, usize> {
+        self.groups()
+            .into_iter()
+            .flat_map(|(group, liberties)| group.into_iter().map(move |stone| (stone, liberties)))
+            .collect()
+    }
+
     pub fn has_liberties(&self, pos: Position) -> bool {
         if let Some(group) = self.get_group(pos) {
             !self.get_liberties(&group).is_empty()
@@ -284,10 +312,7 @@ impl Board {
         for pos in group {
             if let Some(color) = self.remove_stone(pos) {
                 captured += 1;
-                match color {
-                    StoneColor::Black => self.captured_black += 1,
-                    StoneColor::White => self.captured_white += 1,
-                }
+                self.captured[color.index()] += 1;
             }
         }
 
@@ -295,13 +320,55 @@ impl Board {
     }
 
     pub fn get_captured(&self, color: StoneColor) -> usize {
-        match color {
-            StoneColor::Black => self.captured_black,
-            StoneColor::White => self.captured_white,
-        }
+        self.captured[color.index()]
     }
 
     pub fn get_all_stones(&self) -> impl Iterator<Item = (&Position, &StoneColor)> {
         self.stones.iter()
     }
+
+    // Every point of the board in (x, y, z) order
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        let size = self.size as u8;
+        (0..size)
+            .flat_map(move |x| (0..size).flat_map(move |y| (0..size).map(move |z| (x, y, z))))
+            .filter(|&(x, y, z)| self.is_valid_position(x, y, z))
+    }
+
+    // The points the topology cut out of the cube, which are never played and
+    // don't count as empty: no liberties, no territory
+    pub fn holes(&self) -> impl Iterator<Item = Position> + '_ {
+        let size = self.size as u8;
+        (0..size)
+            .flat_map(move |x| (0..size).flat_map(move |y| (0..size).map(move |z| (x, y, z))))
+            .filter(|&position| self.topology.is_hole(self.size, position))
+    }
+
+    // The points without a stone, in (x, y, z) order
+    pub fn empty_positions(&self) -> impl Iterator<Item = Position> + '_ {
+        self.positions().filter(|position| !self.stones.contains_key(position))
+    }
+
+    // Where `color`'s stones are, in no particular order
+    pub fn stones_of(&self, color: StoneColor) -> impl Iterator<Item = Position> + '_ {
+        self.stones
+            .iter()
+            .filter(move |&(_, &stone)| stone == color)
+            .map(|(&position, _)| position)
+    }
+
+    // The plane of points whose coordinate on `axis` (0, 1, 2 for x, y, z) is
+    // `index`, in order of the other two coordinates
+    pub fn layer(&self, axis: usize, index: u8) -> impl Iterator<Item = Position> + '_ {
+        assert!(axis < 3, "no board axis {}", axis);
+        let size = self.size as u8;
+        (0..size).flat_map(move |a| {
+            (0..size).map(move |b| match axis {
+                0 => (index, a, b),
+                1 => (a, index, b),
+                _ => (a, b, index),
+            })
+        })
+        .filter(|&(x, y, z)| self.is_valid_position(x, y, z))
+    }
 }
\ No newline at end of file
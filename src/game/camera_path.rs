@@ -0,0 +1,118 @@
+// Recorded camera motion for fly-arounds: keyframes of the orbit camera on
+// the replay clock, so playing a game back plays the camera with it. Saved
+// with the game record.
+use std::fmt;
+use std::str::FromStr;
+
+// Where the orbit camera is: the same numbers as the renderer's camera preset,
+// kept free of the math library so records don't depend on it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraView {
+    pub distance: f32,
+    pub angle_x: f32,
+    pub angle_y: f32,
+    pub center: [f32; 3],
+}
+
+impl CameraView {
+    fn lerp(self, other: CameraView, t: f32) -> CameraView {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        CameraView {
+            distance: mix(self.distance, other.distance),
+            angle_x: mix(self.angle_x, other.angle_x),
+            angle_y: mix(self.angle_y, other.angle_y),
+            center: [
+                mix(self.center[0], other.center[0]),
+                mix(self.center[1], other.center[1]),
+                mix(self.center[2], other.center[2]),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraKeyframe {
+    // Seconds on the replay clock, from the empty board
+    pub time: f32,
+    pub view: CameraView,
+}
+
+// Written as the time and view separated by colons, as in an SGF value
+impl fmt::Display for CameraKeyframe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let view = &self.view;
+        let [x, y, z] = view.center;
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:{}:{}",
+            self.time, view.distance, view.angle_x, view.angle_y, x, y, z
+        )
+    }
+}
+
+impl FromStr for CameraKeyframe {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(':')
+            .map(|value| value.trim().parse::<f32>().ok().filter(|value| value.is_finite()))
+            .collect::<Option<Vec<f32>>>();
+        match values.as_deref() {
+            Some(&[time, distance, angle_x, angle_y, x, y, z]) if time >= 0.0 => Ok(CameraKeyframe {
+                time,
+                view: CameraView {
+                    distance,
+                    angle_x,
+                    angle_y,
+                    center: [x, y, z],
+                },
+            }),
+            _ => Err(format!("invalid camera keyframe '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    // Keyframes go in time order; one earlier than the last replaces the rest
+    // of the path from there
+    pub fn push(&mut self, keyframe: CameraKeyframe) {
+        self.keyframes.retain(|existing| existing.time < keyframe.time);
+        self.keyframes.push(keyframe);
+    }
+
+    // Seconds from the first keyframe's time to the last's
+    pub fn duration(&self) -> f32 {
+        match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    // The view at `time`, eased linearly between the keyframes either side
+    // and held at the ends; None for an empty path
+    pub fn sample(&self, time: f32) -> Option<CameraView> {
+        let after = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+        match (after.checked_sub(1).map(|i| self.keyframes[i]), self.keyframes.get(after).copied()) {
+            (Some(before), Some(after)) => {
+                let t = (time - before.time) / (after.time - before.time);
+                Some(before.view.lerp(after.view, t))
+            }
+            (Some(only), None) | (None, Some(only)) => Some(only.view),
+            (None, None) => None,
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// Compact binary games: the rules a game was played under and its moves, a
+// varint each, so on boards up to 25³ a move takes two bytes at most and on
+// the largest, MAX_SIZE³ = 26³, three. Colors aren't stored; they follow the
+// turn order. A move list rather than a board keeps the history that ko and
+// superko need, and the extra colors and holes that two bits a point
+// couldn't hold.
+//
+//   version (1 byte), board size (1), players (1), komi (f32, 4, little
+//   endian), variant name length (1) and name (empty for standard), then one
+//   varint per move: 0 to pass, or 1 + x + y·size + z·size² to place
+//
+// Records that carry anything more (annotations, camera paths, thinking
+// times, players' names, results, or colors out of turn) have no compact
+// form and stay SGF-3D.
+use super::{GameRecord, MoveRecord, StoneColor, Variant};
+use std::fmt;
+
+const VERSION: u8 = 1;
+// As in SGF-3D, one letter a coordinate
+const MAX_SIZE: usize = 26;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    Truncated,
+    UnsupportedVersion(u8),
+    InvalidSize(u8),
+    InvalidPlayers(u8),
+    InvalidKomi,
+    InvalidVariant(String),
+    InvalidMove(u64),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Truncated => write!(f, "the game data is cut short"),
+            CodecError::UnsupportedVersion(version) => write!(f, "unknown game data version {}", version),
+            CodecError::InvalidSize(size) => write!(f, "invalid board size {}", size),
+            CodecError::InvalidPlayers(players) => write!(f, "invalid number of players {}", players),
+            CodecError::InvalidKomi => write!(f, "invalid komi"),
+            CodecError::InvalidVariant(variant) => write!(f, "unknown variant '{}'", variant),
+            CodecError::InvalidMove(value) => write!(f, "invalid move {}", value),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+// None when the record carries more than the compact form holds
+pub fn encode(record: &GameRecord) -> Option<Vec<u8>> {
+    let extras = !record.annotations.is_empty()
+        || !record.camera_path.is_empty()
+        || record.black_player.is_some()
+        || record.white_player.is_some()
+        || record.result.is_some()
+        || record.seed.is_some();
+    let in_turn = record
+        .moves
+        .iter()
+        .zip(turns(record.players))
+        .all(|(mv, color)| mv.color == color && mv.thinking.is_none());
+    let variant = match record.variant {
+        Variant::Standard => String::new(),
+        variant => variant.to_string(),
+    };
+    let fits = (1..=MAX_SIZE).contains(&record.board_size)
+        && (2..=StoneColor::MAX_PLAYERS).contains(&record.players)
+        && record.komi.is_finite()
+        && variant.len() <= u8::MAX as usize;
+    if extras || !in_turn || !fits {
+        return None;
+    }
+
+    let size = record.board_size as u64;
+    let mut out = vec![VERSION, record.board_size as u8, record.players];
+    out.extend_from_slice(&record.komi.to_le_bytes());
+    out.push(variant.len() as u8);
+    out.extend_from_slice(variant.as_bytes());
+    for mv in &record.moves {
+        let value = mv.position.map_or(0, |(x, y, z)| 1 + x as u64 + y as u64 * size + z as u64 * size * size);
+        write_varint(&mut out, value);
+    }
+    Some(out)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<GameRecord, CodecError> {
+    let mut reader = Reader { bytes, offset: 0 };
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(CodecError::UnsupportedVersion(version));
+    }
+    let size = reader.byte()?;
+    if !(1..=MAX_SIZE).contains(&(size as usize)) {
+        return Err(CodecError::InvalidSize(size));
+    }
+    let players = reader.byte()?;
+    if !(2..=StoneColor::MAX_PLAYERS).contains(&players) {
+        return Err(CodecError::InvalidPlayers(players));
+    }
+    let komi = f32::from_le_bytes(reader.take(4)?.try_into().expect("took four bytes"));
+    if !komi.is_finite() {
+        return Err(CodecError::InvalidKomi);
+    }
+    let length = reader.byte()? as usize;
+    let name = String::from_utf8_lossy(reader.take(length)?).into_owned();
+    let variant = if name.is_empty() {
+        Variant::Standard
+    } else {
+        name.parse().map_err(|_| CodecError::InvalidVariant(name))?
+    };
+
+    let mut record = GameRecord::new(size as usize, komi);
+    record.variant = variant;
+    record.players = players;
+    let size = size as u64;
+    let mut colors = turns(players);
+    while !reader.is_done() {
+        let value = reader.varint()?;
+        let color = colors.next().expect("turns never run out");
+        let mv = match value {
+            0 => MoveRecord::pass(color),
+            value if value <= size * size * size => {
+                let index = value - 1;
+                let position = ((index % size) as u8, (index / size % size) as u8, (index / (size * size)) as u8);
+                MoveRecord::place(color, position)
+            }
+            value => return Err(CodecError::InvalidMove(value)),
+        };
+        record.moves.push(mv);
+    }
+    Ok(record)
+}
+
+// The colors taking turns, round and round
+fn turns(players: u8) -> impl Iterator<Item = StoneColor> {
+    StoneColor::players(players).iter().copied().cycle()
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_done(&self) -> bool {
+        self.offset == self.bytes.len()
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], CodecError> {
+        let bytes = self.bytes.get(self.offset..self.offset + count).ok_or(CodecError::Truncated)?;
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, CodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    // Little-endian base 128; long enough to overflow is taken as a bad move
+    fn varint(&mut self) -> Result<u64, CodecError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::InvalidMove(value))
+    }
+}
@@ -0,0 +1,58 @@
+// What one move changed on the board: the stone it placed and the stones it
+// captured. Lets a frontend, bot or remote peer follow a game move by move
+// without comparing whole boards, and is small enough to send as-is.
+use super::{Board, StoneColor};
+
+type Position = (u8, u8, u8);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardDiff {
+    pub placed: Vec<(Position, StoneColor)>,
+    // Sorted by position
+    pub removed: Vec<(Position, StoneColor)>,
+}
+
+impl BoardDiff {
+    // A pass, or no move at all
+    pub fn is_empty(&self) -> bool {
+        self.placed.is_empty() && self.removed.is_empty()
+    }
+
+    // Everything that differs between two positions, however many moves
+    // apart, as if one move had made the change; placed stones sorted too
+    pub fn between(before: &Board, after: &Board) -> Self {
+        let changed = |from: &Board, to: &Board| {
+            let mut stones: Vec<(Position, StoneColor)> = from
+                .get_all_stones()
+                .filter(|&(&position, &color)| to.get_stone(position) != Some(color))
+                .map(|(&position, &color)| (position, color))
+                .collect();
+            stones.sort_unstable_by_key(|&(position, _)| position);
+            stones
+        };
+        Self {
+            placed: changed(after, before),
+            removed: changed(before, after),
+        }
+    }
+
+    // Brings a copy of the board before the move up to date
+    pub fn apply(&self, board: &mut Board) {
+        for &(position, _) in &self.removed {
+            board.remove_stone(position);
+        }
+        for &((x, y, z), color) in &self.placed {
+            board.place_stone(color, x, y, z);
+        }
+    }
+
+    // Takes the move back off a board it was applied to
+    pub fn revert(&self, board: &mut Board) {
+        for &(position, _) in &self.placed {
+            board.remove_stone(position);
+        }
+        for &((x, y, z), color) in &self.removed {
+            board.place_stone(color, x, y, z);
+        }
+    }
+}
\ No newline at end of file
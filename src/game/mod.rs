@@ -1,7 +1,44 @@
+pub mod annotation;
 pub mod board;
+pub mod camera_path;
+pub mod codec;
+pub mod diff;
+pub mod observer;
+pub mod placement;
+pub mod premove;
+pub mod record;
 pub mod rules;
+pub mod session;
+pub mod sgf;
+pub mod share;
+pub mod stats;
 pub mod stone;
+pub mod symmetry;
+pub mod timing;
+pub mod topology;
+pub mod undo;
+pub mod variant;
+pub mod victory;
+pub mod what_if;
+pub mod zobrist;
 
+pub use annotation::{Annotation, Annotations, Mark, MarkerColor};
 pub use board::Board;
-pub use rules::GameRules;
-pub use stone::{Stone, StoneColor};
\ No newline at end of file
+pub use camera_path::{CameraKeyframe, CameraPath, CameraView};
+pub use diff::BoardDiff;
+pub use observer::{ObserverId, RulesObserver};
+pub use placement::{Gravity, PlacementRule};
+pub use premove::Premove;
+pub use record::{GameRecord, GameResult, MoveRecord, RecordError};
+pub use rules::{GameRules, MoveError};
+pub use session::{GameSession, SessionEvent, ViewState};
+pub use stats::PositionStats;
+pub use stone::{Stone, StoneColor};
+pub use symmetry::{Axis, Symmetry};
+pub use timing::{format_thinking, ThinkingSummary};
+pub use topology::{Holes, Lattice, Shell, Stack, Topology};
+pub use undo::{UndoMessage, UndoPolicy, UndoRequest};
+pub use variant::Variant;
+pub use victory::{Hill, VictoryCondition};
+pub use what_if::MovePreview;
+pub use zobrist::PositionTable;
\ No newline at end of file
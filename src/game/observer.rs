@@ -0,0 +1,93 @@
+// Hooks for following a game as the rules apply it, for frontends that embed
+// the rules engine without a GameSession. Callbacks run synchronously inside
+// the move, after the board has been updated; a session hears of its moves
+// through one of these and calls its own observers once it's unlocked.
+use super::{GameRules, MoveRecord, StoneColor};
+use std::fmt;
+use std::sync::Arc;
+
+type Position = (u8, u8, u8);
+
+pub trait RulesObserver: Send + Sync {
+    // A stone or pass has been recorded and any captures are off the board
+    fn on_move(&self, _rules: &GameRules, _record: &MoveRecord, _captured: &[Position]) {}
+
+    // The `color` stones a move just took, reported before that move's on_move
+    fn on_capture(&self, _rules: &GameRules, _color: StoneColor, _stones: &[Position]) {}
+
+    // Two passes in a row, reported after the second pass's on_move
+    fn on_game_end(&self, _rules: &GameRules) {}
+}
+
+// Handed out on registration, for removing the observer again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+#[derive(Default)]
+pub(super) struct Observers {
+    next_id: u64,
+    list: Vec<(ObserverId, Arc<dyn RulesObserver>)>,
+}
+
+impl Observers {
+    pub(super) fn add(&mut self, observer: Arc<dyn RulesObserver>) -> ObserverId {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+        self.list.push((id, observer));
+        id
+    }
+
+    pub(super) fn remove(&mut self, id: ObserverId) -> bool {
+        let before = self.list.len();
+        self.list.retain(|(observer_id, _)| *observer_id != id);
+        self.list.len() != before
+    }
+
+    // The observers themselves, for calling once the list is no longer locked
+    pub(super) fn handles(&self) -> Vec<Arc<dyn RulesObserver>> {
+        self.list.iter().map(|(_, observer)| observer.clone()).collect()
+    }
+
+    pub(super) fn each(&self, mut f: impl FnMut(&dyn RulesObserver)) {
+        for (_, observer) in &self.list {
+            f(observer.as_ref());
+        }
+    }
+}
+
+// A copy of the rules starts unobserved: AI search and previews clone the game
+// to try moves out, and those moves aren't the game's
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} observers", self.list.len())
+    }
+}
+
+// Closures registered through GameRules::on_move and friends
+pub(super) struct OnMove<F>(pub(super) F);
+pub(super) struct OnCapture<F>(pub(super) F);
+pub(super) struct OnGameEnd<F>(pub(super) F);
+
+impl<F: Fn(&GameRules, &MoveRecord, &[Position]) + Send + Sync> RulesObserver for OnMove<F> {
+    fn on_move(&self, rules: &GameRules, record: &MoveRecord, captured: &[Position]) {
+        (self.0)(rules, record, captured)
+    }
+}
+
+impl<F: Fn(&GameRules, StoneColor, &[Position]) + Send + Sync> RulesObserver for OnCapture<F> {
+    fn on_capture(&self, rules: &GameRules, color: StoneColor, stones: &[Position]) {
+        (self.0)(rules, color, stones)
+    }
+}
+
+impl<F: Fn(&GameRules) + Send + Sync> RulesObserver for OnGameEnd<F> {
+    fn on_game_end(&self, rules: &GameRules) {
+        (self.0)(rules)
+    }
+}
\ No newline at end of file
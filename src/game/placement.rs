@@ -0,0 +1,50 @@
+// Constraints on where a stone may go beyond the board and capture rules,
+// plugged into GameRules by the variant being played. The rules ask the
+// constraint before anything else; frontends ask it where a stone aimed at a
+// point would land, so the guide can show it.
+use super::Board;
+use std::fmt;
+
+type Position = (u8, u8, u8);
+
+pub trait PlacementRule: Send + Sync + fmt::Debug {
+    fn allows(&self, board: &Board, position: Position) -> bool;
+
+    // The point a stone aimed at `position` would take, if any
+    fn resolve(&self, board: &Board, position: Position) -> Option<Position> {
+        self.allows(board, position).then_some(position)
+    }
+}
+
+// Connect-style: a stone goes on the lowest empty point of its column along
+// `axis`, so every stone rests on the bottom layer or on another stone. Stones
+// left hanging by a capture stay where they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gravity {
+    pub axis: usize,
+}
+
+impl Gravity {
+    // Layers fill from layer 1 up
+    pub const LAYERS: Self = Self { axis: 2 };
+
+    // The column through `position`, bottom first
+    fn column(&self, board: &Board, position: Position) -> impl Iterator<Item = Position> {
+        let axis = self.axis;
+        (0..board.size() as u8).map(move |index| {
+            let mut point = [position.0, position.1, position.2];
+            point[axis] = index;
+            (point[0], point[1], point[2])
+        })
+    }
+}
+
+impl PlacementRule for Gravity {
+    fn allows(&self, board: &Board, position: Position) -> bool {
+        self.resolve(board, position) == Some(position)
+    }
+
+    fn resolve(&self, board: &Board, position: Position) -> Option<Position> {
+        self.column(board, position).find(|&point| board.get_stone(point).is_none())
+    }
+}
\ No newline at end of file
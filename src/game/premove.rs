@@ -0,0 +1,30 @@
+// Pre-moves for fast games: a move chosen while someone else is still to
+// move, held until the chooser's turn comes round and played then. The reply
+// in between may have filled the point, or made it suicide or ko, so it's
+// checked again at that moment rather than when it was staged.
+use super::{GameRules, MoveError, StoneColor};
+
+type Position = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Premove {
+    pub position: Position,
+    // The player it's played for
+    pub color: StoneColor,
+}
+
+impl Premove {
+    pub fn new(position: Position, color: StoneColor) -> Self {
+        Self { position, color }
+    }
+
+    // None while another player is to move; then the point to play, or why
+    // it can no longer be played
+    pub fn due(&self, rules: &GameRules) -> Option<Result<Position, MoveError>> {
+        if rules.current_player() != self.color {
+            return None;
+        }
+        let (x, y, z) = self.position;
+        Some(rules.check_move(x, y, z).map(|()| self.position))
+    }
+}
@@ -0,0 +1,234 @@
+use super::{Annotations, CameraPath, GameRules, MoveError, StoneColor, Symmetry, Variant};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+type Position = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveRecord {
+    pub color: StoneColor,
+    pub position: Option<Position>, // None for a pass
+    // How long the player took to choose it, to the millisecond; None when
+    // the move wasn't timed (typed-in records, older files)
+    pub thinking: Option<Duration>,
+}
+
+impl MoveRecord {
+    pub fn place(color: StoneColor, position: Position) -> Self {
+        Self {
+            color,
+            position: Some(position),
+            thinking: None,
+        }
+    }
+
+    pub fn pass(color: StoneColor) -> Self {
+        Self {
+            color,
+            position: None,
+            thinking: None,
+        }
+    }
+
+    pub fn is_pass(&self) -> bool {
+        self.position.is_none()
+    }
+
+    // The same play, however long either took
+    pub fn is_same_move(&self, other: &MoveRecord) -> bool {
+        self.color == other.color && self.position == other.position
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordError {
+    OutOfTurn { move_number: usize },
+    IllegalMove { move_number: usize, error: MoveError },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::OutOfTurn { move_number } => {
+                write!(f, "move {} is played by the wrong color", move_number)
+            }
+            RecordError::IllegalMove { move_number, error } => {
+                write!(f, "move {} is illegal: {}", move_number, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+// How a game ended, written as SGF writes it: "B+3.5", "W+" for a win with
+// no count (a victory condition), "0" for a draw
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameResult {
+    Win { color: StoneColor, margin: Option<f32> },
+    Draw,
+}
+
+impl GameResult {
+    // The highest score wins, by its lead over the next; a tie for first is a draw
+    pub fn from_scores(scores: &[(StoneColor, f32)]) -> Option<Self> {
+        let mut ranked = scores.to_vec();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        match ranked.as_slice() {
+            [] => None,
+            [(color, _)] => Some(GameResult::Win { color: *color, margin: None }),
+            [(_, first), (_, second), ..] if first == second => Some(GameResult::Draw),
+            [(color, first), (_, second), ..] => Some(GameResult::Win {
+                color: *color,
+                margin: Some(first - second),
+            }),
+        }
+    }
+
+    pub fn winner(self) -> Option<StoneColor> {
+        match self {
+            GameResult::Win { color, .. } => Some(color),
+            GameResult::Draw => None,
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameResult::Win { color, margin: Some(margin) } => write!(f, "{}+{}", color.letter(), margin),
+            GameResult::Win { color, margin: None } => write!(f, "{}+", color.letter()),
+            GameResult::Draw => f.write_str("0"),
+        }
+    }
+}
+
+// Also reads the other SGF spellings: "Draw", and resignation, time or
+// forfeit wins ("W+R", "B+Time") as wins with no count
+impl FromStr for GameResult {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "0" || s.eq_ignore_ascii_case("draw") {
+            return Ok(GameResult::Draw);
+        }
+        let invalid = || format!("invalid result '{}'", s);
+        let (letter, rest) = s.split_once('+').ok_or_else(invalid)?;
+        let color = StoneColor::from_letter(letter).ok_or_else(invalid)?;
+        let margin = match rest.chars().next() {
+            None | Some('R' | 'T' | 'F') => None,
+            Some(_) => Some(rest.parse::<f32>().ok().filter(|margin| margin.is_finite()).ok_or_else(invalid)?),
+        };
+        Ok(GameResult::Win { color, margin })
+    }
+}
+
+// Everything needed to reconstruct a game from the empty board
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub board_size: usize,
+    pub komi: f32,
+    pub variant: Variant,
+    // 2, or up to StoneColor::MAX_PLAYERS for games with the extra colors
+    pub players: u8,
+    pub moves: Vec<MoveRecord>,
+    pub annotations: Annotations,
+    // A fly-around to play alongside the replay; usually empty
+    pub camera_path: CameraPath,
+    // Who played Black and White, and how it ended; kept for finished games
+    pub black_player: Option<String>,
+    pub white_player: Option<String>,
+    pub result: Option<GameResult>,
+    // The seed engine games were played from, so the match runner can play
+    // them again move for move
+    pub seed: Option<u64>,
+}
+
+impl GameRecord {
+    pub fn new(board_size: usize, komi: f32) -> Self {
+        Self {
+            board_size,
+            komi,
+            variant: Variant::Standard,
+            players: 2,
+            moves: Vec::new(),
+            annotations: Annotations::default(),
+            camera_path: CameraPath::default(),
+            black_player: None,
+            white_player: None,
+            result: None,
+            seed: None,
+        }
+    }
+
+    pub fn from_rules(rules: &GameRules, komi: f32) -> Self {
+        Self {
+            board_size: rules.board().size(),
+            komi,
+            variant: rules.variant(),
+            players: rules.players(),
+            moves: rules.moves().to_vec(),
+            annotations: Annotations::default(),
+            camera_path: CameraPath::default(),
+            black_player: None,
+            white_player: None,
+            result: None,
+            seed: None,
+        }
+    }
+
+    // The same game played out with every stone and mark moved by
+    // `symmetry`. Variants whose rules aren't symmetric the same way, like
+    // gravity or holes, may not replay afterwards.
+    pub fn transformed(&self, symmetry: Symmetry) -> Self {
+        let mut record = self.clone();
+        for mv in &mut record.moves {
+            mv.position = mv.position.map(|position| symmetry.apply(position, self.board_size));
+        }
+        record.annotations.transform(symmetry, self.board_size);
+        record
+    }
+
+    pub fn replay(&self) -> Result<GameRules, RecordError> {
+        self.replay_to(self.moves.len())
+    }
+
+    // Replays the first `count` moves onto an empty board
+    pub fn replay_to(&self, count: usize) -> Result<GameRules, RecordError> {
+        self.replay_visiting(count, |_| {})
+    }
+
+    // Like replay(), also showing `visit` the empty board and every position after it
+    pub fn replay_each(&self, visit: impl FnMut(&GameRules)) -> Result<GameRules, RecordError> {
+        self.replay_visiting(self.moves.len(), visit)
+    }
+
+    fn replay_visiting(&self, count: usize, mut visit: impl FnMut(&GameRules)) -> Result<GameRules, RecordError> {
+        let mut rules = self.variant.new_game(self.board_size);
+        rules.set_players(self.players);
+        visit(&rules);
+
+        for (index, record) in self.moves.iter().take(count).enumerate() {
+            let move_number = index + 1;
+            if record.color != rules.current_player() {
+                return Err(RecordError::OutOfTurn { move_number });
+            }
+
+            match record.position {
+                Some((x, y, z)) => {
+                    rules
+                        .check_move(x, y, z)
+                        .map_err(|error| RecordError::IllegalMove { move_number, error })?;
+                    rules.make_move(x, y, z);
+                }
+                None => rules.pass(),
+            }
+            rules.set_thinking_time(record.thinking);
+            visit(&rules);
+        }
+
+        Ok(rules)
+    }
+}
\ No newline at end of file
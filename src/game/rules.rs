@@ -1,33 +1,216 @@
-use super::{Board, StoneColor};
+use super::observer::{Observers, OnCapture, OnGameEnd, OnMove};
+use super::{Board, BoardDiff, MoveRecord, ObserverId, PlacementRule, PositionTable, RulesObserver, StoneColor, Topology, Variant, VictoryCondition};
 use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 
 type Position = (u8, u8, u8);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    OutOfBounds,
+    Occupied,
+    Suicide,
+    Ko,
+    // Ruled out by the variant, e.g. a stone with nothing under it under gravity
+    NotAllowed,
+    // The variant's victory condition has already been met
+    GameOver,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            MoveError::OutOfBounds => "position is outside the board",
+            MoveError::Occupied => "position is already occupied",
+            MoveError::Suicide => "move would leave its own group without liberties",
+            MoveError::Ko => "move would repeat an earlier position",
+            MoveError::NotAllowed => "the variant doesn't allow a stone there",
+            MoveError::GameOver => "the game has already been won",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for MoveError {}
+
 #[derive(Debug, Clone)]
 pub struct GameRules {
     board: Board,
     current_player: StoneColor,
     move_history: Vec<Board>,
-    ko_rule_positions: HashSet<Position>,
+    move_log: Vec<MoveRecord>,
+    // How often each board has occurred this game, start included, for the
+    // positional superko rule: no move may recreate an earlier board
+    positions: PositionTable<u32>,
+    // Who held the victory condition's contested points at each position,
+    // start included, and for how many moves in a row; counted as the moves
+    // are made so the condition never looks back over the game
+    control: Vec<Option<(StoneColor, usize)>>,
+    variant: Variant,
+    // Taking turns in StoneColor order: Black and White, then Red and Green
+    players: u8,
+    placement: Option<Arc<dyn PlacementRule>>,
+    victory: Option<Arc<dyn VictoryCondition>>,
+    observers: Observers,
 }
 
 impl GameRules {
     pub fn new(board_size: usize) -> Self {
-        Self {
+        let mut rules = Self {
             board: Board::new(board_size),
             current_player: StoneColor::Black,
             move_history: Vec::new(),
-            ko_rule_positions: HashSet::new(),
-        }
+            move_log: Vec::new(),
+            positions: PositionTable::new(),
+            control: Vec::new(),
+            variant: Variant::Standard,
+            players: 2,
+            placement: None,
+            victory: None,
+            observers: Observers::default(),
+        };
+        rules.restart_positions();
+        rules
     }
 
     pub fn new_with_dodecahedron(board_size: usize) -> Self {
-        Self {
+        let mut rules = Self {
             board: Board::new_with_dodecahedron(board_size),
             current_player: StoneColor::Black,
             move_history: Vec::new(),
-            ko_rule_positions: HashSet::new(),
+            move_log: Vec::new(),
+            positions: PositionTable::new(),
+            control: Vec::new(),
+            variant: Variant::Standard,
+            players: 2,
+            placement: None,
+            victory: None,
+            observers: Observers::default(),
+        };
+        rules.restart_positions();
+        rules
+    }
+
+    // Sets up `variant`'s rules; meant for a game that hasn't started
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+        variant.apply(self);
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    // Between 2 and StoneColor::MAX_PLAYERS, for a game that hasn't started;
+    // simultaneous play is always between two
+    pub fn set_players(&mut self, players: u8) {
+        self.players = match self.variant {
+            Variant::Simultaneous => 2,
+            _ => players.clamp(2, StoneColor::MAX_PLAYERS),
+        };
+    }
+
+    pub fn players(&self) -> u8 {
+        self.players
+    }
+
+    // Replaces the variant's placement constraint, e.g. with a custom one
+    pub fn set_placement_rule(&mut self, rule: Option<Arc<dyn PlacementRule>>) {
+        self.placement = rule;
+    }
+
+    // Replaces the variant's way to win outright, e.g. with a custom one
+    pub fn set_victory_condition(&mut self, condition: Option<Arc<dyn VictoryCondition>>) {
+        self.victory = condition;
+        self.recount_control();
+    }
+
+    pub fn victory_condition(&self) -> Option<&dyn VictoryCondition> {
+        self.victory.as_deref()
+    }
+
+    // Who holds the contested points now, and after how many moves in a row
+    pub fn control(&self) -> Option<(StoneColor, usize)> {
+        self.control.last().copied().flatten()
+    }
+
+    fn count_control(&mut self) {
+        let streak = control_after(self.victory.as_deref(), &self.board, self.control.last().copied());
+        self.control.push(streak);
+    }
+
+    fn recount_control(&mut self) {
+        let mut control = Vec::with_capacity(self.move_history.len() + 1);
+        for board in self.move_history.iter().chain(std::iter::once(&self.board)) {
+            control.push(control_after(self.victory.as_deref(), board, control.last().copied()));
         }
+        self.control = control;
+    }
+
+    // The side that has won outright under the variant's victory condition
+    pub fn winner(&self) -> Option<StoneColor> {
+        self.victory.as_ref().and_then(|condition| condition.winner(self))
+    }
+
+    // The points the victory condition is fought over, if it has any
+    pub fn contested_points(&self) -> &[Position] {
+        self.victory.as_ref().map_or(&[], |condition| condition.contested())
+    }
+
+    // Swaps in an empty board of `topology`'s shape; for a game that hasn't started
+    pub fn set_topology(&mut self, topology: Arc<dyn Topology>) {
+        self.board = Board::with_topology(self.board.size(), topology);
+        self.move_history.clear();
+        self.move_log.clear();
+        self.restart_positions();
+        self.current_player = StoneColor::Black;
+    }
+
+    // Where a stone aimed at `position` lands: the point itself unless the
+    // variant moves it, None if the variant has nowhere to put it
+    pub fn resolve_placement(&self, position: Position) -> Option<Position> {
+        match &self.placement {
+            Some(rule) => rule.resolve(&self.board, position),
+            None => Some(position),
+        }
+    }
+
+    pub fn add_observer(&mut self, observer: Arc<dyn RulesObserver>) -> ObserverId {
+        self.observers.add(observer)
+    }
+
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        self.observers.remove(id)
+    }
+
+    pub fn on_move(&mut self, f: impl Fn(&GameRules, &MoveRecord, &[Position]) + Send + Sync + 'static) -> ObserverId {
+        self.add_observer(Arc::new(OnMove(f)))
+    }
+
+    pub fn on_capture(&mut self, f: impl Fn(&GameRules, StoneColor, &[Position]) + Send + Sync + 'static) -> ObserverId {
+        self.add_observer(Arc::new(OnCapture(f)))
+    }
+
+    pub fn on_game_end(&mut self, f: impl Fn(&GameRules) + Send + Sync + 'static) -> ObserverId {
+        self.add_observer(Arc::new(OnGameEnd(f)))
+    }
+
+    // Takes on `other`'s game while keeping this one's observers
+    pub fn replace(&mut self, other: GameRules) {
+        let observers = std::mem::take(&mut self.observers);
+        *self = other;
+        self.observers = observers;
+    }
+
+    // Runs `f` with the observers detached, e.g. to replay moves that were
+    // already reported once
+    pub fn unobserved<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let observers = std::mem::take(&mut self.observers);
+        let result = f(self);
+        self.observers = observers;
+        result
     }
 
     pub fn board(&self) -> &Board {
@@ -41,50 +224,80 @@ impl GameRules {
     pub fn clear_board(&mut self) {
         self.board.clear();
         self.move_history.clear();
-        self.ko_rule_positions.clear();
+        self.move_log.clear();
+        self.restart_positions();
         self.current_player = StoneColor::Black;
     }
 
     pub fn reset_with_dodecahedron(&mut self) {
         self.board.reset_with_dodecahedron();
         self.move_history.clear();
-        self.ko_rule_positions.clear();
+        self.move_log.clear();
+        self.restart_positions();
         self.current_player = StoneColor::Black;
     }
 
     pub fn place_test_pattern(&mut self) {
         self.board.place_test_pattern();
         self.move_history.clear();
-        self.ko_rule_positions.clear();
+        self.move_log.clear();
+        self.restart_positions();
         self.current_player = StoneColor::Black;
     }
 
+    // The board as it now stands becomes the game's only position so far
+    fn restart_positions(&mut self) {
+        self.positions.clear();
+        self.positions.insert(self.board.hash(), 1);
+        self.recount_control();
+    }
+
+    fn count_position(&mut self) {
+        *self.positions.get_or_insert_with(self.board.hash(), || 0) += 1;
+    }
+
+    // Boards that have occurred this game, by Zobrist hash
+    pub fn positions(&self) -> &PositionTable<u32> {
+        &self.positions
+    }
+
     pub fn current_player(&self) -> StoneColor {
         self.current_player
     }
 
     pub fn is_legal_move(&self, x: u8, y: u8, z: u8) -> bool {
+        self.check_move(x, y, z).is_ok()
+    }
+
+    pub fn check_move(&self, x: u8, y: u8, z: u8) -> Result<(), MoveError> {
         let pos = (x, y, z);
 
         if !self.board.is_valid_position(x, y, z) {
-            return false;
+            return Err(MoveError::OutOfBounds);
+        }
+
+        if self.winner().is_some() {
+            return Err(MoveError::GameOver);
         }
 
         if self.board.get_stone(pos).is_some() {
-            return false;
+            return Err(MoveError::Occupied);
+        }
+
+        if self.placement.as_ref().is_some_and(|rule| !rule.allows(&self.board, pos)) {
+            return Err(MoveError::NotAllowed);
         }
 
         let mut test_board = self.board.clone();
         if !test_board.place_stone(self.current_player, x, y, z) {
-            return false;
+            return Err(MoveError::Occupied);
         }
 
-        let opponent_color = self.current_player.opposite();
         let mut captured_groups = Vec::new();
 
         for neighbor_pos in test_board.get_neighbors(pos) {
             if let Some(neighbor_color) = test_board.get_stone(neighbor_pos) {
-                if neighbor_color == opponent_color {
+                if neighbor_color != self.current_player {
                     if let Some(group) = test_board.get_group(neighbor_pos) {
                         if test_board.get_liberties(&group).is_empty() {
                             captured_groups.push(group);
@@ -99,14 +312,35 @@ impl GameRules {
         }
 
         if !test_board.has_liberties(pos) {
-            return false;
+            return Err(MoveError::Suicide);
         }
 
-        if self.ko_rule_positions.contains(&pos) {
-            return false;
+        // Under the simultaneous variant White's stone lands with Black's, and
+        // it's the board after both that mustn't repeat. A turn that leaves the
+        // board as it was, as a collision does, repeats nothing.
+        if let Some(&MoveRecord { color, position: Some(black), .. }) = self.pending_move() {
+            test_board = self.board.clone();
+            resolve_simultaneous(&mut test_board, &[(color, black), (self.current_player, pos)]);
+            if test_board.hash() == self.board.hash() {
+                return Ok(());
+            }
         }
 
-        true
+        if self.positions.contains(test_board.hash()) {
+            return Err(MoveError::Ko);
+        }
+
+        Ok(())
+    }
+
+    pub fn legal_moves(&self) -> Vec<Position> {
+        if self.winner().is_some() {
+            return Vec::new();
+        }
+        self.board
+            .empty_positions()
+            .filter(|&(x, y, z)| self.is_legal_move(x, y, z))
+            .collect()
     }
 
     pub fn make_move(&mut self, x: u8, y: u8, z: u8) -> bool {
@@ -115,60 +349,165 @@ impl GameRules {
         }
 
         self.move_history.push(self.board.clone());
-        
+        let record = MoveRecord::place(self.current_player, (x, y, z));
+        self.move_log.push(record);
+        if self.variant == Variant::Simultaneous {
+            self.finish_simultaneous_move(record);
+            return true;
+        }
+
         let pos = (x, y, z);
         self.board.place_stone(self.current_player, x, y, z);
 
-        let opponent_color = self.current_player.opposite();
-        let mut captured_any = false;
+        // Every other player's stones can be taken, each color reported on its own
+        let mut captured_by_color: Vec<(StoneColor, Vec<Position>)> = Vec::new();
 
         for neighbor_pos in self.board.get_neighbors(pos) {
             if let Some(neighbor_color) = self.board.get_stone(neighbor_pos) {
-                if neighbor_color == opponent_color {
+                if neighbor_color != self.current_player {
                     if let Some(group) = self.board.get_group(neighbor_pos) {
                         if self.board.get_liberties(&group).is_empty() {
+                            match captured_by_color.iter_mut().find(|(color, _)| *color == neighbor_color) {
+                                Some((_, stones)) => stones.extend(group.iter().copied()),
+                                None => captured_by_color.push((neighbor_color, group.iter().copied().collect())),
+                            }
                             self.board.capture_group(group);
-                            captured_any = true;
                         }
                     }
                 }
             }
         }
 
-        self.ko_rule_positions.clear();
-        if captured_any && self.move_history.len() >= 2 {
-            let prev_board = &self.move_history[self.move_history.len() - 2];
-            if self.boards_equal(&self.board, prev_board) {
-                self.ko_rule_positions.insert(pos);
-            }
-        }
+        self.count_position();
+        self.count_control();
+        self.current_player = self.current_player.next(self.players);
 
-        self.current_player = self.current_player.opposite();
+        let mut captured = Vec::new();
+        for (color, stones) in &mut captured_by_color {
+            stones.sort_unstable();
+            self.observers.each(|observer| observer.on_capture(self, *color, stones));
+            captured.extend_from_slice(stones);
+        }
+        captured.sort_unstable();
+        self.observers.each(|observer| observer.on_move(self, &record, &captured));
+        if self.is_over() {
+            self.observers.each(|observer| observer.on_game_end(self));
+        }
         true
     }
 
-    fn boards_equal(&self, board1: &Board, board2: &Board) -> bool {
-        if board1.size() != board2.size() {
-            return false;
+    pub fn pass(&mut self) {
+        self.move_history.push(self.board.clone());
+        let record = MoveRecord::pass(self.current_player);
+        self.move_log.push(record);
+        if self.variant == Variant::Simultaneous {
+            self.finish_simultaneous_move(record);
+            return;
         }
+        self.count_position();
+        self.count_control();
+        self.current_player = self.current_player.next(self.players);
 
-        for x in 0..board1.size() {
-            for y in 0..board1.size() {
-                for z in 0..board1.size() {
-                    let pos = (x as u8, y as u8, z as u8);
-                    if board1.get_stone(pos) != board2.get_stone(pos) {
-                        return false;
-                    }
-                }
-            }
+        self.observers.each(|observer| observer.on_move(self, &record, &[]));
+        if self.is_over() {
+            self.observers.each(|observer| observer.on_game_end(self));
         }
-
-        true
     }
 
-    pub fn pass(&mut self) {
-        self.move_history.push(self.board.clone());
+    // Under the simultaneous variant Black's move is held back, off the board,
+    // until White has chosen; the two then land together. Moves are checked on
+    // their own against the position the turn started from, so White may pick
+    // Black's point: then neither stone is placed. Once both are down, every
+    // group left without liberties is captured at once, whatever its color.
+    // Only the ko check looks at both, as check_move explains.
+    fn finish_simultaneous_move(&mut self, record: MoveRecord) {
+        let mut captured = Vec::new();
+        if record.color == StoneColor::White {
+            let black = self.move_log[self.move_log.len() - 2];
+            let plays: Vec<_> = [black, record].iter().filter_map(|mv| mv.position.map(|p| (mv.color, p))).collect();
+            captured = resolve_simultaneous(&mut self.board, &plays);
+        }
+
+        self.count_position();
+        self.count_control();
         self.current_player = self.current_player.opposite();
+        for (color, stones) in &captured {
+            self.observers.each(|observer| observer.on_capture(self, *color, stones));
+        }
+        let all: Vec<Position> = captured.into_iter().flat_map(|(_, stones)| stones).collect();
+        self.observers.each(|observer| observer.on_move(self, &record, &all));
+        if self.is_over() {
+            self.observers.each(|observer| observer.on_game_end(self));
+        }
+    }
+
+    // Black's move still waiting for White's under the simultaneous variant
+    pub fn pending_move(&self) -> Option<&MoveRecord> {
+        let waiting = self.variant == Variant::Simultaneous && self.move_log.len() % 2 == 1;
+        self.move_log.last().filter(|_| waiting)
+    }
+
+    // A pass from every player in a row ends the game, as does an outright win
+    pub fn is_over(&self) -> bool {
+        let moves = &self.move_log;
+        let players = usize::from(self.players);
+        moves.len() >= players && moves[moves.len() - players..].iter().all(|mv| mv.is_pass()) || self.winner().is_some()
+    }
+
+    pub fn moves(&self) -> &[MoveRecord] {
+        &self.move_log
+    }
+
+    // The board before each move, latest first, back to the start position
+    pub fn previous_boards(&self) -> impl Iterator<Item = &Board> {
+        self.move_history.iter().rev()
+    }
+
+    // What the last move changed. Only the placed stone's neighbourhood is
+    // looked at: anything captured was an opposing group touching it. Under
+    // the simultaneous variant it's the whole turn, once White's move has
+    // brought both stones down, and nothing while Black's waits.
+    pub fn diff_last_move(&self) -> BoardDiff {
+        if self.variant == Variant::Simultaneous {
+            let turn_start = self.move_history.len().checked_sub(2).filter(|_| self.pending_move().is_none());
+            return match turn_start {
+                Some(index) => BoardDiff::between(&self.move_history[index], &self.board),
+                None => BoardDiff::default(),
+            };
+        }
+        let (Some(record), Some(before)) = (self.move_log.last(), self.move_history.last()) else {
+            return BoardDiff::default();
+        };
+        let Some(position) = record.position else {
+            return BoardDiff::default();
+        };
+
+        let mut removed = HashSet::new();
+        for neighbor in before.get_neighbors(position) {
+            if before.get_stone(neighbor).is_some_and(|color| color != record.color)
+                && self.board.get_stone(neighbor).is_none()
+                && !removed.contains(&neighbor)
+            {
+                removed.extend(before.get_group(neighbor).unwrap_or_default());
+            }
+        }
+        let mut removed: Vec<_> = removed
+            .into_iter()
+            .filter_map(|position| before.get_stone(position).map(|color| (position, color)))
+            .collect();
+        removed.sort_unstable_by_key(|&(position, _)| position);
+
+        BoardDiff {
+            placed: vec![(position, record.color)],
+            removed,
+        }
+    }
+
+    // Stamps the last move with the time taken to choose it
+    pub fn set_thinking_time(&mut self, thinking: Option<Duration>) {
+        if let Some(record) = self.move_log.last_mut() {
+            record.thinking = thinking.map(|time| Duration::from_millis(time.as_millis() as u64));
+        }
     }
 
     pub fn can_undo(&self) -> bool {
@@ -177,9 +516,17 @@ impl GameRules {
 
     pub fn undo(&mut self) -> bool {
         if let Some(prev_board) = self.move_history.pop() {
+            let hash = self.board.hash();
+            if let Some(count) = self.positions.get_mut(hash) {
+                *count -= 1;
+                if *count == 0 {
+                    self.positions.remove(hash);
+                }
+            }
             self.board = prev_board;
-            self.current_player = self.current_player.opposite();
-            self.ko_rule_positions.clear();
+            self.move_log.pop();
+            self.control.pop();
+            self.current_player = self.current_player.previous(self.players);
             true
         } else {
             false
@@ -187,26 +534,19 @@ impl GameRules {
     }
 
     pub fn get_territory_score(&self) -> (usize, usize) {
-        let mut black_territory = 0;
-        let mut white_territory = 0;
-
-        for x in 0..self.board.size() {
-            for y in 0..self.board.size() {
-                for z in 0..self.board.size() {
-                    let pos = (x as u8, y as u8, z as u8);
-                    if self.board.get_stone(pos).is_none() {
-                        if let Some(controlling_color) = self.get_territory_owner(pos) {
-                            match controlling_color {
-                                StoneColor::Black => black_territory += 1,
-                                StoneColor::White => white_territory += 1,
-                            }
-                        }
-                    }
-                }
+        let territory = self.territory_by_color();
+        (territory[StoneColor::Black.index()], territory[StoneColor::White.index()])
+    }
+
+    // Each player's territory, by StoneColor::index
+    pub fn territory_by_color(&self) -> [usize; StoneColor::MAX_PLAYERS as usize] {
+        let mut territory = [0; StoneColor::MAX_PLAYERS as usize];
+        for pos in self.board.empty_positions() {
+            if let Some(color) = self.get_territory_owner(pos) {
+                territory[color.index()] += 1;
             }
         }
-
-        (black_territory, white_territory)
+        territory
     }
 
     fn get_territory_owner(&self, pos: Position) -> Option<StoneColor> {
@@ -240,4 +580,52 @@ impl GameRules {
             None
         }
     }
-}
\ No newline at end of file
+}
+
+// Who holds `condition`'s contested points on `board`, following on from
+// `before`, the count at the position before it
+fn control_after(
+    condition: Option<&dyn VictoryCondition>,
+    board: &Board,
+    before: Option<Option<(StoneColor, usize)>>,
+) -> Option<(StoneColor, usize)> {
+    let color = condition?.controller(board)?;
+    match before {
+        Some(Some((held, moves))) if held == color => Some((color, moves + 1)),
+        Some(_) => Some((color, 1)),
+        // The start position isn't after a move
+        None => Some((color, 0)),
+    }
+}
+
+// Puts the turn's stones down together under the simultaneous variant, or
+// neither if they share a point, then captures every group left without
+// liberties, whatever its color; returns what was captured, by color
+fn resolve_simultaneous(board: &mut Board, plays: &[(StoneColor, Position)]) -> Vec<(StoneColor, Vec<Position>)> {
+    if plays.len() == 2 && plays[0].1 == plays[1].1 {
+        return Vec::new();
+    }
+    for &(color, (x, y, z)) in plays {
+        board.place_stone(color, x, y, z);
+    }
+
+    let mut dead = Vec::new();
+    for &(_, position) in plays {
+        for point in std::iter::once(position).chain(board.get_neighbors(position)) {
+            if let Some(group) = board.get_group(point) {
+                if board.get_liberties(&group).is_empty() && !dead.contains(&group) {
+                    dead.push(group);
+                }
+            }
+        }
+    }
+    let mut captured = Vec::new();
+    for group in dead {
+        let mut stones: Vec<Position> = group.iter().copied().collect();
+        stones.sort_unstable();
+        let color = board.get_stone(stones[0]).expect("groups have stones");
+        board.capture_group(group);
+        captured.push((color, stones));
+    }
+    captured
+}
@@ -0,0 +1,464 @@
+use super::observer::Observers;
+use super::{
+    Annotation, Annotations, CameraPath, GameRecord, GameRules, Mark, MoveRecord, ObserverId, RecordError, RulesObserver, StoneColor,
+    Symmetry,
+};
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+use instant::Instant;
+
+type Position = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEvent {
+    MovePlayed { color: StoneColor, position: Position, captured: usize },
+    Passed { color: StoneColor },
+    Undone,
+    Reset,
+    Changed,
+}
+
+// Whether the board shows the game itself or an earlier position of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewState {
+    Live,
+    // Looking back at the position after `move_number` of the game's `of` moves
+    Reviewing { move_number: usize, of: usize },
+    // Moves tried from `from` on; the game itself is untouched
+    Variation { from: usize },
+}
+
+impl fmt::Display for ViewState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViewState::Live => f.write_str("playing"),
+            ViewState::Reviewing { move_number, of } => write!(f, "reviewing move {} of {}", move_number, of),
+            ViewState::Variation { from } => write!(f, "variation from move {}", from),
+        }
+    }
+}
+
+// The game as it stood when the user stepped back from it
+struct Review {
+    live: GameRules,
+    // Set aside when a variation starts, as its moves rewrite the later nodes
+    live_annotations: Option<Annotations>,
+}
+
+fn common_prefix(a: &[MoveRecord], b: &[MoveRecord]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a.is_same_move(b)).count()
+}
+
+// The session's event bus. Moves and passes are announced however they were
+// played, as they reach the session through its Pending observer on the
+// rules; other changes are sent by the session itself.
+#[derive(Clone, Default)]
+struct Subscribers(Arc<Mutex<Vec<Sender<SessionEvent>>>>);
+
+impl Subscribers {
+    fn send(&self, event: SessionEvent) {
+        // Receivers that were dropped are pruned on the next notification
+        self.0
+            .lock()
+            .expect("subscriber list poisoned")
+            .retain(|sender| sender.send(event).is_ok());
+    }
+}
+
+// What the rules reported while the session held them locked, kept until the
+// lock is released so that nobody is called back while it's held
+enum Reported {
+    Capture(StoneColor, Vec<Position>),
+    Move(MoveRecord, Vec<Position>),
+    GameEnd,
+}
+
+#[derive(Clone, Default)]
+struct Pending(Arc<Mutex<Vec<Reported>>>);
+
+impl Pending {
+    fn push(&self, reported: Reported) {
+        self.0.lock().expect("pending events poisoned").push(reported);
+    }
+
+    fn take(&self) -> Vec<Reported> {
+        std::mem::take(&mut *self.0.lock().expect("pending events poisoned"))
+    }
+}
+
+impl RulesObserver for Pending {
+    fn on_move(&self, _rules: &GameRules, record: &MoveRecord, captured: &[Position]) {
+        self.push(Reported::Move(*record, captured.to_vec()));
+    }
+
+    fn on_capture(&self, _rules: &GameRules, color: StoneColor, stones: &[Position]) {
+        self.push(Reported::Capture(color, stones.to_vec()));
+    }
+
+    fn on_game_end(&self, _rules: &GameRules) {
+        self.push(Reported::GameEnd);
+    }
+}
+
+// Shared handle to a game: the AI thread, network client and render loop each
+// hold a clone and observe mutations through their own event receiver.
+#[derive(Clone)]
+pub struct GameSession {
+    rules: Arc<RwLock<GameRules>>,
+    // Review markup, saved with the game but not part of the rules
+    annotations: Arc<RwLock<Annotations>>,
+    // A recorded fly-around, saved with the game like the markup
+    camera_path: Arc<RwLock<CameraPath>>,
+    // While reviewing, moves go into a variation and the game waits here
+    review: Arc<Mutex<Option<Review>>>,
+    subscribers: Subscribers,
+    pending: Pending,
+    // Called with the session unlocked, so unlike observers on the rules
+    // themselves they may read or play on the session
+    observers: Arc<Mutex<Observers>>,
+    // When the side to move got the turn, for timing its move
+    turn_started: Arc<Mutex<Instant>>,
+}
+
+impl GameSession {
+    pub fn new(mut rules: GameRules) -> Self {
+        let pending = Pending::default();
+        rules.add_observer(Arc::new(pending.clone()));
+        Self {
+            rules: Arc::new(RwLock::new(rules)),
+            annotations: Arc::new(RwLock::new(Annotations::default())),
+            camera_path: Arc::new(RwLock::new(CameraPath::default())),
+            review: Arc::new(Mutex::new(None)),
+            subscribers: Subscribers::default(),
+            pending,
+            observers: Arc::new(Mutex::new(Observers::default())),
+            turn_started: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    // Time since the turn started, which starts the next one
+    fn end_turn(&self) -> Duration {
+        let mut turn_started = self.turn_started.lock().expect("game session lock poisoned");
+        let now = Instant::now();
+        let thinking = now.duration_since(*turn_started);
+        *turn_started = now;
+        thinking
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, GameRules> {
+        self.rules.read().expect("game session lock poisoned")
+    }
+
+    pub fn snapshot(&self) -> GameRules {
+        self.read().clone()
+    }
+
+    pub fn view(&self) -> ViewState {
+        let review = self.review.lock().expect("game session lock poisoned");
+        let Some(review) = review.as_ref() else {
+            return ViewState::Live;
+        };
+        let shown = self.read();
+        let from = common_prefix(shown.moves(), review.live.moves());
+        if from == shown.moves().len() {
+            ViewState::Reviewing {
+                move_number: from,
+                of: review.live.moves().len(),
+            }
+        } else {
+            ViewState::Variation { from }
+        }
+    }
+
+    // Shows the position one move earlier; the game waits until return_to_game
+    // or play_from_here, and moves played meanwhile form a variation
+    pub fn step_back(&self) -> bool {
+        {
+            let mut review = self.review.lock().expect("game session lock poisoned");
+            let mut rules = self.rules.write().expect("game session lock poisoned");
+            if !rules.can_undo() {
+                return false;
+            }
+            if review.is_none() {
+                *review = Some(Review {
+                    live: rules.clone(),
+                    live_annotations: None,
+                });
+            }
+            rules.undo();
+        }
+        self.notify(SessionEvent::Changed);
+        true
+    }
+
+    // Replays the game's next move; reaching its end makes it live again
+    pub fn step_forward(&self) -> bool {
+        let caught_up = {
+            let review = self.review.lock().expect("game session lock poisoned");
+            let Some(review) = review.as_ref() else {
+                return false;
+            };
+            let mut rules = self.rules.write().expect("game session lock poisoned");
+            let shown = rules.moves().len();
+            let live = review.live.moves();
+            if common_prefix(rules.moves(), live) != shown || shown >= live.len() {
+                return false;
+            }
+            // The move was announced when it was first played
+            rules.unobserved(|rules| match live[shown].position {
+                Some((x, y, z)) => {
+                    rules.make_move(x, y, z);
+                }
+                None => rules.pass(),
+            });
+            rules.set_thinking_time(live[shown].thinking);
+            shown + 1 == live.len()
+        };
+        if caught_up {
+            self.return_to_game();
+        } else {
+            self.notify(SessionEvent::Changed);
+        }
+        true
+    }
+
+    // Back to the game as it was before reviewing, dropping any variation
+    pub fn return_to_game(&self) -> bool {
+        let Some(review) = self.review.lock().expect("game session lock poisoned").take() else {
+            return false;
+        };
+        self.rules.write().expect("game session lock poisoned").replace(review.live);
+        if let Some(annotations) = review.live_annotations {
+            *self.annotations.write().expect("game session lock poisoned") = annotations;
+        }
+        self.end_turn();
+        self.notify(SessionEvent::Changed);
+        true
+    }
+
+    // Makes the position on the board the game, dropping the moves after it
+    pub fn play_from_here(&self) -> bool {
+        if self.review.lock().expect("game session lock poisoned").take().is_none() {
+            return false;
+        }
+        let move_number = self.read().moves().len();
+        self.forget_annotations_after(move_number);
+        self.end_turn();
+        self.notify(SessionEvent::Changed);
+        true
+    }
+
+    // The first move while reviewing starts a variation
+    fn set_aside_live_annotations(&self) {
+        let mut review = self.review.lock().expect("game session lock poisoned");
+        if let Some(review) = review.as_mut().filter(|review| review.live_annotations.is_none()) {
+            review.live_annotations = Some(self.annotations().clone());
+        }
+    }
+
+    pub fn annotations(&self) -> RwLockReadGuard<'_, Annotations> {
+        self.annotations.read().expect("game session lock poisoned")
+    }
+
+    // Toggles `mark` on `position` in the current node; returns the mark now there
+    pub fn annotate(&self, position: Position, mark: Mark) -> Option<Mark> {
+        let move_number = self.read().moves().len();
+        let result = self
+            .annotations
+            .write()
+            .expect("game session lock poisoned")
+            .toggle(move_number, position, mark);
+        self.notify(SessionEvent::Changed);
+        result
+    }
+
+    // Puts `mark` on `position` in the current node, or clears it
+    pub fn set_mark(&self, position: Position, mark: Option<Mark>) {
+        let move_number = self.read().moves().len();
+        {
+            let mut annotations = self.annotations.write().expect("game session lock poisoned");
+            annotations.remove(move_number, position);
+            if let Some(mark) = mark {
+                annotations.insert(Annotation {
+                    move_number,
+                    position,
+                    mark,
+                });
+            }
+        }
+        self.notify(SessionEvent::Changed);
+    }
+
+    // The game so far with its markup, e.g. for saving
+    pub fn record(&self, komi: f32) -> GameRecord {
+        let mut record = GameRecord::from_rules(&self.read(), komi);
+        record.annotations = self.annotations().clone();
+        record.camera_path = self.camera_path();
+        record
+    }
+
+    pub fn camera_path(&self) -> CameraPath {
+        self.camera_path.read().expect("game session lock poisoned").clone()
+    }
+
+    pub fn set_camera_path(&self, path: CameraPath) {
+        *self.camera_path.write().expect("game session lock poisoned") = path;
+    }
+
+    pub fn set_annotations(&self, annotations: Annotations) {
+        *self.annotations.write().expect("game session lock poisoned") = annotations;
+        self.notify(SessionEvent::Changed);
+    }
+
+    // Replaces the game with `record`, markup included
+    pub fn load(&self, record: &GameRecord) -> Result<(), RecordError> {
+        let rules = record.replay()?;
+        self.review.lock().expect("game session lock poisoned").take();
+        *self.annotations.write().expect("game session lock poisoned") = record.annotations.clone();
+        self.set_camera_path(record.camera_path.clone());
+        self.end_turn();
+        self.update(|current| current.replace(rules));
+        Ok(())
+    }
+
+    // Turns the whole game around, as if every move had been played at its
+    // image under `symmetry`; left as it was if the result can't be replayed
+    pub fn transform(&self, symmetry: Symmetry) -> Result<(), RecordError> {
+        // Komi isn't needed to replay the moves
+        let record = self.record(0.0).transformed(symmetry);
+        self.load(&record)
+    }
+
+    // A new move starts a new line, so markup on the nodes it replaces goes
+    fn forget_annotations_after(&self, move_number: usize) {
+        self.annotations.write().expect("game session lock poisoned").truncate(move_number);
+    }
+
+    pub fn subscribe(&self) -> Receiver<SessionEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.0.lock().expect("subscriber list poisoned").push(sender);
+        receiver
+    }
+
+    // Calls `observer` for every move from now on, after the session has been
+    // unlocked and with a copy of the rules as the move left them
+    pub fn add_observer(&self, observer: Arc<dyn RulesObserver>) -> ObserverId {
+        self.observers.lock().expect("game session lock poisoned").add(observer)
+    }
+
+    pub fn remove_observer(&self, id: ObserverId) -> bool {
+        self.observers.lock().expect("game session lock poisoned").remove(id)
+    }
+
+    // Subscribers hear about the move from the rules themselves
+    pub fn make_move(&self, x: u8, y: u8, z: u8) -> bool {
+        self.set_aside_live_annotations();
+        let mut rules = self.rules.write().expect("game session lock poisoned");
+        if !rules.is_legal_move(x, y, z) {
+            return false;
+        }
+        self.forget_annotations_after(rules.moves().len());
+        rules.make_move(x, y, z);
+        rules.set_thinking_time(Some(self.end_turn()));
+        self.release(rules);
+        true
+    }
+
+    pub fn pass(&self) {
+        self.set_aside_live_annotations();
+        let mut rules = self.rules.write().expect("game session lock poisoned");
+        self.forget_annotations_after(rules.moves().len());
+        rules.pass();
+        rules.set_thinking_time(Some(self.end_turn()));
+        self.release(rules);
+    }
+
+    // Unlocks the rules, then passes on what they reported meanwhile
+    fn release(&self, rules: RwLockWriteGuard<'_, GameRules>) {
+        let reported = self.pending.take();
+        if reported.is_empty() {
+            return;
+        }
+        let observers = self.observers.lock().expect("game session lock poisoned").handles();
+        // Observers get the rules as the move left them, not as they are by
+        // the time they're called
+        let seen = (!observers.is_empty()).then(|| rules.clone());
+        drop(rules);
+        for reported in &reported {
+            if let Reported::Move(record, captured) = reported {
+                self.notify(match record.position {
+                    Some(position) => SessionEvent::MovePlayed {
+                        color: record.color,
+                        position,
+                        captured: captured.len(),
+                    },
+                    None => SessionEvent::Passed { color: record.color },
+                });
+            }
+        }
+        let Some(seen) = seen else {
+            return;
+        };
+        for observer in &observers {
+            for reported in &reported {
+                match reported {
+                    Reported::Capture(color, stones) => observer.on_capture(&seen, *color, stones),
+                    Reported::Move(record, captured) => observer.on_move(&seen, record, captured),
+                    Reported::GameEnd => observer.on_game_end(&seen),
+                }
+            }
+        }
+    }
+
+    pub fn undo(&self) -> bool {
+        let undone = self.rules.write().expect("game session lock poisoned").undo();
+        if undone {
+            self.end_turn();
+            self.notify(SessionEvent::Undone);
+        }
+        undone
+    }
+
+    // Takes `moves` back in one go, if `check` still holds for the game as it
+    // stands; nothing can be played between the check and the undos
+    pub fn undo_if(&self, moves: usize, check: impl FnOnce(&GameRules) -> bool) -> bool {
+        let mut rules = self.rules.write().expect("game session lock poisoned");
+        if !check(&rules) || rules.moves().len() < moves {
+            return false;
+        }
+        for _ in 0..moves {
+            rules.undo();
+        }
+        drop(rules);
+        self.end_turn();
+        self.notify(SessionEvent::Undone);
+        true
+    }
+
+    pub fn clear_board(&self) {
+        self.review.lock().expect("game session lock poisoned").take();
+        self.rules.write().expect("game session lock poisoned").clear_board();
+        self.annotations.write().expect("game session lock poisoned").clear();
+        self.set_camera_path(CameraPath::default());
+        self.end_turn();
+        self.notify(SessionEvent::Reset);
+    }
+
+    // Escape hatch for mutations without a dedicated event; subscribers get a
+    // generic Changed notification and should re-read the whole state.
+    // Observers added to the rules here run with the session locked and must
+    // not call back into it; add_observer is for those that do.
+    pub fn update<R>(&self, f: impl FnOnce(&mut GameRules) -> R) -> R {
+        let mut rules = self.rules.write().expect("game session lock poisoned");
+        let result = f(&mut rules);
+        self.release(rules);
+        self.notify(SessionEvent::Changed);
+        result
+    }
+
+    fn notify(&self, event: SessionEvent) {
+        self.subscribers.send(event);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,453 @@
+// SGF-3D: plain SGF with a three-letter coordinate per move (x, y, z),
+// e.g. (;GM[3DGO]FF[4]SZ[5]KM[6.5];B[ccc];W[cdc];B[])
+//
+// Markup uses the standard LB, TR and SQ properties with 3D points, plus MK
+// for colored markers, e.g. ;B[ccc]LB[cdc:A]TR[bcc]MK[ddc:red]
+//
+// Variants other than standard play are named by VR, e.g. VR[gravity].
+//
+// A timed move carries TS with the seconds spent choosing it, e.g. ;B[ccc]TS[4.215]
+//
+// A recorded camera path goes in the root node as CP, one keyframe per value
+// of replay time, distance, the two orbit angles and the orbit center, e.g.
+// CP[0:30:0.5:0.6:2:2:2][4.25:25:1.1:0.6:2:2:2]
+//
+// Finished games name their players and result with the standard PB, PW and
+// RE, e.g. PB[Ann]PW[AI level 2]RE[W+4.5]
+//
+// Games between engines give the seed they were played from as RS, e.g.
+// RS[8731640071]
+//
+// The root node gives the format version as FV, e.g. FV[2]. Records written
+// before versioning have none and count as version 1. Older records are
+// migrated to the current version as they're read; newer ones are refused
+// rather than half understood.
+use super::{Annotation, CameraKeyframe, GameRecord, GameResult, Mark, MoveRecord, StoneColor, Variant};
+use std::fmt;
+use std::time::Duration;
+
+const GAME_ID: &str = "3DGO";
+const MAX_SIZE: usize = 26;
+
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SgfError {
+    UnexpectedEnd,
+    UnexpectedChar { offset: usize, found: char },
+    UnsupportedGame(String),
+    InvalidSize(String),
+    // SZ anywhere but the root node, or after a move, would move the board
+    // out from under what's already on it
+    MisplacedSize,
+    InvalidKomi(String),
+    UnsupportedVariant(String),
+    InvalidPlayers(String),
+    InvalidCoordinate(String),
+    InvalidMarkup(String),
+    InvalidTime(String),
+    InvalidCameraPath(String),
+    InvalidResult(String),
+    InvalidSeed(String),
+    InvalidVersion(String),
+    NewerVersion(u32),
+    Variations,
+}
+
+impl fmt::Display for SgfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SgfError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            SgfError::UnexpectedChar { offset, found } => {
+                write!(f, "unexpected '{}' at offset {}", found, offset)
+            }
+            SgfError::UnsupportedGame(game) => write!(f, "not a 3D Go record (GM[{}])", game),
+            SgfError::InvalidSize(size) => write!(f, "invalid board size '{}'", size),
+            SgfError::MisplacedSize => f.write_str("the board size must be given in the root node, before any move"),
+            SgfError::InvalidKomi(komi) => write!(f, "invalid komi '{}'", komi),
+            SgfError::UnsupportedVariant(variant) => write!(f, "unknown variant '{}'", variant),
+            SgfError::InvalidPlayers(players) => write!(f, "invalid number of players '{}'", players),
+            SgfError::InvalidCoordinate(coord) => write!(f, "invalid coordinate '{}'", coord),
+            SgfError::InvalidMarkup(value) => write!(f, "invalid markup '{}'", value),
+            SgfError::InvalidTime(value) => write!(f, "invalid thinking time '{}'", value),
+            SgfError::InvalidCameraPath(value) => write!(f, "invalid camera keyframe '{}'", value),
+            SgfError::InvalidResult(value) => write!(f, "invalid result '{}'", value),
+            SgfError::InvalidSeed(value) => write!(f, "invalid seed '{}'", value),
+            SgfError::InvalidVersion(value) => write!(f, "invalid format version '{}'", value),
+            SgfError::NewerVersion(version) => write!(
+                f,
+                "the record is format version {}, newer than this build reads ({})",
+                version, FORMAT_VERSION
+            ),
+            SgfError::Variations => write!(f, "variations are not supported"),
+        }
+    }
+}
+
+impl std::error::Error for SgfError {}
+
+pub fn write(record: &GameRecord) -> String {
+    let mut out = format!(
+        "(;GM[{}]FF[4]FV[{}]SZ[{}]KM[{}]",
+        GAME_ID, FORMAT_VERSION, record.board_size, record.komi
+    );
+    if record.variant != Variant::Standard {
+        out.push_str(&format!("VR[{}]", record.variant));
+    }
+    // Not in the SGF standard: the number of players, when more than two
+    if record.players != 2 {
+        out.push_str(&format!("NP[{}]", record.players));
+    }
+    for (key, name) in [("PB", &record.black_player), ("PW", &record.white_player)] {
+        if let Some(name) = name {
+            out.push_str(&format!("{}[{}]", key, escape(name)));
+        }
+    }
+    if let Some(result) = record.result {
+        out.push_str(&format!("RE[{}]", result));
+    }
+    if let Some(seed) = record.seed {
+        out.push_str(&format!("RS[{}]", seed));
+    }
+    if !record.camera_path.is_empty() {
+        out.push_str("CP");
+        for keyframe in record.camera_path.keyframes() {
+            out.push_str(&format!("[{}]", keyframe));
+        }
+    }
+
+    write_markup(&mut out, record, 0);
+    for (index, mv) in record.moves.iter().enumerate() {
+        out.push(';');
+        out.push(mv.color.letter());
+        out.push('[');
+        if let Some(position) = mv.position {
+            out.push_str(&format_coordinate(position));
+        }
+        out.push(']');
+        if let Some(thinking) = mv.thinking {
+            out.push_str(&format!("TS[{:.3}]", thinking.as_secs_f64()));
+        }
+        write_markup(&mut out, record, index + 1);
+    }
+
+    out.push(')');
+    out
+}
+
+// Text values keep their closing brackets and backslashes behind a backslash
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+fn format_coordinate((x, y, z): (u8, u8, u8)) -> String {
+    [x, y, z].iter().map(|&c| (b'a' + c) as char).collect()
+}
+
+// One property per kind of mark, with a value per point
+fn write_markup(out: &mut String, record: &GameRecord, move_number: usize) {
+    for key in ["LB", "TR", "SQ", "MK"] {
+        let mut values = record.annotations.at(move_number).filter_map(|annotation| {
+            let point = format_coordinate(annotation.position);
+            match (key, annotation.mark) {
+                ("LB", Mark::Label(letter)) => Some(format!("{}:{}", point, letter)),
+                ("TR", Mark::Triangle) | ("SQ", Mark::Square) => Some(point),
+                ("MK", Mark::Marker(color)) => Some(format!("{}:{}", point, color)),
+                _ => None,
+            }
+        });
+        if let Some(first) = values.next() {
+            out.push_str(key);
+            for value in std::iter::once(first).chain(values) {
+                out.push('[');
+                out.push_str(&value);
+                out.push(']');
+            }
+        }
+    }
+}
+
+// The version the root node gives, 1 when it gives none
+fn format_version(nodes: &[Node]) -> Result<u32, SgfError> {
+    let Some((_, value)) = nodes.first().and_then(|root| root.iter().find(|(key, _)| key == "FV")) else {
+        return Ok(1);
+    };
+    let version = value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&version| version >= 1)
+        .ok_or_else(|| SgfError::InvalidVersion(value.clone()))?;
+    if version > FORMAT_VERSION {
+        return Err(SgfError::NewerVersion(version));
+    }
+    Ok(version)
+}
+
+// Brings an older record's nodes up to the current format one version at a
+// time, so the reader below only ever sees the current one
+fn migrate(mut nodes: Vec<Node>, version: u32) -> Vec<Node> {
+    for from in version..FORMAT_VERSION {
+        nodes = match from {
+            // Everything added before versioning (markup, TS, VR, NP, CP,
+            // PB, PW and RE) came as a new optional property, so unversioned
+            // records read as they are
+            1 => nodes,
+            _ => unreachable!("no migration from format version {}", from),
+        };
+    }
+    nodes
+}
+
+pub fn parse(input: &str) -> Result<GameRecord, SgfError> {
+    let nodes = Parser::new(input).parse_game_tree()?;
+    let version = format_version(&nodes)?;
+    let nodes = migrate(nodes, version);
+    let mut record = GameRecord::new(19, 0.0);
+
+    for (index, node) in nodes.iter().enumerate() {
+        // Markup belongs to the position after the node's move, wherever it
+        // appears among the node's properties
+        let mut markup = Vec::new();
+        let mut thinking = None;
+        for (key, value) in node {
+            match key.as_str() {
+                "GM" if value != GAME_ID => return Err(SgfError::UnsupportedGame(value.clone())),
+                "SZ" if index > 0 || !record.moves.is_empty() => return Err(SgfError::MisplacedSize),
+                "SZ" => {
+                    record.board_size = value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|size| (1..=MAX_SIZE).contains(size))
+                        .ok_or_else(|| SgfError::InvalidSize(value.clone()))?;
+                }
+                "KM" => {
+                    record.komi = value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|komi: &f32| komi.is_finite())
+                        .ok_or_else(|| SgfError::InvalidKomi(value.clone()))?;
+                }
+                "VR" => {
+                    record.variant = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| SgfError::UnsupportedVariant(value.clone()))?;
+                }
+                "NP" => {
+                    record.players = value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|players| (2..=StoneColor::MAX_PLAYERS).contains(players))
+                        .ok_or_else(|| SgfError::InvalidPlayers(value.clone()))?;
+                }
+                "B" | "W" | "R" | "G" => {
+                    let color = StoneColor::from_letter(key).expect("matched a color letter");
+                    record.moves.push(match parse_coordinate(value, record.board_size)? {
+                        Some(position) => MoveRecord::place(color, position),
+                        None => MoveRecord::pass(color),
+                    });
+                }
+                "CP" => {
+                    let keyframe: CameraKeyframe = value
+                        .parse()
+                        .map_err(|_| SgfError::InvalidCameraPath(value.clone()))?;
+                    record.camera_path.push(keyframe);
+                }
+                "PB" => record.black_player = Some(value.clone()),
+                "PW" => record.white_player = Some(value.clone()),
+                "RE" => {
+                    let result: GameResult = value
+                        .parse()
+                        .map_err(|_| SgfError::InvalidResult(value.clone()))?;
+                    record.result = Some(result);
+                }
+                "RS" => {
+                    let seed = value.trim().parse().map_err(|_| SgfError::InvalidSeed(value.clone()))?;
+                    record.seed = Some(seed);
+                }
+                "LB" | "TR" | "SQ" | "MK" => markup.push((key.as_str(), value.as_str())),
+                "TS" => thinking = Some(parse_thinking(value)?),
+                _ => {}
+            }
+        }
+        // Like markup, the time goes with the node's move wherever it appears
+        if thinking.is_some() && node.iter().any(|(key, _)| StoneColor::from_letter(key).is_some()) {
+            if let Some(last) = record.moves.last_mut() {
+                last.thinking = thinking;
+            }
+        }
+        for (key, value) in markup {
+            let annotation = parse_markup(key, value, record.board_size, record.moves.len())?;
+            record.annotations.insert(annotation);
+        }
+    }
+
+    Ok(record)
+}
+
+fn parse_markup(key: &str, value: &str, board_size: usize, move_number: usize) -> Result<Annotation, SgfError> {
+    let invalid = || SgfError::InvalidMarkup(value.to_string());
+    let (point, extra) = match value.split_once(':') {
+        Some((point, extra)) => (point, Some(extra)),
+        None => (value, None),
+    };
+    let position = parse_coordinate(point, board_size)?.ok_or_else(invalid)?;
+
+    let mark = match (key, extra) {
+        ("LB", Some(text)) => {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(letter), None) if letter.is_ascii_uppercase() => Mark::Label(letter),
+                _ => return Err(invalid()),
+            }
+        }
+        ("TR", None) => Mark::Triangle,
+        ("SQ", None) => Mark::Square,
+        ("MK", Some(color)) => Mark::Marker(color.parse().map_err(|_| invalid())?),
+        _ => return Err(invalid()),
+    };
+    Ok(Annotation {
+        move_number,
+        position,
+        mark,
+    })
+}
+
+fn parse_thinking(value: &str) -> Result<Duration, SgfError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .map(|seconds| Duration::from_millis((seconds * 1000.0).round() as u64))
+        .ok_or_else(|| SgfError::InvalidTime(value.to_string()))
+}
+
+fn parse_coordinate(value: &str, board_size: usize) -> Result<Option<(u8, u8, u8)>, SgfError> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let coords: Vec<u8> = value
+        .bytes()
+        .map(|b| b.wrapping_sub(b'a'))
+        .filter(|&c| (c as usize) < board_size)
+        .collect();
+
+    if value.len() != 3 || coords.len() != 3 {
+        return Err(SgfError::InvalidCoordinate(value.to_string()));
+    }
+
+    Ok(Some((coords[0], coords[1], coords[2])))
+}
+
+type Node = Vec<(String, String)>;
+
+struct Parser<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, offset: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.offset..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SgfError> {
+        self.skip_whitespace();
+        let offset = self.offset;
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(found) => Err(SgfError::UnexpectedChar { offset, found }),
+            None => Err(SgfError::UnexpectedEnd),
+        }
+    }
+
+    fn parse_game_tree(&mut self) -> Result<Vec<Node>, SgfError> {
+        self.expect('(')?;
+        let mut nodes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(';') => {
+                    self.bump();
+                    nodes.push(self.parse_node()?);
+                }
+                Some(')') => {
+                    self.bump();
+                    return Ok(nodes);
+                }
+                Some('(') => return Err(SgfError::Variations),
+                Some(found) => {
+                    return Err(SgfError::UnexpectedChar {
+                        offset: self.offset,
+                        found,
+                    })
+                }
+                None => return Err(SgfError::UnexpectedEnd),
+            }
+        }
+    }
+
+    fn parse_node(&mut self) -> Result<Node, SgfError> {
+        let mut properties = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if !self.peek().is_some_and(|c| c.is_ascii_uppercase()) {
+                return Ok(properties);
+            }
+
+            let start = self.offset;
+            while self.peek().is_some_and(|c| c.is_ascii_uppercase()) {
+                self.bump();
+            }
+            let key = self.input[start..self.offset].to_string();
+
+            // A property may carry several values; keep each as its own pair
+            self.expect('[')?;
+            loop {
+                properties.push((key.clone(), self.parse_value()?));
+                self.skip_whitespace();
+                if self.peek() != Some('[') {
+                    break;
+                }
+                self.bump();
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<String, SgfError> {
+        let mut value = String::new();
+
+        loop {
+            match self.bump() {
+                Some(']') => return Ok(value),
+                Some('\\') => value.push(self.bump().ok_or(SgfError::UnexpectedEnd)?),
+                Some(c) => value.push(c),
+                None => return Err(SgfError::UnexpectedEnd),
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+// Share links: the game's SGF-3D record, deflated and base64url-encoded so it
+// fits in a URL fragment, e.g. https://example.org/3dgo/#game=eJzTtU...
+// Games that are nothing but their moves go in the smaller compact binary
+// form instead, e.g. #g=AQMCAADQQAAOAQ for ;B[bbb];W[aaa] on 3³ at komi 6.5
+use super::{codec, sgf, GameRecord};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::fmt;
+use std::io::{Read, Write};
+
+pub const FRAGMENT_PREFIX: &str = "#game=";
+pub const COMPACT_PREFIX: &str = "#g=";
+
+// Guards against decompression bombs; real records are a few kilobytes
+const MAX_RECORD_BYTES: u64 = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareError {
+    NotAGameLink,
+    Encoding,
+    Compression,
+    Record(sgf::SgfError),
+    Compact(codec::CodecError),
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::NotAGameLink => write!(f, "link does not contain a game"),
+            ShareError::Encoding => write!(f, "link is not valid base64"),
+            ShareError::Compression => write!(f, "link data is corrupted"),
+            ShareError::Record(error) => write!(f, "link contains an invalid record: {}", error),
+            ShareError::Compact(error) => write!(f, "link contains an invalid game: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+pub fn encode(record: &GameRecord) -> String {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(sgf::write(record).as_bytes())
+        .expect("writing to a Vec cannot fail");
+    let compressed = encoder.finish().expect("writing to a Vec cannot fail");
+    URL_SAFE_NO_PAD.encode(compressed)
+}
+
+pub fn decode(payload: &str) -> Result<GameRecord, ShareError> {
+    let compressed = URL_SAFE_NO_PAD
+        .decode(payload.trim())
+        .map_err(|_| ShareError::Encoding)?;
+
+    let mut text = String::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_RECORD_BYTES)
+        .read_to_string(&mut text)
+        .map_err(|_| ShareError::Compression)?;
+
+    sgf::parse(&text).map_err(ShareError::Record)
+}
+
+pub fn to_fragment(record: &GameRecord) -> String {
+    match codec::encode(record) {
+        Some(bytes) => format!("{}{}", COMPACT_PREFIX, URL_SAFE_NO_PAD.encode(bytes)),
+        None => format!("{}{}", FRAGMENT_PREFIX, encode(record)),
+    }
+}
+
+pub fn is_game_link(link: &str) -> bool {
+    link.contains(FRAGMENT_PREFIX) || link.contains(COMPACT_PREFIX)
+}
+
+// Accepts a bare fragment ("#game=..." or "#g=...") or a full URL containing one
+pub fn from_fragment(link: &str) -> Result<GameRecord, ShareError> {
+    if let Some(start) = link.find(COMPACT_PREFIX) {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(link[start + COMPACT_PREFIX.len()..].trim())
+            .map_err(|_| ShareError::Encoding)?;
+        return codec::decode(&bytes).map_err(ShareError::Compact);
+    }
+    let start = link.find(FRAGMENT_PREFIX).ok_or(ShareError::NotAGameLink)?;
+    decode(&link[start + FRAGMENT_PREFIX.len()..])
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+// A summary of the position for the info panel: the stones of each color,
+// the groups they make, how many of those are in atari, the empty points and
+// the size of the largest group. One pass over the board's groups.
+use super::{Board, StoneColor};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionStats {
+    // By StoneColor::index()
+    pub stones: [usize; StoneColor::MAX_PLAYERS as usize],
+    pub groups: usize,
+    pub groups_in_atari: usize,
+    pub empty: usize,
+    pub largest_group: usize,
+}
+
+impl PositionStats {
+    pub fn of(board: &Board) -> Self {
+        let mut stats = Self {
+            empty: board.empty_positions().count(),
+            ..Self::default()
+        };
+        for (_, &color) in board.get_all_stones() {
+            stats.stones[color.index()] += 1;
+        }
+        for (group, liberties) in board.groups() {
+            stats.groups += 1;
+            stats.groups_in_atari += usize::from(liberties == 1);
+            stats.largest_group = stats.largest_group.max(group.len());
+        }
+        stats
+    }
+
+    // One line each, as the panel shows them: the stones of the colors in
+    // play, then the rest
+    pub fn rows(&self, players: u8) -> Vec<String> {
+        let mut rows: Vec<String> = StoneColor::players(players)
+            .iter()
+            .map(|color| format!("{} {}", color.name().to_uppercase(), self.stones[color.index()]))
+            .collect();
+        rows.push(format!("GROUPS {}", self.groups));
+        rows.push(format!("IN ATARI {}", self.groups_in_atari));
+        rows.push(format!("EMPTY {}", self.empty));
+        rows.push(format!("LARGEST {}", self.largest_group));
+        rows
+    }
+}
@@ -2,15 +2,72 @@
 pub enum StoneColor {
     Black,
     White,
+    // The third and fourth players, in games of more than two
+    Red,
+    Green,
 }
 
 impl StoneColor {
+    // In turn order
+    pub const ALL: [StoneColor; 4] = [StoneColor::Black, StoneColor::White, StoneColor::Red, StoneColor::Green];
+    pub const MAX_PLAYERS: u8 = 4;
+
+    // Black and White swap. The extra colors never meet two-player code, but
+    // pair up with each other so this stays total.
     pub fn opposite(&self) -> Self {
         match self {
             StoneColor::Black => StoneColor::White,
             StoneColor::White => StoneColor::Black,
+            StoneColor::Red => StoneColor::Green,
+            StoneColor::Green => StoneColor::Red,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StoneColor::Black => "Black",
+            StoneColor::White => "White",
+            StoneColor::Red => "Red",
+            StoneColor::Green => "Green",
         }
     }
+
+    // As written in records and text commands
+    pub fn letter(self) -> char {
+        match self {
+            StoneColor::Black => 'B',
+            StoneColor::White => 'W',
+            StoneColor::Red => 'R',
+            StoneColor::Green => 'G',
+        }
+    }
+
+    pub fn from_letter(letter: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|color| letter.len() == 1 && letter.starts_with(color.letter()))
+    }
+
+    // The player's place in turn order, from 0
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    // The colors taking part in a game of `players`
+    pub fn players(players: u8) -> &'static [StoneColor] {
+        &Self::ALL[..usize::from(players.clamp(2, Self::MAX_PLAYERS))]
+    }
+
+    // Who moves after this player in a game of `players`
+    pub fn next(self, players: u8) -> Self {
+        let players = Self::players(players);
+        players[(self.index() + 1) % players.len()]
+    }
+
+    pub fn previous(self, players: u8) -> Self {
+        let players = Self::players(players);
+        players[(self.index() + players.len() - 1) % players.len()]
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,70 @@
+// Rotations and reflections of the cube, for turning a whole position around
+// to compose problems or compare the same shape seen from another side. Any
+// of the cube's 48 symmetries is a sequence of these.
+use std::fmt;
+use std::str::FromStr;
+
+type Position = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl FromStr for Axis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "x" => Ok(Axis::X),
+            "y" => Ok(Axis::Y),
+            "z" => Ok(Axis::Z),
+            other => Err(format!("unknown axis '{}', expected x, y or z", other)),
+        }
+    }
+}
+
+impl fmt::Display for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Axis::X => f.write_str("x"),
+            Axis::Y => f.write_str("y"),
+            Axis::Z => f.write_str("z"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    // A quarter turn about the axis: about z, x turns towards y; about x, y
+    // towards z; about y, z towards x
+    Rotate(Axis),
+    // Flips the axis, keeping the other two
+    Reflect(Axis),
+}
+
+impl Symmetry {
+    // Where `position` ends up on a board of `size`
+    pub fn apply(self, (x, y, z): Position, size: usize) -> Position {
+        let flip = |n: u8| (size - 1) as u8 - n;
+        match self {
+            Symmetry::Rotate(Axis::X) => (x, flip(z), y),
+            Symmetry::Rotate(Axis::Y) => (z, y, flip(x)),
+            Symmetry::Rotate(Axis::Z) => (flip(y), x, z),
+            Symmetry::Reflect(Axis::X) => (flip(x), y, z),
+            Symmetry::Reflect(Axis::Y) => (x, flip(y), z),
+            Symmetry::Reflect(Axis::Z) => (x, y, flip(z)),
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Symmetry::Rotate(axis) => write!(f, "rotate {}", axis),
+            Symmetry::Reflect(axis) => write!(f, "mirror {}", axis),
+        }
+    }
+}
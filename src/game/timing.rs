@@ -0,0 +1,76 @@
+// Thinking time: how long each side took over its moves, from the times the
+// session stamps on each move record. Untimed moves (typed-in records, older
+// files) are left out.
+use super::{MoveRecord, StoneColor};
+use std::fmt;
+use std::time::Duration;
+
+// "0.8 s", "42.1 s", "2 min 13 s"
+pub fn format_thinking(time: Duration) -> String {
+    let seconds = time.as_secs_f64();
+    if seconds < 60.0 {
+        format!("{:.1} s", seconds)
+    } else {
+        let seconds = time.as_secs();
+        format!("{} min {} s", seconds / 60, seconds % 60)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongestThink {
+    pub move_number: usize,
+    pub color: StoneColor,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThinkingSummary {
+    // By StoneColor::index
+    totals: [Duration; StoneColor::MAX_PLAYERS as usize],
+    // How many players the game had, going by who moved
+    players: u8,
+    pub longest: LongestThink,
+}
+
+impl ThinkingSummary {
+    // None when no move was timed
+    pub fn new(moves: &[MoveRecord]) -> Option<Self> {
+        let mut totals = [Duration::ZERO; StoneColor::MAX_PLAYERS as usize];
+        let players = moves.iter().map(|record| record.color.index() as u8 + 1).max().unwrap_or(2);
+        let mut longest: Option<LongestThink> = None;
+        for (index, record) in moves.iter().enumerate() {
+            let Some(time) = record.thinking else {
+                continue;
+            };
+            totals[record.color.index()] += time;
+            if longest.is_none_or(|longest| time > longest.time) {
+                longest = Some(LongestThink {
+                    move_number: index + 1,
+                    color: record.color,
+                    time,
+                });
+            }
+        }
+        longest.map(|longest| Self { totals, players, longest })
+    }
+
+    pub fn total(&self, color: StoneColor) -> Duration {
+        self.totals[color.index()]
+    }
+}
+
+// One line per player, then the longest think
+impl fmt::Display for ThinkingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &color in StoneColor::players(self.players) {
+            writeln!(f, "{} thought for {} in total", color.name(), format_thinking(self.total(color)))?;
+        }
+        write!(
+            f,
+            "Longest think: {} on move {} ({})",
+            format_thinking(self.longest.time),
+            self.longest.move_number,
+            self.longest.color.name()
+        )
+    }
+}
\ No newline at end of file
@@ -0,0 +1,171 @@
+// Which points a board has and which of them are adjacent. The standard board
+// is the full size³ lattice with six neighbours per point; variants plug in
+// their own shape, and everything built on Board (liberties, groups,
+// territory, legal moves, the board iterators) follows it.
+use super::zobrist::point_key;
+use std::fmt;
+
+type Position = (u8, u8, u8);
+
+pub trait Topology: Send + Sync + fmt::Debug {
+    // Whether `position`, inside the size³ cube, is a point of the board
+    fn contains(&self, size: usize, position: Position) -> bool;
+
+    // The board points next to `position`
+    fn neighbors(&self, size: usize, position: Position) -> Vec<Position>;
+
+    // Whether `position` is a point cut out of an otherwise whole board, drawn
+    // as a void, rather than just outside the board's shape
+    fn is_hole(&self, _size: usize, _position: Position) -> bool {
+        false
+    }
+}
+
+// One step along each axis in both directions, staying inside the cube
+pub fn lattice_neighbors(size: usize, (x, y, z): Position) -> impl Iterator<Item = Position> {
+    [(-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1)]
+        .into_iter()
+        .map(move |(dx, dy, dz)| (x as i16 + dx, y as i16 + dy, z as i16 + dz))
+        .filter(move |&(nx, ny, nz)| [nx, ny, nz].iter().all(|&c| (0..size as i16).contains(&c)))
+        .map(|(nx, ny, nz)| (nx as u8, ny as u8, nz as u8))
+}
+
+// The full cube: every point, six neighbours inside the board
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lattice;
+
+impl Topology for Lattice {
+    fn contains(&self, _size: usize, _position: Position) -> bool {
+        true
+    }
+
+    fn neighbors(&self, size: usize, position: Position) -> Vec<Position> {
+        lattice_neighbors(size, position).collect()
+    }
+}
+
+// `levels` ordinary 2D boards stacked along z, joined only at the ladder
+// points: a stone there also touches the points straight above and below it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stack {
+    levels: u8,
+    ladders: Vec<(u8, u8)>,
+}
+
+impl Stack {
+    // Ladders on the star points of a size×size board
+    pub fn new(size: usize, levels: u8) -> Self {
+        Self {
+            levels,
+            ladders: star_points(size),
+        }
+    }
+
+    pub fn levels(&self) -> u8 {
+        self.levels
+    }
+
+    pub fn ladders(&self) -> &[(u8, u8)] {
+        &self.ladders
+    }
+
+    fn is_ladder(&self, (x, y, _): Position) -> bool {
+        self.ladders.contains(&(x, y))
+    }
+}
+
+impl Topology for Stack {
+    fn contains(&self, _size: usize, (_, _, z): Position) -> bool {
+        z < self.levels
+    }
+
+    fn neighbors(&self, size: usize, position: Position) -> Vec<Position> {
+        lattice_neighbors(size, position)
+            .filter(|&neighbor| self.contains(size, neighbor))
+            .filter(|&(_, _, z)| z == position.2 || self.is_ladder(position))
+            .collect()
+    }
+}
+
+// Only the surface of the cube: the points with a coordinate on a face, hollow
+// inside. Neighbours are the surface points one step away, so groups run
+// around edges and corners onto the next face; a face point has four, an edge
+// point four and a corner three.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Shell;
+
+impl Topology for Shell {
+    fn contains(&self, size: usize, (x, y, z): Position) -> bool {
+        let last = size.saturating_sub(1) as u8;
+        [x, y, z].iter().any(|&c| c == 0 || c == last)
+    }
+
+    fn neighbors(&self, size: usize, position: Position) -> Vec<Position> {
+        lattice_neighbors(size, position)
+            .filter(|&neighbor| self.contains(size, neighbor))
+            .collect()
+    }
+}
+
+// The full cube with a seeded `percent` of its points cut out. Whether a point
+// is missing depends only on the seed and the point, so a record's seed brings
+// back the same holes on any machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Holes {
+    seed: u64,
+    percent: u8,
+}
+
+impl Holes {
+    pub fn new(seed: u64, percent: u8) -> Self {
+        Self {
+            seed,
+            percent: percent.min(100),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn percent(&self) -> u8 {
+        self.percent
+    }
+}
+
+impl Topology for Holes {
+    fn contains(&self, size: usize, position: Position) -> bool {
+        !self.is_hole(size, position)
+    }
+
+    fn neighbors(&self, size: usize, position: Position) -> Vec<Position> {
+        lattice_neighbors(size, position)
+            .filter(|&neighbor| self.contains(size, neighbor))
+            .collect()
+    }
+
+    fn is_hole(&self, _size: usize, position: Position) -> bool {
+        point_key(self.seed, position) % 100 < u64::from(self.percent)
+    }
+}
+
+// The handicap points of 2D Go: corners on the 4th line (3rd below 13×13),
+// plus the centre and side midpoints on odd boards; just the centre on small ones
+fn star_points(size: usize) -> Vec<(u8, u8)> {
+    let size = size as u8;
+    let center = size / 2;
+    let edge = match size {
+        13.. => 3,
+        9.. => 2,
+        _ => return vec![(center, center)],
+    };
+    let mut lines = vec![edge, size - 1 - edge];
+    if size % 2 == 1 && size >= 13 {
+        lines.insert(1, center);
+    }
+    let mut points: Vec<(u8, u8)> = lines.iter().flat_map(|&x| lines.iter().map(move |&y| (x, y))).collect();
+    if size % 2 == 1 && !points.contains(&(center, center)) {
+        points.push((center, center));
+    }
+    points
+}
\ No newline at end of file
@@ -0,0 +1,84 @@
+// Taking moves back by agreement. The player who wants their last move back
+// asks, and the opponent agrees or declines: a person by answering, the AI by
+// its policy, which agrees in casual games and declines in rated ones, where
+// the result counts. Agreed, the game goes back to before the asker's last
+// move, so any replies to it go too.
+use super::{GameRules, GameSession, StoneColor};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UndoRequest {
+    // The player asking, whose last move is taken back
+    pub color: StoneColor,
+    // How many moves go, theirs and everything played since
+    pub moves: usize,
+}
+
+impl UndoRequest {
+    // None when `color` has no move to take back
+    pub fn new(color: StoneColor, rules: &GameRules) -> Option<Self> {
+        let moves = rules.moves().iter().rev().position(|mv| mv.color == color)? + 1;
+        Some(Self { color, moves })
+    }
+
+    // Takes the moves back, or nothing when the game has changed since the
+    // request and it no longer fits
+    pub fn grant(&self, session: &GameSession) -> bool {
+        session.undo_if(self.moves, |rules| Self::new(self.color, rules) == Some(*self))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoPolicy {
+    Accept,
+    Decline,
+}
+
+impl UndoPolicy {
+    // The AI's answer to every request in a game
+    pub fn for_ai(rated: bool) -> Self {
+        if rated {
+            UndoPolicy::Decline
+        } else {
+            UndoPolicy::Accept
+        }
+    }
+}
+
+// The exchange as lines of text, one message each, for players who aren't at
+// the same screen: "undo B 2" asks, "undo accept" and "undo decline" answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoMessage {
+    Request(UndoRequest),
+    Answer(UndoPolicy),
+}
+
+impl fmt::Display for UndoMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UndoMessage::Request(request) => write!(f, "undo {} {}", request.color.letter(), request.moves),
+            UndoMessage::Answer(UndoPolicy::Accept) => f.write_str("undo accept"),
+            UndoMessage::Answer(UndoPolicy::Decline) => f.write_str("undo decline"),
+        }
+    }
+}
+
+impl FromStr for UndoMessage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid undo message '{}'", s.trim());
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match words[..] {
+            ["undo", "accept"] => Ok(UndoMessage::Answer(UndoPolicy::Accept)),
+            ["undo", "decline"] => Ok(UndoMessage::Answer(UndoPolicy::Decline)),
+            ["undo", letter, moves] => {
+                let color = StoneColor::from_letter(letter).ok_or_else(invalid)?;
+                let moves = moves.parse().ok().filter(|&moves| moves > 0).ok_or_else(invalid)?;
+                Ok(UndoMessage::Request(UndoRequest { color, moves }))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
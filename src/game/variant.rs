@@ -0,0 +1,139 @@
+// The rule variants a game can be played under. Each sets up the hooks it
+// needs on GameRules when the game starts; records name their variant so they
+// replay under the same rules.
+use super::placement::Gravity;
+use super::topology::{Holes, Lattice, Shell, Stack, Topology};
+use super::victory::Hill;
+use super::GameRules;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Variant {
+    #[default]
+    Standard,
+    // Stones drop to the lowest empty point of their column, as on a physical set
+    Gravity,
+    // This many 2D boards stacked up, joined only at their star points
+    Stack { levels: u8 },
+    // Experimental: only the cube's surface is played, groups wrap around its edges
+    Shell,
+    // The full cube with a seeded percentage of its points missing
+    Holes { seed: u64, percent: u8 },
+    // King of the hill: holding the central points for this many moves in a row wins
+    Hill { turns: u8 },
+    // Experimental: both sides choose a move each turn and the two are revealed together
+    Simultaneous,
+}
+
+impl Variant {
+    pub const DEFAULT_STACK_LEVELS: u8 = 3;
+    pub const DEFAULT_HOLE_PERCENT: u8 = 10;
+    pub const DEFAULT_HILL_TURNS: u8 = 10;
+
+    // An empty board under this variant's rules
+    pub fn new_game(self, board_size: usize) -> GameRules {
+        let mut rules = GameRules::new(board_size);
+        rules.set_variant(self);
+        rules
+    }
+
+    pub(super) fn apply(self, rules: &mut GameRules) {
+        let size = rules.board().size();
+        let topology: Arc<dyn Topology> = match self {
+            Variant::Stack { levels } => Arc::new(Stack::new(size, levels.min(size as u8))),
+            Variant::Shell => Arc::new(Shell),
+            Variant::Holes { seed, percent } => Arc::new(Holes::new(seed, percent)),
+            _ => Arc::new(Lattice),
+        };
+        rules.set_topology(topology);
+        match self {
+            Variant::Gravity => rules.set_placement_rule(Some(Arc::new(Gravity::LAYERS))),
+            _ => rules.set_placement_rule(None),
+        }
+        match self {
+            Variant::Hill { turns } => {
+                let hill = Hill::new(rules.board(), turns);
+                rules.set_victory_condition(Some(Arc::new(hill)));
+            }
+            _ => rules.set_victory_condition(None),
+        }
+    }
+}
+
+impl Variant {
+    // "holes" picks a fresh seed; records always carry one, so replays match
+    fn parse_holes(args: Option<&str>) -> Result<Self, String> {
+        let (seed, percent) = match args.map(|args| args.split_once(':')) {
+            None => (None, None),
+            Some(None) => (args, None),
+            Some(Some((seed, percent))) => (Some(seed), Some(percent)),
+        };
+        let seed = match seed {
+            Some(seed) => seed.parse().map_err(|_| format!("invalid hole seed '{}'", seed))?,
+            None => rand::random(),
+        };
+        let percent = match percent {
+            Some(percent) => match percent.parse() {
+                Ok(percent @ 0..=90) => percent,
+                _ => return Err(format!("invalid hole percentage '{}' (expected 0 to 90)", percent)),
+            },
+            None => Self::DEFAULT_HOLE_PERCENT,
+        };
+        Ok(Variant::Holes { seed, percent })
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+        let (name, levels) = match s.split_once(':') {
+            Some((name, levels)) => (name, Some(levels)),
+            None => (s.as_str(), None),
+        };
+        if name == "holes" {
+            return Self::parse_holes(levels);
+        }
+        match (name, levels) {
+            ("standard", None) => Ok(Variant::Standard),
+            ("gravity", None) => Ok(Variant::Gravity),
+            ("shell", None) => Ok(Variant::Shell),
+            ("simultaneous", None) => Ok(Variant::Simultaneous),
+            ("stack", None) => Ok(Variant::Stack {
+                levels: Self::DEFAULT_STACK_LEVELS,
+            }),
+            ("stack", Some(levels)) => match levels.parse() {
+                Ok(levels @ 1..) => Ok(Variant::Stack { levels }),
+                _ => Err(format!("invalid number of stacked boards '{}'", levels)),
+            },
+            ("hill", None) => Ok(Variant::Hill {
+                turns: Self::DEFAULT_HILL_TURNS,
+            }),
+            ("hill", Some(turns)) => match turns.parse() {
+                Ok(turns @ 1..) => Ok(Variant::Hill { turns }),
+                _ => Err(format!("invalid number of moves to hold the hill '{}'", turns)),
+            },
+            _ => Err(format!(
+                "unknown variant '{}' (expected standard, gravity, stack[:levels], shell, holes[:seed[:percent]], hill[:moves] or simultaneous)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Variant::Standard => f.write_str("standard"),
+            Variant::Gravity => f.write_str("gravity"),
+            Variant::Stack { levels } => write!(f, "stack:{}", levels),
+            Variant::Shell => f.write_str("shell"),
+            Variant::Holes { seed, percent } => write!(f, "holes:{}:{}", seed, percent),
+            Variant::Hill { turns } => write!(f, "hill:{}", turns),
+            Variant::Simultaneous => f.write_str("simultaneous"),
+        }
+    }
+}
\ No newline at end of file
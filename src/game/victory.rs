@@ -0,0 +1,99 @@
+// Ways to win outright before the game is scored, plugged into GameRules by
+// the variant being played. The rules ask the condition after every move;
+// once it names a winner the game is over and no more moves are accepted.
+use super::topology::lattice_neighbors;
+use super::{Board, GameRules, StoneColor};
+use std::fmt;
+
+type Position = (u8, u8, u8);
+
+pub trait VictoryCondition: Send + Sync + fmt::Debug {
+    // The side that has won, judged on the game so far
+    fn winner(&self, rules: &GameRules) -> Option<StoneColor>;
+
+    // How the winner won, e.g. "held the hill"
+    fn describe_win(&self) -> &str;
+
+    // Points the condition is fought over, highlighted by the renderer
+    fn contested(&self) -> &[Position] {
+        &[]
+    }
+
+    // Who holds the contested points on `board`, for conditions won by
+    // holding them; the rules count how long they've been held, move by move,
+    // as GameRules::control
+    fn controller(&self, _board: &Board) -> Option<StoneColor> {
+        None
+    }
+}
+
+// King of the hill: holding more of the central points than the opponent
+// after each of `turns` moves in a row (either side's) wins
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hill {
+    points: Vec<Position>,
+    turns: u8,
+}
+
+impl Hill {
+    // The centre and its neighbours on odd boards, the middle 2×2×2 on even
+    // ones, leaving out any the board doesn't have
+    pub fn new(board: &Board, turns: u8) -> Self {
+        let size = board.size() as u8;
+        let low = size.saturating_sub(1) / 2;
+        let high = size / 2;
+        let points: Vec<Position> = if low == high {
+            let center = (low, low, low);
+            std::iter::once(center).chain(lattice_neighbors(board.size(), center)).collect()
+        } else {
+            let sides = [low, high];
+            sides
+                .iter()
+                .flat_map(|&x| sides.into_iter().flat_map(move |y| sides.into_iter().map(move |z| (x, y, z))))
+                .collect()
+        };
+        Self {
+            points: points.into_iter().filter(|&(x, y, z)| board.is_valid_position(x, y, z)).collect(),
+            turns: turns.max(1),
+        }
+    }
+
+    pub fn points(&self) -> &[Position] {
+        &self.points
+    }
+
+    pub fn turns(&self) -> u8 {
+        self.turns
+    }
+}
+
+impl VictoryCondition for Hill {
+    fn winner(&self, rules: &GameRules) -> Option<StoneColor> {
+        rules
+            .control()
+            .filter(|&(_, moves)| moves >= usize::from(self.turns))
+            .map(|(color, _)| color)
+    }
+
+    fn describe_win(&self) -> &str {
+        "held the hill"
+    }
+
+    fn contested(&self) -> &[Position] {
+        &self.points
+    }
+
+    // The side with more stones on the hill than any other, if there is one
+    fn controller(&self, board: &Board) -> Option<StoneColor> {
+        let mut counts = [0; StoneColor::MAX_PLAYERS as usize];
+        for color in self.points.iter().filter_map(|&point| board.get_stone(point)) {
+            counts[color.index()] += 1;
+        }
+        let most = *counts.iter().max()?;
+        let mut leaders = StoneColor::ALL.into_iter().filter(|color| counts[color.index()] == most);
+        match (leaders.next(), leaders.next()) {
+            (Some(leader), None) if most > 0 => Some(leader),
+            _ => None,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,131 @@
+// What a move would do, worked out without playing it: which stones it would
+// capture and how many liberties the new stone's group would be left with.
+// The board isn't copied; reads go through a thin layer holding the one
+// stone placed and the stones taken off, over the board as it stands, so a
+// preview can follow the pointer every frame even on large boards.
+use super::{zobrist, Board, GameRules, MoveError, StoneColor};
+use std::collections::HashSet;
+
+type Position = (u8, u8, u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovePreview {
+    pub position: Position,
+    pub color: StoneColor,
+    // Every stone the move would take, sorted
+    pub captured: Vec<Position>,
+    // The new stone's group once the captures are off, sorted
+    pub group: Vec<Position>,
+    pub liberties: usize,
+}
+
+impl MovePreview {
+    pub fn in_atari(&self) -> bool {
+        self.liberties == 1
+    }
+}
+
+// The board with one stone placed and some taken off, reading through to
+// the real board for everything else
+struct Layer<'a> {
+    board: &'a Board,
+    placed: (Position, StoneColor),
+    removed: HashSet<Position>,
+}
+
+impl Layer<'_> {
+    fn stone(&self, position: Position) -> Option<StoneColor> {
+        if position == self.placed.0 {
+            return Some(self.placed.1);
+        }
+        if self.removed.contains(&position) {
+            return None;
+        }
+        self.board.get_stone(position)
+    }
+
+    // The stones connected to `start` and the empty points around them
+    fn group(&self, start: Position) -> (HashSet<Position>, HashSet<Position>) {
+        let mut group = HashSet::new();
+        let mut liberties = HashSet::new();
+        let Some(color) = self.stone(start) else {
+            return (group, liberties);
+        };
+        let mut stack = vec![start];
+        while let Some(position) = stack.pop() {
+            if !group.insert(position) {
+                continue;
+            }
+            for neighbor in self.board.get_neighbors(position) {
+                match self.stone(neighbor) {
+                    Some(stone) if stone == color => stack.push(neighbor),
+                    Some(_) => {}
+                    None => {
+                        liberties.insert(neighbor);
+                    }
+                }
+            }
+        }
+        (group, liberties)
+    }
+}
+
+// The side to move playing `position`, or why it can't; the same verdict as
+// `GameRules::check_move`
+pub fn preview(rules: &GameRules, position: Position) -> Result<MovePreview, MoveError> {
+    let board = rules.board();
+    let (x, y, z) = position;
+    if !board.is_valid_position(x, y, z) {
+        return Err(MoveError::OutOfBounds);
+    }
+    if rules.winner().is_some() {
+        return Err(MoveError::GameOver);
+    }
+    if board.get_stone(position).is_some() {
+        return Err(MoveError::Occupied);
+    }
+    if rules.resolve_placement(position) != Some(position) {
+        return Err(MoveError::NotAllowed);
+    }
+
+    let color = rules.current_player();
+    let mut layer = Layer {
+        board,
+        placed: (position, color),
+        removed: HashSet::new(),
+    };
+    let mut captured = Vec::new();
+    for neighbor in board.get_neighbors(position) {
+        if layer.stone(neighbor).is_some_and(|stone| stone != color) {
+            let (group, liberties) = layer.group(neighbor);
+            if liberties.is_empty() {
+                captured.extend(group.iter().copied());
+                layer.removed.extend(group);
+            }
+        }
+    }
+
+    let (group, liberties) = layer.group(position);
+    if liberties.is_empty() {
+        return Err(MoveError::Suicide);
+    }
+    let hash = captured
+        .iter()
+        .fold(board.hash() ^ zobrist::stone_key(position, color), |hash, &stone| {
+            hash ^ zobrist::stone_key(stone, board.get_stone(stone).expect("captured stones are on the board"))
+        });
+    if rules.positions().contains(hash) {
+        return Err(MoveError::Ko);
+    }
+
+    captured.sort_unstable();
+    let mut group: Vec<Position> = group.into_iter().collect();
+    group.sort_unstable();
+    Ok(MovePreview {
+        position,
+        color,
+        captured,
+        group,
+        liberties: liberties.len(),
+    })
+}
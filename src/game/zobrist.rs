@@ -0,0 +1,123 @@
+// Zobrist hashing: every (point, color) has a fixed random 64-bit key and a
+// board's hash is the XOR of the keys of its stones, so placing or removing a
+// stone updates it in constant time. The board keeps its hash current; the
+// rules use it to spot repeated positions (superko) and the AI to recognise
+// positions it has already searched, both through a PositionTable.
+use super::StoneColor;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+type Position = (u8, u8, u8);
+
+// splitmix64: a cheap, well-mixed function of the input, so keys need no table
+// and are the same for every board size and every run
+pub fn mix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+pub fn stone_key((x, y, z): Position, color: StoneColor) -> u64 {
+    // 3 is taken by point keys
+    let color = match color {
+        StoneColor::Black => 1,
+        StoneColor::White => 2,
+        StoneColor::Red => 4,
+        StoneColor::Green => 5,
+    };
+    mix(u64::from(x) | u64::from(y) << 8 | u64::from(z) << 16 | color << 24)
+}
+
+// A random value per point for anything else seeded by position (board
+// variants), independent of the stone keys
+pub fn point_key(seed: u64, (x, y, z): Position) -> u64 {
+    mix(seed ^ mix(u64::from(x) | u64::from(y) << 8 | u64::from(z) << 16 | 3 << 24))
+}
+
+// XORed into a board hash where the side to move matters (search trees); the
+// superko rule only compares stones
+pub fn to_move_key(color: StoneColor) -> u64 {
+    match color {
+        StoneColor::Black => 0,
+        StoneColor::White => mix(u64::MAX),
+        StoneColor::Red => mix(u64::MAX - 1),
+        StoneColor::Green => mix(u64::MAX - 2),
+    }
+}
+
+// Zobrist hashes are already uniformly random, so the table uses them as they are
+#[derive(Debug, Default, Clone, Copy)]
+struct KeyHasher(u64);
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(byte);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = value;
+    }
+}
+
+// Something known about each position, looked up by its hash
+#[derive(Debug, Clone)]
+pub struct PositionTable<V> {
+    entries: HashMap<u64, V, BuildHasherDefault<KeyHasher>>,
+}
+
+impl<V> Default for PositionTable<V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::default(),
+        }
+    }
+}
+
+impl<V> PositionTable<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, hash: u64) -> Option<&V> {
+        self.entries.get(&hash)
+    }
+
+    pub fn get_mut(&mut self, hash: u64) -> Option<&mut V> {
+        self.entries.get_mut(&hash)
+    }
+
+    pub fn contains(&self, hash: u64) -> bool {
+        self.entries.contains_key(&hash)
+    }
+
+    pub fn insert(&mut self, hash: u64, value: V) -> Option<V> {
+        self.entries.insert(hash, value)
+    }
+
+    pub fn get_or_insert_with(&mut self, hash: u64, value: impl FnOnce() -> V) -> &mut V {
+        self.entries.entry(hash).or_insert_with(value)
+    }
+
+    pub fn remove(&mut self, hash: u64) -> Option<V> {
+        self.entries.remove(&hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,174 @@
+// Short vibration pulses for placements and captures: navigator.vibrate or the
+// gamepad rumble actuator in browsers, gilrs force feedback on native
+// (behind the `gamepad` feature). Devices without support are silently skipped.
+use crate::game::SessionEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pulse {
+    pub duration_ms: u32,
+    pub strength: f32, // 0.0..=1.0
+}
+
+impl Pulse {
+    pub const PLACEMENT: Pulse = Pulse {
+        duration_ms: 20,
+        strength: 0.3,
+    };
+    pub const CAPTURE: Pulse = Pulse {
+        duration_ms: 120,
+        strength: 0.8,
+    };
+
+    pub fn for_event(event: &SessionEvent) -> Option<Pulse> {
+        match event {
+            SessionEvent::MovePlayed { captured: 0, .. } => Some(Pulse::PLACEMENT),
+            SessionEvent::MovePlayed { .. } => Some(Pulse::CAPTURE),
+            _ => None,
+        }
+    }
+}
+
+pub struct Haptics {
+    enabled: bool,
+    device: Device,
+}
+
+impl Haptics {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            device: Device::new(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn pulse(&mut self, pulse: Pulse) {
+        if self.enabled {
+            self.device.pulse(pulse);
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &SessionEvent) {
+        if let Some(pulse) = Pulse::for_event(event) {
+            self.pulse(pulse);
+        }
+    }
+}
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+struct Device {
+    gilrs: Option<gilrs::Gilrs>,
+    // Dropping an effect stops it, so the latest one is kept until replaced
+    effect: Option<gilrs::ff::Effect>,
+}
+
+#[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+impl Device {
+    fn new() -> Self {
+        let gilrs = gilrs::Gilrs::new()
+            .map_err(|error| log::warn!("gamepad support unavailable: {}", error))
+            .ok();
+        Self { gilrs, effect: None }
+    }
+
+    fn pulse(&mut self, pulse: Pulse) {
+        use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Replay, Ticks};
+
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return;
+        };
+        // Drain events so newly connected controllers are picked up
+        while gilrs.next_event().is_some() {}
+
+        let gamepads: Vec<_> = gilrs
+            .gamepads()
+            .filter(|(_, gamepad)| gamepad.is_ff_supported())
+            .map(|(id, _)| id)
+            .collect();
+        if gamepads.is_empty() {
+            return;
+        }
+
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong {
+                    magnitude: (pulse.strength.clamp(0.0, 1.0) * u16::MAX as f32) as u16,
+                },
+                scheduling: Replay {
+                    play_for: Ticks::from_ms(pulse.duration_ms),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .gamepads(&gamepads)
+            .finish(gilrs)
+            .and_then(|effect| effect.play().map(|_| effect));
+        match effect {
+            Ok(effect) => self.effect = Some(effect),
+            Err(error) => log::warn!("couldn't play rumble effect: {}", error),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+struct Device;
+
+#[cfg(target_arch = "wasm32")]
+impl Device {
+    fn new() -> Self {
+        Device
+    }
+
+    // Both APIs are looked up dynamically since support varies by browser
+    fn pulse(&mut self, pulse: Pulse) {
+        use js_sys::{Array, Function, Object, Reflect};
+        use wasm_bindgen::{JsCast, JsValue};
+
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        let navigator = window.navigator();
+        let get = |target: &JsValue, key: &str| Reflect::get(target, &JsValue::from_str(key)).ok();
+
+        // Phones: the Vibration API has no strength, only duration
+        if let Some(vibrate) = get(&navigator, "vibrate").and_then(|f| f.dyn_into::<Function>().ok()) {
+            let _ = vibrate.call1(&navigator, &JsValue::from(pulse.duration_ms));
+        }
+
+        // Controllers: dual-rumble through each pad's vibrationActuator
+        let Some(gamepads) = get(&navigator, "getGamepads")
+            .and_then(|f| f.dyn_into::<Function>().ok())
+            .and_then(|f| f.call0(&navigator).ok())
+        else {
+            return;
+        };
+        let params = Object::new();
+        let _ = Reflect::set(&params, &"duration".into(), &JsValue::from(pulse.duration_ms));
+        let _ = Reflect::set(&params, &"strongMagnitude".into(), &JsValue::from(pulse.strength));
+        let _ = Reflect::set(&params, &"weakMagnitude".into(), &JsValue::from(pulse.strength));
+
+        for gamepad in Array::from(&gamepads).iter().filter(|pad| !pad.is_null()) {
+            let Some(actuator) = get(&gamepad, "vibrationActuator").filter(|a| !a.is_undefined()) else {
+                continue;
+            };
+            if let Some(play_effect) = get(&actuator, "playEffect").and_then(|f| f.dyn_into::<Function>().ok()) {
+                let _ = play_effect.call2(&actuator, &"dual-rumble".into(), &params);
+            }
+        }
+    }
+}
+
+#[cfg(all(not(feature = "gamepad"), not(target_arch = "wasm32")))]
+struct Device;
+
+#[cfg(all(not(feature = "gamepad"), not(target_arch = "wasm32")))]
+impl Device {
+    fn new() -> Self {
+        Device
+    }
+
+    fn pulse(&mut self, _pulse: Pulse) {}
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+// The pointer's shape for what pressing the button would do where it is: a
+// hand over the 3D view, which a drag orbits (closed while dragging), arrows
+// while Space turns the drag into a pan, a crosshair over a point on the flat
+// board that a click would play and a forbidden sign over one it couldn't.
+use winit::window::CursorIcon;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerTarget {
+    // The 3D view
+    Scene,
+    // A side panel or toolbar button, which a click works
+    Control,
+    // A point on the flat board, and whether a click there would be taken
+    Point { playable: bool },
+    // Anywhere a click does nothing, such as between the flat board's points
+    Inert,
+}
+
+pub fn cursor_icon(target: PointerTarget, dragging: bool, panning: bool) -> CursorIcon {
+    match target {
+        PointerTarget::Scene if panning => CursorIcon::Move,
+        PointerTarget::Scene if dragging => CursorIcon::Grabbing,
+        PointerTarget::Scene => CursorIcon::Grab,
+        PointerTarget::Control => CursorIcon::Pointer,
+        PointerTarget::Point { playable: true } => CursorIcon::Crosshair,
+        PointerTarget::Point { playable: false } => CursorIcon::NotAllowed,
+        PointerTarget::Inert => CursorIcon::Default,
+    }
+}
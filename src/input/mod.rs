@@ -1,3 +1,26 @@
+pub mod cursor;
 pub mod mouse_picker;
+pub mod recording;
+pub mod wheel;
 
-pub use mouse_picker::MousePicker;
\ No newline at end of file
+pub use cursor::PointerTarget;
+pub use mouse_picker::{MousePicker, PickRay};
+pub use recording::{SessionEntry, SessionInput, SessionPlayback, SessionRecorder, SessionRecording};
+pub use wheel::WheelSteps;
+use winit::keyboard::KeyCode;
+
+// Guide bookmark of a number key: 1 to 9 on the top row or the keypad
+pub fn bookmark_slot(key: KeyCode) -> Option<usize> {
+    const KEYS: [(KeyCode, KeyCode); 9] = [
+        (KeyCode::Digit1, KeyCode::Numpad1),
+        (KeyCode::Digit2, KeyCode::Numpad2),
+        (KeyCode::Digit3, KeyCode::Numpad3),
+        (KeyCode::Digit4, KeyCode::Numpad4),
+        (KeyCode::Digit5, KeyCode::Numpad5),
+        (KeyCode::Digit6, KeyCode::Numpad6),
+        (KeyCode::Digit7, KeyCode::Numpad7),
+        (KeyCode::Digit8, KeyCode::Numpad8),
+        (KeyCode::Digit9, KeyCode::Numpad9),
+    ];
+    KEYS.iter().position(|&(digit, numpad)| key == digit || key == numpad)
+}
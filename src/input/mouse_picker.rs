@@ -1,9 +1,51 @@
 use glam::{Vec2, Vec3, Vec4, Mat4, Vec4Swizzles};
-use crate::render::Camera;
+use crate::render::{BoardToWorld, Camera};
 
 pub struct MousePicker;
 
+// One pick, kept for the ray debug view: the ray and the cell it resolved to,
+// with the distance along the ray when it hit a stone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PickRay {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub cell: Option<(u8, u8, u8)>,
+    pub stone_distance: Option<f32>,
+}
+
+impl PickRay {
+    // How far along the ray to draw it: to the stone it hit, level with the
+    // cell it found, or right through the board
+    pub fn length(&self, to_world: &BoardToWorld) -> f32 {
+        let through_board = self.origin.length() + to_world.extent();
+        self.stone_distance
+            .or_else(|| self.cell.map(|cell| (to_world.to_world(cell) - self.origin).dot(self.direction)))
+            .unwrap_or(through_board)
+    }
+}
+
 impl MousePicker {
+    // The stone under the cursor, or else the point where the ray first
+    // crosses a layer of the board
+    pub fn pick(
+        mouse_pos: Vec2,
+        screen_size: Vec2,
+        camera: &Camera,
+        game_rules: &crate::game::GameRules,
+        to_world: &BoardToWorld,
+    ) -> PickRay {
+        let (origin, direction) = Self::screen_to_world_ray(mouse_pos, screen_size, camera);
+        let stone = Self::find_clicked_stone(origin, direction, game_rules, to_world);
+        PickRay {
+            origin,
+            direction,
+            cell: stone
+                .map(|(position, _)| position)
+                .or_else(|| Self::intersect_board_position(origin, direction, to_world)),
+            stone_distance: stone.map(|(_, distance)| distance),
+        }
+    }
+
     pub fn screen_to_world_ray(
         mouse_pos: Vec2,
         screen_size: Vec2,
@@ -35,13 +77,11 @@ impl MousePicker {
     pub fn intersect_board_position(
         ray_origin: Vec3,
         ray_direction: Vec3,
-        board_size: usize,
+        to_world: &BoardToWorld,
     ) -> Option<(u8, u8, u8)> {
-        let board_size_f = board_size as f32;
-        let half_size = board_size_f * 0.5;
-
-        for z in 0..board_size {
-            let z_pos = z as f32 - half_size + 0.5;
+        // Walk the horizontal layers, i.e. those of the board's up axis
+        for layer in 0..to_world.board_size() {
+            let z_pos = to_world.coordinate(layer);
             
             if ray_direction.y.abs() < 0.001 {
                 continue;
@@ -53,18 +93,10 @@ impl MousePicker {
                 continue;
             }
 
-            let intersection = ray_origin + ray_direction * t;
-            let x = intersection.x + half_size - 0.5;
-            let y = intersection.z + half_size - 0.5;
-
-            if x >= 0.0 && x < board_size_f && y >= 0.0 && y < board_size_f {
-                let board_x = x.round() as u8;
-                let board_y = y.round() as u8;
-                let board_z = z as u8;
-
-                if board_x < board_size as u8 && board_y < board_size as u8 && board_z < board_size as u8 {
-                    return Some((board_x, board_y, board_z));
-                }
+            let mut intersection = ray_origin + ray_direction * t;
+            intersection.y = z_pos;
+            if let Some(position) = to_world.to_board(intersection) {
+                return Some(position);
             }
         }
 
@@ -104,27 +136,21 @@ impl MousePicker {
         ray_origin: Vec3,
         ray_direction: Vec3,
         game_rules: &crate::game::GameRules,
+        to_world: &BoardToWorld,
     ) -> Option<((u8, u8, u8), f32)> {
-        let board_size = game_rules.board().size();
-        let half_size = board_size as f32 * 0.5;
-        let stone_radius = 0.4; // Same as stone mesh radius
+        let stone_radius = to_world.stone_radius();
         
         let mut closest_stone: Option<((u8, u8, u8), f32)> = None;
         let mut closest_distance = f32::MAX;
 
         // Check all stones for intersection
-        for ((x, y, z), _color) in game_rules.board().get_all_stones() {
-            // Convert board coordinates to world position (same logic as in update_stones)
-            let world_pos = Vec3::new(
-                *x as f32 - half_size + 0.5,
-                *z as f32 - half_size + 0.5, // Note: y/z swap for rendering
-                *y as f32 - half_size + 0.5,
-            );
+        for (&position, _color) in game_rules.board().get_all_stones() {
+            let world_pos = to_world.to_world(position);
 
             if let Some(distance) = Self::intersect_sphere(ray_origin, ray_direction, world_pos, stone_radius) {
                 if distance < closest_distance {
                     closest_distance = distance;
-                    closest_stone = Some(((*x, *y, *z), distance));
+                    closest_stone = Some((position, distance));
                 }
             }
         }
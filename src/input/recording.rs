@@ -0,0 +1,336 @@
+// Session recordings for bug reports: what reached the window, so a visual or
+// picking bug can be played back exactly from a file the user sends, not
+// just a rules bug from its moves. A recording starts with the command line,
+// saved preferences and opening game the session began with, then keeps
+// every key, pointer, button, wheel, drag and resize as it arrived, the
+// wall-clock length of each frame and the moves the AI chose (its search
+// doesn't repeat), one text line each:
+//
+//     frame 0.011
+//     pointer 412.5 300
+//     key KeyW down
+//     ai 1 2 0
+//
+// The recorder writes each line as it happens, so a crash leaves everything
+// up to it on disk.
+use super::wheel;
+use crate::api::{Move, Position};
+use crate::game::{sgf, GameRecord};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use winit::event::{ElementState, MouseButton, WindowEvent};
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
+
+const HEADER: &str = "3dgo-session 1";
+
+macro_rules! key_names {
+    ($($key:ident)*) => {
+        &[$((KeyCode::$key, stringify!($key))),*]
+    };
+}
+
+// The keys the game answers to; the rest do nothing, so aren't kept
+const KEYS: &[(KeyCode, &str)] = key_names![
+    KeyA KeyB KeyC KeyD KeyE KeyF KeyG KeyH KeyI KeyJ KeyK KeyL KeyM
+    KeyN KeyO KeyP KeyQ KeyR KeyS KeyT KeyU KeyV KeyW KeyX KeyY KeyZ
+    Digit0 Digit1 Digit2 Digit3 Digit4 Digit5 Digit6 Digit7 Digit8 Digit9
+    Numpad0 Numpad1 Numpad2 Numpad3 Numpad4 Numpad5 Numpad6 Numpad7 Numpad8 Numpad9
+    F1 F2 F3 F4 F5 F6 F7 F8 F9 F10 F11 F12
+    ArrowUp ArrowDown ArrowLeft ArrowRight PageUp PageDown Home End Insert Delete
+    Space Enter Tab Backspace Escape Minus Equal Backquote BracketLeft BracketRight Comma Period
+    ShiftLeft ShiftRight ControlLeft ControlRight AltLeft AltRight
+];
+
+const MODIFIERS: [(ModifiersState, &str); 4] = [
+    (ModifiersState::SHIFT, "shift"),
+    (ModifiersState::CONTROL, "ctrl"),
+    (ModifiersState::ALT, "alt"),
+    (ModifiersState::SUPER, "super"),
+];
+
+fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEYS.iter().find(|&&(code, _)| code == key).map(|&(_, name)| name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionInput {
+    Key { key: KeyCode, pressed: bool },
+    Modifiers(ModifiersState),
+    // In physical pixels from the window's top left
+    Pointer { x: f32, y: f32 },
+    // The left button; the others do nothing
+    Button { pressed: bool },
+    // In notches, positive away from the user
+    Wheel(f32),
+    // Mouse motion while the button is held, which orbits the camera
+    Drag { dx: f64, dy: f64 },
+    Resized { width: u32, height: u32 },
+}
+
+impl SessionInput {
+    // The input a window event carries, if it's one the game answers to
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => match event.physical_key {
+                PhysicalKey::Code(key) if key_name(key).is_some() => Some(Self::Key {
+                    key,
+                    pressed: event.state == ElementState::Pressed,
+                }),
+                _ => None,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => Some(Self::Modifiers(modifiers.state())),
+            WindowEvent::CursorMoved { position, .. } => Some(Self::Pointer {
+                x: position.x as f32,
+                y: position.y as f32,
+            }),
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => Some(Self::Button {
+                pressed: *state == ElementState::Pressed,
+            }),
+            WindowEvent::MouseWheel { delta, .. } => Some(Self::Wheel(wheel::notches(delta))),
+            WindowEvent::Resized(size) => Some(Self::Resized {
+                width: size.width,
+                height: size.height,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionEntry {
+    Input(SessionInput),
+    // A frame was drawn; its wall-clock length in seconds
+    Frame(f32),
+    // The AI played
+    Ai(Move),
+}
+
+impl fmt::Display for SessionEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = |pressed: bool| if pressed { "down" } else { "up" };
+        match *self {
+            SessionEntry::Input(SessionInput::Key { key, pressed }) => {
+                write!(f, "key {} {}", key_name(key).unwrap_or("Unidentified"), state(pressed))
+            }
+            SessionEntry::Input(SessionInput::Modifiers(modifiers)) => {
+                f.write_str("mods")?;
+                for (modifier, name) in MODIFIERS {
+                    if modifiers.contains(modifier) {
+                        write!(f, " {}", name)?;
+                    }
+                }
+                Ok(())
+            }
+            SessionEntry::Input(SessionInput::Pointer { x, y }) => write!(f, "pointer {} {}", x, y),
+            SessionEntry::Input(SessionInput::Button { pressed }) => write!(f, "button {}", state(pressed)),
+            SessionEntry::Input(SessionInput::Wheel(notches)) => write!(f, "wheel {}", notches),
+            SessionEntry::Input(SessionInput::Drag { dx, dy }) => write!(f, "drag {} {}", dx, dy),
+            SessionEntry::Input(SessionInput::Resized { width, height }) => write!(f, "resize {} {}", width, height),
+            SessionEntry::Frame(seconds) => write!(f, "frame {}", seconds),
+            SessionEntry::Ai(Move::Place(position)) => write!(f, "ai {} {} {}", position.x, position.y, position.z),
+            SessionEntry::Ai(Move::Pass) => f.write_str("ai pass"),
+        }
+    }
+}
+
+impl FromStr for SessionEntry {
+    type Err = ();
+
+    fn from_str(line: &str) -> Result<Self, ()> {
+        let mut words = line.split_whitespace();
+        let kind = words.next().ok_or(())?;
+        let words: Vec<&str> = words.collect();
+        fn number<T: FromStr>(word: &str) -> Result<T, ()> {
+            word.parse().map_err(|_| ())
+        }
+        let pressed = |word: &str| match word {
+            "down" => Ok(true),
+            "up" => Ok(false),
+            _ => Err(()),
+        };
+        let input = match (kind, words.as_slice()) {
+            ("key", [name, state]) => SessionInput::Key {
+                key: KEYS.iter().find(|&&(_, key)| key == *name).ok_or(())?.0,
+                pressed: pressed(state)?,
+            },
+            ("mods", names) => {
+                let mut modifiers = ModifiersState::empty();
+                for name in names {
+                    modifiers |= MODIFIERS.iter().find(|&&(_, modifier)| modifier == *name).ok_or(())?.0;
+                }
+                SessionInput::Modifiers(modifiers)
+            }
+            ("pointer", [x, y]) => SessionInput::Pointer {
+                x: number(x)?,
+                y: number(y)?,
+            },
+            ("button", [state]) => SessionInput::Button { pressed: pressed(state)? },
+            ("wheel", [notches]) => SessionInput::Wheel(number(notches)?),
+            ("drag", [dx, dy]) => SessionInput::Drag {
+                dx: number(dx)?,
+                dy: number(dy)?,
+            },
+            ("resize", [width, height]) => SessionInput::Resized {
+                width: number(width)?,
+                height: number(height)?,
+            },
+            ("frame", [seconds]) => return Ok(SessionEntry::Frame(number(seconds)?)),
+            ("ai", ["pass"]) => return Ok(SessionEntry::Ai(Move::Pass)),
+            ("ai", [x, y, z]) => return Ok(SessionEntry::Ai(Move::Place(Position::new(number(x)?, number(y)?, number(z)?)))),
+            _ => return Err(()),
+        };
+        Ok(SessionEntry::Input(input))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionError {
+    NotASession,
+    // The line number and the line itself
+    BadLine(usize, String),
+    BadGame(String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::NotASession => write!(f, "not a session recording (no '{}' line at the top)", HEADER),
+            SessionError::BadLine(number, line) => write!(f, "line {}: cannot read '{}'", number, line),
+            SessionError::BadGame(reason) => write!(f, "the recorded game doesn't load: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionRecording {
+    // The command line after the program name
+    pub args: Vec<String>,
+    // The saved preferences, as they were stored
+    pub preferences: String,
+    // The game --load or --review opened, kept so the file isn't needed
+    pub game: Option<GameRecord>,
+    pub entries: Vec<SessionEntry>,
+}
+
+impl SessionRecording {
+    // Everything before the entries
+    fn header(&self) -> String {
+        let mut lines = vec![HEADER.to_string()];
+        lines.extend(self.args.iter().map(|arg| format!("arg {}", arg)));
+        lines.extend(self.preferences.lines().map(|line| format!("pref {}", line)));
+        if let Some(game) = &self.game {
+            lines.extend(sgf::write(game).lines().map(|line| format!("game {}", line)));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = self.header();
+        for entry in &self.entries {
+            text += &format!("{}\n", entry);
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SessionError> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, line)| line.trim_end()) != Some(HEADER) {
+            return Err(SessionError::NotASession);
+        }
+        let mut recording = Self::default();
+        let mut preferences = Vec::new();
+        let mut game = Vec::new();
+        for (index, line) in lines {
+            // Arguments and preferences are taken whole, spaces and all
+            match line.split_once(' ') {
+                Some(("arg", arg)) => recording.args.push(arg.to_string()),
+                Some(("pref", preference)) => preferences.push(preference),
+                Some(("game", sgf)) => game.push(sgf),
+                _ if line.trim().is_empty() => {}
+                _ => {
+                    let entry = line.parse().map_err(|()| SessionError::BadLine(index + 1, line.to_string()))?;
+                    recording.entries.push(entry);
+                }
+            }
+        }
+        recording.preferences = preferences.join("\n");
+        if !game.is_empty() {
+            let game = sgf::parse(&game.join("\n")).map_err(|error| SessionError::BadGame(error.to_string()))?;
+            recording.game = Some(game);
+        }
+        Ok(recording)
+    }
+
+    // The AI's moves in the order it made them, to be made again in place of
+    // a new search
+    pub fn ai_moves(&self) -> VecDeque<Move> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                SessionEntry::Ai(chosen) => Some(*chosen),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+// Writes a recording as the session goes
+pub struct SessionRecorder {
+    out: Box<dyn Write>,
+}
+
+impl SessionRecorder {
+    // Starts with the header of `recording`; its entries are left to record()
+    pub fn start(recording: &SessionRecording, mut out: Box<dyn Write>) -> io::Result<Self> {
+        out.write_all(recording.header().as_bytes())?;
+        out.flush()?;
+        Ok(Self { out })
+    }
+
+    pub fn record(&mut self, entry: SessionEntry) {
+        let written = writeln!(self.out, "{}", entry).and_then(|()| self.out.flush());
+        if let Err(error) = written {
+            log::warn!("session recording stopped: {}", error);
+            self.out = Box::new(io::sink());
+        }
+    }
+}
+
+// Hands a recording's inputs back a frame at a time
+pub struct SessionPlayback {
+    entries: VecDeque<SessionEntry>,
+}
+
+impl SessionPlayback {
+    pub fn new(recording: &SessionRecording) -> Self {
+        Self {
+            entries: recording.entries.iter().copied().collect(),
+        }
+    }
+
+    // The inputs that came before the next frame, and that frame's length;
+    // inputs after the last frame come with a frame of no length, so
+    // whatever ended the session still happens. None once it's all played.
+    pub fn next_frame(&mut self) -> Option<(Vec<SessionInput>, f32)> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let mut inputs = Vec::new();
+        while let Some(entry) = self.entries.pop_front() {
+            match entry {
+                SessionEntry::Input(input) => inputs.push(input),
+                SessionEntry::Frame(seconds) => return Some((inputs, seconds)),
+                SessionEntry::Ai(_) => {}
+            }
+        }
+        Some((inputs, 0.0))
+    }
+}
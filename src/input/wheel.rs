@@ -0,0 +1,71 @@
+// Scrolling turned into whole steps of the guide or a panel's layer. A wheel
+// notch arrives as a line (three, in browsers that scroll by lines), while
+// touchpads and most browsers send pixels in a stream of small events. Both
+// are measured in notches and added up, so a touchpad gesture moves a layer
+// for each notch's worth of travel rather than one for every event. The
+// sensitivity scales how far a notch goes.
+use winit::event::MouseScrollDelta;
+
+// A notch's worth of scrolling, where it comes in pixels
+const PIXELS_PER_NOTCH: f32 = 100.0;
+#[cfg(target_arch = "wasm32")]
+const LINES_PER_NOTCH: f32 = 3.0;
+#[cfg(not(target_arch = "wasm32"))]
+const LINES_PER_NOTCH: f32 = 1.0;
+
+// How far a scroll went, in notches, positive away from the user. Browsers
+// and macOS turn Shift+wheel into sideways scrolling, so that counts when
+// there's no vertical scrolling, as the wheel does with Shift elsewhere.
+pub fn notches(delta: &MouseScrollDelta) -> f32 {
+    let (x, y) = match *delta {
+        MouseScrollDelta::LineDelta(x, y) => (x / LINES_PER_NOTCH, y / LINES_PER_NOTCH),
+        MouseScrollDelta::PixelDelta(position) => (position.x as f32 / PIXELS_PER_NOTCH, position.y as f32 / PIXELS_PER_NOTCH),
+    };
+    if y != 0.0 {
+        y
+    } else {
+        x
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelSteps {
+    sensitivity: f32,
+    // Scrolling not yet worth a whole step
+    travel: f32,
+}
+
+impl Default for WheelSteps {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl WheelSteps {
+    pub const MIN_SENSITIVITY: f32 = 0.25;
+    pub const MAX_SENSITIVITY: f32 = 4.0;
+
+    // Steps per notch
+    pub fn new(sensitivity: f32) -> Self {
+        Self {
+            sensitivity: sensitivity.clamp(Self::MIN_SENSITIVITY, Self::MAX_SENSITIVITY),
+            travel: 0.0,
+        }
+    }
+
+    pub fn sensitivity(&self) -> f32 {
+        self.sensitivity
+    }
+
+    // The whole steps a scroll of `notches` completes; the rest is kept for
+    // the next one, unless that turns back
+    pub fn steps(&mut self, notches: f32) -> i32 {
+        if notches * self.travel < 0.0 {
+            self.travel = 0.0;
+        }
+        self.travel += notches * self.sensitivity;
+        let steps = self.travel.trunc();
+        self.travel -= steps;
+        steps as i32
+    }
+}
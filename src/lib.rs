@@ -1,19 +1,81 @@
-pub mod game;
-pub mod render;
-pub mod input;
+pub mod api;
+pub mod prelude;
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+
+// Internals shared with the bundled frontends. They're only public with the
+// `frontends` feature, which the binaries and tests build on; a library
+// that turns default features off sees the api facade alone.
+macro_rules! internal {
+    ($($(#[$attr:meta])* $name:ident;)*) => {$(
+        $(#[$attr])*
+        #[cfg(feature = "frontends")]
+        #[doc(hidden)]
+        pub mod $name;
+        $(#[$attr])*
+        #[cfg(not(feature = "frontends"))]
+        #[allow(dead_code, unused_imports)]
+        pub(crate) mod $name;
+    )*};
+}
+
+internal! {
+    accessibility;
+    ai;
+    audio;
+    haptics;
+    settings;
+    game;
+    render;
+    input;
+    storage;
+    achievements;
+    profiles;
+    archive;
+    preferences;
+    dialogs;
+    teaching;
+    simultaneous;
+    scripting;
+    tournament;
+    engine_configs;
+    power;
+    #[cfg(not(target_arch = "wasm32"))]
+    crash;
+    calibration;
+    window;
+    attract;
+}
+
+pub use api::{BoardDiff, Game, GameObserver, GameSettings, Move, Position, ScoringRule};
 
-use game::{GameRules, StoneColor};
-use render::{Graphics, Camera, CameraController, Instance, GuideSystem};
-use input::MousePicker;
+use ai::{analysis, influence, Engine, RandomEngine};
+use game::{GameSession, Mark, PositionStats, Premove, SessionEvent, StoneColor, ThinkingSummary, UndoPolicy, UndoRequest, ViewState};
+use accessibility::Announcer;
+use attract::AttractMode;
+use audio::AudioPlayer;
+use dialogs::FileKind;
+use game::{sgf, what_if, GameRecord};
+use haptics::Haptics;
+use settings::{AudioSettings, BlindMode, LayoutSettings, StereoSettings, ThemeKind, UiScale};
+use preferences::Preferences;
+use storage::SaveSlots;
+use std::sync::mpsc::Receiver;
+use render::{booklet, gltf, heatmap, AnimationClock, AnnotationTool, BoardToWorld, BoardView, Graphics, Camera, CameraController, HeatCell, GuideSystem, InfluenceCell, Instance, ScoreGraph, UiHit};
+use input::{cursor, MousePicker, PointerTarget, WheelSteps};
 use glam::Vec3;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
+use std::sync::Arc;
 use winit::{
+    application::ApplicationHandler,
     event::*,
-    event_loop::{ControlFlow, EventLoop},
-    window::{Window, WindowBuilder},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
+    window::{CursorIcon, Window, WindowId},
 };
-use instant::Instant;
+#[cfg(target_arch = "wasm32")]
+use winit::event_loop::EventLoopProxy;
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures;
@@ -22,123 +84,424 @@ use wasm_bindgen_futures;
 use web_sys::{console, HtmlCanvasElement};
 
 struct GameState {
-    rules: GameRules,
-    black_stone_instances: Vec<Instance>,
-    white_stone_instances: Vec<Instance>,
-    selected_position: Option<(u8, u8, u8)>,
+    session: GameSession,
+    session_events: Receiver<SessionEvent>,
+    // The stones as drawn, plus the last-move, hover and selection markers
+    board_view: BoardView,
     mouse_position: glam::Vec2,
-    animation_paused: bool,
     guide_system: GuideSystem,
     pending_ai_move: bool,
+    // A move chosen while the AI's reply is pending, played once it has come
+    premove: Option<Premove>,
+    engine: Box<dyn Engine>,
+    save_slots: SaveSlots,
+    audio: AudioPlayer,
+    haptics: Haptics,
+    // Only the browser build has somewhere quiet to announce to (a live region)
+    announcer: Announcer,
+    // Analysis mode: the engine's view of the position drawn as a heatmap
+    analysis: bool,
+    analyst: Box<dyn Engine>,
+    // The influence cloud, shown while analysis is on
+    show_influence: bool,
+    // Shells over stones in atari or on two liberties
+    show_liberties: bool,
+    show_score_graph: bool,
+    // Visualization training, and whether X has revealed the position anyway
+    blind: BlindMode,
+    revealed: bool,
+    // Marking tool while annotating a position for review or teaching
+    annotation_tool: Option<AnnotationTool>,
+    // Beginner aid: ghost dots on every legal point for the side to move
+    show_legal_moves: bool,
+    // The position summary in the bottom-right corner
+    show_stats_panel: bool,
+    // The guide jumps to each AI move and pulses there
+    follow_moves: bool,
+    // Point and position the what-if preview was last worked out for
+    what_if_key: Option<((u8, u8, u8), u64, usize)>,
+    layout: LayoutSettings,
 }
 
 impl GameState {
-    fn new() -> Self {
-        let rules = GameRules::new_with_dodecahedron(3);
-        let black_stone_instances = Vec::new();
-        let white_stone_instances = Vec::new();
-        let guide_system = GuideSystem::new(3);
+    fn new(session: GameSession) -> Self {
+        let session_events = session.subscribe();
+        let guide_system = GuideSystem::new(session.read().board().size());
 
         Self {
-            rules,
-            black_stone_instances,
-            white_stone_instances,
-            selected_position: None,
+            session,
+            session_events,
+            board_view: BoardView::new(),
             mouse_position: glam::Vec2::ZERO,
-            animation_paused: false,
             guide_system,
             pending_ai_move: false,
+            premove: None,
+            engine: Box::new(RandomEngine::new()),
+            save_slots: SaveSlots::new(storage::default_backend()),
+            audio: AudioPlayer::new(AudioSettings::default()),
+            haptics: Haptics::new(true),
+            announcer: Announcer::new(cfg!(target_arch = "wasm32")),
+            analysis: false,
+            analyst: settings::AiSettings::default().create_engine(&GameSettings::default()),
+            show_influence: false,
+            show_liberties: false,
+            show_score_graph: false,
+            blind: BlindMode::Off,
+            revealed: false,
+            annotation_tool: None,
+            show_legal_moves: false,
+            show_stats_panel: false,
+            follow_moves: true,
+            what_if_key: None,
+            layout: LayoutSettings::default(),
         }
     }
 
-    fn update_stones(&mut self) {
-        self.black_stone_instances.clear();
-        self.white_stone_instances.clear();
-        let board_size = self.rules.board().size();
-        let half_size = board_size as f32 * 0.5;
-
-        for ((x, y, z), color) in self.rules.board().get_all_stones() {
-            let pos = Vec3::new(
-                *x as f32 - half_size + 0.5,
-                *z as f32 - half_size + 0.5,
-                *y as f32 - half_size + 0.5,
-            );
-            
-            let mut instance = Instance::new(pos);
-            instance.scale = Vec3::splat(1.2);
-            
-            match color {
-                StoneColor::Black => {
-                    self.black_stone_instances.push(instance);
-                }
-                StoneColor::White => {
-                    self.white_stone_instances.push(instance);
-                }
+    // Window title for the position on the board
+    fn status(&self) -> String {
+        let view = self.session.view();
+        crate::window::status_title(&self.session.read(), view)
+    }
+
+    fn to_world(&self) -> BoardToWorld {
+        BoardToWorld::new(self.session.read().board().size(), &self.layout)
+    }
+
+    // Estimated score after every move so far
+    fn score_graph(&self) -> Option<ScoreGraph> {
+        if !self.show_score_graph {
+            return None;
+        }
+        let settings = GameSettings::default();
+        let record = GameRecord::from_rules(&self.session.read(), settings.komi);
+        let moves = record.moves.len();
+        match analysis::analyze(&record, &settings) {
+            Ok(analysis) => Some(ScoreGraph::new(analysis, moves)),
+            Err(error) => {
+                log::warn!("couldn't analyze game: {}", error);
+                None
             }
         }
     }
 
-    fn handle_mouse_click(&mut self, camera: &Camera, screen_size: glam::Vec2) -> bool {
-        let (ray_origin, ray_direction) = MousePicker::screen_to_world_ray(
-            self.mouse_position,
-            screen_size,
-            camera,
-        );
+    // The blind mode as currently drawn
+    fn blind_view(&self) -> BlindMode {
+        if self.revealed {
+            BlindMode::Off
+        } else {
+            self.blind
+        }
+    }
+
+    // Markup of the position on the board, placed at its intersections
+    fn annotation_marks(&self) -> Vec<(Vec3, Mark)> {
+        let to_world = self.to_world();
+        let move_number = self.session.read().moves().len();
+        self.session
+            .annotations()
+            .at(move_number)
+            .map(|annotation| (to_world.to_world(annotation.position), annotation.mark))
+            .collect()
+    }
+
+    fn annotate(&self, position: (u8, u8, u8)) {
+        let Some(tool) = self.annotation_tool else {
+            return;
+        };
+        match self.session.annotate(position, tool.mark()) {
+            Some(Mark::Label(letter)) => log::warn!("Label {} on {}", letter, accessibility::format_point(position)),
+            Some(_) => log::warn!("Marked {} with a {}", accessibility::format_point(position), tool.name()),
+            None => log::warn!("Cleared {}", accessibility::format_point(position)),
+        }
+    }
+
+    // Where the legal-move dots go, empty when the hint is off
+    fn legal_moves(&self) -> Vec<Vec3> {
+        if !self.show_legal_moves {
+            return Vec::new();
+        }
+        let to_world = self.to_world();
+        let rules = self.session.read();
+        rules.legal_moves().into_iter().map(|position| to_world.to_world(position)).collect()
+    }
+
+    // The info panel's lines, when it's on; none while blind training hides
+    // the stones or their colors
+    fn stats_rows(&self) -> Vec<String> {
+        if !self.show_stats_panel || self.blind_view() != BlindMode::Off {
+            return Vec::new();
+        }
+        let rules = self.session.read();
+        PositionStats::of(rules.board()).rows(rules.players())
+    }
+
+    // Shells over the endangered stones; none while blind training hides
+    // the stones or their colors
+    fn liberty_shells(&self) -> [Vec<Instance>; render::liberties::LEVELS] {
+        if !self.show_liberties || self.blind_view() != BlindMode::Off {
+            return Default::default();
+        }
+        render::liberties::shells(self.session.read().board(), &self.to_world())
+    }
+
+    // Shells for the what-if preview of playing `point`, while Alt is held.
+    // None when nothing changed since last time: the preview is only worked
+    // out again when the point or the position does
+    fn what_if(&mut self, point: Option<(u8, u8, u8)>) -> Option<[Vec<Instance>; render::what_if::KINDS]> {
+        // Blind training keeps the stones' colors to itself
+        let point = point.filter(|_| self.blind_view() == BlindMode::Off);
+        let to_world = self.to_world();
+        let rules = self.session.read();
+        let key = point.map(|point| (point, rules.board().hash(), rules.moves().len()));
+        if key == self.what_if_key {
+            return None;
+        }
+        self.what_if_key = key;
+        let preview = point.and_then(|point| what_if::preview(&rules, point).ok());
+        Some(preview.map_or_else(Default::default, |preview| render::what_if::shells(&preview, &to_world)))
+    }
+
+    // Who holds which empty points, empty unless analysis and the cloud are on
+    fn influence(&self) -> Vec<InfluenceCell> {
+        if !(self.analysis && self.show_influence) {
+            return Vec::new();
+        }
+        let rules = self.session.read();
+        let field = influence::influence(rules.board());
+        render::influence::cells(&field, rules.board(), &self.to_world())
+    }
+
+    // Engine policy over the current position, empty when analysis is off
+    fn heatmap(&mut self) -> Vec<HeatCell> {
+        if !self.analysis {
+            return Vec::new();
+        }
+        let game = Game::from(self.session.snapshot());
+        let policy = self.analyst.policy(&game);
+        heatmap::cells(&policy, &self.to_world())
+    }
+
+    // Slot messages go through the logger so the browser build shows them in the console
+    fn select_save_slot(&mut self, index: usize) {
+        self.save_slots.select(index);
+        log::warn!("Save slots (F1-F4 select, F5 save, F9 load):");
+        for line in self.save_slots.describe() {
+            log::warn!("  {}", line);
+        }
+    }
+
+    fn save_to_slot(&mut self) {
+        let record = self.session.record(GameSettings::default().komi);
+        match self.save_slots.save(&record) {
+            Ok(()) => log::warn!("Saved to slot {}", self.save_slots.selected() + 1),
+            Err(error) => log::error!("{}", error),
+        }
+    }
 
-        if let Some((x, y, z)) = MousePicker::intersect_board_position(
-            ray_origin,
-            ray_direction,
-            self.rules.board().size(),
-        ) {
-            if self.rules.make_move(x, y, z) {
-                self.update_stones();
-                return true;
+    fn load_from_slot(&mut self) {
+        match self.save_slots.load() {
+            Ok(Some(record)) => {
+                self.pending_ai_move = false;
+                if let Err(error) = self.session.load(&record) {
+                    log::error!("{}", error);
+                    return;
+                }
+                log::warn!("Loaded slot {}", self.save_slots.selected() + 1);
             }
+            Ok(None) => log::warn!("Slot {} is empty", self.save_slots.selected() + 1),
+            Err(error) => log::error!("{}", error),
         }
+    }
 
-        false
+    fn save_game_as(&mut self) {
+        let record = self.session.record(GameSettings::default().komi);
+        match dialogs::save_file(FileKind::Game, &sgf::write(&record)) {
+            Ok(Some(location)) => log::warn!("Saved game to {}", location),
+            Ok(None) => {}
+            Err(error) => log::error!("{}", error),
+        }
     }
 
-    fn place_stone_at_guide(&mut self) -> bool {
-        let (x, y, z) = self.guide_system.get_intersection_position();
-        if self.rules.make_move(x, y, z) {
-            self.update_stones();
-            return true;
+    fn open_game(&mut self) {
+        // The browser reads the file asynchronously, so the result arrives via the session
+        let session = self.session.clone();
+        let result = dialogs::open_file(FileKind::Game, move |text| {
+            let loaded = sgf::parse(&text)
+                .map_err(|e| e.to_string())
+                .and_then(|record| session.load(&record).map_err(|e| e.to_string()));
+            if let Err(error) = loaded {
+                log::error!("cannot open game: {}", error);
+            }
+        });
+        if let Err(error) = result {
+            log::error!("{}", error);
+        }
+    }
+
+    fn export_gltf(&mut self) {
+        let scene = gltf::export_board(&self.session.read(), &self.to_world());
+        match dialogs::save_file(FileKind::Gltf, &scene) {
+            Ok(Some(location)) => log::warn!("Exported board to {}", location),
+            Ok(None) => {}
+            Err(error) => log::error!("{}", error),
         }
-        false
     }
 
-    fn make_ai_move(&mut self) -> bool {
-        // Simple AI: find all empty positions and choose randomly
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        let board_size = self.rules.board().size();
-        let mut empty_positions = Vec::new();
+    fn export_booklet(&mut self) {
+        let record = self.session.record(GameSettings::default().komi);
+        let exported = booklet::export_booklet(&record, &self.to_world(), booklet::DEFAULT_MOVES_PER_PAGE)
+            .map_err(|error| error.to_string())
+            .and_then(|pdf| dialogs::save_file(FileKind::Booklet, &pdf));
+        match exported {
+            Ok(Some(location)) => log::warn!("Exported a booklet of the game to {}", location),
+            Ok(None) => {}
+            Err(error) => log::error!("{}", error),
+        }
+    }
 
-        for x in 0..board_size {
-            for y in 0..board_size {
-                for z in 0..board_size {
-                    if self.rules.board().get_stone((x as u8, y as u8, z as u8)).is_none() {
-                        empty_positions.push((x as u8, y as u8, z as u8));
+    // Drains pending session notifications; returns true if the board changed
+    fn poll_session_events(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.session_events.try_recv() {
+            self.audio.handle_event(&event);
+            self.haptics.handle_event(&event);
+            self.announcer.handle_event(&event, &self.session.read());
+            // Undoing, a new game or a different position leaves nothing to answer
+            if !matches!(event, SessionEvent::MovePlayed { .. } | SessionEvent::Passed { .. }) {
+                self.premove = None;
+            }
+            if let SessionEvent::Passed { .. } | SessionEvent::MovePlayed { .. } = event {
+                let rules = self.session.read();
+                if let Some(win) = accessibility::describe_win(&rules) {
+                    log::warn!("{}", win);
+                }
+                if rules.is_over() {
+                    if let Some(summary) = ThinkingSummary::new(rules.moves()) {
+                        log::warn!("{}", summary);
                     }
                 }
             }
+            changed = true;
         }
+        if changed {
+            // A new game may have been started with a different board size
+            let board_size = self.session.read().board().size();
+            if self.guide_system.board_size() != board_size {
+                self.guide_system = GuideSystem::new(board_size);
+            }
+            self.update_stones();
+        }
+        changed
+    }
+
+    fn update_stones(&mut self) {
+        self.board_view.sync(&self.session.read(), &self.layout, self.blind_view());
+    }
+
+    // The stone under the cursor, if any
+    fn pick_stone(&self, camera: &Camera, screen_size: glam::Vec2) -> Option<(u8, u8, u8)> {
+        let (ray_origin, ray_direction) = MousePicker::screen_to_world_ray(self.mouse_position, screen_size, camera);
+        MousePicker::find_clicked_stone(ray_origin, ray_direction, &self.session.read(), &self.to_world())
+            .map(|(position, _distance)| position)
+    }
 
-        if !empty_positions.is_empty() {
-            let random_pos = empty_positions[rng.gen_range(0..empty_positions.len())];
-            if self.rules.make_move(random_pos.0, random_pos.1, random_pos.2) {
-                self.update_stones();
-                return true;
+    // Backspace: asks the AI to take back the last move of whoever is to
+    // move, and its reply with it. Games here aren't rated, so it agrees.
+    fn request_undo(&mut self) {
+        if self.pending_ai_move || self.session.view() != ViewState::Live {
+            return;
+        }
+        let request = {
+            let rules = self.session.read();
+            UndoRequest::new(rules.current_player(), &rules)
+        };
+        let Some(request) = request else {
+            log::warn!("Nothing to take back");
+            return;
+        };
+        match UndoPolicy::for_ai(false) {
+            UndoPolicy::Accept => {
+                request.grant(&self.session);
             }
+            UndoPolicy::Decline => log::warn!("The AI declines to take the move back"),
+        }
+    }
+
+    // While the AI's reply is pending the guide's point is staged as a
+    // pre-move instead, or taken back if it's the one already staged; true
+    // once a stone is played
+    fn place_stone_at_guide(&mut self) -> bool {
+        let position = self.guide_system.get_intersection_position();
+        if !self.pending_ai_move {
+            let (x, y, z) = position;
+            return self.session.make_move(x, y, z);
+        }
+        if self.premove.is_some_and(|premove| premove.position == position) {
+            self.premove = None;
+            log::warn!("Pre-move at {} taken back", accessibility::format_point(position));
+        } else {
+            let rules = self.session.read();
+            let color = rules.current_player().next(rules.players());
+            drop(rules);
+            self.premove = Some(Premove::new(position, color));
+            log::warn!("Pre-move at {}: played after the AI's reply if it's still legal", accessibility::format_point(position));
         }
         false
     }
+
+    // Plays the staged pre-move once its turn has come, or drops it if the
+    // reply has made it illegal; true once a stone is played
+    fn play_premove_if_due(&mut self) -> bool {
+        let Some(premove) = self.premove else {
+            return false;
+        };
+        let due = premove.due(&self.session.read());
+        match due {
+            None => false,
+            Some(Ok((x, y, z))) => {
+                self.premove = None;
+                self.session.make_move(x, y, z)
+            }
+            Some(Err(error)) => {
+                self.premove = None;
+                log::warn!("Pre-move at {} dropped: {}", accessibility::format_point(premove.position), error);
+                false
+            }
+        }
+    }
+
+    fn premove_ghost(&self) -> Option<(Instance, StoneColor)> {
+        let premove = self.premove?;
+        let to_world = self.to_world();
+        let mut instance = Instance::new(to_world.to_world(premove.position));
+        instance.scale = Vec3::splat(to_world.stone_scale());
+        Some((instance, premove.color))
+    }
+
+    // The point the AI played, if it placed a stone
+    fn make_ai_move(&mut self) -> Option<(u8, u8, u8)> {
+        // Moves while reviewing are the user's variation, not the game
+        if self.session.view() != ViewState::Live {
+            return None;
+        }
+        let game = Game::from(self.session.snapshot());
+        match self.engine.select_move(&game) {
+            Move::Place(position) => {
+                let (x, y, z) = (position.x, position.y, position.z);
+                self.session.make_move(x, y, z).then_some((x, y, z))
+            }
+            Move::Pass => {
+                self.session.pass();
+                None
+            }
+        }
+    }
 }
 
+#[doc(hidden)]
 pub mod minimal;
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub async fn run() {
     // AGGRESSIVE DEBUG MODE - Restore complex renderer
     log::warn!("🔥 STARTING AGGRESSIVE DEBUG MODE 🔥");
@@ -153,229 +516,712 @@ pub async fn run() {
         }
     }
 
-    let event_loop = EventLoop::new();
-    let window = WindowBuilder::new()
-        .with_title("3D Go")
-        .build(&event_loop)
-        .unwrap();
+    let preferences = Preferences::load(&*storage::preferences_backend());
+    let event_loop = EventLoop::<Graphics>::with_user_event()
+        .build()
+        .expect("couldn't create the event loop");
+    let app = App {
+        preferences,
+        #[cfg(target_arch = "wasm32")]
+        proxy: event_loop.create_proxy(),
+        #[cfg(target_arch = "wasm32")]
+        pending_window: None,
+        running: None,
+    };
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            // Returns at once; the browser drives the loop from here
+            use winit::platform::web::EventLoopExtWebSys;
+            event_loop.spawn_app(app);
+        } else {
+            let mut app = app;
+            if let Err(error) = event_loop.run_app(&mut app) {
+                log::error!("{}", error);
+            }
+        }
+    }
+}
 
-    #[cfg(target_arch = "wasm32")]
-    {
-        use winit::platform::web::WindowExtWebSys;
-        web_sys::window()
-            .and_then(|win| win.document())
-            .and_then(|doc| {
-                let dst = doc.get_element_by_id("wasm-example")?;
-                let canvas = web_sys::Element::from(window.canvas());
-                dst.append_child(&canvas).ok()?;
-                Some(())
-            })
-            .expect("Couldn't append canvas to document body.");
-    }
-
-    let mut graphics = Graphics::new(&window).await;
-    let mut camera = Camera::new(graphics.size.width, graphics.size.height);
-    let mut camera_controller = CameraController::new(10.0, 1.0);
-    let mut game_state = GameState::new();
-    
-    let mut last_frame_time = Instant::now();
-    let mut mouse_pressed = false;
-
-    game_state.update_stones();
-
-    event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Poll;
-
-        match event {
-            Event::WindowEvent {
-                ref event,
-                window_id,
-            } if window_id == window.id() => {
-                match event {
-                    WindowEvent::CloseRequested
-                    | WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
-                                ..
-                            },
-                        ..
-                    } => *control_flow = ControlFlow::Exit,
+fn open_window(event_loop: &ActiveEventLoop, preferences: &Preferences) -> Window {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            // The page decides the canvas size
+            let _ = preferences;
+            web::build_window(event_loop)
+        } else {
+            let mut attributes = Window::default_attributes()
+                .with_title("3D Go")
+                .with_window_icon(crate::window::icon());
+            if let Some(geometry) = preferences.window {
+                attributes = attributes.with_inner_size(winit::dpi::PhysicalSize::new(geometry.width, geometry.height));
+                if let Some((x, y)) = geometry.position {
+                    attributes = attributes.with_position(winit::dpi::PhysicalPosition::new(x, y));
+                }
+            }
+            event_loop.create_window(attributes).expect("couldn't create the window")
+        }
+    }
+}
 
-                    WindowEvent::Resized(physical_size) => {
-                        graphics.resize(*physical_size);
-                        camera.update_aspect(physical_size.width, physical_size.height);
-                    }
+// The window, its renderer and the game shown in it
+struct Running {
+    window: Arc<Window>,
+    graphics: Graphics,
+    camera: Camera,
+    camera_controller: CameraController,
+    game_state: GameState,
+    clock: AnimationClock,
+    theme_kind: ThemeKind,
+    mouse_pressed: bool,
+    // The pointer shape last set, so it's only changed when it has to
+    cursor: CursorIcon,
+    modifiers: ModifiersState,
+    wheel: WheelSteps,
+    // The demo game the page plays to itself when nobody's there
+    attract: AttractMode,
+    // The windowed geometry from last time, kept while fullscreen
+    #[cfg(not(target_arch = "wasm32"))]
+    saved_window: Option<preferences::WindowGeometry>,
+}
 
-                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                        graphics.resize(**new_inner_size);
-                        camera.update_aspect(new_inner_size.width, new_inner_size.height);
-                    }
+impl Running {
+    fn start(window: Arc<Window>, mut graphics: Graphics, preferences: &Preferences) -> Self {
+        let theme_kind = preferences.theme.unwrap_or_default();
+        graphics.set_theme(theme_kind.theme());
+        graphics.set_panels_visible(preferences.panels_visible);
+        graphics.set_ui_scale(preferences.ui_scale.unwrap_or_default());
+        graphics.set_guide_style(preferences.guide_style.unwrap_or_default());
+        let camera = Camera::new(graphics.size.width, graphics.size.height);
+        let mut camera_controller = CameraController::new(10.0, 1.0);
+        if let Some(preset) = preferences.camera {
+            camera_controller.set_preset(preset);
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let session = web::session();
+                let attract = AttractMode::new(web::attract_after());
+            } else {
+                let board_size = preferences.board_size.unwrap_or(3);
+                let session = GameSession::new(game::GameRules::new_with_dodecahedron(board_size));
+                let attract = AttractMode::new(None);
+            }
+        }
+        let mut game_state = GameState::new(session);
+        if let Some(layout) = preferences.layout {
+            game_state.layout = layout;
+            graphics.set_layout(layout);
+        }
+
+        game_state.update_stones();
+        window.set_title(&game_state.status());
+
+        Self {
+            window,
+            graphics,
+            camera,
+            camera_controller,
+            game_state,
+            clock: AnimationClock::default(),
+            theme_kind,
+            mouse_pressed: false,
+            cursor: CursorIcon::Default,
+            modifiers: ModifiersState::empty(),
+            wheel: WheelSteps::new(preferences.wheel_sensitivity.unwrap_or(1.0)),
+            attract,
+            #[cfg(not(target_arch = "wasm32"))]
+            saved_window: preferences.window,
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, event: WindowEvent) {
+        let Self {
+            window,
+            graphics,
+            camera,
+            camera_controller,
+            game_state,
+            clock,
+            theme_kind,
+            mouse_pressed,
+            cursor,
+            modifiers,
+            wheel,
+            attract,
+            ..
+        } = self;
+
+        // Any key, click, scroll or touch ends the demo game and does nothing
+        // else; moving the pointer only keeps it from starting
+        let ends_demo = matches!(
+            event,
+            WindowEvent::KeyboardInput { event: KeyEvent { state: ElementState::Pressed, .. }, .. }
+                | WindowEvent::MouseInput { state: ElementState::Pressed, .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::Touch(_)
+        );
+        if ends_demo {
+            if let Some(preset) = attract.input(&game_state.session) {
+                camera_controller.set_preset(preset);
+                graphics.set_banner(None);
+                *mouse_pressed = false;
+                return;
+            }
+        } else if let WindowEvent::CursorMoved { .. } = event {
+            attract.activity();
+        }
+
+        match &event {
+            WindowEvent::CloseRequested
+            | WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(KeyCode::Escape),
+                        ..
+                    },
+                ..
+            } => event_loop.exit(),
+
+            WindowEvent::Resized(physical_size) => {
+                graphics.resize(*physical_size);
+                camera.update_aspect(physical_size.width, physical_size.height);
+            }
 
-                    WindowEvent::KeyboardInput { input, .. } => {
-                        if let Some(key) = input.virtual_keycode {
-                            // Handle special game commands only on key press
-                            if input.state == ElementState::Pressed {
-                                match key {
-                                    // Guide plane controls
-                                    VirtualKeyCode::W => {
-                                        game_state.guide_system.move_y(1);  // Y plane forward
-                                    }
-                                    VirtualKeyCode::S => {
-                                        game_state.guide_system.move_y(-1); // Y plane backward  
-                                    }
-                                    VirtualKeyCode::A => {
-                                        game_state.guide_system.move_x(-1); // X plane left
-                                    }
-                                    VirtualKeyCode::D => {
-                                        game_state.guide_system.move_x(1);  // X plane right
-                                    }
-                                    VirtualKeyCode::Space => {
-                                        // Place stone at guide intersection
-                                        if game_state.place_stone_at_guide() {
-                                            game_state.pending_ai_move = true;
-                                        }
-                                    }
-                                    VirtualKeyCode::R => {
-                                        // Reset - clear the board
-                                        game_state.rules.clear_board();
-                                        game_state.update_stones();
-                                        game_state.pending_ai_move = false;
-                                    }
-                                    // Zoom controls
-                                    VirtualKeyCode::Q | VirtualKeyCode::Left => {
-                                        camera_controller.zoom_in();
-                                    }
-                                    VirtualKeyCode::E | VirtualKeyCode::Right => {
-                                        camera_controller.zoom_out();
-                                    }
-                                    _ => {
-                                        // Pass all other keys to camera controller
-                                        camera_controller.process_keyboard(key, input.state);
-                                    }
+            WindowEvent::KeyboardInput { event: input, .. } => {
+                if let PhysicalKey::Code(key) = input.physical_key {
+                    // Handle special game commands only on key press
+                    if input.state == ElementState::Pressed {
+                        match key {
+                            // Guide plane controls
+                            KeyCode::KeyW => {
+                                game_state.guide_system.move_y(1);  // Y plane forward
+                            }
+                            KeyCode::KeyS => {
+                                game_state.guide_system.move_y(-1); // Y plane backward  
+                            }
+                            KeyCode::KeyA => {
+                                game_state.guide_system.move_x(-1); // X plane left
+                            }
+                            KeyCode::KeyD => {
+                                game_state.guide_system.move_x(1);  // X plane right
+                            }
+                            KeyCode::Space => {
+                                // Place stone at guide intersection
+                                if game_state.place_stone_at_guide() {
+                                    game_state.pending_ai_move = true;
                                 }
-                            } else {
-                                // Always pass key releases to camera controller
+                            }
+                            KeyCode::KeyR => {
+                                // Reset - clear the board
+                                game_state.session.clear_board();
+                                game_state.pending_ai_move = false;
+                            }
+                            // Sound
+                            KeyCode::KeyM => {
+                                let muted = game_state.audio.toggle_mute();
+                                log::warn!("Sound {}", if muted { "muted" } else { "on" });
+                            }
+                            // UI scale: Ctrl+- and Ctrl+= step it, Ctrl+0 resets it
+                            KeyCode::Minus | KeyCode::Equal | KeyCode::Digit0 if modifiers.control_key() => {
+                                let scale = match key {
+                                    KeyCode::Minus => graphics.ui_scale().stepped(-1),
+                                    KeyCode::Equal => graphics.ui_scale().stepped(1),
+                                    _ => UiScale::default(),
+                                };
+                                graphics.set_ui_scale(scale);
+                                log::warn!("UI scale: {:.0}%", scale.factor() * 100.0);
+                            }
+                            KeyCode::Minus | KeyCode::Equal => {
+                                let step = AudioSettings::VOLUME_STEP;
+                                let delta = if key == KeyCode::Minus { -step } else { step };
+                                let volume = game_state.audio.adjust_volume(delta);
+                                log::warn!("Volume {:.0}%", volume * 100.0);
+                            }
+                            KeyCode::F11 => crate::window::toggle_fullscreen(window),
+                            // Theme
+                            KeyCode::KeyC => {
+                                *theme_kind = theme_kind.next();
+                                graphics.set_theme(theme_kind.theme());
+                                log::warn!("Theme: {}", theme_kind);
+                            }
+                            // Side panels
+                            KeyCode::Tab => {
+                                let visible = !graphics.panels_visible();
+                                graphics.set_panels_visible(visible);
+                                log::warn!("Side panels {}", if visible { "shown" } else { "hidden" });
+                            }
+                            // Score graph
+                            // Guide planes or crosshair
+                            KeyCode::KeyG if modifiers.shift_key() => {
+                                let style = graphics.guide_style().next();
+                                graphics.set_guide_style(style);
+                                log::warn!("Guide: {}", style);
+                            }
+                            KeyCode::KeyG => {
+                                game_state.show_score_graph = !game_state.show_score_graph;
+                                graphics.set_score_graph(game_state.score_graph());
+                            }
+                            // Analysis heatmap
+                            KeyCode::KeyH => {
+                                game_state.analysis = !game_state.analysis;
+                                graphics.set_heatmap(&game_state.heatmap());
+                                graphics.set_influence(&game_state.influence());
+                                log::warn!("Analysis heatmap: {}", if game_state.analysis { "on" } else { "off" });
+                            }
+                            // Influence cloud, part of analysis mode
+                            KeyCode::KeyI => {
+                                game_state.show_influence = !game_state.show_influence;
+                                graphics.set_influence(&game_state.influence());
+                                log::warn!("Influence cloud: {}", if game_state.show_influence { "on" } else { "off" });
+                                if game_state.show_influence && !game_state.analysis {
+                                    log::warn!("Press H for analysis mode to see it");
+                                }
+                            }
+                            // Liberty display mode
+                            KeyCode::KeyU => {
+                                game_state.show_liberties = !game_state.show_liberties;
+                                graphics.set_liberty_shells(&game_state.liberty_shells());
+                                log::warn!("Liberty shells: {}", if game_state.show_liberties { "on" } else { "off" });
+                            }
+                            // Position info panel
+                            KeyCode::Backquote => {
+                                game_state.show_stats_panel = !game_state.show_stats_panel;
+                                graphics.set_stats_rows(game_state.stats_rows());
+                                log::warn!("Position info: {}", if game_state.show_stats_panel { "on" } else { "off" });
+                            }
+                            // Blind training
+                            KeyCode::KeyB | KeyCode::KeyX => {
+                                if key == KeyCode::KeyB {
+                                    game_state.blind = game_state.blind.next();
+                                    game_state.revealed = false;
+                                    log::warn!("Blind mode: {}", game_state.blind);
+                                } else if game_state.blind != BlindMode::Off {
+                                    game_state.revealed = !game_state.revealed;
+                                    log::warn!("Position {}", if game_state.revealed { "revealed" } else { "hidden" });
+                                }
+                                graphics.set_blind(game_state.blind_view());
+                                game_state.update_stones();
+                                graphics.set_liberty_shells(&game_state.liberty_shells());
+                                graphics.set_stats_rows(game_state.stats_rows());
+                            }
+                            // Annotation tools
+                            KeyCode::KeyN => {
+                                game_state.annotation_tool = AnnotationTool::cycle(game_state.annotation_tool);
+                                graphics.set_annotation_tool(game_state.annotation_tool);
+                                match game_state.annotation_tool {
+                                    Some(tool) => log::warn!("Annotating: {} (Enter marks the guide point)", tool.name()),
+                                    None => log::warn!("Annotating off"),
+                                }
+                            }
+                            KeyCode::Enter => {
+                                let position = game_state.guide_system.get_intersection_position();
+                                game_state.annotate(position);
+                            }
+                            // Legal-move hints
+                            KeyCode::KeyK => {
+                                game_state.show_legal_moves = !game_state.show_legal_moves;
+                                graphics.set_legal_moves(&game_state.legal_moves());
+                                log::warn!("Legal move hints: {}", if game_state.show_legal_moves { "on" } else { "off" });
+                            }
+                            // Following the AI's moves with the guide
+                            KeyCode::KeyF => {
+                                game_state.follow_moves = !game_state.follow_moves;
+                                log::warn!("Guide follows AI moves: {}", if game_state.follow_moves { "on" } else { "off" });
+                            }
+                            KeyCode::KeyP => {
+                                let paused = clock.toggle_pause();
+                                log::warn!("Animation: {}", if paused { "paused" } else { "running" });
+                            }
+                            KeyCode::Backspace => game_state.request_undo(),
+                            // Reviewing earlier positions
+                            KeyCode::PageUp => {
+                                game_state.session.step_back();
+                            }
+                            KeyCode::PageDown => {
+                                game_state.session.step_forward();
+                            }
+                            KeyCode::End => {
+                                if game_state.session.return_to_game() {
+                                    log::warn!("Back to the game");
+                                }
+                            }
+                            KeyCode::Insert => {
+                                if game_state.session.play_from_here() {
+                                    log::warn!("Playing on from move {}", game_state.session.read().moves().len());
+                                }
+                            }
+                            // Stereo output
+                            KeyCode::KeyV => {
+                                let mut stereo = graphics.stereo();
+                                stereo.mode = stereo.mode.next();
+                                graphics.set_stereo(stereo);
+                                log::warn!("Stereo: {}", stereo.mode);
+                            }
+                            KeyCode::BracketLeft | KeyCode::BracketRight => {
+                                let step = StereoSettings::SEPARATION_STEP;
+                                let delta = if key == KeyCode::BracketLeft { -step } else { step };
+                                let mut stereo = graphics.stereo();
+                                stereo.adjust_separation(delta);
+                                graphics.set_stereo(stereo);
+                                log::warn!("Eye separation {:.2}", stereo.eye_separation);
+                            }
+                            // Lattice spacing
+                            KeyCode::Comma | KeyCode::Period => {
+                                let step = LayoutSettings::PITCH_STEP;
+                                let delta = if key == KeyCode::Comma { -step } else { step };
+                                game_state.layout.adjust_pitch(delta);
+                                graphics.set_layout(game_state.layout);
+                                game_state.update_stones();
+                                graphics.set_heatmap(&game_state.heatmap());
+                                graphics.set_influence(&game_state.influence());
+                                graphics.set_liberty_shells(&game_state.liberty_shells());
+                                graphics.set_legal_moves(&game_state.legal_moves());
+                                graphics.set_annotations(game_state.annotation_marks());
+                                log::warn!("Stone spacing {:.1}", game_state.layout.pitch);
+                            }
+                            // Save slots
+                            KeyCode::F1 => game_state.select_save_slot(0),
+                            KeyCode::F2 => game_state.select_save_slot(1),
+                            KeyCode::F3 => game_state.select_save_slot(2),
+                            KeyCode::F4 => game_state.select_save_slot(3),
+                            KeyCode::F5 => game_state.save_to_slot(),
+                            KeyCode::F9 => game_state.load_from_slot(),
+                            // Files
+                            KeyCode::F6 => game_state.save_game_as(),
+                            KeyCode::F7 => game_state.open_game(),
+                            KeyCode::F8 if modifiers.shift_key() => game_state.export_booklet(),
+                            KeyCode::F8 => game_state.export_gltf(),
+                            #[cfg(target_arch = "wasm32")]
+                            KeyCode::KeyL => {
+                                // Copy a link that reopens this position
+                                if let Err(error) = web::copy_share_link() {
+                                    log::warn!("couldn't copy share link: {:?}", error);
+                                }
+                            }
+                            // Zoom controls
+                            KeyCode::KeyQ | KeyCode::ArrowLeft => {
+                                camera_controller.zoom_in();
+                            }
+                            KeyCode::KeyE | KeyCode::ArrowRight => {
+                                camera_controller.zoom_out();
+                            }
+                            // Guide bookmarks: Shift+1-9 stores the guide point, 1-9 jumps back to it
+                            key if input::bookmark_slot(key).is_some() => {
+                                let slot = input::bookmark_slot(key).expect("matched a number key");
+                                if modifiers.shift_key() {
+                                    let point = game_state.guide_system.store_bookmark(slot);
+                                    log::warn!("Bookmark {}: {}", slot + 1, accessibility::format_point(point));
+                                } else if game_state.guide_system.recall_bookmark(slot).is_none() {
+                                    log::warn!("No bookmark {} yet (Shift+{} stores the guide point)", slot + 1, slot + 1);
+                                }
+                            }
+                            _ => {
+                                // Pass all other keys to camera controller
                                 camera_controller.process_keyboard(key, input.state);
                             }
                         }
+                    } else {
+                        // Always pass key releases to camera controller
+                        camera_controller.process_keyboard(key, input.state);
                     }
+                }
+            }
 
-                    WindowEvent::CursorMoved { position, .. } => {
-                        game_state.mouse_position = glam::Vec2::new(position.x as f32, position.y as f32);
-                    }
+            // A hidden tab, a minimized window or a canvas scrolled out of
+            // view: nothing is drawn, the AI waits and animations stop until
+            // it shows again
+            WindowEvent::Occluded(occluded) => {
+                clock.set_suspended(*occluded);
+                if *occluded {
+                    event_loop.set_control_flow(ControlFlow::Wait);
+                } else {
+                    event_loop.set_control_flow(ControlFlow::Poll);
+                    window.request_redraw();
+                }
+            }
 
-                    WindowEvent::MouseInput {
-                        state: ElementState::Pressed,
-                        button: MouseButton::Left,
-                        ..
-                    } => {
-                        mouse_pressed = true;
+            WindowEvent::ModifiersChanged(state) => *modifiers = state.state(),
+
+            WindowEvent::CursorMoved { position, .. } => {
+                game_state.mouse_position = glam::Vec2::new(position.x as f32, position.y as f32);
+                let screen_size = glam::Vec2::new(graphics.size.width as f32, graphics.size.height as f32);
+                // Nothing on the board is under the pointer while it's over the UI
+                let hover = match graphics.ui_hit(position.x as f32, position.y as f32) {
+                    Some(_) => None,
+                    None => game_state.pick_stone(camera, screen_size),
+                };
+                game_state.board_view.set_hover(hover);
+            }
+
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                *mouse_pressed = true;
+            }
+
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } if *mouse_pressed => {
+                *mouse_pressed = false;
+                let mouse = game_state.mouse_position;
+                match graphics.ui_hit(mouse.x, mouse.y) {
+                    // While annotating, the toolbar picks the tool
+                    Some(UiHit::ToolbarButton(tool)) => {
+                        game_state.annotation_tool = Some(tool);
+                        graphics.set_annotation_tool(Some(tool));
+                        log::warn!("Annotating: {}", tool.name());
+                    }
+                    // A click pins a side panel's layer, a second one resumes cycling
+                    Some(UiHit::SidePanel(index)) => {
+                        if let Some(side_view) = graphics.side_view_mut(index) {
+                            let state = if side_view.toggle_pin() { "pinned" } else { "cycling" };
+                            log::warn!("{} view: {}", side_view.direction.label(), state);
+                        }
                     }
+                    // The split view is only in the desktop frontend
+                    Some(UiHit::LayerBoard(_)) => {}
+                    None => {
+                        // Check if we clicked on a stone to select it and orbit around it
+                        let screen_size = glam::Vec2::new(
+                            graphics.size.width as f32,
+                            graphics.size.height as f32,
+                        );
 
-                    WindowEvent::MouseInput {
-                        state: ElementState::Released,
-                        button: MouseButton::Left,
-                        ..
-                    } => {
-                        if mouse_pressed {
-                            // Check if we clicked on a stone to set new orbit center
-                            let screen_size = glam::Vec2::new(
-                                graphics.size.width as f32,
-                                graphics.size.height as f32,
-                            );
-                            
-                            let (ray_origin, ray_direction) = MousePicker::screen_to_world_ray(
-                                game_state.mouse_position,
-                                screen_size,
-                                &camera,
-                            );
-
-                            if let Some(((x, y, z), _distance)) = MousePicker::find_clicked_stone(
-                                ray_origin,
-                                ray_direction,
-                                &game_state.rules,
-                            ) {
-                                // Convert board coordinates to world position for orbit center
-                                let board_size = game_state.rules.board().size();
-                                let half_size = board_size as f32 * 0.5;
-                                let new_center = glam::Vec3::new(
-                                    x as f32 - half_size + 0.5,
-                                    z as f32 - half_size + 0.5, // y/z swap for rendering
-                                    y as f32 - half_size + 0.5,
-                                );
-                                
-                                camera_controller.set_orbit_center(new_center);
+                        if let Some((x, y, z)) = game_state.pick_stone(camera, screen_size) {
+                            // With a tool selected, clicking a stone marks it instead
+                            if game_state.annotation_tool.is_some() {
+                                game_state.annotate((x, y, z));
+                            } else {
+                                let new_center = game_state.to_world().to_world((x, y, z));
+                                game_state.board_view.select(Some((x, y, z)));
+
+                                camera_controller.set_orbit_center(new_center, clock);
                                 println!("New orbit center: stone at ({}, {}, {}) -> world pos: {:?}", x, y, z, new_center);
                             }
-                            
-                            mouse_pressed = false;
                         }
                     }
+                }
+            }
 
-                    WindowEvent::MouseWheel { delta, .. } => {
-                        let scroll_amount = match delta {
-                            MouseScrollDelta::LineDelta(_, y) => *y,
-                            MouseScrollDelta::PixelDelta(pos) => pos.y as f32 * 0.01,
-                        };
-                        // Mouse wheel controls Z plane movement
-                        if scroll_amount > 0.0 {
-                            game_state.guide_system.move_z(1);
-                        } else if scroll_amount < 0.0 {
-                            game_state.guide_system.move_z(-1);
+            WindowEvent::MouseWheel { delta, .. } => {
+                let steps = wheel.steps(input::wheel::notches(delta));
+                // Over a side panel the wheel steps its layer, over the flat
+                // board the layer it shows; elsewhere it moves the Z guide plane
+                let mouse = game_state.mouse_position;
+                match graphics.ui_hit(mouse.x, mouse.y) {
+                    _ if steps == 0 => {}
+                    Some(UiHit::SidePanel(index)) => {
+                        if let Some(side_view) = graphics.side_view_mut(index) {
+                            side_view.step_layer(steps as isize);
                         }
                     }
-
-                    _ => {}
+                    Some(UiHit::LayerBoard(_)) => {
+                        let layer_axis = game_state.to_world().board_axis(1);
+                        game_state.guide_system.move_along(layer_axis, steps);
+                    }
+                    _ => game_state.guide_system.move_z(steps),
                 }
             }
 
-            Event::DeviceEvent {
-                event: DeviceEvent::MouseMotion { delta },
-                ..
-            } => {
-                if mouse_pressed {
-                    camera_controller.process_mouse(delta.0, delta.1);
-                }
-            }
+            WindowEvent::RedrawRequested => {
+                clock.tick();
 
-            Event::RedrawRequested(window_id) if window_id == window.id() => {
-                let now = Instant::now();
-                let dt = now.duration_since(last_frame_time).as_secs_f32();
-                last_frame_time = now;
+                // The page playing through the JS API counts as someone there
+                #[cfg(target_arch = "wasm32")]
+                if web::take_scripted() {
+                    if let Some(preset) = attract.input(&game_state.session) {
+                        camera_controller.set_preset(preset);
+                    }
+                }
+                let demo_board = BoardToWorld::new(attract.board_size(), &game_state.layout);
+                let far_corner = [attract.board_size() - 1; 3];
+                let demo_center = (demo_board.indices_to_world([0; 3]) + demo_board.indices_to_world(far_corner)) / 2.0;
+                if let Some(preset) = attract.update(clock.real_dt(), &game_state.session, camera_controller.preset(), demo_center) {
+                    camera_controller.set_preset(preset);
+                }
+                graphics.set_banner(attract.is_playing().then(|| attract::BANNER.to_string()));
 
                 // Handle pending AI move
                 if game_state.pending_ai_move {
-                    game_state.make_ai_move();
-                    game_state.pending_ai_move = false;
+                    if let Some(position) = game_state.make_ai_move().filter(|_| game_state.follow_moves) {
+                        game_state.guide_system.follow_move(position);
+                    }
+                    game_state.pending_ai_move = game_state.play_premove_if_due();
                 }
+                game_state.guide_system.tick(clock.dt());
 
-                camera_controller.update_camera(&mut camera, dt);
-                graphics.update_camera(&camera);
-
-                // Create guide plane instances
-                let guide_instances = vec![game_state.guide_system.get_dot_instance()];
+                // Pick up board changes from any session holder (AI, network, input)
+                if game_state.poll_session_events() {
+                    window.set_title(&game_state.status());
+                    graphics.set_heatmap(&game_state.heatmap());
+                    graphics.set_influence(&game_state.influence());
+                    graphics.set_liberty_shells(&game_state.liberty_shells());
+                    graphics.set_legal_moves(&game_state.legal_moves());
+                    graphics.set_annotations(game_state.annotation_marks());
+                    graphics.set_score_graph(game_state.score_graph());
+                    graphics.set_stats_rows(game_state.stats_rows());
+                }
+                game_state.guide_system.snap_to_placement(&game_state.session.read());
+                let guide_point = game_state.guide_system.get_intersection_position();
+                if let Some(shells) = game_state.what_if(modifiers.alt_key().then_some(guide_point)) {
+                    graphics.set_what_if(&shells);
+                }
+                graphics.set_premove(game_state.premove_ghost());
 
-                match graphics.render(&guide_instances, &game_state.black_stone_instances, &game_state.white_stone_instances, &game_state.rules, &camera, Some(&game_state.guide_system)) {
-                    Ok(_) => {}
-                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                        graphics.resize(graphics.size);
+                #[cfg(target_arch = "wasm32")]
+                {
+                    if attract.is_playing() {
+                        web::discard_events();
+                    } else {
+                        web::dispatch_events();
                     }
-                    Err(wgpu::SurfaceError::OutOfMemory) => {
-                        *control_flow = ControlFlow::Exit;
-                    }
-                    Err(wgpu::SurfaceError::Timeout) => {
-                        log::warn!("Surface timeout");
+                    if let Some((width, height)) = web::take_pending_resize() {
+                        let _ = window.request_inner_size(winit::dpi::LogicalSize::new(width, height));
                     }
                 }
-            }
 
-            Event::MainEventsCleared => {
-                window.request_redraw();
+                camera_controller.update_camera(camera, clock);
+                // Clicks on the flat board don't play here; Space does
+                let mouse = game_state.mouse_position;
+                let target = match graphics.ui_hit(mouse.x, mouse.y) {
+                    None => PointerTarget::Scene,
+                    Some(UiHit::LayerBoard(_)) => PointerTarget::Inert,
+                    Some(_) => PointerTarget::Control,
+                };
+                let icon = cursor::cursor_icon(target, *mouse_pressed, camera_controller.is_panning());
+                if icon != *cursor {
+                    window.set_cursor(icon);
+                    *cursor = icon;
+                }
+
+                let rules = game_state.session.read();
+                // Timeouts and lost surfaces just skip the frame; running out of memory ends the game
+                if let Err(error) = graphics.render(&game_state.board_view, &rules, camera, Some(&game_state.guide_system), clock) {
+                    log::error!("{}", error);
+                    event_loop.exit();
+                }
             }
 
             _ => {}
         }
-    });
+    }
+
+    // Remember the window and view for next time; the browser page owns the
+    // canvas size, so only native windows keep geometry
+    fn save_preferences(&self) {
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let window_geometry = None;
+            } else {
+                let size = self.window.inner_size();
+                let window_geometry = if self.window.fullscreen().is_none() {
+                    Some(crate::preferences::WindowGeometry {
+                        width: size.width,
+                        height: size.height,
+                        position: self.window.outer_position().ok().map(|position| (position.x, position.y)),
+                    })
+                } else {
+                    self.saved_window
+                };
+            }
+        }
+        let saved = Preferences {
+            window: window_geometry,
+            panels_visible: self.graphics.panels_visible(),
+            camera: Some(self.camera_controller.preset()),
+            board_size: Some(self.game_state.session.read().board().size()),
+            layout: Some(self.game_state.layout),
+            theme: Some(self.theme_kind),
+            ui_scale: Some(self.graphics.ui_scale()),
+            guide_style: Some(self.graphics.guide_style()),
+            wheel_sensitivity: Some(self.wheel.sensitivity()),
+        };
+        if let Err(error) = saved.save(&mut *storage::preferences_backend()) {
+            log::warn!("cannot save preferences: {}", error);
+        }
+    }
+}
+
+struct App {
+    preferences: Preferences,
+    // Browsers hand out the GPU adapter and device asynchronously, so the
+    // renderer comes back to the loop as a user event
+    #[cfg(target_arch = "wasm32")]
+    proxy: EventLoopProxy<Graphics>,
+    #[cfg(target_arch = "wasm32")]
+    pending_window: Option<Arc<Window>>,
+    running: Option<Running>,
+}
+
+impl ApplicationHandler<Graphics> for App {
+    // The first resume opens the window; later ones (after a suspend) keep it
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        #[cfg(target_arch = "wasm32")]
+        if self.pending_window.is_some() {
+            return;
+        }
+        if self.running.is_some() {
+            return;
+        }
+        event_loop.set_control_flow(ControlFlow::Poll);
+        let window = Arc::new(open_window(event_loop, &self.preferences));
+
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let proxy = self.proxy.clone();
+                let surface_window = window.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let graphics = Graphics::new(surface_window).await;
+                    if proxy.send_event(graphics).is_err() {
+                        log::warn!("the event loop closed before the renderer was ready");
+                    }
+                });
+                self.pending_window = Some(window);
+            } else {
+                let graphics = pollster::block_on(Graphics::new(window.clone()));
+                self.running = Some(Running::start(window, graphics, &self.preferences));
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, graphics: Graphics) {
+        if let Some(window) = self.pending_window.take() {
+            self.running = Some(Running::start(window, graphics, &self.preferences));
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
+        if let Some(running) = self.running.as_mut().filter(|running| running.window.id() == window_id) {
+            running.window_event(event_loop, event);
+        }
+    }
+
+    fn device_event(&mut self, _event_loop: &ActiveEventLoop, _device_id: DeviceId, event: DeviceEvent) {
+        let Some(running) = self.running.as_mut() else {
+            return;
+        };
+        if let DeviceEvent::MouseMotion { delta } = event {
+            if running.mouse_pressed {
+                running.camera_controller.process_mouse(delta.0, delta.1);
+            }
+        }
+    }
+
+    // Polls for the next frame only while the game is on screen
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(running) = self.running.as_ref().filter(|running| !running.clock.is_suspended()) {
+            running.window.request_redraw();
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(running) = &self.running {
+            running.save_preferences();
+        }
+    }
 }
\ No newline at end of file
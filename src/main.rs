@@ -1,103 +1,1873 @@
-mod game;
-mod render;
-mod input;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use go3d::accessibility::{self, Announcer, Command};
+use go3d::achievements::{self, Achievement};
+use go3d::archive::{Archive, ArchiveFilter, ResultFilter};
+use go3d::ai::{analysis, influence, patterns::PatternLibrary, Engine};
+use go3d::api::{Game, Move};
+use go3d::game::{sgf, what_if, Board, BoardDiff, CameraKeyframe, CameraPath, GameRecord, GameResult, GameRules, GameSession, Mark, MoveRecord, PositionStats, Premove, SessionEvent, Stack, StoneColor, ThinkingSummary, UndoPolicy, UndoRequest, Variant, ViewState};
+use go3d::render::{self, booklet, gltf, heatmap, AnimationClock, AnnotationTool, BoardToWorld, BoardView, CaptureAnimations, CaptureStyle, GhostTrails, Graphics, Camera, CameraController, CameraPreset, GuideSystem, HeatCell, InfluenceCell, Instance, ScoreGraph, UiHit};
+use go3d::input::{self, cursor, MousePicker, PickRay, PointerTarget, SessionEntry, SessionInput, SessionPlayback, SessionRecorder, SessionRecording, WheelSteps};
+use go3d::audio::AudioPlayer;
+use go3d::calibration::Calibration;
+use go3d::crash;
+use go3d::dialogs::{self, FileKind};
+use go3d::engine_configs::{self, EngineConfig, EngineConfigs};
+use go3d::haptics::Haptics;
+use go3d::preferences::{Preferences, WindowGeometry};
+use go3d::profiles::{self, Opponent, Outcome, Profiles};
+use go3d::scripting::{Script, ScriptCommand, ScriptError};
+use go3d::tournament::{self, Entrant, Pairing, Tournament};
+use go3d::settings::{AiBudget, AiSettings, AudioSettings, BlindMode, GameSettings, GuideStyle, LayoutSettings, ScoringRule, StereoSettings, ThemeKind, UiScale, UpAxis};
+use go3d::storage::{self, SaveSlots};
+use go3d::window as chrome;
+use glam::Vec3;
+use std::io::{IsTerminal, Write};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use winit::{
+    application::ApplicationHandler,
+    event::*,
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::{KeyCode, ModifiersState},
+    window::{CursorIcon, Window, WindowId},
+};
+use instant::Instant;
+
+#[derive(Parser, Debug)]
+#[command(name = "go3d", version, about = "Go on a three-dimensional lattice")]
+struct Cli {
+    /// Board edge length; the board has size³ intersections
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(GameSettings::MIN_BOARD_SIZE as i64..=GameSettings::MAX_BOARD_SIZE as i64))]
+    size: u8,
+
+    /// Scoring rules: territory or area
+    #[arg(long, default_value_t = ScoringRule::Territory)]
+    rules: ScoringRule,
+
+    /// Points added to White's score
+    #[arg(long, default_value_t = 0.5, allow_negative_numbers = true)]
+    komi: f32,
+
+    /// Rule variant: standard; gravity (stones drop to the lowest empty point
+    /// of their column); stack[:levels] (2D boards joined at their star
+    /// points); shell (only the surface of the cube); holes[:seed[:percent]]
+    /// (a seeded 10% or given share of the points missing); hill[:moves]
+    /// (holding the centre for 10 or the given number of moves in a row wins);
+    /// or simultaneous (experimental: Black's move stays hidden until White's
+    /// is chosen, then both land together)
+    #[arg(long, default_value_t = Variant::Standard)]
+    variant: Variant,
+
+    /// Colors taking turns at the board: 2, or 3 or 4 for hot-seat games
+    /// (Black, White, Red, Green; area scored, no komi)
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(2..=StoneColor::MAX_PLAYERS as i64))]
+    players: u8,
+
+    /// Let the computer play White (0 = random, 1 = greedy, 2 = MCTS); the
+    /// calibrate tool ranks the levels, shown with --stats
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=AiSettings::MAX_LEVEL as i64))]
+    ai_level: Option<u8>,
+
+    /// Color scheme: classic, or color-blind (shape-coded stones, safe palette)
+    #[arg(long, default_value_t = ThemeKind::Classic)]
+    theme: ThemeKind,
+
+    /// Distance between neighbouring points on screen; raise it to spread out large boards
+    #[arg(long, default_value_t = LayoutSettings::default().pitch, value_parser = parse_pitch)]
+    pitch: f32,
+
+    /// Rendered stone radius, independent of --pitch
+    #[arg(long, default_value_t = LayoutSettings::default().stone_radius, value_parser = parse_stone_radius)]
+    stone_radius: f32,
+
+    /// Board axis drawn pointing up on screen: z (layers stacked vertically) or y
+    #[arg(long, default_value_t = UpAxis::Z)]
+    up: UpAxis,
+
+    /// Size of the panels, overlay text and toolbar: 0.75 to 2, or 75% to
+    /// 200%; Ctrl+- and Ctrl+= change it while playing
+    #[arg(long, default_value_t = UiScale::default())]
+    ui_scale: UiScale,
+
+    /// How the guide marks the point a stone would go: planes (three faint
+    /// planes) or crosshair (only the lines where they cross); Shift+G
+    /// switches while playing
+    #[arg(long, value_name = "STYLE", default_value_t = GuideStyle::Planes)]
+    guide_style: GuideStyle,
+
+    /// Guide steps per notch of the mouse wheel, 0.25 to 4; touchpads add up
+    /// a notch's worth of travel for each
+    #[arg(long, default_value_t = 1.0, value_parser = parse_wheel_sensitivity)]
+    wheel_sensitivity: f32,
+
+    /// Visualization training: one-color (all stones look alike) or hidden
+    /// (only the latest stone is drawn); X reveals the position
+    #[arg(long, value_name = "MODE", default_value_t = BlindMode::Off)]
+    blind: BlindMode,
+
+    /// Announce moves in the terminal (and aloud with --features speech) and
+    /// accept typed commands such as "play B d4-2" on stdin
+    #[arg(long)]
+    accessible: bool,
+
+    /// Search threads for --ai-level 2 (default: one per core)
+    #[arg(long, default_value_t = 0, hide_default_value = true)]
+    threads: usize,
+
+    /// Search playouts per move for --ai-level 2
+    #[arg(long, default_value_t = AiSettings::default().playouts, value_parser = parse_positive)]
+    playouts: usize,
+
+    /// How much of the MCTS search to spend: auto (a quarter of the playouts
+    /// on at most two threads when running on battery), full or saver
+    #[arg(long, value_name = "BUDGET", default_value_t = AiBudget::Auto)]
+    ai_budget: AiBudget,
+
+    /// Seed the AI's random choices, and the sandbox's falling stones, so a
+    /// game plays out the same again (level 2 only with --threads 1);
+    /// --headless and --tournament games keep theirs in the record
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Let a saved engine configuration play White (--engines lists them);
+    /// L in the window moves on to the next one
+    #[arg(long, value_name = "NAME", value_parser = parse_engine_name, conflicts_with_all = ["ai_level", "playouts", "threads"])]
+    engine: Option<String>,
+
+    /// Save --ai-level, --playouts and --threads as a named engine
+    /// configuration, then exit
+    #[arg(long, value_name = "NAME", value_parser = parse_engine_name, requires = "ai_level")]
+    save_engine: Option<String>,
+
+    /// Save the engine configuration in FILE under its name line, or else
+    /// the file name, then exit
+    #[arg(long, value_name = "FILE")]
+    import_engine: Option<PathBuf>,
+
+    /// Print a saved engine configuration, ready for --import-engine, then exit
+    #[arg(long, value_name = "NAME", value_parser = parse_engine_name)]
+    export_engine: Option<String>,
+
+    /// Print the saved engine configurations, then exit
+    #[arg(long)]
+    engines: bool,
+
+    /// Profile name for Black; finished games update its rating
+    #[arg(long, value_name = "NAME", value_parser = parse_player_name)]
+    player: Option<String>,
+
+    /// Profile name for a human White (instead of --ai-level)
+    #[arg(long, value_name = "NAME", value_parser = parse_player_name, requires = "player", conflicts_with_all = ["ai_level", "engine"])]
+    opponent: Option<String>,
+
+    /// Unrated fun mode: captured stones tumble out of the lattice and
+    /// bounce around the bounding box
+    #[arg(long, conflicts_with = "player")]
+    sandbox: bool,
+
+    /// Print the player ratings and statistics (and --player's trophies), then exit
+    #[arg(long)]
+    stats: bool,
+
+    /// Print the archive of finished games, newest first, then exit
+    #[arg(long)]
+    games: bool,
+
+    /// Only archived games with this player on either side
+    #[arg(long, value_name = "NAME")]
+    games_with: Option<String>,
+
+    /// Only archived games on this board size
+    #[arg(long, value_name = "SIZE")]
+    games_size: Option<usize>,
+
+    /// Only archived games won by black, white, red or green, or drawn
+    #[arg(long, value_name = "RESULT")]
+    games_result: Option<ResultFilter>,
+
+    /// Open game N of the --games list in review, from the empty board
+    #[arg(long, value_name = "N", conflicts_with = "load")]
+    review: Option<usize>,
+
+    /// Open an SGF-3D game record
+    #[arg(long, value_name = "FILE")]
+    load: Option<PathBuf>,
+
+    /// Run a Rhai script that reacts to the game, e.g. a tutorial (needs
+    /// --features scripting)
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// Join a network game
+    #[arg(long, value_name = "URL")]
+    connect: Option<String>,
+
+    /// Run without a window: replay --load, or let the AI play itself
+    #[arg(long)]
+    headless: bool,
+
+    /// Run an engine tournament without a window, one entrant per AI level or
+    /// saved engine listed (e.g. 0,1,2,deep), on the --size, --rules and
+    /// --komi given; levels search with --playouts and --threads
+    #[arg(long, value_name = "ENTRANTS", value_delimiter = ',')]
+    tournament: Vec<String>,
+
+    /// Tournament pairing: round-robin or swiss
+    #[arg(long, default_value_t = Pairing::RoundRobin, requires = "tournament")]
+    pairing: Pairing,
+
+    /// Number of Swiss rounds (default: enough to leave one unbeaten entrant)
+    #[arg(long, requires = "tournament")]
+    rounds: Option<usize>,
+
+    /// Save every tournament game as an SGF-3D record in this directory
+    #[arg(long, value_name = "DIR", requires = "tournament")]
+    archive: Option<PathBuf>,
+
+    /// Write the --load or --review game as a printable PDF booklet of layer
+    /// diagrams, then exit
+    #[arg(long, value_name = "FILE")]
+    booklet: Option<PathBuf>,
+
+    /// Moves on each booklet page, for --booklet and Shift+F8
+    #[arg(long, value_name = "N", default_value_t = booklet::DEFAULT_MOVES_PER_PAGE, value_parser = parse_positive)]
+    booklet_every: usize,
+
+    /// Write a PNG picture of the final position of the --load or --review
+    /// game, drawn without the GPU, then exit
+    #[arg(long, value_name = "FILE")]
+    thumbnail: Option<PathBuf>,
+
+    /// Width and height of the --thumbnail picture in pixels
+    #[arg(long, value_name = "PIXELS", default_value_t = 256, value_parser = clap::value_parser!(u32).range(16..=4096))]
+    thumbnail_size: u32,
+
+    /// Step through the --load record one move per second
+    #[arg(long, requires = "load")]
+    replay: bool,
+
+    /// Animation speed: 0.5 plays layer cycling, captures and camera glides
+    /// at half speed (P pauses them)
+    #[arg(long, default_value_t = 1.0, value_parser = parse_animation_speed)]
+    animation_speed: f32,
+
+    /// Write per-pass GPU timings and buffer upload volumes for this session
+    /// to FILE as CSV
+    #[arg(long, value_name = "FILE")]
+    profile_render: Option<PathBuf>,
+
+    /// Record the session's input, frame times and AI moves to FILE, for a bug
+    /// report --play-session can replay exactly
+    #[arg(long, value_name = "FILE", conflicts_with = "play_session")]
+    record_session: Option<PathBuf>,
+
+    /// Play back a --record-session file with the command line, preferences
+    /// and game it was recorded with; other options are ignored
+    #[arg(long, value_name = "FILE")]
+    play_session: Option<PathBuf>,
+
+    /// Start from the side-view test pattern
+    #[arg(short, long)]
+    test: bool,
+
+    /// Print debug keyboard help
+    #[arg(short, long)]
+    debug: bool,
+}
+
+fn parse_in_range(value: &str, min: f32, max: f32) -> Result<f32, String> {
+    let value: f32 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if !(min..=max).contains(&value) {
+        return Err(format!("must be between {} and {}", min, max));
+    }
+    Ok(value)
+}
+
+fn parse_pitch(value: &str) -> Result<f32, String> {
+    parse_in_range(value, LayoutSettings::MIN_PITCH, LayoutSettings::MAX_PITCH)
+}
+
+fn parse_stone_radius(value: &str) -> Result<f32, String> {
+    parse_in_range(value, LayoutSettings::MIN_STONE_RADIUS, LayoutSettings::MAX_STONE_RADIUS)
+}
+
+fn parse_animation_speed(value: &str) -> Result<f32, String> {
+    parse_in_range(value, 0.1, 10.0)
+}
+
+fn parse_wheel_sensitivity(value: &str) -> Result<f32, String> {
+    parse_in_range(value, WheelSteps::MIN_SENSITIVITY, WheelSteps::MAX_SENSITIVITY)
+}
+
+fn parse_player_name(name: &str) -> Result<String, String> {
+    profiles::validate_name(name).map_err(|e| e.to_string())?;
+    Ok(name.to_string())
+}
+
+fn parse_positive(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("'{}' is not a positive whole number", value)),
+        Ok(playouts) => Ok(playouts),
+    }
+}
+
+fn parse_engine_name(name: &str) -> Result<String, String> {
+    engine_configs::validate_name(name).map_err(|e| e.to_string())?;
+    Ok(name.to_string())
+}
+
+impl Cli {
+    // Who is playing Black, and against whom, when both are known
+    fn players(&self, ai: &AiSettings) -> Option<(String, Opponent)> {
+        let player = self.player.clone()?;
+        let opponent = match &self.opponent {
+            Some(name) => Opponent::Human(name.clone()),
+            None if ai.enabled => Opponent::Ai(ai.level),
+            None => return None,
+        };
+        Some((player, opponent))
+    }
+
+    fn game_settings(&self) -> GameSettings {
+        GameSettings {
+            board_size: self.size as usize,
+            scoring: self.rules,
+            komi: self.komi,
+            variant: self.variant,
+            players: self.players,
+        }
+    }
+
+    fn layout(&self) -> LayoutSettings {
+        LayoutSettings {
+            pitch: self.pitch,
+            stone_radius: self.stone_radius,
+            up: self.up,
+        }
+    }
+
+    // Board options left at their defaults take the last session's values
+    fn apply_preferences(&mut self, preferences: &Preferences, matches: &ArgMatches) {
+        let unset = |id: &str| matches.value_source(id) == Some(ValueSource::DefaultValue);
+        if let Some(size) = preferences.board_size.filter(|_| unset("size")) {
+            self.size = size as u8;
+        }
+        if let Some(layout) = preferences.layout {
+            if unset("pitch") {
+                self.pitch = layout.pitch;
+            }
+            if unset("stone_radius") {
+                self.stone_radius = layout.stone_radius;
+            }
+            if unset("up") {
+                self.up = layout.up;
+            }
+        }
+        if let Some(theme) = preferences.theme.filter(|_| unset("theme")) {
+            self.theme = theme;
+        }
+        if let Some(scale) = preferences.ui_scale.filter(|_| unset("ui_scale")) {
+            self.ui_scale = scale;
+        }
+        if let Some(style) = preferences.guide_style.filter(|_| unset("guide_style")) {
+            self.guide_style = style;
+        }
+        if let Some(sensitivity) = preferences.wheel_sensitivity.filter(|_| unset("wheel_sensitivity")) {
+            self.wheel_sensitivity = sensitivity;
+        }
+    }
+
+    fn ai_settings(&self) -> Result<AiSettings, String> {
+        if let Some(name) = &self.engine {
+            let config = load_engines().get(name).map_err(|e| e.to_string())?;
+            return Ok(AiSettings {
+                budget: self.ai_budget,
+                seed: self.seed,
+                ..config.ai_settings()
+            });
+        }
+        Ok(AiSettings {
+            enabled: self.ai_level.is_some(),
+            level: self.ai_level.unwrap_or(AiSettings::default().level),
+            playouts: self.playouts,
+            threads: self.threads,
+            budget: self.ai_budget,
+            seed: self.seed,
+            ..AiSettings::default()
+        })
+    }
+
+    // The --tournament entrants: AI levels searching with --playouts and
+    // --threads, and saved engines under their own names
+    fn tournament_entries(&self) -> Result<Vec<(Option<String>, AiSettings)>, String> {
+        let engines = load_engines();
+        self.tournament
+            .iter()
+            .map(|entrant| match entrant.parse::<u8>() {
+                Ok(level) if level <= AiSettings::MAX_LEVEL => {
+                    let ai = AiSettings {
+                        enabled: true,
+                        level,
+                        playouts: self.playouts,
+                        threads: self.threads,
+                        budget: self.ai_budget,
+                        ..AiSettings::default()
+                    };
+                    Ok((None, ai))
+                }
+                Ok(level) => Err(format!("no AI level {} (0 to {})", level, AiSettings::MAX_LEVEL)),
+                Err(_) => engines
+                    .get(entrant)
+                    .map(|config| {
+                        let ai = AiSettings {
+                            budget: self.ai_budget,
+                            ..config.ai_settings()
+                        };
+                        (Some(config.name.clone()), ai)
+                    })
+                    .map_err(|e| e.to_string()),
+            })
+            .collect()
+    }
+
+    // Which archived games --games lists and --review and Z open
+    fn archive_filter(&self) -> ArchiveFilter {
+        ArchiveFilter {
+            player: self.games_with.clone(),
+            board_size: self.games_size,
+            result: self.games_result,
+        }
+    }
+
+    fn load_record(&self) -> Result<Option<GameRecord>, String> {
+        if let Some(number) = self.review {
+            let entries = load_archive().entries(&self.archive_filter()).map_err(|e| e.to_string())?;
+            let entry = number
+                .checked_sub(1)
+                .and_then(|index| entries.get(index))
+                .ok_or_else(|| format!("no archived game {} (--games lists {})", number, entries.len()))?;
+            return Ok(Some(entry.record.clone()));
+        }
+        let Some(path) = &self.load else {
+            return Ok(None);
+        };
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let record = sgf::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        // Validate up front so a broken record never reaches the event loop
+        record.replay().map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Some(record))
+    }
+}
+
+struct Replay {
+    record: GameRecord,
+    next_move: usize,
+    last_step: Instant,
+}
+
+impl Replay {
+    const STEP_SECONDS: f32 = 1.0;
+
+    fn from_start(record: GameRecord) -> Self {
+        Self {
+            record,
+            next_move: 0,
+            last_step: Instant::now(),
+        }
+    }
+
+    // Seconds since the empty board; stands still between moves while the
+    // replay is paused
+    fn clock(&self) -> f32 {
+        let step = self.last_step.elapsed().as_secs_f32().min(Self::STEP_SECONDS);
+        self.next_move as f32 * Self::STEP_SECONDS + step
+    }
+}
+
+struct GameState {
+    session: GameSession,
+    session_events: Receiver<SessionEvent>,
+    // The stones as drawn, plus the last-move, hover and selection markers
+    board_view: BoardView,
+    mouse_position: glam::Vec2,
+    engine: Option<Box<dyn Engine>>,
+    ai_color: StoneColor,
+    // Kept for the saved engines L switches to
+    ai_budget: AiBudget,
+    replay: Option<Replay>,
+    // A camera path being recorded over a replay, or the one driving the
+    // camera through it
+    camera_recording: Option<CameraPath>,
+    camera_playback: Option<CameraPath>,
+    save_slots: SaveSlots,
+    settings: GameSettings,
+    audio: AudioPlayer,
+    haptics: Haptics,
+    announcer: Announcer,
+    commands: Option<Receiver<String>>,
+    // Analysis mode: the engine's view of the position drawn as a heatmap,
+    // and the continuations of any known local shapes
+    analysis: bool,
+    analyst: Box<dyn Engine>,
+    patterns: PatternLibrary,
+    // The influence cloud, shown while analysis is on
+    show_influence: bool,
+    // Shells over stones in atari or on two liberties
+    show_liberties: bool,
+    // Picking debug view, and the last click's pick ray while it's on
+    pick_debug: bool,
+    last_pick: Option<PickRay>,
+    show_score_graph: bool,
+    // Visualization training, and whether X has revealed the position anyway
+    blind: BlindMode,
+    revealed: bool,
+    // Marking tool while annotating a position for review or teaching
+    annotation_tool: Option<AnnotationTool>,
+    // Beginner aid: ghost dots on every legal point for the side to move
+    show_legal_moves: bool,
+    // The position summary in the bottom-right corner
+    show_stats_panel: bool,
+    // The guide jumps to each AI move and pulses there
+    follow_moves: bool,
+    // Moves on each page of an exported booklet
+    booklet_every: usize,
+    // Point and position the what-if preview was last worked out for
+    what_if_key: Option<((u8, u8, u8), u64, usize)>,
+    profiles: Profiles,
+    // Black's profile and White; None leaves the ratings alone
+    players: Option<(String, Opponent)>,
+    // Finished games, the ones Z steps through, and how far it's got
+    archive: Archive,
+    archive_filter: ArchiveFilter,
+    archive_cursor: usize,
+    // The moves of the game last archived, so each game is kept once
+    archived_moves: Vec<MoveRecord>,
+    // Saved engine configurations, and how far L has stepped through them
+    engines: EngineConfigs,
+    engine_cursor: usize,
+    toasts: chrome::Toasts,
+    captures: CaptureAnimations,
+    // Fading ghosts of the stones each review step adds or takes away, the
+    // board they're worked out against, and whether the last change was seen
+    // while reviewing
+    ghosts: GhostTrails,
+    ghost_board: Board,
+    reviewing: bool,
+    layout: LayoutSettings,
+    script: Option<Script>,
+    // The script's suggested move, drawn as a dot until the next move
+    proposal: Option<(u8, u8, u8)>,
+    // A move chosen while the AI is to move, played once it has replied
+    premove: Option<Premove>,
+    // A request to take moves back, waiting for the other player at this screen
+    undo_request: Option<UndoRequest>,
+    // The AI's moves from a session being played back, made in place of its own
+    recorded_ai_moves: VecDeque<Move>,
+}
+
+impl GameState {
+    fn new(rules: GameRules, settings: &GameSettings, ai: &AiSettings) -> Self {
+        let ghost_board = rules.board().clone();
+        let session = GameSession::new(rules);
+        let session_events = session.subscribe();
+
+        Self {
+            session,
+            session_events,
+            board_view: BoardView::new(),
+            mouse_position: glam::Vec2::ZERO,
+            engine: ai.enabled.then(|| ai.create_engine(settings)),
+            ai_color: ai.color,
+            ai_budget: ai.budget,
+            replay: None,
+            camera_recording: None,
+            camera_playback: None,
+            save_slots: SaveSlots::new(storage::default_backend()),
+            settings: settings.clone(),
+            audio: AudioPlayer::new(AudioSettings::default()),
+            haptics: Haptics::new(true),
+            announcer: Announcer::new(false),
+            commands: None,
+            analysis: false,
+            analyst: ai.create_engine(settings),
+            patterns: PatternLibrary::builtin(),
+            show_influence: false,
+            show_liberties: false,
+            pick_debug: false,
+            last_pick: None,
+            show_score_graph: false,
+            blind: BlindMode::Off,
+            revealed: false,
+            annotation_tool: None,
+            show_legal_moves: false,
+            show_stats_panel: false,
+            follow_moves: true,
+            booklet_every: booklet::DEFAULT_MOVES_PER_PAGE,
+            what_if_key: None,
+            profiles: load_profiles(),
+            players: None,
+            archive: load_archive(),
+            archive_filter: ArchiveFilter::default(),
+            archive_cursor: 0,
+            archived_moves: Vec::new(),
+            engines: load_engines(),
+            engine_cursor: 0,
+            toasts: chrome::Toasts::default(),
+            captures: CaptureAnimations::default(),
+            ghosts: GhostTrails::default(),
+            ghost_board,
+            reviewing: false,
+            layout: LayoutSettings::default(),
+            script: None,
+            proposal: None,
+            premove: None,
+            undo_request: None,
+            recorded_ai_moves: VecDeque::new(),
+        }
+    }
+
+    fn show_stats(&self) {
+        println!("Player ratings:");
+        for line in self.profiles.stats_table() {
+            println!("  {}", line);
+        }
+        for (name, _) in self.human_players() {
+            println!("Trophies of {}:", name);
+            for line in self.profiles.trophies(&name) {
+                println!("  {}", line);
+            }
+        }
+    }
+
+    // Profiles at the board and the color each plays
+    fn human_players(&self) -> Vec<(String, StoneColor)> {
+        let Some((player, opponent)) = &self.players else {
+            return Vec::new();
+        };
+        let player_color = self.ai_color.opposite();
+        let mut humans = vec![(player.clone(), player_color)];
+        if let Opponent::Human(name) = opponent {
+            humans.push((name.clone(), player_color.opposite()));
+        }
+        humans
+    }
+
+    fn unlock(&mut self, name: &str, achievement: Achievement) {
+        match self.profiles.unlock(name, achievement) {
+            Ok(true) => {
+                let text = format!("{} unlocked {}: {}", name, achievement.title(), achievement.description());
+                if self.announcer.is_enabled() {
+                    self.announcer.say(&text);
+                } else {
+                    println!("{}", text);
+                }
+                self.toasts.push(format!("🏆 {} — {}", name, achievement.title()));
+            }
+            Ok(false) => {}
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+
+    fn check_achievements(&mut self, event: &SessionEvent) {
+        if self.replay.is_some() || self.session.view() != ViewState::Live {
+            return;
+        }
+        for (name, color) in self.human_players() {
+            let earned = achievements::check_event(event, &self.session.read(), color);
+            for achievement in earned {
+                self.unlock(&name, achievement);
+            }
+        }
+    }
+
+    // Two passes in a row end the game; replays are someone else's result and
+    // variations never happened
+    // Who took how long, once both sides have passed
+    fn show_thinking_summary(&self) {
+        let rules = self.session.read();
+        if !rules.is_over() || self.replay.is_some() || self.session.view() != ViewState::Live {
+            return;
+        }
+        if let Some(summary) = ThinkingSummary::new(rules.moves()) {
+            println!("{}", summary);
+        }
+    }
+
+    // Games of three or four are never rated, so their result is only printed
+    fn show_final_scores(&self) {
+        let rules = self.session.read();
+        if rules.players() == 2 || !rules.is_over() || self.replay.is_some() || self.session.view() != ViewState::Live {
+            return;
+        }
+        let scores: Vec<String> = self
+            .settings
+            .scores(&rules)
+            .iter()
+            .map(|(color, score)| format!("{} {}", color.name(), score))
+            .collect();
+        println!("Game over: {}", scores.join(" - "));
+    }
+
+    // One HUD row per player in games of three or four; two-player games
+    // have the score graph instead
+    fn score_rows(&self) -> Vec<String> {
+        let rules = self.session.read();
+        if rules.players() == 2 {
+            return Vec::new();
+        }
+        self.settings
+            .scores(&rules)
+            .iter()
+            .map(|(color, score)| format!("{} {}", color.name().to_uppercase(), score))
+            .collect()
+    }
+
+    // Variants with a victory condition can end on a move
+    fn show_winner(&self) {
+        if self.replay.is_some() || self.session.view() != ViewState::Live {
+            return;
+        }
+        if let Some(win) = accessibility::describe_win(&self.session.read()) {
+            println!("{}", win);
+        }
+    }
+
+    fn record_result_if_over(&mut self) {
+        let Some((player, opponent)) = &self.players else {
+            return;
+        };
+        let rules = self.session.snapshot();
+        let moves = rules.moves();
+        if !rules.is_over() || self.replay.is_some() || self.session.view() != ViewState::Live {
+            return;
+        }
+
+        let (black, white) = self.settings.score(&rules);
+        let outcome = rules.winner().map_or(Outcome::from_score((black, white)), Outcome::Win);
+        let player_color = self.ai_color.opposite();
+        if let Err(error) = self.profiles.record_game(player, player_color, opponent, outcome, moves.len()) {
+            eprintln!("{}", error);
+            return;
+        }
+        println!("Game over: Black {} - White {}", black, white);
+
+        let board_size = rules.board().size();
+        for (name, color) in self.human_players() {
+            let games = self.profiles.get(&name).map_or(0, |profile| profile.games());
+            let won = outcome == Outcome::Win(color);
+            for achievement in achievements::check_game_end(board_size, won, games) {
+                self.unlock(&name, achievement);
+            }
+        }
+        self.show_stats();
+    }
+
+    // Every game that ends here goes in the archive, named when the players
+    // are known; passing on after the end doesn't add it again
+    fn archive_if_over(&mut self) {
+        let rules = self.session.snapshot();
+        if !rules.is_over() || self.replay.is_some() || self.session.view() != ViewState::Live {
+            return;
+        }
+        let mut before = rules.clone();
+        if before.undo() && before.is_over() || self.archived_moves == rules.moves() {
+            return;
+        }
+        self.archived_moves = rules.moves().to_vec();
+
+        let mut record = self.session.record(self.settings.komi);
+        record.result = match rules.winner() {
+            Some(color) => Some(GameResult::Win { color, margin: None }),
+            None => GameResult::from_scores(&self.settings.scores(&rules)),
+        };
+        if let Some((player, opponent)) = &self.players {
+            let (player, opponent) = (Some(player.clone()), Some(opponent.to_string()));
+            (record.black_player, record.white_player) = match self.ai_color.opposite() {
+                StoneColor::Black => (player, opponent),
+                _ => (opponent, player),
+            };
+        }
+        match self.archive.add(&record) {
+            Ok(_) => println!("Game archived (Z browses the archive)"),
+            Err(error) => eprintln!("cannot archive the game: {}", error),
+        }
+    }
+
+    // Opens the next archived game, newest first, at its empty board for
+    // stepping through with Page Down
+    fn browse_archive(&mut self) {
+        let entries = match self.archive.entries(&self.archive_filter) {
+            Ok(entries) => entries,
+            Err(error) => {
+                eprintln!("{}", error);
+                return;
+            }
+        };
+        if entries.is_empty() {
+            println!("No archived games");
+            return;
+        }
+        let index = self.archive_cursor % entries.len();
+        self.archive_cursor = index + 1;
+        let entry = &entries[index];
+
+        self.replay = None;
+        self.camera_recording = None;
+        self.camera_playback = None;
+        if let Err(error) = self.session.load(&entry.record) {
+            eprintln!("{}", error);
+            return;
+        }
+        while self.session.step_back() {}
+        println!(
+            "Archive {}/{}: {} (Page Down steps through, Z for the next)",
+            index + 1,
+            entries.len(),
+            entry.describe()
+        );
+    }
+
+    // Hands the AI's side to the next saved engine configuration
+    fn next_engine(&mut self) {
+        if let Some((_, Opponent::Human(name))) = &self.players {
+            println!("{} is playing {:?}; saved engines are for games against the AI", name, self.ai_color);
+            return;
+        }
+        let configs = match self.engines.list() {
+            Ok(configs) => configs,
+            Err(error) => {
+                eprintln!("{}", error);
+                return;
+            }
+        };
+        if configs.is_empty() {
+            println!("No saved engines (--save-engine NAME keeps the current one)");
+            return;
+        }
+        let index = self.engine_cursor % configs.len();
+        self.engine_cursor = index + 1;
+        let config = &configs[index];
+
+        let ai = AiSettings {
+            budget: self.ai_budget,
+            ..config.ai_settings()
+        };
+        self.engine = Some(ai.create_engine(&self.settings));
+        if let Some((_, opponent)) = &mut self.players {
+            *opponent = Opponent::Ai(config.level);
+        }
+        println!("{:?} is played by {} (L for the next)", self.ai_color, config.describe());
+        if let Some(note) = budget_note(&ai) {
+            println!("{}", note);
+        }
+    }
+
+    // Estimated score over the game; a replay shows its whole record with the
+    // cursor at the current move
+    fn score_graph(&self) -> Option<ScoreGraph> {
+        if !self.show_score_graph {
+            return None;
+        }
+        let (record, current_move) = match &self.replay {
+            Some(replay) => (replay.record.clone(), replay.next_move),
+            None => {
+                let record = GameRecord::from_rules(&self.session.read(), self.settings.komi);
+                let moves = record.moves.len();
+                (record, moves)
+            }
+        };
+        match analysis::analyze(&record, &self.settings) {
+            Ok(analysis) => Some(ScoreGraph::new(analysis, current_move)),
+            Err(error) => {
+                log::warn!("couldn't analyze game: {}", error);
+                None
+            }
+        }
+    }
+
+    // The blind mode as currently drawn
+    fn blind_view(&self) -> BlindMode {
+        if self.revealed {
+            BlindMode::Off
+        } else {
+            self.blind
+        }
+    }
+
+    // Markup of the position on the board, placed at its intersections
+    fn annotation_marks(&self) -> Vec<(Vec3, Mark)> {
+        let to_world = self.to_world();
+        let move_number = self.session.read().moves().len();
+        self.session
+            .annotations()
+            .at(move_number)
+            .map(|annotation| (to_world.to_world(annotation.position), annotation.mark))
+            .collect()
+    }
+
+    fn annotate(&self, position: (u8, u8, u8)) {
+        let Some(tool) = self.annotation_tool else {
+            return;
+        };
+        match self.session.annotate(position, tool.mark()) {
+            Some(Mark::Label(letter)) => println!("Label {} on {}", letter, accessibility::format_point(position)),
+            Some(_) => println!("Marked {} with a {}", accessibility::format_point(position), tool.name()),
+            None => println!("Cleared {}", accessibility::format_point(position)),
+        }
+    }
+
+    // Where the legal-move dots go: every legal point while the hint is on,
+    // the script's suggestion and, in analysis mode, pattern continuations
+    fn legal_moves(&self) -> Vec<Vec3> {
+        let to_world = self.to_world();
+        let mut dots: Vec<Vec3> = self.proposal.iter().map(|&position| to_world.to_world(position)).collect();
+        if self.analysis {
+            let rules = self.session.read();
+            let continuations = self.patterns.matches(&rules).into_iter().map(|found| found.continuation);
+            dots.extend(continuations.map(|position| to_world.to_world(position)));
+        }
+        if self.show_legal_moves {
+            let rules = self.session.read();
+            dots.extend(rules.legal_moves().into_iter().map(|position| to_world.to_world(position)));
+        }
+        dots
+    }
+
+    fn start_script(&mut self) {
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        let result = script.start(&self.session.read());
+        self.apply_script(result);
+    }
+
+    fn run_script(&mut self, event: &SessionEvent) {
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        let result = script.handle_event(event, &self.session.read());
+        self.apply_script(result);
+    }
+
+    // A failing hook is reported and the script keeps running for later events
+    fn apply_script(&mut self, result: Result<Vec<ScriptCommand>, ScriptError>) {
+        let commands = match result {
+            Ok(commands) => commands,
+            Err(error) => {
+                eprintln!("{}", error);
+                return;
+            }
+        };
+        for command in commands {
+            let text = match command {
+                ScriptCommand::Mark { point, mark } => {
+                    self.session.set_mark(point, mark);
+                    continue;
+                }
+                ScriptCommand::Propose(point) => {
+                    self.proposal = Some(point);
+                    format!("Suggested move: {}", accessibility::format_point(point))
+                }
+                ScriptCommand::Say(text) => text,
+            };
+            if self.announcer.is_enabled() {
+                self.announcer.say(&text);
+            } else {
+                println!("{}", text);
+            }
+            self.toasts.push(text);
+        }
+    }
+
+    // Named continuations of the known shapes on the board, in analysis mode
+    fn show_patterns(&self) {
+        if !self.analysis {
+            return;
+        }
+        for found in self.patterns.matches(&self.session.read()) {
+            println!("{}: {}", found.name, accessibility::format_point(found.continuation));
+        }
+    }
+
+    // The info panel's lines, when it's on; none while blind training hides
+    // the stones or their colors
+    fn stats_rows(&self) -> Vec<String> {
+        if !self.show_stats_panel || self.blind_view() != BlindMode::Off {
+            return Vec::new();
+        }
+        let rules = self.session.read();
+        PositionStats::of(rules.board()).rows(rules.players())
+    }
+
+    // Shells over the endangered stones; none while blind training hides
+    // the stones or their colors
+    fn liberty_shells(&self) -> [Vec<Instance>; render::liberties::LEVELS] {
+        if !self.show_liberties || self.blind_view() != BlindMode::Off {
+            return Default::default();
+        }
+        render::liberties::shells(self.session.read().board(), &self.to_world())
+    }
+
+    // Shells for the what-if preview of playing `point`, while Alt is held.
+    // None when nothing changed since last time: the preview is only worked
+    // out again when the point or the position does
+    fn what_if(&mut self, point: Option<(u8, u8, u8)>) -> Option<[Vec<Instance>; render::what_if::KINDS]> {
+        // Blind training keeps the stones' colors to itself
+        let point = point.filter(|_| self.blind_view() == BlindMode::Off);
+        let to_world = self.to_world();
+        let rules = self.session.read();
+        let key = point.map(|point| (point, rules.board().hash(), rules.moves().len()));
+        if key == self.what_if_key {
+            return None;
+        }
+        self.what_if_key = key;
+        let preview = point.and_then(|point| what_if::preview(&rules, point).ok());
+        Some(preview.map_or_else(Default::default, |preview| render::what_if::shells(&preview, &to_world)))
+    }
+
+    // Who holds which empty points, empty unless analysis and the cloud are on
+    fn influence(&self) -> Vec<InfluenceCell> {
+        if !(self.analysis && self.show_influence) {
+            return Vec::new();
+        }
+        let rules = self.session.read();
+        let field = influence::influence(rules.board());
+        render::influence::cells(&field, rules.board(), &self.to_world())
+    }
+
+    // Engine policy over the current position, empty when analysis is off
+    fn heatmap(&mut self) -> Vec<HeatCell> {
+        if !self.analysis {
+            return Vec::new();
+        }
+        let game = Game::from(self.session.snapshot());
+        let policy = self.analyst.policy(&game);
+        heatmap::cells(&policy, &self.to_world())
+    }
+
+    fn enable_accessibility(&mut self) {
+        self.announcer = Announcer::new(true);
+        self.commands = Some(accessibility::read_commands_from_stdin());
+        self.announcer.say(&accessibility::describe_status(&self.session.read()));
+        self.announcer.say(accessibility::HELP);
+    }
+
+    fn run_text_commands(&mut self) {
+        let Some(commands) = &self.commands else {
+            return;
+        };
+        let lines: Vec<String> = commands.try_iter().collect();
+        for line in lines {
+            let board_size = self.session.read().board().size();
+            let reply = Command::parse(&line, board_size).and_then(|command| command.execute(&self.session));
+            match reply {
+                Ok(Some(text)) => self.announcer.say(&text),
+                Ok(None) => {}
+                Err(error) => self.announcer.say(&format!("Error: {}", error)),
+            }
+        }
+    }
+
+    // The point the AI played, if it placed a stone
+    // Whether the AI is about to reply in the game itself, so a move now is a pre-move
+    fn ai_to_move(&self) -> bool {
+        self.engine.is_some()
+            && self.replay.is_none()
+            && self.session.view() == ViewState::Live
+            && self.session.read().current_player() == self.ai_color
+    }
+
+    // A point clicked or chosen with the guide: played straight away on the
+    // user's turn, staged for after the AI's reply on its turn, and the
+    // staged move taken back when it's chosen again
+    fn play(&mut self, position: (u8, u8, u8)) {
+        if !self.ai_to_move() {
+            let (x, y, z) = position;
+            self.session.make_move(x, y, z);
+            return;
+        }
+        if self.premove.is_some_and(|premove| premove.position == position) {
+            self.premove = None;
+            println!("Pre-move at {} taken back", accessibility::format_point(position));
+        } else {
+            let color = self.ai_color.next(self.session.read().players());
+            self.premove = Some(Premove::new(position, color));
+            println!("Pre-move at {}: played after the AI's reply if it's still legal", accessibility::format_point(position));
+        }
+    }
+
+    // Backspace: asks to take back the user's last move. The AI answers at
+    // once by its policy; otherwise the request waits for the other player,
+    // who agrees by pressing Backspace too or declines by playing on.
+    fn request_undo(&mut self) {
+        if self.replay.is_some() || self.session.view() != ViewState::Live {
+            return;
+        }
+        if let Some(request) = self.undo_request.take() {
+            let agreed = request.grant(&self.session);
+            let asker = request.color.name();
+            self.toasts.push(if agreed { format!("Agreed: {}'s move taken back", asker) } else { "Nothing to take back".to_string() });
+            return;
+        }
+        let request = {
+            let rules = self.session.read();
+            let color = match self.engine {
+                Some(_) => Some(self.ai_color.next(rules.players())),
+                None => rules.moves().last().map(|last| last.color),
+            };
+            color.and_then(|color| UndoRequest::new(color, &rules))
+        };
+        let Some(request) = request else {
+            self.toasts.push("Nothing to take back".to_string());
+            return;
+        };
+        if self.engine.is_none() {
+            self.toasts.push(format!("{} asks to take back a move: Backspace agrees, playing on declines", request.color.name()));
+            self.undo_request = Some(request);
+            return;
+        }
+        match UndoPolicy::for_ai(self.players.is_some()) {
+            UndoPolicy::Accept => {
+                request.grant(&self.session);
+            }
+            UndoPolicy::Decline => self.toasts.push("The AI declines: moves aren't taken back in rated games".to_string()),
+        }
+    }
+
+    // What the pointer is over, for its shape: on the flat board, whether a
+    // click would play, stage or mark the point under it. Blind training
+    // doesn't give occupied points away.
+    fn pointer_target(&self, hit: Option<UiHit>) -> PointerTarget {
+        match hit {
+            None => PointerTarget::Scene,
+            Some(UiHit::LayerBoard(None)) => PointerTarget::Inert,
+            Some(UiHit::LayerBoard(Some((x, y, z)))) => {
+                let staging = self.ai_to_move();
+                let rules = self.session.read();
+                let playable = if self.annotation_tool.is_some() || self.blind_view() != BlindMode::Off {
+                    true
+                } else if staging {
+                    rules.board().get_stone((x, y, z)).is_none()
+                } else {
+                    rules.check_move(x, y, z).is_ok()
+                };
+                PointerTarget::Point { playable }
+            }
+            Some(_) => PointerTarget::Control,
+        }
+    }
+
+    // Plays the staged pre-move once its turn has come, or drops it if the
+    // reply has made it illegal
+    fn play_premove_if_due(&mut self) {
+        let Some(premove) = self.premove else {
+            return;
+        };
+        let due = premove.due(&self.session.read());
+        match due {
+            None => {}
+            Some(Ok((x, y, z))) => {
+                self.premove = None;
+                self.session.make_move(x, y, z);
+            }
+            Some(Err(error)) => {
+                self.premove = None;
+                println!("Pre-move at {} dropped: {}", accessibility::format_point(premove.position), error);
+            }
+        }
+    }
+
+    fn premove_ghost(&self) -> Option<(Instance, StoneColor)> {
+        let premove = self.premove?;
+        let to_world = self.to_world();
+        let mut instance = Instance::new(to_world.to_world(premove.position));
+        instance.scale = Vec3::splat(to_world.stone_scale());
+        Some((instance, premove.color))
+    }
+
+    // The move the AI made, if it was its turn
+    fn make_ai_move_if_due(&mut self) -> Option<Move> {
+        let engine = self.engine.as_mut()?;
+        // No replies while the user looks back through the game
+        if self.replay.is_some() || self.session.view() != ViewState::Live {
+            return None;
+        }
+        if self.session.read().current_player() != self.ai_color {
+            return None;
+        }
+
+        let chosen = match self.recorded_ai_moves.pop_front() {
+            Some(chosen) => chosen,
+            None => engine.select_move(&Game::from(self.session.snapshot())),
+        };
+        match chosen {
+            Move::Place(position) => self.session.make_move(position.x, position.y, position.z).then_some(chosen),
+            Move::Pass => {
+                self.session.pass();
+                Some(chosen)
+            }
+        }
+    }
+
+    fn advance_replay(&mut self) {
+        // Paused while reviewing
+        if self.session.view() != ViewState::Live {
+            return;
+        }
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+        if replay.last_step.elapsed().as_secs_f32() < Replay::STEP_SECONDS {
+            return;
+        }
+
+        replay.last_step = Instant::now();
+        replay.next_move += 1;
+        // The record was validated on load, so replaying a prefix cannot fail
+        if let Ok(rules) = replay.record.replay_to(replay.next_move) {
+            self.session.update(|current| current.replace(rules));
+        }
+        if replay.next_move >= replay.record.moves.len() {
+            self.replay = None;
+        }
+    }
+
+    // Replays the game so far from the empty board; false if there's nothing to replay
+    fn start_replay(&mut self) -> bool {
+        if self.replay.is_some() || self.session.view() != ViewState::Live {
+            return false;
+        }
+        let record = self.session.record(self.settings.komi);
+        if record.moves.is_empty() {
+            return false;
+        }
+        if let Ok(rules) = record.replay_to(0) {
+            self.session.update(|current| current.replace(rules));
+        }
+        self.replay = Some(Replay::from_start(record));
+        true
+    }
+
+    // Jumps a running replay to its end, so stopping early keeps the whole game
+    fn finish_replay(&mut self) {
+        if let Some(replay) = self.replay.take() {
+            if let Ok(rules) = replay.record.replay() {
+                self.session.update(|current| current.replace(rules));
+            }
+        }
+    }
+
+    // J: replays the game while the camera's moves are recorded, until the
+    // replay ends or J is pressed again
+    fn toggle_camera_recording(&mut self) {
+        if self.camera_recording.is_some() {
+            self.finish_replay();
+            self.stop_camera_recording();
+        } else if self.start_replay() {
+            self.camera_playback = None;
+            self.camera_recording = Some(CameraPath::default());
+            println!("Recording the camera over a replay (J to stop)");
+        } else {
+            println!("Nothing to replay");
+        }
+    }
+
+    fn stop_camera_recording(&mut self) {
+        let Some(path) = self.camera_recording.take() else {
+            return;
+        };
+        println!(
+            "Recorded a camera path of {} keyframes over {:.1}s; it's saved with the game",
+            path.keyframes().len(),
+            path.duration()
+        );
+        self.session.set_camera_path(path);
+    }
+
+    // O: replays the game with the camera following the recorded path
+    fn play_camera_path(&mut self) {
+        if self.camera_playback.is_some() {
+            self.camera_playback = None;
+            self.finish_replay();
+            return;
+        }
+        let path = self.session.camera_path();
+        if path.is_empty() {
+            println!("No camera path recorded (J records one)");
+        } else if self.camera_recording.is_none() && self.start_replay() {
+            self.camera_playback = Some(path);
+        }
+    }
+
+    // Each frame: samples the camera into a path being recorded, or returns
+    // where a path being played puts it
+    fn follow_camera_path(&mut self, current: CameraPreset) -> Option<CameraPreset> {
+        const KEYFRAME_SECONDS: f32 = 0.25;
+        let clock = self.replay.as_ref().map(Replay::clock);
+        if let Some(path) = self.camera_recording.as_mut() {
+            match clock {
+                Some(time) => {
+                    if path.keyframes().last().is_none_or(|last| time - last.time >= KEYFRAME_SECONDS) {
+                        path.push(CameraKeyframe {
+                            time,
+                            view: current.into(),
+                        });
+                    }
+                }
+                None => self.stop_camera_recording(),
+            }
+            return None;
+        }
+        let Some(time) = clock else {
+            // The replay ended or was stopped
+            self.camera_playback = None;
+            return None;
+        };
+        self.camera_playback.as_ref()?.sample(time).map(CameraPreset::from)
+    }
+
+    fn select_save_slot(&mut self, index: usize) {
+        self.save_slots.select(index);
+        println!("Save slots (F1-F4 select, F5 save, F9 load):");
+        for line in self.save_slots.describe() {
+            println!("  {}", line);
+        }
+    }
+
+    fn save_to_slot(&mut self) {
+        let record = self.session.record(self.settings.komi);
+        match self.save_slots.save(&record) {
+            Ok(()) => println!("Saved to slot {}", self.save_slots.selected() + 1),
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+
+    fn load_from_slot(&mut self) {
+        match self.save_slots.load() {
+            Ok(Some(record)) => {
+                self.replay = None;
+                self.camera_recording = None;
+                self.camera_playback = None;
+                if let Err(error) = self.session.load(&record) {
+                    eprintln!("{}", error);
+                    return;
+                }
+                println!("Loaded slot {}", self.save_slots.selected() + 1);
+            }
+            Ok(None) => println!("Slot {} is empty", self.save_slots.selected() + 1),
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+
+    fn save_game_as(&mut self) {
+        let record = self.session.record(self.settings.komi);
+        match dialogs::save_file(FileKind::Game, &sgf::write(&record)) {
+            Ok(Some(location)) => println!("Saved game to {}", location),
+            Ok(None) => {}
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+
+    fn open_game(&mut self) {
+        // The browser reads the file asynchronously, so the result arrives via the session
+        let session = self.session.clone();
+        let result = dialogs::open_file(FileKind::Game, move |text| {
+            let loaded = sgf::parse(&text)
+                .map_err(|e| e.to_string())
+                .and_then(|record| session.load(&record).map_err(|e| e.to_string()));
+            if let Err(error) = loaded {
+                eprintln!("cannot open game: {}", error);
+            }
+        });
+        if let Err(error) = result {
+            eprintln!("{}", error);
+        }
+    }
+
+    fn export_gltf(&mut self) {
+        let scene = gltf::export_board(&self.session.read(), &self.to_world());
+        match dialogs::save_file(FileKind::Gltf, &scene) {
+            Ok(Some(location)) => println!("Exported board to {}", location),
+            Ok(None) => {}
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+
+    fn export_booklet(&mut self) {
+        let record = self.session.record(self.settings.komi);
+        let exported = booklet::export_booklet(&record, &self.to_world(), self.booklet_every)
+            .map_err(|error| error.to_string())
+            .and_then(|pdf| dialogs::save_file(FileKind::Booklet, &pdf));
+        match exported {
+            Ok(Some(location)) => println!("Exported a booklet of the game to {}", location),
+            Ok(None) => {}
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+
+    // Drains pending session notifications; returns true if the board changed
+    fn poll_session_events(&mut self) -> bool {
+        let mut changed = false;
+        let mut captured = false;
+        while let Ok(event) = self.session_events.try_recv() {
+            captured |= matches!(event, SessionEvent::MovePlayed { captured: 1.., .. });
+            self.audio.handle_event(&event);
+            self.haptics.handle_event(&event);
+            self.announcer.handle_event(&event, &self.session.read());
+            self.check_achievements(&event);
+            if let SessionEvent::Passed { .. } | SessionEvent::MovePlayed { .. } = event {
+                self.show_winner();
+                self.record_result_if_over();
+                self.archive_if_over();
+                self.show_final_scores();
+                self.show_thinking_summary();
+                self.show_patterns();
+            }
+            if event != SessionEvent::Changed {
+                self.proposal = None;
+            }
+            // Playing on declines a request to take moves back
+            if event != SessionEvent::Changed {
+                self.undo_request = None;
+            }
+            // Undoing, a new game or a different position leaves nothing to answer
+            if !matches!(event, SessionEvent::MovePlayed { .. } | SessionEvent::Passed { .. }) {
+                self.premove = None;
+            }
+            self.run_script(&event);
+            changed = true;
+        }
+        if changed {
+            crash::set_game(&self.session.record(self.settings.komi));
+            let removed = self.update_stones();
+            if captured {
+                self.animate_captures(&removed);
+            }
+            self.leave_ghosts();
+        }
+        changed
+    }
+
+    // Stepping through a game in review, including the step back to the
+    // live game, leaves ghosts of what each step changed. Blind training
+    // would have them give the position away.
+    fn leave_ghosts(&mut self) {
+        let board = self.session.read().board().clone();
+        let reviewing = matches!(self.session.view(), ViewState::Reviewing { .. });
+        if (reviewing || self.reviewing) && self.blind_view() == BlindMode::Off {
+            self.ghosts.spawn(&BoardDiff::between(&self.ghost_board, &board), &self.to_world());
+        }
+        self.ghost_board = board;
+        self.reviewing = reviewing;
+    }
+
+    // Fades the ghost trails; true while there are any to redraw
+    fn update_ghosts(&mut self, dt: f32) -> bool {
+        if self.ghosts.is_empty() {
+            return false;
+        }
+        self.ghosts.update(dt);
+        true
+    }
+
+    fn ghost_instances(&self) -> [Vec<Instance>; StoneColor::MAX_PLAYERS as usize] {
+        StoneColor::ALL.map(|color| self.ghosts.instances(color).collect())
+    }
+
+    // Window title for the position on the board
+    fn status(&self) -> String {
+        let view = self.session.view();
+        chrome::status_title(&self.session.read(), view)
+    }
+
+    fn to_world(&self) -> BoardToWorld {
+        BoardToWorld::new(self.session.read().board().size(), &self.layout)
+    }
+
+    // Hands the stones that vanished from the board to the animations
+    fn animate_captures(&mut self, removed: &[((u8, u8, u8), StoneColor)]) {
+        let to_world = self.to_world();
+        let rules = self.session.read();
+        for &(position, color) in removed {
+            if rules.board().get_stone(position).is_none() {
+                self.captures.spawn(color, position, &to_world);
+            }
+        }
+        drop(rules);
+        self.show_captures();
+    }
+
+    // Advances captured stones still on screen
+    fn update_captures(&mut self, dt: f32) {
+        if self.captures.is_empty() {
+            return;
+        }
+        self.captures.update(dt);
+        self.show_captures();
+    }
+
+    // Syncs the drawn stones with the board; returns the ones no longer drawn
+    fn update_stones(&mut self) -> Vec<((u8, u8, u8), StoneColor)> {
+        let removed = self.board_view.sync(&self.session.read(), &self.layout, self.blind_view());
+        self.show_captures();
+        removed
+    }
+
+    // Falling captures are drawn after the board stones, except when blind
+    // training hides the position
+    fn show_captures(&mut self) {
+        let hidden = self.blind_view() == BlindMode::Hidden;
+        for color in StoneColor::ALL {
+            let falling = self.captures.instances(color).filter(|_| !hidden);
+            self.board_view.set_transient(color, falling);
+        }
+    }
+
+    // Keeps the click's pick ray for the debug view, and prints it so a
+    // picking problem can be reported exactly
+    fn record_pick(&mut self, camera: &Camera, screen_size: glam::Vec2) {
+        if !self.pick_debug {
+            return;
+        }
+        let pick = MousePicker::pick(self.mouse_position, screen_size, camera, &self.session.read(), &self.to_world());
+        let cell = match pick.cell {
+            Some(cell) => accessibility::format_point(cell),
+            None => "no cell".to_string(),
+        };
+        println!(
+            "Pick at ({:.0}, {:.0}) of {}x{}: ray from {:.3} along {:.3} -> {}{}",
+            self.mouse_position.x,
+            self.mouse_position.y,
+            screen_size.x,
+            screen_size.y,
+            pick.origin,
+            pick.direction,
+            cell,
+            if pick.stone_distance.is_some() { " (stone)" } else { "" },
+        );
+        self.last_pick = Some(pick);
+    }
+
+    // The last pick ray and its cell, drawn while picking is being debugged
+    fn pick_debug_instances(&self) -> (Option<Instance>, Option<Instance>) {
+        let Some(pick) = self.last_pick.filter(|_| self.pick_debug) else {
+            return (None, None);
+        };
+        let to_world = self.to_world();
+        let ray = render::pick_debug::ray(pick.origin, pick.direction, pick.length(&to_world), &to_world);
+        (Some(ray), pick.cell.map(|cell| render::pick_debug::cell(cell, &to_world)))
+    }
+
+    // The stone under the cursor, if any
+    fn pick_stone(&self, camera: &Camera, screen_size: glam::Vec2) -> Option<(u8, u8, u8)> {
+        let (ray_origin, ray_direction) = MousePicker::screen_to_world_ray(self.mouse_position, screen_size, camera);
+        MousePicker::find_clicked_stone(ray_origin, ray_direction, &self.session.read(), &self.to_world())
+            .map(|(position, _distance)| position)
+    }
+}
+
+// Said whenever the search is scaled down, so a weaker AI or a changed
+// experiment is never a surprise
+fn budget_note(ai: &AiSettings) -> Option<String> {
+    if !ai.enabled || ai.level < AiSettings::MAX_LEVEL || !ai.saving() {
+        return None;
+    }
+    let (playouts, threads) = ai.search();
+    Some(format!(
+        "Saving power: MCTS searches {} of {} playouts on up to {} threads (--ai-budget full for all of it)",
+        playouts, ai.playouts, threads
+    ))
+}
 
-use game::{GameRules, StoneColor};
-use render::{Graphics, Camera, CameraController, Instance};
-use input::MousePicker;
-use glam::Vec3;
-use winit::{
-    event::*,
-    event_loop::{ControlFlow, EventLoop},
-    window::{Window, WindowBuilder},
-};
-use instant::Instant;
+fn load_engines() -> EngineConfigs {
+    EngineConfigs::new(storage::engine_backend())
+}
 
-struct GameState {
-    rules: GameRules,
-    black_stone_instances: Vec<Instance>,
-    white_stone_instances: Vec<Instance>,
-    selected_position: Option<(u8, u8, u8)>,
-    mouse_position: glam::Vec2,
-    animation_paused: bool,
+fn load_archive() -> Archive {
+    Archive::new(storage::archive_backend())
 }
 
-impl GameState {
-    fn new() -> Self {
-        let rules = GameRules::new_with_dodecahedron(3); // Use 3x3x3 board
-        let black_stone_instances = Vec::new();
-        let white_stone_instances = Vec::new();
+fn load_profiles() -> Profiles {
+    Profiles::load(storage::profile_backend()).unwrap_or_else(|error| {
+        log::warn!("{}; starting with no profiles", error);
+        Profiles::load(Box::<storage::MemoryBackend>::default()).expect("memory storage cannot fail")
+    })
+}
 
-        Self {
-            rules,
-            black_stone_instances,
-            white_stone_instances,
-            selected_position: None,
-            mouse_position: glam::Vec2::ZERO,
-            animation_paused: false,
-        }
-    }
-
-    fn update_stones(&mut self) {
-        self.black_stone_instances.clear();
-        self.white_stone_instances.clear();
-        let board_size = self.rules.board().size();
-        let half_size = board_size as f32 * 0.5;
-
-        for ((x, y, z), color) in self.rules.board().get_all_stones() {
-            let pos = Vec3::new(
-                *x as f32 - half_size + 0.5,
-                *z as f32 - half_size + 0.5,
-                *y as f32 - half_size + 0.5,
-            );
-            
-            let mut instance = Instance::new(pos);
-            // Scale stones to be more visible
-            instance.scale = Vec3::splat(1.2);
-            
-            // Add to appropriate instance list based on color
-            match color {
-                StoneColor::Black => {
-                    self.black_stone_instances.push(instance);
-                }
-                StoneColor::White => {
-                    self.white_stone_instances.push(instance);
-                }
+fn run_headless(settings: &GameSettings, ai: &AiSettings, record: Option<GameRecord>) {
+    let rules = match record {
+        Some(record) => {
+            println!("Replaying {} moves on a {}³ board", record.moves.len(), record.board_size);
+            if let Some(seed) = record.seed {
+                println!("Played by engines from seed {} (--headless --seed {} with the same engines plays it again)", seed, seed);
+            }
+            let review = analysis::analyze(&record, settings).expect("record validated on load");
+            for blunder in &review.blunders {
+                println!("  move {}: {:?} lost about {:.1} points", blunder.move_number, blunder.color, blunder.loss);
             }
+            record.replay().expect("record validated on load")
+        }
+        None => {
+            // Self-play with the same engine strength on both sides
+            let (mut black, mut white) = (ai.create_engine(settings), ai.create_engine(settings));
+            let seed = ai.seed.unwrap_or_else(rand::random);
+            println!("Seed {} (--seed {} plays this game again)", seed, seed);
+            let result = tournament::play_match(&mut *black, &mut *white, settings, seed, |move_number, color, mv| match mv {
+                Move::Place(position) => println!("{:>4}. {:?} {}", move_number, color, position),
+                Move::Pass => println!("{:>4}. {:?} pass", move_number, color),
+            });
+            result.record.replay().expect("engine games are legal")
+        }
+    };
+
+    let (black, white) = settings.score(&rules);
+    println!(
+        "Score ({} rules, komi {}): Black {} - White {}",
+        settings.scoring, settings.komi, black, white
+    );
+}
+
+fn run_tournament(cli: &Cli, settings: GameSettings) {
+    let entrants = match cli.tournament_entries() {
+        Ok(entries) => Entrant::lineup(entries),
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+    let seed = cli.seed.unwrap_or_else(rand::random);
+    let mut tournament = match Tournament::new(entrants, settings, cli.pairing, cli.rounds, cli.archive.clone(), seed) {
+        Ok(tournament) => tournament,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+    println!("{} tournament, {} rounds, seed {}", cli.pairing, tournament.rounds(), seed);
+    for entrant in tournament.entrants() {
+        if let Some(note) = budget_note(&entrant.ai) {
+            println!("{}: {}", entrant.name, note);
         }
     }
 
-    fn handle_mouse_click(&mut self, camera: &Camera, screen_size: glam::Vec2) -> bool {
-        let (ray_origin, ray_direction) = MousePicker::screen_to_world_ray(
-            self.mouse_position,
-            screen_size,
-            camera,
+    let names: Vec<String> = tournament.entrants().iter().map(|entrant| entrant.name.clone()).collect();
+    let result = tournament.run(|game| {
+        let (black, white) = game.result.score;
+        println!(
+            "Round {}: {} (Black) {} - {} {} (White), {} moves",
+            game.round,
+            names[game.black],
+            black,
+            white,
+            names[game.white],
+            game.result.record.moves.len()
         );
+    });
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
 
-        if let Some((x, y, z)) = MousePicker::intersect_board_position(
-            ray_origin,
-            ray_direction,
-            self.rules.board().size(),
-        ) {
-            if self.rules.make_move(x, y, z) {
-                self.update_stones();
-                return true;
-            }
+    println!();
+    for line in tournament.standings_table() {
+        println!("{}", line);
+    }
+}
+
+// --save-engine, --import-engine, --export-engine and --engines
+fn manage_engines(cli: &Cli, ai_settings: &AiSettings) {
+    let mut engines = load_engines();
+    let fail = |error: String| -> ! {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    };
+    if let Some(name) = &cli.save_engine {
+        let config = EngineConfig::new(name, ai_settings).unwrap_or_else(|e| fail(e.to_string()));
+        engines.save(&config).unwrap_or_else(|e| fail(e.to_string()));
+        println!("Saved {}", config.describe());
+    }
+    if let Some(path) = &cli.import_engine {
+        let text = std::fs::read_to_string(path).unwrap_or_else(|e| fail(format!("cannot read {}: {}", path.display(), e)));
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        let config = engines
+            .import(&text, stem)
+            .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)));
+        println!("Imported {}", config.describe());
+    }
+    if let Some(name) = &cli.export_engine {
+        print!("{}", engines.export(name).unwrap_or_else(|e| fail(e.to_string())));
+    }
+    if cli.engines {
+        let configs = engines.list().unwrap_or_else(|e| fail(e.to_string()));
+        if configs.is_empty() {
+            println!("No saved engines");
+        }
+        for config in configs {
+            println!("{}", config.describe());
         }
+    }
+}
 
-        false
+// The report a crash left last time, offered once for a bug report
+fn offer_crash_report() {
+    let mut backend = storage::crash_backend();
+    let (slot, report) = match crash::take_pending(&mut *backend) {
+        Ok(Some(pending)) => pending,
+        Ok(None) => return,
+        Err(error) => {
+            log::warn!("couldn't check for crash reports: {}", error);
+            return;
+        }
+    };
+    println!("go3d crashed last time; the report is {} in the crashes folder of the data directory", slot);
+    if !std::io::stdin().is_terminal() {
+        return;
+    }
+    print!("Copy it to the clipboard for a bug report? [y/N] ");
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() || !answer.trim().eq_ignore_ascii_case("y") {
+        return;
+    }
+    match crash::copy_to_clipboard(&report) {
+        Ok(()) => println!("Copied"),
+        Err(error) => eprintln!("{}", error),
     }
 }
 
-async fn run() {
+fn run(cli: Cli, preferences: Preferences, session: Option<SessionRecording>) {
     env_logger::init();
-    
-    // Check for command-line arguments
-    let args: Vec<String> = std::env::args().collect();
-    let use_test_pattern = args.iter().any(|arg| arg == "--test" || arg == "-t");
-    let debug_mode = args.iter().any(|arg| arg == "--debug" || arg == "-d");
-    
-    if debug_mode || use_test_pattern {
+    offer_crash_report();
+
+    let settings = cli.game_settings();
+    let ai_settings = match cli.ai_settings() {
+        Ok(ai_settings) => ai_settings,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+    crash::set_settings(format!("{:?}\n{:?}\n{:?}", settings, ai_settings, cli.layout()));
+
+    if let Some(url) = &cli.connect {
+        eprintln!("cannot connect to {}: network play is not available in this build", url);
+        std::process::exit(2);
+    }
+
+    if settings.players > 2 {
+        if ai_settings.enabled || cli.player.is_some() || cli.headless || !cli.tournament.is_empty() {
+            eprintln!("--players {} is hot-seat only: the AI, ratings and tournaments are for two", settings.players);
+            std::process::exit(2);
+        }
+        if settings.variant == Variant::Simultaneous {
+            eprintln!("the simultaneous variant is for two players");
+            std::process::exit(2);
+        }
+    }
+
+    // A played-back session has its game with it, in case the file is gone
+    let loaded = match &session {
+        Some(recording) => Ok(recording.game.clone()),
+        None => cli.load_record(),
+    };
+    let record = match loaded {
+        Ok(record) => record,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
+    let script = match cli.script.as_deref().map(Script::load).transpose() {
+        Ok(script) => script,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
+    if cli.stats {
+        let profiles = load_profiles();
+        for line in profiles.stats_table() {
+            println!("{}", line);
+        }
+        let calibration = Calibration::load(&*storage::profile_backend());
+        println!("\nAI levels on {}³ boards:", settings.board_size);
+        for level in 0..=AiSettings::MAX_LEVEL {
+            println!("  {}", calibration.label(level, settings.board_size));
+        }
+        if let Some(player) = &cli.player {
+            println!("\nTrophies of {}:", player);
+            for line in profiles.trophies(player) {
+                println!("{}", line);
+            }
+        }
+        return;
+    }
+
+    if cli.games {
+        for line in load_archive().describe(&cli.archive_filter()) {
+            println!("{}", line);
+        }
+        return;
+    }
+
+    if let Some(path) = &cli.booklet {
+        let Some(record) = &record else {
+            eprintln!("--booklet needs a game: --load FILE or --review N");
+            std::process::exit(2);
+        };
+        let to_world = BoardToWorld::new(record.board_size, &cli.layout());
+        let written = booklet::export_booklet(record, &to_world, cli.booklet_every)
+            .map_err(|error| error.to_string())
+            .and_then(|pdf| std::fs::write(path, pdf).map_err(|error| format!("cannot write {}: {}", path.display(), error)));
+        if let Err(error) = written {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        println!("Wrote a booklet of the game to {}", path.display());
+        return;
+    }
+
+    if let Some(path) = &cli.thumbnail {
+        let Some(record) = &record else {
+            eprintln!("--thumbnail needs a game: --load FILE or --review N");
+            std::process::exit(2);
+        };
+        let rules = record.replay().expect("record validated on load");
+        let written = render::thumbnail(rules.board(), cli.thumbnail_size).save(path);
+        if let Err(error) = written {
+            eprintln!("cannot write {}: {}", path.display(), error);
+            std::process::exit(1);
+        }
+        println!("Wrote a thumbnail of the game to {}", path.display());
+        return;
+    }
+
+    if cli.save_engine.is_some() || cli.import_engine.is_some() || cli.export_engine.is_some() || cli.engines {
+        manage_engines(&cli, &ai_settings);
+        return;
+    }
+
+    if let Some(note) = budget_note(&ai_settings).filter(|_| cli.tournament.is_empty()) {
+        println!("{}", note);
+    }
+
+    if cli.headless {
+        run_headless(&settings, &ai_settings, record);
+        return;
+    }
+
+    if !cli.tournament.is_empty() {
+        run_tournament(&cli, settings);
+        return;
+    }
+
+    if cli.debug || cli.test {
         println!("\n========================================");
         println!("3D GO DEBUG MODE ACTIVATED");
         println!("========================================");
@@ -110,251 +1880,914 @@ async fn run() {
         println!("========================================\n");
     }
     
-    let event_loop = EventLoop::new();
-    let window = WindowBuilder::new()
-        .with_title("3D Go")
-        .with_inner_size(winit::dpi::LogicalSize::new(1024, 768))
-        .build(&event_loop)
-        .unwrap();
-
-    let mut graphics = Graphics::new(&window).await;
-    let mut camera = Camera::new(graphics.size.width, graphics.size.height);
-    let mut camera_controller = CameraController::new(10.0, 1.0);
-    let mut game_state = GameState::new();
-    
-    // Load test pattern if requested
-    if use_test_pattern {
-        println!("Loading test pattern...");
-        game_state.rules.place_test_pattern();
+    let recorder = cli.record_session.as_ref().map(|path| {
+        let recording = SessionRecording {
+            args: session_args(),
+            preferences: preferences.to_text(),
+            game: record.clone(),
+            entries: Vec::new(),
+        };
+        let recorder = std::fs::File::create(path)
+            .and_then(|file| SessionRecorder::start(&recording, Box::new(std::io::BufWriter::new(file))))
+            .unwrap_or_else(|error| {
+                eprintln!("cannot record the session into {}: {}", path.display(), error);
+                std::process::exit(1);
+            });
+        println!("Recording the session into {}", path.display());
+        recorder
+    });
+
+    let event_loop = EventLoop::new().expect("couldn't create the event loop");
+    let mut app = App {
+        launch: Some(Launch {
+            cli,
+            preferences,
+            settings,
+            ai_settings,
+            record,
+            script,
+            recorder,
+            session,
+        }),
+        running: None,
+    };
+    if let Err(error) = event_loop.run_app(&mut app) {
+        eprintln!("{}", error);
     }
-    
-    let mut last_frame_time = Instant::now();
-    let mut mouse_pressed = false;
-    let target_fps = 90.0;
-    let target_frame_time = std::time::Duration::from_secs_f32(1.0 / target_fps);
-
-    game_state.update_stones();
-
-    event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Poll;
-
-        match event {
-            Event::WindowEvent {
-                ref event,
-                window_id,
-            } if window_id == window.id() => {
-                match event {
-                    WindowEvent::CloseRequested
-                    | WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
-                                ..
-                            },
-                        ..
-                    } => *control_flow = ControlFlow::Exit,
-
-                    WindowEvent::Resized(physical_size) => {
-                        graphics.resize(*physical_size);
-                        camera.update_aspect(physical_size.width, physical_size.height);
+}
+
+// What the command line asked for, held until there is a window to show it in
+struct Launch {
+    cli: Cli,
+    preferences: Preferences,
+    settings: GameSettings,
+    ai_settings: AiSettings,
+    record: Option<GameRecord>,
+    script: Option<Script>,
+    recorder: Option<SessionRecorder>,
+    // --play-session: the recording to play back
+    session: Option<SessionRecording>,
+}
+
+// The window, its renderer and the game shown in it
+struct Running {
+    window: Arc<Window>,
+    graphics: Graphics,
+    camera: Camera,
+    camera_controller: CameraController,
+    game_state: GameState,
+    clock: AnimationClock,
+    theme_kind: ThemeKind,
+    mouse_pressed: bool,
+    // The pointer shape last set, so it's only changed when it has to
+    cursor: CursorIcon,
+    modifiers: ModifiersState,
+    wheel: WheelSteps,
+    last_frame_time: Instant,
+    // The windowed geometry from last time, kept while fullscreen
+    saved_window: Option<WindowGeometry>,
+    // --record-session writes each input and frame here; --play-session
+    // reads them back from the rest of the recording instead of the window
+    recorder: Option<SessionRecorder>,
+    playback: Option<SessionPlayback>,
+    // Off for a played-back session, which mustn't replace the user's own
+    saves_preferences: bool,
+}
+
+impl Running {
+    const TARGET_FPS: f32 = 90.0;
+
+    fn start(event_loop: &ActiveEventLoop, launch: Launch) -> Self {
+        let Launch {
+            cli,
+            preferences,
+            settings,
+            ai_settings,
+            record,
+            script,
+            recorder,
+            session,
+        } = launch;
+
+        let mut attributes = Window::default_attributes()
+            .with_title("3D Go")
+            .with_window_icon(chrome::icon());
+        attributes = match preferences.window {
+            Some(geometry) => {
+                attributes = attributes.with_inner_size(winit::dpi::PhysicalSize::new(geometry.width, geometry.height));
+                match geometry.position {
+                    Some((x, y)) => attributes.with_position(winit::dpi::PhysicalPosition::new(x, y)),
+                    None => attributes,
+                }
+            }
+            None => attributes.with_inner_size(winit::dpi::LogicalSize::new(1024, 768)),
+        };
+        let window = Arc::new(event_loop.create_window(attributes).expect("couldn't create the window"));
+
+        // Natively the surface is ready straight away, so blocking on it is fine
+        let mut graphics = pollster::block_on(Graphics::new(window.clone()));
+        graphics.set_theme(cli.theme.theme());
+        graphics.set_layout(cli.layout());
+        graphics.set_ui_scale(cli.ui_scale);
+        graphics.set_guide_style(cli.guide_style);
+        if let Some(path) = &cli.profile_render {
+            let started = std::fs::File::create(path)
+                .and_then(|file| graphics.start_profiling(Box::new(std::io::BufWriter::new(file))));
+            match started {
+                Ok(timed) => println!(
+                    "Profiling the renderer into {} ({})",
+                    path.display(),
+                    if timed { "GPU timings and upload volumes" } else { "no GPU timestamps here, upload volumes only" }
+                ),
+                Err(error) => eprintln!("Couldn't profile the renderer into {}: {}", path.display(), error),
+            }
+        }
+        let camera = Camera::new(graphics.size.width, graphics.size.height);
+        let mut camera_controller = CameraController::new(10.0, 1.0);
+        if let Some(preset) = preferences.camera {
+            camera_controller.set_preset(preset);
+        }
+        graphics.set_panels_visible(preferences.panels_visible);
+
+        // A loaded record decides the board size; --replay starts it from move zero
+        let board_size = record.as_ref().map_or(settings.board_size, |r| r.board_size);
+        let rules = match &record {
+            Some(record) if cli.replay => GameRules::new(record.board_size),
+            Some(record) => record.replay().expect("record validated on load"),
+            // The demo position is only for standard play
+            None if settings.variant == Variant::Standard && settings.players == 2 => GameRules::new_with_dodecahedron(board_size),
+            None => settings.new_game(),
+        };
+        let mut game_state = GameState::new(rules, &settings, &ai_settings);
+        if let Some(record) = &record {
+            game_state.session.set_annotations(record.annotations.clone());
+            game_state.session.set_camera_path(record.camera_path.clone());
+        }
+        if let Variant::Stack { levels } = settings.variant {
+            let stack = Stack::new(board_size, levels.min(board_size as u8));
+            let ladders: Vec<String> = stack
+                .ladders()
+                .iter()
+                .map(|&(x, y)| format!("{}{}", (b'a' + x) as char, y + 1))
+                .collect();
+            println!("{} stacked boards, joined at {}", stack.levels(), ladders.join(", "));
+        }
+        if let Variant::Holes { seed, percent } = settings.variant {
+            let missing = game_state.session.read().board().holes().count();
+            println!("{} points missing; --variant holes:{}:{} plays these holes again", missing, seed, percent);
+        }
+        if settings.variant == Variant::Simultaneous {
+            println!("Black's moves stay hidden until White has chosen; a shared point stays empty");
+        }
+        if ai_settings.enabled {
+            let calibration = Calibration::load(&*storage::profile_backend());
+            println!("Playing against {}", calibration.label(ai_settings.level, board_size));
+        }
+        game_state.layout = cli.layout();
+        game_state.blind = cli.blind;
+        graphics.set_blind(game_state.blind_view());
+        game_state.players = cli.players(&ai_settings);
+        game_state.archive_filter = cli.archive_filter();
+        game_state.booklet_every = cli.booklet_every;
+        if cli.review.is_some() {
+            while game_state.session.step_back() {}
+            println!("Reviewing from the empty board: Page Down steps forward, End jumps to the result");
+        }
+        if cli.sandbox {
+            game_state.captures = CaptureAnimations::new(CaptureStyle::Physics);
+        }
+        if let Some(seed) = cli.seed {
+            game_state.captures.reseed(seed);
+        }
+        if cli.accessible {
+            game_state.enable_accessibility();
+        }
+        if cli.replay {
+            // A recorded fly-around plays along with it
+            if let Some(record) = record.as_ref().filter(|record| !record.camera_path.is_empty()) {
+                game_state.camera_playback = Some(record.camera_path.clone());
+            }
+            game_state.replay = record.map(Replay::from_start);
+            // Review mode opens with the score graph
+            game_state.show_score_graph = true;
+            graphics.set_score_graph(game_state.score_graph());
+        }
+        *graphics.guide_system_mut() = GuideSystem::new(board_size);
+        graphics.set_score_rows(game_state.score_rows());
+
+        // Load test pattern if requested
+        if cli.test {
+            println!("Loading test pattern...");
+            game_state.session.update(|rules| rules.place_test_pattern());
+        }
+
+        let mut clock = AnimationClock::default();
+        clock.set_speed(cli.animation_speed);
+
+        game_state.script = script;
+        game_state.start_script();
+        if let Some(recording) = &session {
+            game_state.recorded_ai_moves = recording.ai_moves();
+        }
+
+        game_state.update_stones();
+        graphics.set_legal_moves(&game_state.legal_moves());
+        graphics.set_annotations(game_state.annotation_marks());
+        window.set_title(&game_state.toasts.title_or(game_state.status()));
+
+        Self {
+            window,
+            graphics,
+            camera,
+            camera_controller,
+            game_state,
+            clock,
+            theme_kind: cli.theme,
+            mouse_pressed: false,
+            cursor: CursorIcon::Default,
+            modifiers: ModifiersState::empty(),
+            wheel: WheelSteps::new(cli.wheel_sensitivity),
+            last_frame_time: Instant::now(),
+            saved_window: preferences.window,
+            recorder,
+            playback: session.as_ref().map(SessionPlayback::new),
+            saves_preferences: session.is_none(),
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, event: WindowEvent) {
+        if let Some(input) = SessionInput::from_window_event(&event) {
+            // A session being played back takes no input of its own, but Escape still quits
+            if self.playback.is_none() {
+                self.input(event_loop, input);
+            } else if input == (SessionInput::Key { key: KeyCode::Escape, pressed: true }) {
+                event_loop.exit();
+            }
+            return;
+        }
+        let recorded_frame = match event {
+            WindowEvent::RedrawRequested => self.play_back_frame(event_loop),
+            _ => None,
+        };
+
+        let Self {
+            window,
+            graphics,
+            camera,
+            camera_controller,
+            game_state,
+            clock,
+            mouse_pressed,
+            cursor,
+            modifiers,
+            last_frame_time,
+            recorder,
+            ..
+        } = self;
+
+        match &event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+
+            // A hidden tab, a minimized window or a canvas scrolled out of
+            // view: nothing is drawn, the AI waits and animations stop until
+            // it shows again
+            WindowEvent::Occluded(occluded) => {
+                clock.set_suspended(*occluded);
+                if *occluded {
+                    event_loop.set_control_flow(ControlFlow::Wait);
+                } else {
+                    event_loop.set_control_flow(ControlFlow::Poll);
+                    window.request_redraw();
+                }
+            }
+
+            WindowEvent::RedrawRequested => {
+                *last_frame_time = Instant::now();
+                match recorded_frame {
+                    Some(real_dt) => clock.advance(real_dt),
+                    None => clock.tick(),
+                }
+                if let Some(recorder) = recorder {
+                    recorder.record(SessionEntry::Frame(clock.real_dt()));
+                }
+
+                game_state.run_text_commands();
+                game_state.advance_replay();
+                if let Some(chosen) = game_state.make_ai_move_if_due() {
+                    if let Some(recorder) = recorder {
+                        recorder.record(SessionEntry::Ai(chosen));
                     }
+                    if let (Move::Place(position), true) = (chosen, game_state.follow_moves) {
+                        graphics.guide_system_mut().follow_move(position.into());
+                    }
+                }
+                game_state.play_premove_if_due();
+                graphics.guide_system_mut().tick(clock.dt());
+                graphics.guide_system_mut().snap_to_placement(&game_state.session.read());
+                let guide_point = graphics.guide_system_mut().get_intersection_position();
+                if let Some(shells) = game_state.what_if(modifiers.alt_key().then_some(guide_point)) {
+                    graphics.set_what_if(&shells);
+                }
+                if game_state.poll_session_events() {
+                    window.set_title(&game_state.toasts.title_or(game_state.status()));
+                    let rules = game_state.session.read();
 
-                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                        graphics.resize(**new_inner_size);
-                        camera.update_aspect(new_inner_size.width, new_inner_size.height);
+                    // Loading a game may have changed the board size
+                    let board_size = rules.board().size();
+                    if graphics.guide_system_mut().board_size() != board_size {
+                        *graphics.guide_system_mut() = GuideSystem::new(board_size);
                     }
+                    drop(rules);
+                    graphics.set_heatmap(&game_state.heatmap());
+                    graphics.set_influence(&game_state.influence());
+                    graphics.set_liberty_shells(&game_state.liberty_shells());
+                    graphics.set_legal_moves(&game_state.legal_moves());
+                    graphics.set_annotations(game_state.annotation_marks());
+                    graphics.set_score_graph(game_state.score_graph());
+                    graphics.set_stats_rows(game_state.stats_rows());
+                    graphics.set_score_rows(game_state.score_rows());
+                }
+                if game_state.toasts.tick() {
+                    window.set_title(&game_state.toasts.title_or(game_state.status()));
+                }
+
+                game_state.update_captures(clock.dt());
+                if game_state.update_ghosts(clock.dt()) {
+                    graphics.set_ghosts(&game_state.ghost_instances());
+                }
+                graphics.set_premove(game_state.premove_ghost());
+                if let Some(preset) = game_state.follow_camera_path(camera_controller.preset()) {
+                    camera_controller.set_preset(preset);
+                }
+                camera_controller.update_camera(camera, clock);
+                let mouse = game_state.mouse_position;
+                let target = game_state.pointer_target(graphics.ui_hit(mouse.x, mouse.y));
+                let icon = cursor::cursor_icon(target, *mouse_pressed, camera_controller.is_panning());
+                if icon != *cursor {
+                    window.set_cursor(icon);
+                    *cursor = icon;
+                }
+
+                let rules = game_state.session.read();
+                // Timeouts and lost surfaces just skip the frame; running out of memory ends the game
+                if let Err(error) = graphics.render(&game_state.board_view, &rules, camera, None, clock) {
+                    eprintln!("{}", error);
+                    event_loop.exit();
+                }
+            }
 
-                    WindowEvent::KeyboardInput { input, .. } => {
-                        if let Some(key) = input.virtual_keycode {
-                            
-                            // Handle special game commands only on key press
-                            if input.state == ElementState::Pressed {
-                                match key {
-                                    VirtualKeyCode::R => {
-                                        // Reset - clear the board
-                                        game_state.rules.clear_board();
-                                        game_state.update_stones();
-                                    }
-                                    VirtualKeyCode::T => {
-                                        // Test pattern for debugging side views
-                                        println!("\n==== ACTIVATING TEST PATTERN MODE ====");
-                                        game_state.rules.place_test_pattern();
-                                        game_state.update_stones();
-                                        println!("Press P to pause/resume animation");
-                                        println!("================================\n");
-                                    }
-                                    VirtualKeyCode::P => {
-                                        // Toggle animation pause
-                                        game_state.animation_paused = !game_state.animation_paused;
-                                        println!("Animation: {}", if game_state.animation_paused { "PAUSED" } else { "RUNNING" });
-                                    }
-                                    // Guide plane controls
-                                    VirtualKeyCode::A => {
-                                        graphics.guide_system_mut().move_x(-1);
-                                    }
-                                    VirtualKeyCode::D => {
-                                        graphics.guide_system_mut().move_x(1);
-                                    }
-                                    VirtualKeyCode::W => {
-                                        graphics.guide_system_mut().move_y(1);
-                                    }
-                                    VirtualKeyCode::S => {
-                                        graphics.guide_system_mut().move_y(-1);
-                                    }
-                                    VirtualKeyCode::Space => {
-                                        // Place stone at guide intersection
-                                        let (x, y, z) = graphics.guide_system_mut().get_intersection_position();
-                                        if game_state.rules.make_move(x, y, z) {
-                                            game_state.update_stones();
-                                        }
-                                    }
-                                    // Zoom controls
-                                    VirtualKeyCode::Q | VirtualKeyCode::Left => {
-                                        camera_controller.zoom_in();
-                                    }
-                                    VirtualKeyCode::E | VirtualKeyCode::Right => {
-                                        camera_controller.zoom_out();
-                                    }
-                                    VirtualKeyCode::Up | VirtualKeyCode::Down => {
-                                        // Arrow keys up/down don't do anything now (used to be W/S for camera)
-                                        // Ignore these since W/S now control guide planes
-                                    }
-                                    _ => {
-                                        // Pass remaining keys to camera controller (but not Q/E/arrows)
-                                        match key {
-                                            VirtualKeyCode::Q | VirtualKeyCode::E | 
-                                            VirtualKeyCode::Left | VirtualKeyCode::Right |
-                                            VirtualKeyCode::Up | VirtualKeyCode::Down => {
-                                                // These are handled above, don't pass to camera controller
-                                            }
-                                            _ => {
-                                                camera_controller.process_keyboard(key, input.state);
-                                            }
-                                        }
-                                    }
+            _ => {}
+        }
+    }
+
+    // Everything a session recording keeps goes through here, from the window
+    // or from the recording
+    fn input(&mut self, event_loop: &ActiveEventLoop, input: SessionInput) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(SessionEntry::Input(input));
+        }
+        let Self {
+            window,
+            graphics,
+            camera,
+            camera_controller,
+            game_state,
+            clock,
+            theme_kind,
+            mouse_pressed,
+            modifiers,
+            wheel,
+            ..
+        } = self;
+
+        match input {
+            SessionInput::Key {
+                key: KeyCode::Escape,
+                pressed: true,
+            } => event_loop.exit(),
+
+            SessionInput::Resized { width, height } => {
+                graphics.resize(winit::dpi::PhysicalSize::new(width, height));
+                let scene = graphics.scene_size();
+                camera.update_aspect(scene.width, scene.height);
+            }
+
+            SessionInput::Key { key, pressed } => {
+                let state = if pressed { ElementState::Pressed } else { ElementState::Released };
+                // Handle special game commands only on key press
+                if pressed {
+                    match key {
+                        KeyCode::KeyR => {
+                            // Reset - clear the board
+                            game_state.session.clear_board();
+                        }
+                        KeyCode::KeyT => {
+                            // Test pattern for debugging side views
+                            println!("\n==== ACTIVATING TEST PATTERN MODE ====");
+                            game_state.session.update(|rules| rules.place_test_pattern());
+                            println!("Press P to pause/resume animation");
+                            println!("================================\n");
+                        }
+                        // Sound
+                        KeyCode::KeyM => {
+                            let muted = game_state.audio.toggle_mute();
+                            println!("Sound {}", if muted { "muted" } else { "on" });
+                        }
+                        // UI scale: Ctrl+- and Ctrl+= step it, Ctrl+0 resets it
+                        KeyCode::Minus | KeyCode::Equal | KeyCode::Digit0 if modifiers.control_key() => {
+                            let scale = match key {
+                                KeyCode::Minus => graphics.ui_scale().stepped(-1),
+                                KeyCode::Equal => graphics.ui_scale().stepped(1),
+                                _ => UiScale::default(),
+                            };
+                            graphics.set_ui_scale(scale);
+                            println!("UI scale: {:.0}%", scale.factor() * 100.0);
+                        }
+                        KeyCode::Minus | KeyCode::Equal => {
+                            let step = AudioSettings::VOLUME_STEP;
+                            let delta = if key == KeyCode::Minus { -step } else { step };
+                            let volume = game_state.audio.adjust_volume(delta);
+                            println!("Volume {:.0}%", volume * 100.0);
+                        }
+                        KeyCode::F11 => chrome::toggle_fullscreen(window),
+                        // Picking debug view
+                        KeyCode::F12 => {
+                            game_state.pick_debug = !game_state.pick_debug;
+                            let (ray, cell) = game_state.pick_debug_instances();
+                            graphics.set_pick_debug(ray, cell);
+                            println!("Pick ray debugging: {}", if game_state.pick_debug { "on, click to cast" } else { "off" });
+                        }
+                        // Theme
+                        KeyCode::KeyC => {
+                            *theme_kind = theme_kind.next();
+                            graphics.set_theme(theme_kind.theme());
+                            println!("Theme: {}", theme_kind);
+                        }
+                        // Side panels
+                        KeyCode::Tab => {
+                            let visible = !graphics.panels_visible();
+                            graphics.set_panels_visible(visible);
+                            println!("Side panels {}", if visible { "shown" } else { "hidden" });
+                        }
+                        // Score graph
+                        // Guide planes or crosshair
+                        KeyCode::KeyG if modifiers.shift_key() => {
+                            let style = graphics.guide_style().next();
+                            graphics.set_guide_style(style);
+                            println!("Guide: {}", style);
+                        }
+                        KeyCode::KeyG => {
+                            game_state.show_score_graph = !game_state.show_score_graph;
+                            graphics.set_score_graph(game_state.score_graph());
+                        }
+                        // Analysis heatmap
+                        KeyCode::KeyH => {
+                            game_state.analysis = !game_state.analysis;
+                            graphics.set_heatmap(&game_state.heatmap());
+                            graphics.set_influence(&game_state.influence());
+                            graphics.set_legal_moves(&game_state.legal_moves());
+                            println!("Analysis heatmap: {}", if game_state.analysis { "on" } else { "off" });
+                            game_state.show_patterns();
+                        }
+                        // Influence cloud, part of analysis mode
+                        KeyCode::KeyI => {
+                            game_state.show_influence = !game_state.show_influence;
+                            graphics.set_influence(&game_state.influence());
+                            println!("Influence cloud: {}", if game_state.show_influence { "on" } else { "off" });
+                            if game_state.show_influence && !game_state.analysis {
+                                println!("Press H for analysis mode to see it");
+                            }
+                        }
+                        // Liberty display mode
+                        // Camera paths
+                        KeyCode::KeyJ => game_state.toggle_camera_recording(),
+                        // Split view: the guide's layer as a flat board on the right
+                        KeyCode::KeyY => {
+                            graphics.set_split_view(!graphics.split_view());
+                            let scene = graphics.scene_size();
+                            camera.update_aspect(scene.width, scene.height);
+                            println!("Split view: {}", if graphics.split_view() { "on" } else { "off" });
+                        }
+                        KeyCode::KeyO => game_state.play_camera_path(),
+                        KeyCode::KeyU => {
+                            game_state.show_liberties = !game_state.show_liberties;
+                            graphics.set_liberty_shells(&game_state.liberty_shells());
+                            println!("Liberty shells: {}", if game_state.show_liberties { "on" } else { "off" });
+                        }
+                        // Position info panel
+                        KeyCode::Backquote => {
+                            game_state.show_stats_panel = !game_state.show_stats_panel;
+                            graphics.set_stats_rows(game_state.stats_rows());
+                            println!("Position info: {}", if game_state.show_stats_panel { "on" } else { "off" });
+                        }
+                        // Blind training
+                        KeyCode::KeyB | KeyCode::KeyX => {
+                            if key == KeyCode::KeyB {
+                                game_state.blind = game_state.blind.next();
+                                game_state.revealed = false;
+                                println!("Blind mode: {}", game_state.blind);
+                            } else if game_state.blind != BlindMode::Off {
+                                game_state.revealed = !game_state.revealed;
+                                println!("Position {}", if game_state.revealed { "revealed" } else { "hidden" });
+                            }
+                            graphics.set_blind(game_state.blind_view());
+                            game_state.update_stones();
+                            graphics.set_liberty_shells(&game_state.liberty_shells());
+                            graphics.set_stats_rows(game_state.stats_rows());
+                        }
+                        // Annotation tools
+                        KeyCode::KeyN => {
+                            game_state.annotation_tool = AnnotationTool::cycle(game_state.annotation_tool);
+                            graphics.set_annotation_tool(game_state.annotation_tool);
+                            match game_state.annotation_tool {
+                                Some(tool) => println!("Annotating: {} (Enter marks the guide point)", tool.name()),
+                                None => println!("Annotating off"),
+                            }
+                        }
+                        KeyCode::Enter => {
+                            let position = graphics.guide_system_mut().get_intersection_position();
+                            game_state.annotate(position);
+                        }
+                        // Legal-move hints
+                        KeyCode::KeyK => {
+                            game_state.show_legal_moves = !game_state.show_legal_moves;
+                            graphics.set_legal_moves(&game_state.legal_moves());
+                            println!("Legal move hints: {}", if game_state.show_legal_moves { "on" } else { "off" });
+                        }
+                        KeyCode::Backspace => game_state.request_undo(),
+                        // Reviewing earlier positions
+                        KeyCode::PageUp => {
+                            game_state.session.step_back();
+                        }
+                        KeyCode::PageDown => {
+                            game_state.session.step_forward();
+                        }
+                        KeyCode::KeyZ => game_state.browse_archive(),
+                        KeyCode::KeyL => game_state.next_engine(),
+                        // Following the AI's moves with the guide
+                        KeyCode::KeyF => {
+                            game_state.follow_moves = !game_state.follow_moves;
+                            println!("Guide follows AI moves: {}", if game_state.follow_moves { "on" } else { "off" });
+                        }
+                        KeyCode::End => {
+                            if game_state.session.return_to_game() {
+                                println!("Back to the game");
+                            }
+                        }
+                        KeyCode::Insert => {
+                            if game_state.session.play_from_here() {
+                                println!("Playing on from move {}", game_state.session.read().moves().len());
+                            }
+                        }
+                        // Stereo output
+                        KeyCode::KeyV => {
+                            let mut stereo = graphics.stereo();
+                            stereo.mode = stereo.mode.next();
+                            graphics.set_stereo(stereo);
+                            println!("Stereo: {}", stereo.mode);
+                        }
+                        KeyCode::BracketLeft | KeyCode::BracketRight => {
+                            let step = StereoSettings::SEPARATION_STEP;
+                            let delta = if key == KeyCode::BracketLeft { -step } else { step };
+                            let mut stereo = graphics.stereo();
+                            stereo.adjust_separation(delta);
+                            graphics.set_stereo(stereo);
+                            println!("Eye separation {:.2}", stereo.eye_separation);
+                        }
+                        // Lattice spacing
+                        KeyCode::Comma | KeyCode::Period => {
+                            let step = LayoutSettings::PITCH_STEP;
+                            let delta = if key == KeyCode::Comma { -step } else { step };
+                            game_state.layout.adjust_pitch(delta);
+                            graphics.set_layout(game_state.layout);
+                            game_state.update_stones();
+                            graphics.set_heatmap(&game_state.heatmap());
+                            graphics.set_influence(&game_state.influence());
+                            graphics.set_liberty_shells(&game_state.liberty_shells());
+                            let (ray, cell) = game_state.pick_debug_instances();
+                            graphics.set_pick_debug(ray, cell);
+                            graphics.set_legal_moves(&game_state.legal_moves());
+                            graphics.set_annotations(game_state.annotation_marks());
+                            println!("Stone spacing {:.1}", game_state.layout.pitch);
+                        }
+                        // Save slots
+                        KeyCode::F1 => game_state.select_save_slot(0),
+                        KeyCode::F2 => game_state.select_save_slot(1),
+                        KeyCode::F3 => game_state.select_save_slot(2),
+                        KeyCode::F4 => game_state.select_save_slot(3),
+                        KeyCode::F5 => game_state.save_to_slot(),
+                        KeyCode::F9 => game_state.load_from_slot(),
+                        // Files
+                        KeyCode::F6 => game_state.save_game_as(),
+                        KeyCode::F7 => game_state.open_game(),
+                        KeyCode::F8 if modifiers.shift_key() => game_state.export_booklet(),
+                        KeyCode::F8 => game_state.export_gltf(),
+                        KeyCode::F10 => game_state.show_stats(),
+                        KeyCode::KeyP => {
+                            // Toggle animation pause
+                            let paused = clock.toggle_pause();
+                            println!("Animation: {}", if paused { "PAUSED" } else { "RUNNING" });
+                        }
+                        // Guide plane controls
+                        KeyCode::KeyA => {
+                            graphics.guide_system_mut().move_x(-1);
+                        }
+                        KeyCode::KeyD => {
+                            graphics.guide_system_mut().move_x(1);
+                        }
+                        KeyCode::KeyW => {
+                            graphics.guide_system_mut().move_y(1);
+                        }
+                        KeyCode::KeyS => {
+                            graphics.guide_system_mut().move_y(-1);
+                        }
+                        KeyCode::Space => {
+                            // Place stone at guide intersection
+                            let position = graphics.guide_system_mut().get_intersection_position();
+                            game_state.play(position);
+                        }
+                        // Zoom controls
+                        KeyCode::KeyQ | KeyCode::ArrowLeft => {
+                            camera_controller.zoom_in();
+                        }
+                        KeyCode::KeyE | KeyCode::ArrowRight => {
+                            camera_controller.zoom_out();
+                        }
+                        KeyCode::ArrowUp | KeyCode::ArrowDown => {
+                            // Arrow keys up/down don't do anything now (used to be W/S for camera)
+                            // Ignore these since W/S now control guide planes
+                        }
+                        // Guide bookmarks: Shift+1-9 stores the guide point, 1-9 jumps back to it
+                        key if input::bookmark_slot(key).is_some() => {
+                            let slot = input::bookmark_slot(key).expect("matched a number key");
+                            let guide = graphics.guide_system_mut();
+                            if modifiers.shift_key() {
+                                let point = guide.store_bookmark(slot);
+                                println!("Bookmark {}: {}", slot + 1, accessibility::format_point(point));
+                            } else if guide.recall_bookmark(slot).is_none() {
+                                println!("No bookmark {} yet (Shift+{} stores the guide point)", slot + 1, slot + 1);
+                            }
+                        }
+                        _ => {
+                            // Pass remaining keys to camera controller (but not Q/E/arrows)
+                            match key {
+                                KeyCode::KeyQ | KeyCode::KeyE | 
+                                KeyCode::ArrowLeft | KeyCode::ArrowRight |
+                                KeyCode::ArrowUp | KeyCode::ArrowDown => {
+                                    // These are handled above, don't pass to camera controller
+                                }
+                                _ => {
+                                    camera_controller.process_keyboard(key, state);
                                 }
-                            } else {
-                                // Always pass key releases to camera controller
-                                camera_controller.process_keyboard(key, input.state);
                             }
                         }
                     }
+                } else {
+                    // Always pass key releases to camera controller
+                    camera_controller.process_keyboard(key, state);
+                }
+            }
 
-                    WindowEvent::CursorMoved { position, .. } => {
-                        game_state.mouse_position = glam::Vec2::new(position.x as f32, position.y as f32);
-                    }
+            SessionInput::Modifiers(state) => *modifiers = state,
 
-                    WindowEvent::MouseInput {
-                        state: ElementState::Pressed,
-                        button: MouseButton::Left,
-                        ..
-                    } => {
-                        mouse_pressed = true;
+            SessionInput::Pointer { x, y } => {
+                game_state.mouse_position = glam::Vec2::new(x, y);
+                let scene = graphics.scene_size();
+                let screen_size = glam::Vec2::new(scene.width as f32, scene.height as f32);
+                // Nothing on the board is under the pointer while it's over the UI;
+                // on the flat board the guide follows the pointer instead
+                let hover = match graphics.ui_hit(x, y) {
+                    Some(UiHit::LayerBoard(Some(point))) => {
+                        graphics.guide_system_mut().set_intersection(point);
+                        game_state.session.read().board().get_stone(point).map(|_| point)
                     }
+                    Some(_) => None,
+                    None => game_state.pick_stone(camera, screen_size),
+                };
+                game_state.board_view.set_hover(hover);
+            }
 
-                    WindowEvent::MouseInput {
-                        state: ElementState::Released,
-                        button: MouseButton::Left,
-                        ..
-                    } => {
-                        if mouse_pressed {
-                            // Check if we clicked on a stone to set new orbit center
-                            let screen_size = glam::Vec2::new(
-                                graphics.size.width as f32,
-                                graphics.size.height as f32,
-                            );
-                            
-                            let (ray_origin, ray_direction) = MousePicker::screen_to_world_ray(
-                                game_state.mouse_position,
-                                screen_size,
-                                &camera,
-                            );
-
-                            if let Some(((x, y, z), _distance)) = MousePicker::find_clicked_stone(
-                                ray_origin,
-                                ray_direction,
-                                &game_state.rules,
-                            ) {
-                                // Convert board coordinates to world position for orbit center
-                                let board_size = game_state.rules.board().size();
-                                let half_size = board_size as f32 * 0.5;
-                                let new_center = glam::Vec3::new(
-                                    x as f32 - half_size + 0.5,
-                                    z as f32 - half_size + 0.5, // y/z swap for rendering
-                                    y as f32 - half_size + 0.5,
-                                );
-                                
-                                camera_controller.set_orbit_center(new_center);
-                                println!("New orbit center: stone at ({}, {}, {}) -> world pos: {:?}", x, y, z, new_center);
+            SessionInput::Button { pressed: true } => *mouse_pressed = true,
+
+            SessionInput::Button { pressed: false } if *mouse_pressed => {
+                *mouse_pressed = false;
+                let mouse = game_state.mouse_position;
+                match graphics.ui_hit(mouse.x, mouse.y) {
+                    // While annotating, the toolbar picks the tool
+                    Some(UiHit::ToolbarButton(tool)) => {
+                        game_state.annotation_tool = Some(tool);
+                        graphics.set_annotation_tool(Some(tool));
+                        println!("Annotating: {}", tool.name());
+                    }
+                    // A click pins a side panel's layer, a second one resumes cycling
+                    Some(UiHit::SidePanel(index)) => {
+                        if let Some(side_view) = graphics.side_view_mut(index) {
+                            let state = if side_view.toggle_pin() { "pinned" } else { "cycling" };
+                            println!("{} view: {}", side_view.direction.label(), state);
+                        }
+                    }
+                    // A point on the flat board plays there, or marks it while annotating
+                    Some(UiHit::LayerBoard(point)) => {
+                        if let Some(position) = point {
+                            if game_state.annotation_tool.is_some() {
+                                game_state.annotate(position);
+                            } else {
+                                game_state.play(position);
                             }
-                            
-                            mouse_pressed = false;
                         }
                     }
+                    None => {
+                        // Check if we clicked on a stone to select it and orbit around it
+                        let scene = graphics.scene_size();
+                        let screen_size = glam::Vec2::new(scene.width as f32, scene.height as f32);
 
-                    WindowEvent::MouseWheel { delta, .. } => {
-                        let scroll_amount = match delta {
-                            MouseScrollDelta::LineDelta(_, y) => *y,
-                            MouseScrollDelta::PixelDelta(pos) => pos.y as f32 * 0.01,
-                        };
-                        
-                        // Mouse wheel = Z-axis guide plane movement only
-                        if scroll_amount > 0.0 {
-                            graphics.guide_system_mut().move_z(1);
-                        } else if scroll_amount < 0.0 {
-                            graphics.guide_system_mut().move_z(-1);
+                        game_state.record_pick(camera, screen_size);
+                        let (ray, cell) = game_state.pick_debug_instances();
+                        graphics.set_pick_debug(ray, cell);
+
+                        if let Some((x, y, z)) = game_state.pick_stone(camera, screen_size) {
+                            // With a tool selected, clicking a stone marks it instead
+                            if game_state.annotation_tool.is_some() {
+                                game_state.annotate((x, y, z));
+                            } else {
+                                let new_center = game_state.to_world().to_world((x, y, z));
+                                game_state.board_view.select(Some((x, y, z)));
+
+                                camera_controller.set_orbit_center(new_center, clock);
+                                println!("New orbit center: stone at ({}, {}, {}) -> world pos: {:?}", x, y, z, new_center);
+                            }
                         }
                     }
-
-                    _ => {}
                 }
             }
 
-            Event::DeviceEvent {
-                event: DeviceEvent::MouseMotion { delta },
-                ..
-            } => {
-                if mouse_pressed {
-                    camera_controller.process_mouse(delta.0, delta.1);
+            SessionInput::Wheel(notches) => {
+                let steps = wheel.steps(notches);
+                // Over a side panel the wheel steps its layer, over the flat
+                // board the layer it shows; elsewhere it moves the Z guide plane
+                let mouse = game_state.mouse_position;
+                match graphics.ui_hit(mouse.x, mouse.y) {
+                    _ if steps == 0 => {}
+                    Some(UiHit::SidePanel(index)) => {
+                        if let Some(side_view) = graphics.side_view_mut(index) {
+                            side_view.step_layer(steps as isize);
+                        }
+                    }
+                    Some(UiHit::LayerBoard(_)) => {
+                        let layer_axis = game_state.to_world().board_axis(1);
+                        graphics.guide_system_mut().move_along(layer_axis, steps);
+                    }
+                    _ => graphics.guide_system_mut().move_z(steps),
                 }
             }
 
-            Event::RedrawRequested(window_id) if window_id == window.id() => {
-                let now = Instant::now();
-                let dt = now.duration_since(last_frame_time).as_secs_f32();
-                last_frame_time = now;
+            SessionInput::Button { pressed: false } => {}
 
-                camera_controller.update_camera(&mut camera, dt);
-                graphics.update_camera(&camera);
+            // Only recorded while the button is held
+            SessionInput::Drag { dx, dy } => camera_controller.process_mouse(dx, dy),
+        }
+    }
 
-                match graphics.render(&[], &game_state.black_stone_instances, &game_state.white_stone_instances, &game_state.rules, &camera, None) {
-                    Ok(_) => {}
-                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                        graphics.resize(graphics.size);
-                    }
-                    Err(wgpu::SurfaceError::OutOfMemory) => {
-                        *control_flow = ControlFlow::Exit;
-                    }
-                    Err(wgpu::SurfaceError::Timeout) => {
-                        log::warn!("Surface timeout");
-                    }
-                }
+    // --play-session: feeds in the inputs that came before the recording's next
+    // frame and gives that frame's length, until the recording runs out
+    fn play_back_frame(&mut self, event_loop: &ActiveEventLoop) -> Option<f32> {
+        let (inputs, real_dt) = match self.playback.as_mut()?.next_frame() {
+            Some(frame) => frame,
+            None => {
+                self.playback = None;
+                println!("End of the recorded session; the window takes input again");
+                return None;
             }
+        };
+        for input in inputs {
+            self.input(event_loop, input);
+        }
+        Some(real_dt)
+    }
 
-            Event::MainEventsCleared => {
-                let now = Instant::now();
-                let elapsed = now.duration_since(last_frame_time);
-                if elapsed >= target_frame_time {
-                    window.request_redraw();
-                }
+    // Remember the window and view for next time; fullscreen keeps the windowed geometry
+    fn save_preferences(&self) {
+        let size = self.window.inner_size();
+        let window_geometry = if self.window.fullscreen().is_none() {
+            Some(WindowGeometry {
+                width: size.width,
+                height: size.height,
+                position: self.window.outer_position().ok().map(|position| (position.x, position.y)),
+            })
+        } else {
+            self.saved_window
+        };
+        let preferences = Preferences {
+            window: window_geometry,
+            panels_visible: self.graphics.panels_visible(),
+            camera: Some(self.camera_controller.preset()),
+            board_size: Some(self.game_state.session.read().board().size()),
+            layout: Some(self.game_state.layout),
+            theme: Some(self.theme_kind),
+            ui_scale: Some(self.graphics.ui_scale()),
+            guide_style: Some(self.graphics.guide_style()),
+            wheel_sensitivity: Some(self.wheel.sensitivity()),
+        };
+        if let Err(error) = preferences.save(&mut *storage::preferences_backend()) {
+            eprintln!("cannot save preferences: {}", error);
+        }
+    }
+}
+
+struct App {
+    launch: Option<Launch>,
+    running: Option<Running>,
+}
+
+impl ApplicationHandler for App {
+    // The first resume opens the window; later ones (after a suspend) keep it
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if let Some(launch) = self.launch.take() {
+            event_loop.set_control_flow(ControlFlow::Poll);
+            self.running = Some(Running::start(event_loop, launch));
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
+        if let Some(running) = self.running.as_mut().filter(|running| running.window.id() == window_id) {
+            running.window_event(event_loop, event);
+        }
+    }
+
+    fn device_event(&mut self, event_loop: &ActiveEventLoop, _device_id: DeviceId, event: DeviceEvent) {
+        let Some(running) = self.running.as_mut() else {
+            return;
+        };
+        if let DeviceEvent::MouseMotion { delta } = event {
+            if running.mouse_pressed && running.playback.is_none() {
+                running.input(event_loop, SessionInput::Drag { dx: delta.0, dy: delta.1 });
             }
+        }
+    }
 
-            _ => {}
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        let Some(running) = self.running.as_ref().filter(|running| !running.clock.is_suspended()) else {
+            return;
+        };
+        let elapsed = Instant::now().duration_since(running.last_frame_time);
+        if elapsed >= std::time::Duration::from_secs_f32(1.0 / Running::TARGET_FPS) {
+            running.window.request_redraw();
         }
-    });
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(running) = self.running.as_ref().filter(|running| running.saves_preferences) {
+            running.save_preferences();
+        }
+    }
 }
 
 fn main() {
-    pollster::block_on(run());
+    crash::install();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    if let Some(path) = &cli.play_session {
+        play_session(path);
+        return;
+    }
+    // One-shot runs don't pick up the last session's board
+    let preferences = if cli.stats || cli.headless || !cli.tournament.is_empty() {
+        Preferences::default()
+    } else {
+        Preferences::load(&*storage::preferences_backend())
+    };
+    cli.apply_preferences(&preferences, &matches);
+    run(cli, preferences, None);
+}
+
+// The command line to keep with a recorded session, less --record-session itself
+fn session_args() -> Vec<String> {
+    let mut args = std::env::args().skip(1);
+    let mut kept = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--record-session" {
+            args.next();
+        } else if !arg.starts_with("--record-session=") {
+            kept.push(arg);
+        }
+    }
+    kept
+}
+
+// --play-session: starts up as the recorded session did, then replays it
+fn play_session(path: &Path) {
+    let recording = std::fs::read_to_string(path)
+        .map_err(|error| format!("cannot read {}: {}", path.display(), error))
+        .and_then(|text| SessionRecording::parse(&text).map_err(|error| format!("{}: {}", path.display(), error)))
+        .unwrap_or_else(|message| {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        });
+    let args = std::iter::once("go3d".to_string()).chain(recording.args.iter().cloned());
+    let matches = Cli::command().try_get_matches_from(args).unwrap_or_else(|error| error.exit());
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    let preferences = Preferences::parse(&recording.preferences);
+    cli.apply_preferences(&preferences, &matches);
+    println!("Playing back the session recorded in {} (Escape quits)", path.display());
+    run(cli, preferences, Some(recording));
 }
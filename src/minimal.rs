@@ -1,15 +1,17 @@
-use wgpu::util::DeviceExt;
+use std::sync::Arc;
 use winit::{
+    application::ApplicationHandler,
     event::*,
-    event_loop::{ControlFlow, EventLoop},
-    window::{WindowBuilder},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::{KeyCode, PhysicalKey},
+    window::{Window, WindowId},
 };
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
 struct MinimalRenderer {
-    surface: wgpu::Surface,
+    surface: wgpu::Surface<'static>,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
@@ -18,14 +20,14 @@ struct MinimalRenderer {
 }
 
 impl MinimalRenderer {
-    async fn new(window: &winit::window::Window) -> Self {
+    async fn new(window: Arc<Window>) -> Self {
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
-            dx12_shader_compiler: Default::default(),
+            ..Default::default()
         });
         
-        let surface = unsafe { instance.create_surface(&window) }.unwrap();
+        let surface = instance.create_surface(window).unwrap();
         
         let adapter = instance.request_adapter(
             &wgpu::RequestAdapterOptions {
@@ -37,13 +39,14 @@ impl MinimalRenderer {
 
         let (device, queue) = adapter.request_device(
             &wgpu::DeviceDescriptor {
-                features: wgpu::Features::empty(),
-                limits: if cfg!(target_arch = "wasm32") {
+                required_features: wgpu::Features::empty(),
+                required_limits: if cfg!(target_arch = "wasm32") {
                     wgpu::Limits::downlevel_webgl2_defaults()
                 } else {
                     wgpu::Limits::default()
                 },
                 label: None,
+                memory_hints: wgpu::MemoryHints::default(),
             },
             None,
         ).await.unwrap();
@@ -62,6 +65,7 @@ impl MinimalRenderer {
             present_mode: surface_caps.present_modes[0],
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
+            desired_maximum_frame_latency: 2,
         };
         surface.configure(&device, &config);
 
@@ -100,11 +104,13 @@ impl MinimalRenderer {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
+                compilation_options: Default::default(),
                 buffers: &[],
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
+                compilation_options: Default::default(),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
                     blend: Some(wgpu::BlendState::REPLACE),
@@ -127,6 +133,7 @@ impl MinimalRenderer {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
 
         Self {
@@ -160,10 +167,12 @@ impl MinimalRenderer {
                             b: 0.3,
                             a: 1.0,
                         }),
-                        store: true,
+                        store: wgpu::StoreOp::Store,
                     },
                 })],
                 depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
             });
 
             render_pass.set_pipeline(&self.render_pipeline);
@@ -196,66 +205,108 @@ pub async fn run_minimal() {
         }
     }
 
-    let event_loop = EventLoop::new();
-    let window = WindowBuilder::new()
-        .with_title("Minimal 3D Go Test")
-        .build(&event_loop)
-        .unwrap();
+    let event_loop = EventLoop::<MinimalRenderer>::with_user_event()
+        .build()
+        .expect("couldn't create the event loop");
+    let app = MinimalApp {
+        #[cfg(target_arch = "wasm32")]
+        proxy: event_loop.create_proxy(),
+        window: None,
+        renderer: None,
+    };
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            use winit::platform::web::EventLoopExtWebSys;
+            event_loop.spawn_app(app);
+        } else {
+            let mut app = app;
+            if let Err(error) = event_loop.run_app(&mut app) {
+                eprintln!("{}", error);
+            }
+        }
+    }
+}
 
+struct MinimalApp {
     #[cfg(target_arch = "wasm32")]
-    {
-        use winit::platform::web::WindowExtWebSys;
-        web_sys::window()
-            .and_then(|win| win.document())
-            .and_then(|doc| {
-                let dst = doc.get_element_by_id("wasm-example")?;
-                let canvas = web_sys::Element::from(window.canvas());
-                dst.append_child(&canvas).ok()?;
-                Some(())
-            })
-            .expect("Couldn't append canvas to document body.");
+    proxy: winit::event_loop::EventLoopProxy<MinimalRenderer>,
+    window: Option<Arc<Window>>,
+    renderer: Option<MinimalRenderer>,
+}
+
+impl ApplicationHandler<MinimalRenderer> for MinimalApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        event_loop.set_control_flow(ControlFlow::Poll);
+        let attributes = Window::default_attributes().with_title("Minimal 3D Go Test");
+        let window = Arc::new(event_loop.create_window(attributes).unwrap());
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            use winit::platform::web::WindowExtWebSys;
+            web_sys::window()
+                .and_then(|win| win.document())
+                .and_then(|doc| {
+                    let dst = doc.get_element_by_id("wasm-example")?;
+                    let canvas = web_sys::Element::from(window.canvas()?);
+                    dst.append_child(&canvas).ok()?;
+                    Some(())
+                })
+                .expect("Couldn't append canvas to document body.");
+        }
+
+        self.window = Some(window.clone());
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                let proxy = self.proxy.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let _ = proxy.send_event(MinimalRenderer::new(window).await);
+                });
+            } else {
+                self.renderer = Some(pollster::block_on(MinimalRenderer::new(window)));
+            }
+        }
     }
 
-    let mut renderer = MinimalRenderer::new(&window).await;
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, renderer: MinimalRenderer) {
+        self.renderer = Some(renderer);
+    }
 
-    event_loop.run(move |event, _, control_flow| {
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _window_id: WindowId, event: WindowEvent) {
+        let Some(renderer) = self.renderer.as_mut() else {
+            return;
+        };
         match event {
-            Event::WindowEvent {
-                ref event,
-                window_id,
-            } if window_id == window.id() => {
-                match event {
-                    WindowEvent::CloseRequested
-                    | WindowEvent::KeyboardInput {
-                        input:
-                            KeyboardInput {
-                                state: ElementState::Pressed,
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
-                                ..
-                            },
+            WindowEvent::CloseRequested
+            | WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(KeyCode::Escape),
                         ..
-                    } => *control_flow = ControlFlow::Exit,
-                    WindowEvent::Resized(physical_size) => {
-                        renderer.resize(*physical_size);
-                    }
-                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                        renderer.resize(**new_inner_size);
-                    }
-                    _ => {}
-                }
+                    },
+                ..
+            } => event_loop.exit(),
+            WindowEvent::Resized(physical_size) => {
+                renderer.resize(physical_size);
             }
-            Event::RedrawRequested(window_id) if window_id == window.id() => {
+            WindowEvent::RedrawRequested => {
                 match renderer.render() {
                     Ok(_) => {}
                     Err(wgpu::SurfaceError::Lost) => renderer.resize(renderer.size),
-                    Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
+                    Err(wgpu::SurfaceError::OutOfMemory) => event_loop.exit(),
                     Err(e) => eprintln!("{:?}", e),
                 }
             }
-            Event::MainEventsCleared => {
-                window.request_redraw();
-            }
             _ => {}
         }
-    });
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+// Whether the machine is short of power for a full engine search. The web
+// build always is: wasm searches on the thread that draws the frames. Natively
+// Linux reports its power supplies under /sys/class/power_supply; elsewhere
+// the answer is unknown and taken as mains power.
+
+// Power supply state, as far as it can be told
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Mains,
+    Battery,
+    Unknown,
+}
+
+pub fn source() -> PowerSource {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            linux_source(std::path::Path::new("/sys/class/power_supply"))
+        } else {
+            PowerSource::Unknown
+        }
+    }
+}
+
+// True when the engine should search less than it was asked to
+pub fn constrained() -> bool {
+    cfg!(target_arch = "wasm32") || source() == PowerSource::Battery
+}
+
+// Any mains adapter online means mains power; otherwise a discharging battery
+// means battery power
+#[cfg(target_os = "linux")]
+fn linux_source(supplies: &std::path::Path) -> PowerSource {
+    let Ok(entries) = std::fs::read_dir(supplies) else {
+        return PowerSource::Unknown;
+    };
+    let read = |supply: &std::path::Path, file: &str| {
+        std::fs::read_to_string(supply.join(file)).map(|text| text.trim().to_string()).unwrap_or_default()
+    };
+    let mut source = PowerSource::Unknown;
+    for entry in entries.flatten() {
+        let supply = entry.path();
+        match read(&supply, "type").as_str() {
+            "Mains" if read(&supply, "online") == "1" => return PowerSource::Mains,
+            "Battery" if read(&supply, "status") == "Discharging" => source = PowerSource::Battery,
+            _ => {}
+        }
+    }
+    source
+}
@@ -0,0 +1,210 @@
+// What the last session left behind: window geometry, whether the side panels
+// were shown, where the camera was, and the board settings, UI scale, guide
+// style and wheel sensitivity in use. Kept as `key=value` lines through the storage backend; anything
+// missing or unreadable falls back to the defaults, so a stale file never
+// stops a launch. A file from a newer build is ignored the same way.
+use crate::input::WheelSteps;
+use crate::render::CameraPreset;
+use crate::settings::{GameSettings, GuideStyle, LayoutSettings, ThemeKind, UiScale};
+use crate::storage::{self, Backend, StorageError};
+use glam::Vec3;
+
+const SLOT: &str = "preferences";
+pub const FORMAT_VERSION: u32 = 2;
+// Smaller than this is a minimized or broken window, not a size to come back to
+const MIN_WINDOW: (u32, u32) = (320, 240);
+
+// Physical pixels; the position is the outer top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub width: u32,
+    pub height: u32,
+    pub position: Option<(i32, i32)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preferences {
+    pub window: Option<WindowGeometry>,
+    pub panels_visible: bool,
+    pub camera: Option<CameraPreset>,
+    pub board_size: Option<usize>,
+    pub layout: Option<LayoutSettings>,
+    pub theme: Option<ThemeKind>,
+    pub ui_scale: Option<UiScale>,
+    pub guide_style: Option<GuideStyle>,
+    pub wheel_sensitivity: Option<f32>,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            window: None,
+            panels_visible: true,
+            camera: None,
+            board_size: None,
+            layout: None,
+            theme: None,
+            ui_scale: None,
+            guide_style: None,
+            wheel_sensitivity: None,
+        }
+    }
+}
+
+fn floats<const N: usize>(value: &str) -> Option<[f32; N]> {
+    let fields: Vec<f32> = value.split(',').map(|field| field.trim().parse().ok()).collect::<Option<_>>()?;
+    let fields: [f32; N] = fields.try_into().ok()?;
+    fields.iter().all(|field| field.is_finite()).then_some(fields)
+}
+
+fn parse_window(value: &str) -> Option<WindowGeometry> {
+    let (size, position) = match value.split_once('@') {
+        Some((size, position)) => (size, Some(position)),
+        None => (value, None),
+    };
+    let (width, height) = size.split_once('x')?;
+    let (width, height): (u32, u32) = (width.parse().ok()?, height.parse().ok()?);
+    if width < MIN_WINDOW.0 || height < MIN_WINDOW.1 {
+        return None;
+    }
+    let position = match position {
+        Some(position) => {
+            let (x, y) = position.split_once(',')?;
+            Some((x.parse().ok()?, y.parse().ok()?))
+        }
+        None => None,
+    };
+    Some(WindowGeometry { width, height, position })
+}
+
+fn parse_layout(value: &str) -> Option<LayoutSettings> {
+    let (numbers, up) = value.rsplit_once(',')?;
+    let [pitch, stone_radius] = floats(numbers)?;
+    Some(LayoutSettings {
+        pitch: pitch.clamp(LayoutSettings::MIN_PITCH, LayoutSettings::MAX_PITCH),
+        stone_radius: stone_radius.clamp(LayoutSettings::MIN_STONE_RADIUS, LayoutSettings::MAX_STONE_RADIUS),
+        up: up.parse().ok()?,
+    })
+}
+
+// Brings older preference lines up to the current format one version at a
+// time
+fn migrate(lines: Vec<String>, version: u32) -> Vec<String> {
+    let mut lines = lines;
+    for from in version..FORMAT_VERSION {
+        lines = match from {
+            // Every key so far came as a new optional line, so unversioned
+            // files read as they are
+            1 => lines,
+            _ => unreachable!("no migration from preferences version {}", from),
+        };
+    }
+    lines
+}
+
+impl Preferences {
+    // Nothing saved yet, or nothing readable, gives the defaults
+    pub fn load(backend: &dyn Backend) -> Self {
+        match backend.load(SLOT) {
+            Ok(Some(data)) => Self::parse(&data),
+            Ok(None) => Self::default(),
+            Err(error) => {
+                log::warn!("{}; starting with default preferences", error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, backend: &mut dyn Backend) -> Result<(), StorageError> {
+        backend.save(SLOT, &self.to_text())
+    }
+
+    pub fn parse(data: &str) -> Self {
+        let mut preferences = Self::default();
+        let (version, lines) = match storage::split_version(data, FORMAT_VERSION) {
+            Ok(split) => split,
+            Err(error) => {
+                log::warn!("{}; starting with default preferences", error);
+                return preferences;
+            }
+        };
+        for line in migrate(lines, version).iter().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let read = line.split_once('=').and_then(|(key, value)| {
+                let value = value.trim();
+                match key.trim() {
+                    "window" => preferences.window = Some(parse_window(value)?),
+                    "panels" => {
+                        preferences.panels_visible = match value {
+                            "on" => true,
+                            "off" => false,
+                            _ => return None,
+                        }
+                    }
+                    "camera" => {
+                        let [distance, angle_x, angle_y, x, y, z] = floats(value)?;
+                        preferences.camera = Some(CameraPreset {
+                            distance,
+                            angle_x,
+                            angle_y,
+                            center: Vec3::new(x, y, z),
+                        });
+                    }
+                    "size" => preferences.board_size = Some(value.parse().ok().filter(|size| (GameSettings::MIN_BOARD_SIZE..=GameSettings::MAX_BOARD_SIZE).contains(size))?),
+                    "layout" => preferences.layout = Some(parse_layout(value)?),
+                    "theme" => preferences.theme = Some(value.parse().ok()?),
+                    "ui_scale" => preferences.ui_scale = Some(UiScale::new(value.parse().ok()?)),
+                    "guide" => preferences.guide_style = Some(value.parse().ok()?),
+                    "wheel" => {
+                        let sensitivity: f32 = value.parse().ok().filter(|sensitivity: &f32| sensitivity.is_finite())?;
+                        preferences.wheel_sensitivity =
+                            Some(sensitivity.clamp(WheelSteps::MIN_SENSITIVITY, WheelSteps::MAX_SENSITIVITY));
+                    }
+                    _ => return None,
+                }
+                Some(())
+            });
+            if read.is_none() {
+                log::warn!("skipping unreadable preference line '{}'", line);
+            }
+        }
+        preferences
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![storage::version_line(FORMAT_VERSION)];
+        if let Some(window) = self.window {
+            let mut line = format!("window={}x{}", window.width, window.height);
+            if let Some((x, y)) = window.position {
+                line += &format!("@{},{}", x, y);
+            }
+            lines.push(line);
+        }
+        lines.push(format!("panels={}", if self.panels_visible { "on" } else { "off" }));
+        if let Some(camera) = self.camera {
+            let center = camera.center;
+            lines.push(format!(
+                "camera={},{},{},{},{},{}",
+                camera.distance, camera.angle_x, camera.angle_y, center.x, center.y, center.z
+            ));
+        }
+        if let Some(size) = self.board_size {
+            lines.push(format!("size={}", size));
+        }
+        if let Some(layout) = self.layout {
+            lines.push(format!("layout={},{},{}", layout.pitch, layout.stone_radius, layout.up));
+        }
+        if let Some(theme) = self.theme {
+            lines.push(format!("theme={}", theme));
+        }
+        if let Some(scale) = self.ui_scale {
+            lines.push(format!("ui_scale={}", scale));
+        }
+        if let Some(style) = self.guide_style {
+            lines.push(format!("guide={}", style));
+        }
+        if let Some(sensitivity) = self.wheel_sensitivity {
+            lines.push(format!("wheel={}", sensitivity));
+        }
+        lines.join("\n")
+    }
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+//! Convenience re-exports: `use go3d::prelude::*;`
+
+pub use crate::ai::{Engine, GreedyEngine, MctsEngine, RandomEngine};
+pub use crate::api::{BoardDiff, Game, GameObserver, GameSettings, Move, Position, ScoringRule};
+pub use crate::game::{MoveError, StoneColor};
\ No newline at end of file
@@ -0,0 +1,318 @@
+// Named local players with Elo-style ratings, kept through the storage
+// backend. AI levels are fixed-strength opponents: playing them moves the
+// human's rating but never theirs.
+use crate::achievements::Achievement;
+use crate::game::StoneColor;
+use crate::storage::{self, Backend, StorageError};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+const SLOT: &str = "profiles";
+// Version 2 gives every line its achievements field, empty or not
+pub const FORMAT_VERSION: u32 = 2;
+const INITIAL_RATING: f32 = 1500.0;
+// How far one game can move a rating
+const K_FACTOR: f32 = 32.0;
+
+// Random, greedy and MCTS, roughly where they land against club players
+pub fn ai_rating(level: u8) -> f32 {
+    800.0 + 400.0 * level as f32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Opponent {
+    Human(String),
+    Ai(u8),
+}
+
+impl fmt::Display for Opponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Opponent::Human(name) => f.write_str(name),
+            Opponent::Ai(level) => write!(f, "AI level {}", level),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win(StoneColor),
+    Draw,
+}
+
+impl Outcome {
+    // From a final (black, white) score
+    pub fn from_score((black, white): (f32, f32)) -> Self {
+        match black.total_cmp(&white) {
+            std::cmp::Ordering::Greater => Outcome::Win(StoneColor::Black),
+            std::cmp::Ordering::Less => Outcome::Win(StoneColor::White),
+            std::cmp::Ordering::Equal => Outcome::Draw,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub rating: f32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    // Summed over all games, for the average game length
+    pub total_moves: u32,
+    pub achievements: BTreeSet<Achievement>,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rating: INITIAL_RATING,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            total_moves: 0,
+            achievements: BTreeSet::new(),
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    pub fn average_moves(&self) -> Option<f32> {
+        let games = self.games();
+        (games > 0).then(|| self.total_moves as f32 / games as f32)
+    }
+
+    // `score` is 1 for a win, 0.5 for a draw and 0 for a loss
+    fn record(&mut self, opponent_rating: f32, score: f32, moves: usize) {
+        let expected = 1.0 / (1.0 + 10f32.powf((opponent_rating - self.rating) / 400.0));
+        self.rating += K_FACTOR * (score - expected);
+        match score {
+            s if s > 0.5 => self.wins += 1,
+            s if s < 0.5 => self.losses += 1,
+            _ => self.draws += 1,
+        }
+        self.total_moves += moves as u32;
+    }
+
+    // Tab-separated: name, rating, wins, losses, draws, total moves and the
+    // comma-separated achievements
+    fn to_line(&self) -> String {
+        let achievements: Vec<String> = self.achievements.iter().map(Achievement::to_string).collect();
+        format!(
+            "{}\t{:.1}\t{}\t{}\t{}\t{}\t{}",
+            self.name,
+            self.rating,
+            self.wins,
+            self.losses,
+            self.draws,
+            self.total_moves,
+            achievements.join(",")
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [name, rating, wins, losses, draws, total_moves, achievements] = fields[..] else {
+            return None;
+        };
+        // Unknown ids are dropped so an older build can still read the line
+        let achievements = achievements
+            .split(',')
+            .filter_map(|id| id.parse().ok())
+            .collect();
+        Some(Self {
+            name: name.to_string(),
+            rating: rating.parse().ok()?,
+            wins: wins.parse().ok()?,
+            losses: losses.parse().ok()?,
+            draws: draws.parse().ok()?,
+            total_moves: total_moves.parse().ok()?,
+            achievements,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileError {
+    BadName(String),
+    SameName(String),
+    Storage(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::BadName(name) => {
+                write!(f, "player name '{}' must be 1-32 characters without tabs or newlines", name)
+            }
+            ProfileError::SameName(name) => write!(f, "'{}' can't play against themselves", name),
+            ProfileError::Storage(reason) => write!(f, "couldn't save profiles: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl From<StorageError> for ProfileError {
+    fn from(error: StorageError) -> Self {
+        ProfileError::Storage(error.to_string())
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<(), ProfileError> {
+    let length = name.chars().count();
+    if !(1..=32).contains(&length) || name.chars().any(char::is_control) || name.trim() != name {
+        return Err(ProfileError::BadName(name.to_string()));
+    }
+    Ok(())
+}
+
+// Brings older profile lines up to the current format one version at a time
+fn migrate(lines: Vec<String>, version: u32) -> Vec<String> {
+    let mut lines = lines;
+    for from in version..FORMAT_VERSION {
+        lines = match from {
+            // Profiles from before achievements end at the total moves
+            1 => lines
+                .into_iter()
+                .map(|line| if line.split('\t').count() == 6 { line + "\t" } else { line })
+                .collect(),
+            _ => unreachable!("no migration from profiles version {}", from),
+        };
+    }
+    lines
+}
+
+pub struct Profiles {
+    backend: Box<dyn Backend>,
+    players: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    // Unreadable lines are skipped rather than losing every other profile;
+    // profiles from a newer build are refused, so they aren't saved over
+    pub fn load(backend: Box<dyn Backend>) -> Result<Self, StorageError> {
+        let data = backend.load(SLOT)?.unwrap_or_default();
+        let (version, lines) = storage::split_version(&data, FORMAT_VERSION).map_err(|error| StorageError::Version {
+            slot: SLOT.to_string(),
+            error,
+        })?;
+        let players = migrate(lines, version)
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                let profile = Profile::from_line(line);
+                if profile.is_none() {
+                    log::warn!("skipping unreadable profile line '{}'", line);
+                }
+                profile
+            })
+            .map(|profile| (profile.name.clone(), profile))
+            .collect();
+        Ok(Self { backend, players })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.players.get(name)
+    }
+
+    // Highest rated first
+    pub fn ranked(&self) -> Vec<&Profile> {
+        let mut players: Vec<&Profile> = self.players.values().collect();
+        players.sort_by(|a, b| b.rating.total_cmp(&a.rating).then_with(|| a.name.cmp(&b.name)));
+        players
+    }
+
+    // Updates both players' records (only `player`'s against an AI) and saves
+    pub fn record_game(
+        &mut self,
+        player: &str,
+        player_color: StoneColor,
+        opponent: &Opponent,
+        outcome: Outcome,
+        moves: usize,
+    ) -> Result<(), ProfileError> {
+        validate_name(player)?;
+        if let Opponent::Human(name) = opponent {
+            validate_name(name)?;
+            if name == player {
+                return Err(ProfileError::SameName(name.clone()));
+            }
+        }
+
+        let score = match outcome {
+            Outcome::Win(color) if color == player_color => 1.0,
+            Outcome::Win(_) => 0.0,
+            Outcome::Draw => 0.5,
+        };
+        let player_rating = self.entry(player).rating;
+        let opponent_rating = match opponent {
+            Opponent::Human(name) => self.entry(name).rating,
+            Opponent::Ai(level) => ai_rating(*level),
+        };
+
+        self.entry(player).record(opponent_rating, score, moves);
+        if let Opponent::Human(name) = opponent {
+            self.entry(name).record(player_rating, 1.0 - score, moves);
+        }
+        self.save()
+    }
+
+    // Returns true when `achievement` is new for `name`
+    pub fn unlock(&mut self, name: &str, achievement: Achievement) -> Result<bool, ProfileError> {
+        validate_name(name)?;
+        if !self.entry(name).achievements.insert(achievement) {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    fn entry(&mut self, name: &str) -> &mut Profile {
+        self.players
+            .entry(name.to_string())
+            .or_insert_with(|| Profile::new(name))
+    }
+
+    fn save(&mut self) -> Result<(), ProfileError> {
+        let mut data = vec![storage::version_line(FORMAT_VERSION)];
+        data.extend(self.players.values().map(Profile::to_line));
+        self.backend.save(SLOT, &data.join("\n"))?;
+        Ok(())
+    }
+
+    // The stats screen: one header and one row per player, best first
+    pub fn stats_table(&self) -> Vec<String> {
+        if self.players.is_empty() {
+            return vec!["No games recorded yet".to_string()];
+        }
+        let mut lines = vec![format!(
+            "{:<20} {:>6} {:>5} {:>5} {:>5} {:>9}",
+            "Player", "Rating", "Won", "Lost", "Drawn", "Avg moves"
+        )];
+        for profile in self.ranked() {
+            let average = profile
+                .average_moves()
+                .map_or("-".to_string(), |average| format!("{:.1}", average));
+            lines.push(format!(
+                "{:<20} {:>6.0} {:>5} {:>5} {:>5} {:>9}",
+                profile.name, profile.rating, profile.wins, profile.losses, profile.draws, average
+            ));
+        }
+        lines
+    }
+    // The trophies screen: every achievement, ticked when `name` has it
+    pub fn trophies(&self, name: &str) -> Vec<String> {
+        let unlocked = self.get(name).map(|profile| &profile.achievements);
+        Achievement::ALL
+            .into_iter()
+            .map(|achievement| {
+                let mark = if unlocked.is_some_and(|set| set.contains(&achievement)) { 'x' } else { ' ' };
+                format!("[{}] {:<12} {}", mark, achievement.title(), achievement.description())
+            })
+            .collect()
+    }
+}
\ No newline at end of file
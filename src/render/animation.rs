@@ -0,0 +1,246 @@
+// Captured stones leaving the board. Normally they shrink away where they
+// stood; in the sandbox toy mode they fall out of the lattice under gravity
+// and bounce off the bounding box instead. Both run on the same stone list.
+//
+// Also the ghost trails left while scrubbing through a game: every stone a
+// step adds or takes away leaves a translucent ghost that fades over a moment,
+// so stepping quickly shows the flow of the game.
+//
+// The falling stones' kicks are random; seeding them makes a replayed session
+// scatter captures the same way.
+use super::variation::StoneLook;
+use super::{BoardToWorld, Instance};
+use crate::game::{BoardDiff, StoneColor};
+use crate::settings::LayoutSettings;
+use glam::Vec3;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+const SHRINK_SECONDS: f32 = 0.35;
+
+const GRAVITY: f32 = 9.8;
+const RESTITUTION: f32 = 0.45;
+// Horizontal speed kept per second while rolling on the floor
+const FLOOR_FRICTION: f32 = 0.3;
+const KICK_SPEED: f32 = 1.5;
+const PHYSICS_SECONDS: f32 = 6.0;
+// Physics stones shrink away over the end of their lifetime
+const FADE_SECONDS: f32 = 1.0;
+
+const GHOST_SECONDS: f32 = 0.8;
+// A new stone's ghost starts as a halo this much larger and closes in on it
+const ARRIVAL_SCALE: f32 = 1.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptureStyle {
+    #[default]
+    Shrink,
+    Physics,
+}
+
+#[derive(Debug, Clone)]
+struct FallingStone {
+    color: StoneColor,
+    look: StoneLook,
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptureAnimations {
+    style: CaptureStyle,
+    // Half the bounding box edge, in world units
+    half_extent: f32,
+    layout: LayoutSettings,
+    stones: Vec<FallingStone>,
+    kicks: SmallRng,
+}
+
+impl Default for CaptureAnimations {
+    fn default() -> Self {
+        Self {
+            style: CaptureStyle::default(),
+            half_extent: 0.0,
+            layout: LayoutSettings::default(),
+            stones: Vec::new(),
+            kicks: SmallRng::from_entropy(),
+        }
+    }
+}
+
+impl CaptureAnimations {
+    pub fn new(style: CaptureStyle) -> Self {
+        Self {
+            style,
+            ..Self::default()
+        }
+    }
+
+    pub fn style(&self) -> CaptureStyle {
+        self.style
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stones.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.stones.clear();
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.kicks = SmallRng::seed_from_u64(seed);
+    }
+
+    pub fn spawn(&mut self, color: StoneColor, position: (u8, u8, u8), to_world: &BoardToWorld) {
+        self.layout = *to_world.layout();
+        self.half_extent = to_world.extent() * 0.5;
+        let look = StoneLook::at(position);
+        let position = to_world.to_world(position);
+        // A small random shove so a captured group breaks apart as it falls
+        let rng = &mut self.kicks;
+        let velocity = match self.style {
+            CaptureStyle::Shrink => Vec3::ZERO,
+            CaptureStyle::Physics => Vec3::new(
+                rng.gen_range(-KICK_SPEED..KICK_SPEED),
+                rng.gen_range(0.0..KICK_SPEED),
+                rng.gen_range(-KICK_SPEED..KICK_SPEED),
+            ),
+        };
+        self.stones.push(FallingStone {
+            color,
+            look,
+            position,
+            velocity,
+            age: 0.0,
+        });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        let lifetime = self.lifetime();
+        let limit = (self.half_extent - self.layout.stone_radius).max(0.0);
+        for stone in &mut self.stones {
+            stone.age += dt;
+            if self.style == CaptureStyle::Physics {
+                Self::step(stone, dt, limit);
+            }
+        }
+        self.stones.retain(|stone| stone.age < lifetime);
+    }
+
+    fn lifetime(&self) -> f32 {
+        match self.style {
+            CaptureStyle::Shrink => SHRINK_SECONDS,
+            CaptureStyle::Physics => PHYSICS_SECONDS,
+        }
+    }
+
+    // Semi-implicit Euler, then push the stone back inside the box (`limit`
+    // is the furthest its centre may be from the origin on any axis) and
+    // reflect the velocity component that carried it out
+    fn step(stone: &mut FallingStone, dt: f32, limit: f32) {
+        stone.velocity.y -= GRAVITY * dt;
+        stone.position += stone.velocity * dt;
+
+        for axis in 0..3 {
+            if stone.position[axis].abs() > limit {
+                stone.position[axis] = stone.position[axis].clamp(-limit, limit);
+                stone.velocity[axis] *= -RESTITUTION;
+            }
+        }
+
+        let on_floor = stone.position.y <= -limit + f32::EPSILON;
+        if on_floor {
+            let keep = FLOOR_FRICTION.powf(dt);
+            stone.velocity.x *= keep;
+            stone.velocity.z *= keep;
+            // Stop the endless micro-bounces once the stone has settled
+            if stone.velocity.y.abs() < GRAVITY * dt * 2.0 {
+                stone.velocity.y = 0.0;
+            }
+        }
+    }
+
+    fn scale(&self, stone: &FallingStone) -> f32 {
+        let remaining = match self.style {
+            CaptureStyle::Shrink => 1.0 - stone.age / SHRINK_SECONDS,
+            CaptureStyle::Physics => ((PHYSICS_SECONDS - stone.age) / FADE_SECONDS).min(1.0),
+        };
+        self.layout.stone_scale() * remaining.clamp(0.0, 1.0)
+    }
+
+    pub fn instances(&self, color: StoneColor) -> impl Iterator<Item = Instance> + '_ {
+        self.stones
+            .iter()
+            .filter(move |stone| stone.color == color)
+            .map(|stone| {
+                let mut instance = Instance::new(stone.position);
+                instance.scale = Vec3::splat(self.scale(stone));
+                stone.look.apply(&mut instance);
+                instance
+            })
+    }
+}
+#[derive(Debug, Clone)]
+struct Ghost {
+    color: StoneColor,
+    position: Vec3,
+    // Placed by the step, rather than taken away
+    arriving: bool,
+    age: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GhostTrails {
+    stone_scale: f32,
+    ghosts: Vec<Ghost>,
+}
+
+impl GhostTrails {
+    pub fn is_empty(&self) -> bool {
+        self.ghosts.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.ghosts.clear();
+    }
+
+    // A ghost for every stone `diff` placed or took away
+    pub fn spawn(&mut self, diff: &BoardDiff, to_world: &BoardToWorld) {
+        self.stone_scale = to_world.stone_scale();
+        let placed = diff.placed.iter().map(|stone| (stone, true));
+        let removed = diff.removed.iter().map(|stone| (stone, false));
+        for (&(position, color), arriving) in placed.chain(removed) {
+            self.ghosts.push(Ghost {
+                color,
+                position: to_world.to_world(position),
+                arriving,
+                age: 0.0,
+            });
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for ghost in &mut self.ghosts {
+            ghost.age += dt;
+        }
+        self.ghosts.retain(|ghost| ghost.age < GHOST_SECONDS);
+    }
+
+    // Ghosts of stones taken away shrink to nothing; those of stones placed
+    // close in from a halo onto the stone
+    fn scale(&self, ghost: &Ghost) -> f32 {
+        let remaining = (1.0 - ghost.age / GHOST_SECONDS).clamp(0.0, 1.0);
+        let scale = if ghost.arriving { 1.0 + (ARRIVAL_SCALE - 1.0) * remaining } else { remaining };
+        self.stone_scale * scale
+    }
+
+    pub fn instances(&self, color: StoneColor) -> impl Iterator<Item = Instance> + '_ {
+        self.ghosts.iter().filter(move |ghost| ghost.color == color).map(|ghost| {
+            let mut instance = Instance::new(ghost.position);
+            instance.scale = Vec3::splat(self.scale(ghost));
+            instance
+        })
+    }
+}
@@ -0,0 +1,156 @@
+// Review markup drawn over the scene: every annotation of the current node as
+// a screen-facing billboard at its intersection, and the toolbar of marking
+// tools shown while annotating. Sizes are at a UI scale of 1; each function
+// takes the scale in use.
+use super::{ScreenMesh, UIVertex};
+use crate::game::{Mark, MarkerColor};
+use glam::{Mat4, Vec3};
+
+const BILLBOARD_RADIUS: f32 = 11.0;
+const OUTLINE: f32 = 2.0;
+const BACKDROP: [f32; 4] = [0.1, 0.1, 0.1, 0.85];
+const SHAPE: [f32; 4] = [0.95, 0.95, 0.95, 1.0];
+
+const BUTTON: f32 = 30.0;
+const BUTTON_SPACING: f32 = 4.0;
+const MARGIN: f32 = 20.0;
+const BUTTON_BACKGROUND: [f32; 4] = [0.15, 0.15, 0.15, 0.85];
+const BUTTON_SELECTED: [f32; 4] = [0.35, 0.45, 0.7, 0.95];
+
+// Glyph size of the text renderer used for labels
+pub const TEXT_SIZE: f32 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationTool {
+    Label,
+    Triangle,
+    Square,
+    Marker(MarkerColor),
+}
+
+impl AnnotationTool {
+    pub const ALL: [AnnotationTool; 7] = [
+        AnnotationTool::Label,
+        AnnotationTool::Triangle,
+        AnnotationTool::Square,
+        AnnotationTool::Marker(MarkerColor::Red),
+        AnnotationTool::Marker(MarkerColor::Green),
+        AnnotationTool::Marker(MarkerColor::Blue),
+        AnnotationTool::Marker(MarkerColor::Yellow),
+    ];
+
+    // The mark this tool places; labels get their letter when placed
+    pub fn mark(self) -> Mark {
+        match self {
+            AnnotationTool::Label => Mark::Label('A'),
+            AnnotationTool::Triangle => Mark::Triangle,
+            AnnotationTool::Square => Mark::Square,
+            AnnotationTool::Marker(color) => Mark::Marker(color),
+        }
+    }
+
+    // Steps through the tools and back to none (not annotating)
+    pub fn cycle(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(Self::ALL[0]),
+            Some(tool) => {
+                let index = Self::ALL.iter().position(|&t| t == tool).unwrap_or(0);
+                Self::ALL.get(index + 1).copied()
+            }
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            AnnotationTool::Label => "label".to_string(),
+            AnnotationTool::Triangle => "triangle".to_string(),
+            AnnotationTool::Square => "square".to_string(),
+            AnnotationTool::Marker(color) => format!("{} marker", color),
+        }
+    }
+}
+
+// Letters to draw with the text renderer: (letter, left, top) in pixels
+pub type Labels = Vec<(char, f32, f32)>;
+
+fn draw_mark(mesh: &mut ScreenMesh, labels: &mut Labels, center: (f32, f32), radius: f32, mark: Mark, scale: f32) {
+    let (x, y) = center;
+    let outline = OUTLINE * scale;
+    match mark {
+        Mark::Label(letter) => {
+            mesh.regular(center, radius, 16, BACKDROP);
+            let half_text = TEXT_SIZE * scale * 0.5;
+            labels.push((letter, x - half_text, y - half_text));
+        }
+        Mark::Triangle => {
+            mesh.regular((x, y + radius * 0.15), radius + outline * 2.0, 3, BACKDROP);
+            mesh.regular((x, y + radius * 0.15), radius, 3, SHAPE);
+        }
+        Mark::Square => {
+            let half = radius * 0.8;
+            mesh.rect(x - half - outline, y - half - outline, (half + outline) * 2.0, (half + outline) * 2.0, BACKDROP);
+            mesh.rect(x - half, y - half, half * 2.0, half * 2.0, SHAPE);
+        }
+        Mark::Marker(color) => {
+            mesh.regular(center, radius + outline, 16, BACKDROP);
+            mesh.regular(center, radius, 16, color.rgba());
+        }
+    }
+}
+
+// Pixel position of a world point, None behind the camera
+fn project(view_proj: &Mat4, world: Vec3, screen: (f32, f32)) -> Option<(f32, f32)> {
+    let clip = *view_proj * world.extend(1.0);
+    if clip.w <= f32::EPSILON {
+        return None;
+    }
+    let ndc = clip.truncate() / clip.w;
+    if !(-1.0..=1.0).contains(&ndc.z) {
+        return None;
+    }
+    Some(((ndc.x + 1.0) * 0.5 * screen.0, (1.0 - ndc.y) * 0.5 * screen.1))
+}
+
+// Billboards for `marks` (world position, mark), drawn on top of the scene,
+// which takes the leftmost `scene_width` pixels of the screen
+pub fn billboards(marks: &[(Vec3, Mark)], view_proj: &Mat4, scene_width: f32, screen_width: f32, screen_height: f32, scale: f32) -> (Vec<UIVertex>, Vec<u16>, Labels) {
+    let mut mesh = ScreenMesh::new(screen_width, screen_height);
+    let mut labels = Labels::new();
+    for &(world, mark) in marks {
+        if let Some(center) = project(view_proj, world, (scene_width, screen_height)) {
+            draw_mark(&mut mesh, &mut labels, center, BILLBOARD_RADIUS * scale, mark, scale);
+        }
+    }
+    let (vertices, indices) = mesh.into_parts();
+    (vertices, indices, labels)
+}
+
+fn button_origin(index: usize, scale: f32) -> (f32, f32) {
+    ((MARGIN + index as f32 * (BUTTON + BUTTON_SPACING)) * scale, MARGIN * scale)
+}
+
+// One button per tool along the top-left edge, the selected one highlighted
+pub fn toolbar(selected: AnnotationTool, screen_width: f32, screen_height: f32, scale: f32) -> (Vec<UIVertex>, Vec<u16>, Labels) {
+    let mut mesh = ScreenMesh::new(screen_width, screen_height);
+    let mut labels = Labels::new();
+    let button = BUTTON * scale;
+    for (index, tool) in AnnotationTool::ALL.into_iter().enumerate() {
+        let (left, top) = button_origin(index, scale);
+        let background = if tool == selected { BUTTON_SELECTED } else { BUTTON_BACKGROUND };
+        mesh.rect(left, top, button, button, background);
+        let center = (left + button * 0.5, top + button * 0.5);
+        draw_mark(&mut mesh, &mut labels, center, button * 0.3, tool.mark(), scale);
+    }
+    let (vertices, indices) = mesh.into_parts();
+    (vertices, indices, labels)
+}
+
+// The tool whose button is under the pointer
+pub fn toolbar_hit(x: f32, y: f32, scale: f32) -> Option<AnnotationTool> {
+    let button = BUTTON * scale;
+    AnnotationTool::ALL.into_iter().enumerate().find_map(|(index, tool)| {
+        let (left, top) = button_origin(index, scale);
+        let inside = (left..left + button).contains(&x) && (top..top + button).contains(&y);
+        inside.then_some(tool)
+    })
+}
\ No newline at end of file
@@ -2,9 +2,8 @@ use glam::{Vec3, Mat4};
 use super::{Instance, Vertex, Mesh};
 
 pub struct AxisIndicator {
-    pub x_axis_mesh: (wgpu::Buffer, wgpu::Buffer, u32),
-    pub y_axis_mesh: (wgpu::Buffer, wgpu::Buffer, u32),
-    pub z_axis_mesh: (wgpu::Buffer, wgpu::Buffer, u32),
+    // All three arrows in one mesh, drawn with a single instance
+    pub mesh: (wgpu::Buffer, wgpu::Buffer, u32),
     pub position: Vec3,
     pub scale: f32,
 }
@@ -12,18 +11,12 @@ pub struct AxisIndicator {
 impl AxisIndicator {
     pub fn new(device: &wgpu::Device) -> Self {
         // Create arrow mesh for each axis
-        let x_axis_data = Self::create_axis_arrow([1.0, 0.0, 0.0], Vec3::X);  // Red X
-        let y_axis_data = Self::create_axis_arrow([0.0, 1.0, 0.0], Vec3::Y);  // Green Y  
-        let z_axis_data = Self::create_axis_arrow([0.0, 0.0, 1.0], Vec3::Z);  // Blue Z
-        
-        let x_axis_mesh = Self::create_mesh_buffers(device, &x_axis_data);
-        let y_axis_mesh = Self::create_mesh_buffers(device, &y_axis_data);
-        let z_axis_mesh = Self::create_mesh_buffers(device, &z_axis_data);
+        let mut arrows = Self::create_axis_arrow([1.0, 0.0, 0.0], Vec3::X);  // Red X
+        arrows.append(&Self::create_axis_arrow([0.0, 1.0, 0.0], Vec3::Y));  // Green Y
+        arrows.append(&Self::create_axis_arrow([0.0, 0.0, 1.0], Vec3::Z));  // Blue Z
 
         Self {
-            x_axis_mesh,
-            y_axis_mesh,
-            z_axis_mesh,
+            mesh: Self::create_mesh_buffers(device, &arrows),
             position: Vec3::new(-0.9, -0.8, 0.0), // Bottom-left of screen
             scale: 0.2,  // Bigger scale for ~100px appearance
         }
@@ -132,15 +125,15 @@ impl AxisIndicator {
     }
 
     fn create_mesh_buffers(device: &wgpu::Device, mesh: &Mesh) -> (wgpu::Buffer, wgpu::Buffer, u32) {
-        use wgpu::util::DeviceExt;
+        use super::profiler;
         
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let vertex_buffer = profiler::buffer_init(device, &wgpu::util::BufferInitDescriptor {
             label: Some("Axis Vertex Buffer"),
             contents: bytemuck::cast_slice(&mesh.vertices),
             usage: wgpu::BufferUsages::VERTEX,
         });
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let index_buffer = profiler::buffer_init(device, &wgpu::util::BufferInitDescriptor {
             label: Some("Axis Index Buffer"),
             contents: bytemuck::cast_slice(&mesh.indices),
             usage: wgpu::BufferUsages::INDEX,
@@ -149,7 +142,7 @@ impl AxisIndicator {
         (vertex_buffer, index_buffer, mesh.indices.len() as u32)
     }
 
-    pub fn get_instances(&self, view_matrix: &Mat4) -> (Instance, Instance, Instance) {
+    pub fn get_instance(&self, view_matrix: &Mat4) -> Instance {
         // Extract rotation from view matrix and apply to axis indicator
         let rotation = Mat4::from_cols(
             view_matrix.x_axis.truncate().extend(0.0),
@@ -160,18 +153,9 @@ impl AxisIndicator {
         
         let rotation_quat = glam::Quat::from_mat4(&rotation);
 
-        let mut x_instance = Instance::new(self.position);
-        x_instance.rotation = rotation_quat;
-        x_instance.scale = Vec3::splat(self.scale);
-
-        let mut y_instance = Instance::new(self.position);
-        y_instance.rotation = rotation_quat;
-        y_instance.scale = Vec3::splat(self.scale);
-
-        let mut z_instance = Instance::new(self.position);
-        z_instance.rotation = rotation_quat;
-        z_instance.scale = Vec3::splat(self.scale);
-
-        (x_instance, y_instance, z_instance)
+        let mut instance = Instance::new(self.position);
+        instance.rotation = rotation_quat;
+        instance.scale = Vec3::splat(self.scale);
+        instance
     }
 }
\ No newline at end of file
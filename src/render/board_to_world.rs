@@ -0,0 +1,103 @@
+// The one mapping between board coordinates and world space. Board x always
+// runs along world X; whichever board axis the user picked as "up" runs along
+// world Y (the camera's up) and the remaining one along world Z.
+use super::variation::StoneLook;
+use super::Instance;
+use crate::settings::{LayoutSettings, UpAxis};
+use glam::Vec3;
+
+type Position = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoardToWorld {
+    board_size: usize,
+    layout: LayoutSettings,
+}
+
+impl BoardToWorld {
+    pub fn new(board_size: usize, layout: &LayoutSettings) -> Self {
+        Self {
+            board_size,
+            layout: *layout,
+        }
+    }
+
+    pub fn board_size(&self) -> usize {
+        self.board_size
+    }
+
+    pub fn layout(&self) -> &LayoutSettings {
+        &self.layout
+    }
+
+    // World axis (0 = X, 1 = Y, 2 = Z) that board axis `board_axis` runs along
+    pub fn world_axis(&self, board_axis: usize) -> usize {
+        match (self.layout.up, board_axis) {
+            (_, 0) => 0,
+            (UpAxis::Z, 1) | (UpAxis::Y, 2) => 2,
+            _ => 1,
+        }
+    }
+
+    // Inverse of `world_axis`; the mapping only ever swaps board y and z
+    pub fn board_axis(&self, world_axis: usize) -> usize {
+        self.world_axis(world_axis)
+    }
+
+    // World coordinate of lattice index `index` along any one axis
+    pub fn coordinate(&self, index: usize) -> f32 {
+        self.layout.coordinate(index as u8, self.board_size)
+    }
+
+    // World position of lattice indices, which may lie outside the board
+    // (e.g. one step above the top layer)
+    pub fn indices_to_world(&self, indices: [usize; 3]) -> Vec3 {
+        let mut world = Vec3::ZERO;
+        for (board_axis, &index) in indices.iter().enumerate() {
+            world[self.world_axis(board_axis)] = self.coordinate(index);
+        }
+        world
+    }
+
+    // A stone at `position`, sized by the layout and given its own look
+    pub fn stone_instance(&self, position: Position) -> Instance {
+        let mut instance = Instance::new(self.to_world(position));
+        instance.scale = Vec3::splat(self.stone_scale());
+        StoneLook::at(position).apply(&mut instance);
+        instance
+    }
+
+    pub fn to_world(self, (x, y, z): Position) -> Vec3 {
+        self.indices_to_world([x as usize, y as usize, z as usize])
+    }
+
+    // Nearest board point to `world`, or None outside the board
+    pub fn to_board(self, world: Vec3) -> Option<Position> {
+        let mut indices = [0u8; 3];
+        for (board_axis, index) in indices.iter_mut().enumerate() {
+            let along = self.layout.index(world[self.world_axis(board_axis)], self.board_size).round();
+            if along < 0.0 || along >= self.board_size as f32 {
+                return None;
+            }
+            *index = along as u8;
+        }
+        Some((indices[0], indices[1], indices[2]))
+    }
+
+    // Edge length of the bounding box
+    pub fn extent(&self) -> f32 {
+        self.layout.extent(self.board_size)
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.layout.pitch
+    }
+
+    pub fn stone_radius(&self) -> f32 {
+        self.layout.stone_radius
+    }
+
+    pub fn stone_scale(&self) -> f32 {
+        self.layout.stone_scale()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,140 @@
+// The stones as drawn, kept between frames. Each sync diffs the board against
+// what was drawn last and only adds or removes the instances that changed; a
+// new board size, layout or blind mode redraws from scratch. Also holds the
+// markers drawn over the board: the point under the cursor, the selected
+// stone and the last move.
+use super::{BoardToWorld, Instance};
+use crate::game::{GameRules, StoneColor};
+use crate::settings::{BlindMode, LayoutSettings};
+use std::collections::HashMap;
+
+type Position = (u8, u8, u8);
+
+fn slot(color: StoneColor) -> usize {
+    color.index()
+}
+
+const SLOTS: usize = StoneColor::MAX_PLAYERS as usize;
+
+#[derive(Default)]
+pub struct BoardView {
+    // Where each drawn stone's instance sits in its color's list
+    drawn: HashMap<Position, (StoneColor, usize)>,
+    // Per color: board stones first, in step with `positions`, then any
+    // transient instances (falling captures) after them
+    instances: [Vec<Instance>; SLOTS],
+    positions: [Vec<Position>; SLOTS],
+    drawn_with: Option<(BoardToWorld, BlindMode)>,
+    hover: Option<Position>,
+    selection: Option<Position>,
+    last_move: Option<Position>,
+}
+
+impl BoardView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Brings the instances up to date with `rules` and returns the stones that
+    // are no longer drawn (captured, or hidden by blind training)
+    pub fn sync(&mut self, rules: &GameRules, layout: &LayoutSettings, blind: BlindMode) -> Vec<(Position, StoneColor)> {
+        let board = rules.board();
+        let to_world = BoardToWorld::new(board.size(), layout);
+        if self.drawn_with != Some((to_world, blind)) {
+            self.clear();
+            self.drawn_with = Some((to_world, blind));
+        }
+        // Transients are dropped so the lists line up with `positions` again
+        for (color_slot, positions) in self.positions.iter().enumerate() {
+            self.instances[color_slot].truncate(positions.len());
+        }
+
+        self.last_move = rules.moves().last().and_then(|record| record.position);
+        let last_move = self.last_move;
+        let shown = |position: Position| blind != BlindMode::Hidden || Some(position) == last_move;
+
+        let stale: Vec<(Position, StoneColor)> = self
+            .drawn
+            .iter()
+            .filter(|&(&position, &(color, _))| board.get_stone(position) != Some(color) || !shown(position))
+            .map(|(&position, &(color, _))| (position, color))
+            .collect();
+        for &(position, color) in &stale {
+            self.remove(position, color);
+        }
+
+        for (&position, &color) in board.get_all_stones() {
+            if shown(position) && !self.drawn.contains_key(&position) {
+                self.add(position, color, &to_world);
+            }
+        }
+
+        // Markers only make sense on the board as it now stands
+        if self.selection.is_some_and(|position| board.get_stone(position).is_none()) {
+            self.selection = None;
+        }
+        if self.hover.is_some_and(|position| board.get_stone(position).is_none()) {
+            self.hover = None;
+        }
+        stale
+    }
+
+    fn clear(&mut self) {
+        self.drawn.clear();
+        for color_slot in 0..SLOTS {
+            self.instances[color_slot].clear();
+            self.positions[color_slot].clear();
+        }
+    }
+
+    fn add(&mut self, position: Position, color: StoneColor, to_world: &BoardToWorld) {
+        let color_slot = slot(color);
+        self.drawn.insert(position, (color, self.positions[color_slot].len()));
+        self.positions[color_slot].push(position);
+        self.instances[color_slot].push(to_world.stone_instance(position));
+    }
+
+    // Swaps the last stone of the same color into the freed slot
+    fn remove(&mut self, position: Position, color: StoneColor) {
+        let Some((_, index)) = self.drawn.remove(&position) else {
+            return;
+        };
+        let color_slot = slot(color);
+        self.positions[color_slot].swap_remove(index);
+        self.instances[color_slot].swap_remove(index);
+        if let Some(&moved) = self.positions[color_slot].get(index) {
+            self.drawn.insert(moved, (color, index));
+        }
+    }
+
+    // Drawn after the board stones until replaced; empty clears them
+    pub fn set_transient(&mut self, color: StoneColor, instances: impl IntoIterator<Item = Instance>) {
+        let color_slot = slot(color);
+        self.instances[color_slot].truncate(self.positions[color_slot].len());
+        self.instances[color_slot].extend(instances);
+    }
+
+    pub fn stones(&self, color: StoneColor) -> &[Instance] {
+        &self.instances[slot(color)]
+    }
+
+    pub fn hover(&self) -> Option<Position> {
+        self.hover
+    }
+
+    pub fn set_hover(&mut self, position: Option<Position>) {
+        self.hover = position;
+    }
+
+    pub fn selection(&self) -> Option<Position> {
+        self.selection
+    }
+
+    pub fn select(&mut self, position: Option<Position>) {
+        self.selection = position;
+    }
+
+    pub fn last_move(&self) -> Option<Position> {
+        self.last_move
+    }
+}
\ No newline at end of file
@@ -0,0 +1,290 @@
+// Exports a game as a printable PDF booklet: one A4 page for every `every`
+// moves, showing the position after them as flat diagrams, one per layer
+// along the up axis, laid out as the split view's layer board is. Stones
+// played on the page carry their move numbers; moves whose stones are gone by
+// the end of the page (captured, or passes) are listed underneath. The pages
+// are vector drawings in plain ASCII, so the file prints sharply at any size
+// and goes through the same save path as the other text exports.
+use super::BoardToWorld;
+use crate::accessibility::format_point;
+use crate::game::{GameRecord, GameRules, RecordError, StoneColor};
+use std::collections::HashMap;
+
+type Position = (u8, u8, u8);
+
+pub const DEFAULT_MOVES_PER_PAGE: usize = 10;
+
+// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 40.0;
+const TITLE_SIZE: f32 = 16.0;
+const TEXT_SIZE: f32 = 9.0;
+// Above the diagrams for the title and game line, below them for the notes
+const HEADER: f32 = 48.0;
+const FOOTER: f32 = 60.0;
+// Between diagrams, and above each one for its layer label
+const GAP: f32 = 14.0;
+const LABEL_SPACE: f32 = 12.0;
+// Helvetica's average glyph width, as a share of the text size
+const GLYPH_WIDTH: f32 = 0.55;
+const NOTE_COLUMNS: usize = 100;
+
+const WOOD: [f32; 3] = [0.93, 0.82, 0.62];
+const LINE: [f32; 3] = [0.25, 0.2, 0.15];
+const HOLE: [f32; 3] = [0.55, 0.55, 0.55];
+// Stone fill and the color of a number on it, by StoneColor::index()
+const STONES: [([f32; 3], [f32; 3]); 4] = [
+    ([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]),
+    ([1.0, 1.0, 1.0], [0.0, 0.0, 0.0]),
+    ([0.8, 0.12, 0.1], [1.0, 1.0, 1.0]),
+    ([0.1, 0.55, 0.2], [1.0, 1.0, 1.0]),
+];
+
+// The whole booklet; fails only if the record doesn't replay
+pub fn export_booklet(record: &GameRecord, to_world: &BoardToWorld, every: usize) -> Result<String, RecordError> {
+    let every = every.max(1);
+    let pages = record.moves.len().div_ceil(every).max(1);
+    let mut contents = Vec::with_capacity(pages);
+    // One replay, drawing each page as the position it shows goes by
+    let mut played = 0;
+    record.replay_each(|rules| {
+        let first = contents.len() * every;
+        let last = (first + every).min(record.moves.len());
+        if played == last {
+            contents.push(draw_page(record, rules, to_world, first..last));
+        }
+        played += 1;
+    })?;
+    Ok(write_pdf(&contents))
+}
+
+// The page showing the position after `moves` (indices into the record)
+fn draw_page(record: &GameRecord, rules: &GameRules, to_world: &BoardToWorld, moves: std::ops::Range<usize>) -> String {
+    let mut canvas = Canvas::default();
+    let title = if moves.is_empty() {
+        "Empty board".to_string()
+    } else {
+        format!("Moves {}-{} of {}", moves.start + 1, moves.end, record.moves.len())
+    };
+    canvas.text((MARGIN, MARGIN + TITLE_SIZE), TITLE_SIZE, [0.0; 3], &title);
+    canvas.text((MARGIN, MARGIN + TITLE_SIZE + 16.0), TEXT_SIZE, [0.0; 3], &game_line(record));
+
+    // The latest move at each point on the page, and the moves not on the board
+    let board = rules.board();
+    let mut numbers: HashMap<Position, usize> = HashMap::new();
+    for index in moves.clone() {
+        if let Some(position) = record.moves[index].position {
+            numbers.insert(position, index + 1);
+        }
+    }
+    let mut notes = Vec::new();
+    for index in moves {
+        let mv = &record.moves[index];
+        match mv.position {
+            None => notes.push(format!("{} {} passed", index + 1, mv.color.name())),
+            Some(position) if numbers.get(&position) != Some(&(index + 1)) || board.get_stone(position).is_none() => {
+                notes.push(format!("{} {} at {}", index + 1, mv.color.name(), format_point(position)));
+            }
+            Some(_) => {}
+        }
+    }
+    numbers.retain(|&position, _| board.get_stone(position).is_some());
+
+    // Layers in a grid as near square as fits
+    let size = to_world.board_size().max(1);
+    let columns = (size as f32).sqrt().ceil() as usize;
+    let rows = size.div_ceil(columns);
+    let area = (PAGE_WIDTH - 2.0 * MARGIN, PAGE_HEIGHT - 2.0 * MARGIN - HEADER - FOOTER);
+    let slot = ((area.0 + GAP) / columns as f32).min((area.1 + GAP) / rows as f32);
+    let extent = slot - GAP - LABEL_SPACE;
+    let cell = extent / size as f32;
+    let holes: Vec<Position> = board.holes().collect();
+    for layer in 0..size {
+        let left = MARGIN + (layer % columns) as f32 * slot;
+        let top = MARGIN + HEADER + (layer / columns) as f32 * slot + LABEL_SPACE;
+        let label = format!("layer {} {}/{}", ["x", "y", "z"][to_world.board_axis(1)], layer + 1, size);
+        canvas.text((left, top - 4.0), TEXT_SIZE, [0.0; 3], &label);
+        canvas.rect((left, top), (extent, extent), WOOD);
+        for i in 0..size {
+            let along = (i as f32 + 0.5) * cell;
+            let (first, last) = (cell * 0.5, extent - cell * 0.5);
+            canvas.line((left + first, top + along), (left + last, top + along), LINE);
+            canvas.line((left + along, top + first), (left + along, top + last), LINE);
+        }
+
+        // Columns along world X and rows along world Z, as on the layer board
+        let place = |position: Position| {
+            let indices = [position.0, position.1, position.2].map(usize::from);
+            (indices[to_world.board_axis(1)] == layer).then(|| {
+                let (column, row) = (indices[to_world.board_axis(0)], indices[to_world.board_axis(2)]);
+                (left + (column as f32 + 0.5) * cell, top + (row as f32 + 0.5) * cell)
+            })
+        };
+        for center in holes.iter().filter_map(|&hole| place(hole)) {
+            canvas.cross(center, cell * 0.3, HOLE);
+        }
+        for (&position, &color) in board.get_all_stones() {
+            let Some(center) = place(position) else {
+                continue;
+            };
+            let (fill, ink) = STONES[color.index()];
+            canvas.circle(center, cell * 0.46, fill, color == StoneColor::White);
+            if let Some(number) = numbers.get(&position) {
+                let text = number.to_string();
+                let text_size = cell * if text.len() > 2 { 0.34 } else { 0.45 };
+                let width = text.len() as f32 * text_size * GLYPH_WIDTH;
+                canvas.text((center.0 - width / 2.0, center.1 + text_size * 0.35), text_size, ink, &text);
+            }
+        }
+    }
+
+    // As many lines of notes as the footer holds, the last one cut short
+    let mut lines = wrap(&notes.join(", "), NOTE_COLUMNS);
+    let fits = (FOOTER / (TEXT_SIZE * 1.4)) as usize;
+    if lines.len() > fits {
+        lines.truncate(fits);
+        lines[fits - 1] += " ...";
+    }
+    let mut y = PAGE_HEIGHT - MARGIN - FOOTER + TEXT_SIZE * 2.0;
+    for line in lines {
+        canvas.text((MARGIN, y), TEXT_SIZE, [0.0; 3], &line);
+        y += TEXT_SIZE * 1.4;
+    }
+    canvas.ops
+}
+
+// "5x5x5, komi 6.5, gravity, Ann (Black) vs AI level 2 (White), W+4.5"
+fn game_line(record: &GameRecord) -> String {
+    let size = record.board_size;
+    let mut parts = vec![format!("{}x{}x{}", size, size, size), format!("komi {}", record.komi)];
+    if record.players > 2 {
+        parts.push(format!("{} players", record.players));
+    }
+    parts.push(record.variant.to_string());
+    if let (Some(black), Some(white)) = (&record.black_player, &record.white_player) {
+        parts.push(format!("{} (Black) vs {} (White)", black, white));
+    }
+    if let Some(result) = &record.result {
+        parts.push(result.to_string());
+    }
+    parts.join(", ")
+}
+
+// Breaks at the spaces so no line runs past `columns` characters
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split(' ').filter(|word| !word.is_empty()) {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= columns => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+// A page's drawing operators. Takes points from the top-left corner, as the
+// rest of the 2D code does, and flips them into PDF's bottom-left space.
+#[derive(Default)]
+struct Canvas {
+    ops: String,
+}
+
+impl Canvas {
+    fn point(&self, (x, y): (f32, f32)) -> String {
+        format!("{:.2} {:.2}", x, PAGE_HEIGHT - y)
+    }
+
+    fn fill_color(&mut self, [r, g, b]: [f32; 3]) {
+        self.ops += &format!("{:.3} {:.3} {:.3} rg\n", r, g, b);
+    }
+
+    fn rect(&mut self, (left, top): (f32, f32), (width, height): (f32, f32), color: [f32; 3]) {
+        self.fill_color(color);
+        let corner = self.point((left, top + height));
+        self.ops += &format!("{} {:.2} {:.2} re f\n", corner, width, height);
+    }
+
+    fn line(&mut self, from: (f32, f32), to: (f32, f32), [r, g, b]: [f32; 3]) {
+        let (from, to) = (self.point(from), self.point(to));
+        self.ops += &format!("{:.3} {:.3} {:.3} RG 0.6 w {} m {} l S\n", r, g, b, from, to);
+    }
+
+    fn cross(&mut self, (x, y): (f32, f32), half: f32, color: [f32; 3]) {
+        self.line((x - half, y - half), (x + half, y + half), color);
+        self.line((x - half, y + half), (x + half, y - half), color);
+    }
+
+    // Four Bézier quarters; outlined in black when asked, for white stones
+    fn circle(&mut self, (x, y): (f32, f32), radius: f32, fill: [f32; 3], outline: bool) {
+        const KAPPA: f32 = 0.5523;
+        let k = radius * KAPPA;
+        self.fill_color(fill);
+        let start = self.point((x + radius, y));
+        self.ops += &format!("{} m\n", start);
+        let quarters = [
+            [(x + radius, y - k), (x + k, y - radius), (x, y - radius)],
+            [(x - k, y - radius), (x - radius, y - k), (x - radius, y)],
+            [(x - radius, y + k), (x - k, y + radius), (x, y + radius)],
+            [(x + k, y + radius), (x + radius, y + k), (x + radius, y)],
+        ];
+        for quarter in quarters {
+            let [a, b, c] = quarter.map(|point| self.point(point));
+            self.ops += &format!("{} {} {} c\n", a, b, c);
+        }
+        self.ops += if outline { "0 0 0 RG 0.6 w b\n" } else { "f\n" };
+    }
+
+    // Helvetica; anything outside printable ASCII shows as '?'
+    fn text(&mut self, baseline: (f32, f32), size: f32, color: [f32; 3], text: &str) {
+        let escaped: String = text
+            .chars()
+            .map(|c| match c {
+                '(' | ')' | '\\' => format!("\\{}", c),
+                ' '..='~' => c.to_string(),
+                _ => "?".to_string(),
+            })
+            .collect();
+        self.fill_color(color);
+        let at = self.point(baseline);
+        self.ops += &format!("BT /F1 {:.2} Tf {} Td ({}) Tj ET\n", size, at, escaped);
+    }
+}
+
+// The catalog, the page tree and the font, then a page object and its content
+// stream for each page, and the cross-reference table giving their offsets
+fn write_pdf(pages: &[String]) -> String {
+    let page_id = |index: usize| 4 + 2 * index;
+    let kids: Vec<String> = (0..pages.len()).map(|index| format!("{} 0 R", page_id(index))).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+    for (index, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            page_id(index) + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content));
+    }
+
+    let mut out = "%PDF-1.4\n".to_string();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out += &format!("{} 0 obj\n{}\nendobj\n", index + 1, object);
+    }
+    let xref = out.len();
+    out += &format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        out += &format!("{:010} 00000 n \n", offset);
+    }
+    out += &format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref);
+    out
+}
@@ -1,6 +1,10 @@
 use glam::{Mat4, Vec3, Vec4};
 use std::f32::consts::FRAC_PI_2;
 use bytemuck::{Pod, Zeroable};
+use super::clock::{AnimationClock, Easing, Tween};
+use crate::game::CameraView;
+use winit::event::ElementState;
+use winit::keyboard::KeyCode;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Pod, Zeroable)]
@@ -9,6 +13,15 @@ pub struct CameraUniform {
     view_pos: [f32; 4],
 }
 
+impl CameraUniform {
+    pub fn new(view_proj: Mat4, eye: Vec3) -> Self {
+        Self {
+            view_proj: view_proj.to_cols_array_2d(),
+            view_pos: Vec4::new(eye.x, eye.y, eye.z, 1.0).to_array(),
+        }
+    }
+}
+
 pub struct Camera {
     pub eye: Vec3,     // Camera position
     pub target: Vec3,  // Look-at target
@@ -52,6 +65,53 @@ impl Camera {
             view_pos: Vec4::new(self.eye.x, self.eye.y, self.eye.z, 1.0).to_array(),
         }
     }
+
+    // Left and right eye uniforms for stereo output. Both eyes converge on the
+    // orbit target, so the board centre sits at screen depth.
+    pub fn stereo_uniforms(&self, eye_separation: f32, aspect: f32) -> (CameraUniform, CameraUniform) {
+        let right = (self.target - self.eye).cross(self.up).normalize_or_zero();
+        let proj = Mat4::perspective_rh(self.fovy, aspect, self.znear, self.zfar);
+        let eye_uniform = |offset: f32| {
+            let eye = self.eye + right * offset;
+            CameraUniform {
+                view_proj: (proj * Mat4::look_at_rh(eye, self.target, self.up)).to_cols_array_2d(),
+                view_pos: Vec4::new(eye.x, eye.y, eye.z, 1.0).to_array(),
+            }
+        };
+        (eye_uniform(-eye_separation * 0.5), eye_uniform(eye_separation * 0.5))
+    }
+}
+
+// Where the orbit camera sits, kept between sessions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPreset {
+    pub distance: f32,
+    pub angle_x: f32,
+    pub angle_y: f32,
+    pub center: Vec3,
+}
+
+// Camera paths in game records store the same numbers
+impl From<CameraView> for CameraPreset {
+    fn from(view: CameraView) -> Self {
+        Self {
+            distance: view.distance,
+            angle_x: view.angle_x,
+            angle_y: view.angle_y,
+            center: Vec3::from_array(view.center),
+        }
+    }
+}
+
+impl From<CameraPreset> for CameraView {
+    fn from(preset: CameraPreset) -> Self {
+        Self {
+            distance: preset.distance,
+            angle_x: preset.angle_x,
+            angle_y: preset.angle_y,
+            center: preset.center.to_array(),
+        }
+    }
 }
 
 pub struct CameraController {
@@ -71,9 +131,13 @@ pub struct CameraController {
     orbit_angle_y: f32,
     pan_offset: Vec3,  // Offset from board center for panning
     board_center: Vec3,  // The center of the board (0,0,0)
+    // Gliding to a newly picked orbit center: from, to, progress
+    center_transition: Option<(Vec3, Vec3, Tween)>,
 }
 
 impl CameraController {
+    const CENTER_TRANSITION_SECONDS: f32 = 0.4;
+
     pub fn new(speed: f32, sensitivity: f32) -> Self {
         Self {
             speed,
@@ -92,38 +156,39 @@ impl CameraController {
             orbit_angle_y: FRAC_PI_2 / 6.0,  // Slight elevation
             pan_offset: Vec3::ZERO,
             board_center: Vec3::ZERO,  // Board center is at origin
+            center_transition: None,
         }
     }
 
-    pub fn process_keyboard(&mut self, key: winit::event::VirtualKeyCode, state: winit::event::ElementState) -> bool {
-        let is_pressed = state == winit::event::ElementState::Pressed;
+    pub fn process_keyboard(&mut self, key: KeyCode, state: ElementState) -> bool {
+        let is_pressed = state == ElementState::Pressed;
         
         match key {
-            winit::event::VirtualKeyCode::W | winit::event::VirtualKeyCode::Up => {
+            KeyCode::KeyW | KeyCode::ArrowUp => {
                 self.is_forward_pressed = is_pressed;
                 true
             }
-            winit::event::VirtualKeyCode::A | winit::event::VirtualKeyCode::Left => {
+            KeyCode::KeyA | KeyCode::ArrowLeft => {
                 self.is_left_pressed = is_pressed;
                 true
             }
-            winit::event::VirtualKeyCode::S | winit::event::VirtualKeyCode::Down => {
+            KeyCode::KeyS | KeyCode::ArrowDown => {
                 self.is_backward_pressed = is_pressed;
                 true
             }
-            winit::event::VirtualKeyCode::D | winit::event::VirtualKeyCode::Right => {
+            KeyCode::KeyD | KeyCode::ArrowRight => {
                 self.is_right_pressed = is_pressed;
                 true
             }
-            winit::event::VirtualKeyCode::Space => {
+            KeyCode::Space => {
                 self.is_space_pressed = is_pressed;
                 true
             }
-            winit::event::VirtualKeyCode::Q => {
+            KeyCode::KeyQ => {
                 self.is_up_pressed = is_pressed;
                 true
             }
-            winit::event::VirtualKeyCode::LShift => {
+            KeyCode::ShiftLeft => {
                 self.is_down_pressed = is_pressed;
                 true
             }
@@ -150,7 +215,16 @@ impl CameraController {
         self.orbit_distance = (self.orbit_distance + zoom_step).min(50.0);
     }
 
-    pub fn update_camera(&mut self, camera: &mut Camera, dt: f32) {
+    pub fn update_camera(&mut self, camera: &mut Camera, clock: &AnimationClock) {
+        // Input keeps working while animations are paused
+        let dt = clock.real_dt();
+        if let Some((from, to, tween)) = self.center_transition {
+            self.board_center = from.lerp(to, tween.progress(clock));
+            if tween.is_finished(clock) {
+                self.center_transition = None;
+            }
+        }
+
         // Handle mouse movement
         if self.mouse_dx.abs() > 0.01 || self.mouse_dy.abs() > 0.01 {
             if self.is_space_pressed {
@@ -214,13 +288,35 @@ impl CameraController {
         self.is_space_pressed
     }
 
-    pub fn set_orbit_center(&mut self, new_center: Vec3) {
-        self.board_center = new_center;
-        // Reset pan offset when changing orbit center
+    // Glides to the new center rather than jumping there
+    pub fn set_orbit_center(&mut self, new_center: Vec3, clock: &AnimationClock) {
+        // Keep looking at the same point while the pan offset is folded in
+        let from = self.board_center + self.pan_offset;
         self.pan_offset = Vec3::ZERO;
+        self.board_center = from;
+        self.center_transition = Some((from, new_center, Tween::start(clock, Self::CENTER_TRANSITION_SECONDS, Easing::InOut)));
     }
 
     pub fn get_orbit_center(&self) -> Vec3 {
         self.board_center + self.pan_offset
     }
+
+    pub fn preset(&self) -> CameraPreset {
+        CameraPreset {
+            distance: self.orbit_distance,
+            angle_x: self.orbit_angle_x,
+            angle_y: self.orbit_angle_y,
+            center: self.get_orbit_center(),
+        }
+    }
+
+    // Jumps straight there; values out of the usual range are pulled back in
+    pub fn set_preset(&mut self, preset: CameraPreset) {
+        self.orbit_distance = preset.distance.clamp(5.0, 100.0);
+        self.orbit_angle_x = preset.angle_x;
+        self.orbit_angle_y = preset.angle_y.clamp(-FRAC_PI_2 + 0.1, FRAC_PI_2 - 0.1);
+        self.board_center = preset.center;
+        self.pan_offset = Vec3::ZERO;
+        self.center_transition = None;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,195 @@
+// The one clock everything animated runs on: side-view layer cycling,
+// capture animations and camera transitions. Frontends tick it once per
+// frame; systems read its delta or time, or run tweens against it, so their
+// speed doesn't depend on the frame rate, and pausing or slowing the clock
+// affects all of them at once. Camera input reads the real delta instead, so
+// the view can still be moved while animations are paused.
+use instant::Instant;
+
+// Seconds since some fixed start
+pub trait TimeSource {
+    fn now(&self) -> f64;
+}
+
+// `instant::Instant`: performance.now() in browsers, the monotonic clock natively
+pub struct SystemTime {
+    start: Instant,
+}
+
+impl Default for SystemTime {
+    fn default() -> Self {
+        Self { start: Instant::now() }
+    }
+}
+
+impl TimeSource for SystemTime {
+    fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+}
+
+// Advanced by hand, for stepping frames at a fixed rate (headless runs, recordings)
+#[derive(Default)]
+pub struct ManualTime {
+    seconds: std::cell::Cell<f64>,
+}
+
+impl ManualTime {
+    pub fn advance(&self, seconds: f64) {
+        self.seconds.set(self.seconds.get() + seconds);
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> f64 {
+        self.seconds.get()
+    }
+}
+
+pub struct AnimationClock {
+    source: Box<dyn TimeSource>,
+    last_tick: f64,
+    real_dt: f32,
+    // Animation time and delta: scaled by the speed, frozen while paused
+    time: f64,
+    dt: f32,
+    speed: f32,
+    paused: bool,
+    // Stopped while the window is hidden, apart from the user's pause
+    suspended: bool,
+}
+
+impl Default for AnimationClock {
+    fn default() -> Self {
+        Self::new(Box::<SystemTime>::default())
+    }
+}
+
+impl AnimationClock {
+    // A stall longer than this (a dragged window, a backgrounded tab) counts
+    // as this much, so animations don't jump to their end
+    const MAX_DT: f32 = 0.25;
+
+    pub fn new(source: Box<dyn TimeSource>) -> Self {
+        let last_tick = source.now();
+        Self {
+            source,
+            last_tick,
+            real_dt: 0.0,
+            time: 0.0,
+            dt: 0.0,
+            speed: 1.0,
+            paused: false,
+            suspended: false,
+        }
+    }
+
+    // Once per frame, before anything animated is updated
+    pub fn tick(&mut self) {
+        let now = self.source.now();
+        let real_dt = ((now - self.last_tick) as f32).clamp(0.0, Self::MAX_DT);
+        self.last_tick = now;
+        self.advance(real_dt);
+    }
+
+    // In place of tick(): a frame of a given wall-clock length, for playing
+    // back a recorded session
+    pub fn advance(&mut self, real_dt: f32) {
+        self.real_dt = real_dt;
+        self.dt = if self.paused || self.suspended { 0.0 } else { self.real_dt * self.speed };
+        self.time += self.dt as f64;
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    // Wall-clock delta, for input that should respond while paused
+    pub fn real_dt(&self) -> f32 {
+        self.real_dt
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn toggle_pause(&mut self) -> bool {
+        self.paused = !self.paused;
+        self.paused
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    // Resuming restarts the delta from now, so the time spent hidden isn't
+    // played back as one long frame
+    pub fn set_suspended(&mut self, suspended: bool) {
+        if self.suspended && !suspended {
+            self.last_tick = self.source.now();
+        }
+        self.suspended = suspended;
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    // Smoothstep: starts and ends gently
+    InOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::InOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+// A 0-to-1 progress over a stretch of animation time
+#[derive(Debug, Clone, Copy)]
+pub struct Tween {
+    start: f64,
+    duration: f32,
+    easing: Easing,
+}
+
+impl Tween {
+    pub fn start(clock: &AnimationClock, duration: f32, easing: Easing) -> Self {
+        Self {
+            start: clock.time(),
+            duration,
+            easing,
+        }
+    }
+
+    pub fn progress(&self, clock: &AnimationClock) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        let t = ((clock.time() - self.start) as f32 / self.duration).clamp(0.0, 1.0);
+        self.easing.apply(t)
+    }
+
+    pub fn is_finished(&self, clock: &AnimationClock) -> bool {
+        clock.time() - self.start >= self.duration as f64
+    }
+}
\ No newline at end of file
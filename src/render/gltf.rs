@@ -0,0 +1,94 @@
+// Exports the stones on the board as a self-contained glTF 2.0 scene (.gltf with
+// the geometry embedded as a data URI), in the same world layout as the renderer.
+use super::{BoardToWorld, Mesh};
+use crate::game::GameRules;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+
+pub fn export_board(rules: &GameRules, to_world: &BoardToWorld) -> String {
+    let sphere = Mesh::create_sphere(to_world.stone_radius(), 20, 20, [1.0; 3]);
+
+    // Buffer layout: positions, normals, then u32 indices
+    let mut buffer: Vec<u8> = Vec::new();
+    for vertex in &sphere.vertices {
+        buffer.extend(vertex.position.iter().flat_map(|c| c.to_le_bytes()));
+    }
+    let normals_offset = buffer.len();
+    for vertex in &sphere.vertices {
+        buffer.extend(vertex.normal.iter().flat_map(|c| c.to_le_bytes()));
+    }
+    let indices_offset = buffer.len();
+    buffer.extend(sphere.indices.iter().flat_map(|i| i.to_le_bytes()));
+
+    let vertex_count = sphere.vertices.len();
+    let board = rules.board();
+
+    let mut stones: Vec<_> = board.get_all_stones().collect();
+    stones.sort_by_key(|&(&position, _)| position);
+    let nodes: Vec<String> = stones
+        .iter()
+        .map(|&(&(x, y, z), &color)| {
+            let translation = to_world.to_world((x, y, z));
+            let mesh = color.index();
+            format!(
+                r#"{{"name":"{:?} ({}, {}, {})","mesh":{},"translation":[{},{},{}]}}"#,
+                color,
+                x,
+                y,
+                z,
+                mesh,
+                translation.x,
+                translation.y,
+                translation.z
+            )
+        })
+        .collect();
+    let node_indices: Vec<String> = (0..nodes.len()).map(|i| i.to_string()).collect();
+
+    let primitive = |material: usize| {
+        format!(
+            r#"{{"primitives":[{{"attributes":{{"POSITION":0,"NORMAL":1}},"indices":2,"material":{}}}]}}"#,
+            material
+        )
+    };
+
+    format!(
+        concat!(
+            r#"{{"asset":{{"version":"2.0","generator":"go3d"}},"scene":0,"#,
+            r#""scenes":[{{"name":"3D Go board","nodes":[{}]}}],"nodes":[{}],"#,
+            r#""meshes":[{},{},{},{}],"#,
+            r#""materials":["#,
+            r#"{{"name":"Black stone","pbrMetallicRoughness":{{"baseColorFactor":[0.1,0.1,0.1,1],"metallicFactor":0,"roughnessFactor":0.4}}}},"#,
+            r#"{{"name":"White stone","pbrMetallicRoughness":{{"baseColorFactor":[0.9,0.9,0.9,1],"metallicFactor":0,"roughnessFactor":0.4}}}},"#,
+            r#"{{"name":"Red stone","pbrMetallicRoughness":{{"baseColorFactor":[0.8,0.15,0.1,1],"metallicFactor":0,"roughnessFactor":0.4}}}},"#,
+            r#"{{"name":"Green stone","pbrMetallicRoughness":{{"baseColorFactor":[0.1,0.6,0.2,1],"metallicFactor":0,"roughnessFactor":0.4}}}}],"#,
+            r#""accessors":["#,
+            r#"{{"bufferView":0,"componentType":5126,"count":{},"type":"VEC3","min":[{r},{r},{r}],"max":[{R},{R},{R}]}},"#,
+            r#"{{"bufferView":1,"componentType":5126,"count":{},"type":"VEC3"}},"#,
+            r#"{{"bufferView":2,"componentType":5125,"count":{},"type":"SCALAR"}}],"#,
+            r#""bufferViews":["#,
+            r#"{{"buffer":0,"byteOffset":0,"byteLength":{},"target":34962}},"#,
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34962}},"#,
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34963}}],"#,
+            r#""buffers":[{{"byteLength":{},"uri":"data:application/octet-stream;base64,{}"}}]}}"#
+        ),
+        node_indices.join(","),
+        nodes.join(","),
+        primitive(0),
+        primitive(1),
+        primitive(2),
+        primitive(3),
+        vertex_count,
+        vertex_count,
+        sphere.indices.len(),
+        normals_offset,
+        normals_offset,
+        indices_offset - normals_offset,
+        indices_offset,
+        buffer.len() - indices_offset,
+        buffer.len(),
+        STANDARD.encode(&buffer),
+        r = -to_world.stone_radius(),
+        R = to_world.stone_radius(),
+    )
+}
\ No newline at end of file
@@ -1,15 +1,33 @@
-use glam::Vec3;
-use super::Instance;
+// The guide: three planes, one across each board axis, whose intersection is
+// the point a stone would be placed on; the crosshair style draws only the
+// lines where they cross. Positions are board indices; where
+// they land in the world, and which plane faces which world axis, comes from
+// BoardToWorld alone, so the planes always meet where that point is drawn.
+use glam::{Quat, Vec3};
+use std::f32::consts::{FRAC_PI_2, TAU};
+use super::{BoardToWorld, Instance};
+use crate::game::GameRules;
+
+// Crosshair line thickness, relative to the lattice pitch
+const LINE_THICKNESS: f32 = 0.03;
 
 #[derive(Clone)]
 pub struct GuideSystem {
-    pub plane_x_pos: i32,  // Position along X axis (for YZ plane)
-    pub plane_y_pos: i32,  // Position along Y axis (for XZ plane)  
-    pub plane_z_pos: i32,  // Position along Z axis (for XY plane)
+    // Board indices of the planes across board x, y and z
+    pub plane_x_pos: i32,
+    pub plane_y_pos: i32,
+    pub plane_z_pos: i32,
     board_size: i32,
+    // Intersections stored for jumping back to, one per number key
+    bookmarks: [Option<(u8, u8, u8)>; GuideSystem::BOOKMARKS],
+    // Seconds left of the pulse after the guide jumped to a move
+    pulse: f32,
 }
 
 impl GuideSystem {
+    pub const BOOKMARKS: usize = 9;
+    pub const PULSE_SECONDS: f32 = 0.8;
+
     pub fn new(board_size: usize) -> Self {
         let size = board_size as i32;
         Self {
@@ -17,9 +35,15 @@ impl GuideSystem {
             plane_y_pos: size / 2,
             plane_z_pos: size / 2,
             board_size: size,
+            bookmarks: [None; Self::BOOKMARKS],
+            pulse: 0.0,
         }
     }
 
+    pub fn board_size(&self) -> usize {
+        self.board_size as usize
+    }
+
     pub fn move_x(&mut self, delta: i32) {
         self.plane_x_pos = (self.plane_x_pos + delta).clamp(0, self.board_size - 1);
     }
@@ -32,52 +56,116 @@ impl GuideSystem {
         self.plane_z_pos = (self.plane_z_pos + delta).clamp(0, self.board_size - 1);
     }
 
+    // Moves the plane across board axis `board_axis` (0 = x, 1 = y, 2 = z)
+    pub fn move_along(&mut self, board_axis: usize, delta: i32) {
+        match board_axis {
+            0 => self.move_x(delta),
+            1 => self.move_y(delta),
+            _ => self.move_z(delta),
+        }
+    }
+
+    // Puts all three planes through `position`
+    pub fn set_intersection(&mut self, (x, y, z): (u8, u8, u8)) {
+        let clamp = |n: u8| (n as i32).clamp(0, self.board_size - 1);
+        (self.plane_x_pos, self.plane_y_pos, self.plane_z_pos) = (clamp(x), clamp(y), clamp(z));
+    }
+
     pub fn get_intersection_position(&self) -> (u8, u8, u8) {
         (self.plane_x_pos as u8, self.plane_y_pos as u8, self.plane_z_pos as u8)
     }
 
-    pub fn get_plane_instances(&self) -> (Instance, Instance, Instance) {
-        let half_size = self.board_size as f32 * 0.5;
-        
-        // YZ plane (controlled by X position)
-        let mut yz_plane = Instance::new(Vec3::new(
-            self.plane_x_pos as f32 - half_size + 0.5,
-            0.0,
-            0.0
-        ));
-        yz_plane.scale = Vec3::splat(self.board_size as f32);
-
-        // XZ plane (controlled by Y position)
-        let mut xz_plane = Instance::new(Vec3::new(
-            0.0,
-            self.plane_z_pos as f32 - half_size + 0.5,  // Note: swapped for rendering
-            0.0
-        ));
-        xz_plane.scale = Vec3::splat(self.board_size as f32);
-
-        // XY plane (controlled by Z position)
-        let mut xy_plane = Instance::new(Vec3::new(
-            0.0,
-            0.0,
-            self.plane_y_pos as f32 - half_size + 0.5  // Note: swapped for rendering
-        ));
-        xy_plane.scale = Vec3::splat(self.board_size as f32);
-
-        (yz_plane, xz_plane, xy_plane)
-    }
-
-    pub fn get_dot_instance(&self) -> Instance {
-        let half_size = self.board_size as f32 * 0.5;
-        
-        // Position at intersection with highest z (which is the max z position in the board)
-        let highest_z = self.board_size as f32 - half_size + 0.5;
-        
-        let mut dot = Instance::new(Vec3::new(
-            self.plane_x_pos as f32 - half_size + 0.5,
-            highest_z,  // Always at highest Z position
-            self.plane_y_pos as f32 - half_size + 0.5,  // Swapped for rendering
-        ));
-        dot.scale = Vec3::splat(0.125);  // 1/8th the size of a stone
+    // Stores the guide intersection under bookmark `slot` (0 to 8) and
+    // returns it
+    pub fn store_bookmark(&mut self, slot: usize) -> (u8, u8, u8) {
+        let position = self.get_intersection_position();
+        self.bookmarks[slot.min(Self::BOOKMARKS - 1)] = Some(position);
+        position
+    }
+
+    // Moves the guide to bookmark `slot`, if one is stored there
+    pub fn recall_bookmark(&mut self, slot: usize) -> Option<(u8, u8, u8)> {
+        let position = (*self.bookmarks.get(slot)?)?;
+        self.set_intersection(position);
+        Some(position)
+    }
+
+    // Puts the guide through a move just played and pulses the planes, so a
+    // move deep inside the lattice is easy to find
+    pub fn follow_move(&mut self, position: (u8, u8, u8)) {
+        self.set_intersection(position);
+        self.pulse = Self::PULSE_SECONDS;
+    }
+
+    // Once per frame, with the animation clock's delta
+    pub fn tick(&mut self, dt: f32) {
+        self.pulse = (self.pulse - dt).max(0.0);
+    }
+
+    pub fn is_pulsing(&self) -> bool {
+        self.pulse > 0.0
+    }
+
+    // 0 at rest; two beats that fade out over the pulse
+    fn pulse_amount(&self) -> f32 {
+        let left = self.pulse / Self::PULSE_SECONDS;
+        (left * TAU).sin().abs() * left
+    }
+
+    // Under a variant that decides where stones land (gravity), keeps the guide
+    // on the point a stone placed from it would take
+    pub fn snap_to_placement(&mut self, rules: &GameRules) {
+        if let Some((x, y, z)) = rules.resolve_placement(self.get_intersection_position()) {
+            (self.plane_x_pos, self.plane_y_pos, self.plane_z_pos) = (x as i32, y as i32, z as i32);
+        }
+    }
+
+    fn plane_positions(&self) -> [usize; 3] {
+        [self.plane_x_pos as usize, self.plane_y_pos as usize, self.plane_z_pos as usize]
+    }
+
+    // Instances of the XY guide plane mesh, turned to face along the world X,
+    // Y and Z axes in that order, so all three draw in one call
+    pub fn get_plane_instances(&self, to_world: &BoardToWorld) -> [Instance; 3] {
+        let positions = self.plane_positions();
+        let facing = [
+            Quat::from_rotation_y(FRAC_PI_2),
+            Quat::from_rotation_x(FRAC_PI_2),
+            Quat::IDENTITY,
+        ];
+        std::array::from_fn(|world_axis| {
+            let mut center = Vec3::ZERO;
+            center[world_axis] = to_world.coordinate(positions[to_world.board_axis(world_axis)]);
+            let mut instance = Instance::new(center);
+            instance.rotation = facing[world_axis];
+            instance.scale = Vec3::splat(to_world.extent() * (1.0 + 0.1 * self.pulse_amount()));
+            instance
+        })
+    }
+
+    // Instances of a unit cube stretched into a thin rod across the board
+    // along the world X, Y and Z axes in that order, all through the guide
+    // intersection: the lines where the planes cross
+    pub fn get_line_instances(&self, to_world: &BoardToWorld) -> [Instance; 3] {
+        let intersection = to_world.indices_to_world(self.plane_positions());
+        let thickness = LINE_THICKNESS * to_world.pitch() * (1.0 + 2.0 * self.pulse_amount());
+        std::array::from_fn(|world_axis| {
+            let mut center = intersection;
+            center[world_axis] = 0.0;
+            let mut instance = Instance::new(center);
+            instance.scale = Vec3::splat(thickness);
+            instance.scale[world_axis] = to_world.extent();
+            instance
+        })
+    }
+
+    pub fn get_dot_instance(&self, to_world: &BoardToWorld) -> Instance {
+        // Above the guide intersection, one step over the top layer
+        let mut indices = self.plane_positions();
+        indices[to_world.board_axis(1)] = self.board_size();
+
+        let mut dot = Instance::new(to_world.indices_to_world(indices));
+        dot.scale = Vec3::splat(0.125 * to_world.stone_scale() * (1.0 + 2.0 * self.pulse_amount()));  // 1/8th the size of a stone
         
         dot
     }
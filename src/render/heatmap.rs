@@ -0,0 +1,45 @@
+// Analysis overlay: translucent cubes on empty intersections, sized and
+// colored by how much weight the engine puts on playing there
+use super::BoardToWorld;
+use crate::api::Position;
+use glam::Vec3;
+
+// Number of color bands; each band is drawn from its own pre-colored mesh
+pub const LEVELS: usize = 5;
+
+// Cells below this share of the strongest candidate are left out as clutter
+const MIN_INTENSITY: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeatCell {
+    pub position: Vec3, // world space
+    pub intensity: f32, // 0.0..=1.0, relative to the strongest candidate
+}
+
+impl HeatCell {
+    pub fn level(&self) -> usize {
+        ((self.intensity * LEVELS as f32) as usize).min(LEVELS - 1)
+    }
+
+    // Relative to the lattice pitch
+    pub fn scale(&self) -> f32 {
+        0.25 + 0.45 * self.intensity
+    }
+}
+
+// Converts an engine policy to world-space cells
+pub fn cells(policy: &[(Position, f32)], to_world: &BoardToWorld) -> Vec<HeatCell> {
+    let strongest = policy.iter().map(|&(_, weight)| weight).fold(0.0f32, f32::max);
+    if strongest <= 0.0 {
+        return Vec::new();
+    }
+
+    policy
+        .iter()
+        .map(|&(position, weight)| HeatCell {
+            position: to_world.to_world((position.x, position.y, position.z)),
+            intensity: weight / strongest,
+        })
+        .filter(|cell| cell.intensity >= MIN_INTENSITY)
+        .collect()
+}
\ No newline at end of file
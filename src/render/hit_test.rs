@@ -0,0 +1,43 @@
+// Hit-testing for the 2D UI drawn over the board. Pointer input asks here
+// first; only points that miss every panel and button, and the split view's
+// flat board, reach the 3D board.
+use super::annotations::{toolbar_hit, AnnotationTool};
+use super::layer_board::LayerBoard;
+use super::ui::panel_rect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiHit {
+    // Index into the side views
+    SidePanel(usize),
+    ToolbarButton(AnnotationTool),
+    // Over the split view's flat board: the point under the pointer, or None
+    // between the points' cells
+    LayerBoard(Option<(u8, u8, u8)>),
+}
+
+// `toolbar_shown` is whether the annotation toolbar is up, which sits on top;
+// `layer_board` is the split view's flat board, when it's on. The side panels
+// follow the 3D view's right edge, `scene_width`; panels and toolbar are
+// sized by `ui_scale`.
+pub fn hit_test(
+    x: f32,
+    y: f32,
+    scene_width: f32,
+    ui_scale: f32,
+    panel_count: usize,
+    toolbar_shown: bool,
+    layer_board: Option<&LayerBoard>,
+) -> Option<UiHit> {
+    if let Some(tool) = toolbar_hit(x, y, ui_scale).filter(|_| toolbar_shown) {
+        return Some(UiHit::ToolbarButton(tool));
+    }
+    if let Some(board) = layer_board.filter(|board| board.contains(x)) {
+        return Some(UiHit::LayerBoard(board.point_at(x, y)));
+    }
+    (0..panel_count)
+        .find(|&index| {
+            let (left, top, width, height) = panel_rect(index, scene_width, ui_scale);
+            (left..left + width).contains(&x) && (top..top + height).contains(&y)
+        })
+        .map(UiHit::SidePanel)
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+// Analysis overlay: the influence field as a translucent cloud of cubes on the
+// empty points, one color per side, larger where the side's hold is stronger
+use super::BoardToWorld;
+use crate::game::Board;
+use glam::Vec3;
+use std::collections::HashMap;
+
+type Position = (u8, u8, u8);
+
+// Points with less than this share of the strongest influence are left clear
+const MIN_STRENGTH: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InfluenceCell {
+    pub position: Vec3, // world space
+    // 0 for Black's influence, 1 for White's
+    pub side: usize,
+    pub strength: f32, // 0.0..=1.0, relative to the strongest empty point
+}
+
+impl InfluenceCell {
+    // Relative to the lattice pitch
+    pub fn scale(&self) -> f32 {
+        0.3 + 0.6 * self.strength
+    }
+}
+
+// The empty points of `board` under someone's influence
+pub fn cells(field: &HashMap<Position, i32>, board: &Board, to_world: &BoardToWorld) -> Vec<InfluenceCell> {
+    let empty: Vec<(Position, i32)> = board
+        .empty_positions()
+        .filter_map(|position| field.get(&position).map(|&value| (position, value)))
+        .filter(|&(_, value)| value != 0)
+        .collect();
+    let strongest = empty.iter().map(|&(_, value)| value.abs()).max().unwrap_or(0);
+    if strongest == 0 {
+        return Vec::new();
+    }
+
+    empty
+        .into_iter()
+        .map(|(position, value)| InfluenceCell {
+            position: to_world.to_world(position),
+            side: usize::from(value < 0),
+            strength: value.abs() as f32 / strongest as f32,
+        })
+        .filter(|cell| cell.strength >= MIN_STRENGTH)
+        .collect()
+}
@@ -0,0 +1,172 @@
+// The split view's flat board: the layer the guide planes are on, drawn
+// face-on as a 2D Go board in the right half of the window. It's seen from
+// above, so its columns run along world X and its rows along world Z, and the
+// layer is the one the guide picks along the up axis. Points on it map back
+// to the board, so clicks and hovers reach the 3D view too.
+use super::ui_panels::{ScreenMesh, UIVertex};
+use super::{BoardToWorld, BoardView, GuideSystem, Theme};
+use crate::game::GameRules;
+use crate::settings::BlindMode;
+
+type Position = (u8, u8, u8);
+
+const MARGIN: f32 = 40.0;
+// Room above the board for the layer label, and its size, at a UI scale of 1
+const LABEL_SPACE: f32 = 24.0;
+const LABEL_SIZE: f32 = 12.0;
+const BACKGROUND: [f32; 4] = [0.12, 0.12, 0.14, 1.0];
+const WOOD: [f32; 4] = [0.78, 0.62, 0.38, 1.0];
+const LINE: [f32; 4] = [0.2, 0.15, 0.1, 1.0];
+const LINE_WIDTH: f32 = 1.5;
+const STONE_SIDES: usize = 24;
+
+// Where the flat board sits in a window of the given size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerBoard {
+    to_world: BoardToWorld,
+    // The guide intersection; the layer is its coordinate along the up axis
+    guide: Position,
+    layer: usize,
+    // Left edge of the half it's drawn in
+    area_left: f32,
+    // Top-left corner of the board and the spacing of its points, in pixels
+    left: f32,
+    top: f32,
+    cell: f32,
+    // The UI scale the label is drawn at
+    scale: f32,
+}
+
+impl LayerBoard {
+    pub fn new(to_world: BoardToWorld, guide: &GuideSystem, screen_width: f32, screen_height: f32, scale: f32) -> Self {
+        let guide = guide.get_intersection_position();
+        let layer = [guide.0, guide.1, guide.2][to_world.board_axis(1)] as usize;
+        let area_left = screen_width / 2.0;
+        let size = to_world.board_size().max(1) as f32;
+        let label_space = LABEL_SPACE * scale;
+        let cell = ((area_left - 2.0 * MARGIN) / size)
+            .min((screen_height - 2.0 * MARGIN - label_space) / size)
+            .max(1.0);
+        Self {
+            to_world,
+            guide,
+            layer,
+            area_left,
+            left: area_left + (area_left - cell * size) / 2.0,
+            top: label_space + (screen_height - label_space - cell * size) / 2.0,
+            cell,
+            scale,
+        }
+    }
+
+    // Left edge of the flat board's half; the 3D view gets the rest
+    pub fn area_left(&self) -> f32 {
+        self.area_left
+    }
+
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
+    // Column and row of a position, if it's on the layer shown
+    fn cell_of(&self, position: Position) -> Option<(usize, usize)> {
+        let indices = [position.0, position.1, position.2].map(usize::from);
+        (indices[self.to_world.board_axis(1)] == self.layer)
+            .then(|| (indices[self.to_world.board_axis(0)], indices[self.to_world.board_axis(2)]))
+    }
+
+    fn center(&self, (column, row): (usize, usize)) -> (f32, f32) {
+        (
+            self.left + (column as f32 + 0.5) * self.cell,
+            self.top + (row as f32 + 0.5) * self.cell,
+        )
+    }
+
+    // Whether the pointer is over the flat board's half at all
+    pub fn contains(&self, x: f32) -> bool {
+        x >= self.area_left
+    }
+
+    // The board point nearest the pointer, or None off the board
+    pub fn point_at(&self, x: f32, y: f32) -> Option<Position> {
+        let size = self.to_world.board_size();
+        let column = ((x - self.left) / self.cell).floor();
+        let row = ((y - self.top) / self.cell).floor();
+        if column < 0.0 || row < 0.0 || column >= size as f32 || row >= size as f32 {
+            return None;
+        }
+        let mut indices = [0u8; 3];
+        indices[self.to_world.board_axis(0)] = column as u8;
+        indices[self.to_world.board_axis(1)] = self.layer as u8;
+        indices[self.to_world.board_axis(2)] = row as u8;
+        Some((indices[0], indices[1], indices[2]))
+    }
+
+    // "LAYER z 3/9", counted from 1 for people, with where it goes and its
+    // text size
+    pub fn label(&self) -> (String, f32, f32, f32) {
+        let axis = ["x", "y", "z"][self.to_world.board_axis(1)];
+        let text = format!("LAYER {} {}/{}", axis, self.layer + 1, self.to_world.board_size());
+        (text, self.left, self.top - LABEL_SPACE * self.scale, LABEL_SIZE * self.scale)
+    }
+
+    // The board, its stones and markers. Blind training hides colors, and in
+    // hidden mode every stone but the latest, as in the 3D view.
+    pub fn geometry(
+        &self,
+        rules: &GameRules,
+        board: &BoardView,
+        theme: &Theme,
+        blind: BlindMode,
+        screen: (f32, f32),
+    ) -> (Vec<UIVertex>, Vec<u16>) {
+        let mut mesh = ScreenMesh::new(screen.0, screen.1);
+        mesh.rect(self.area_left, 0.0, self.area_left, screen.1, BACKGROUND);
+        let size = self.to_world.board_size();
+        let extent = self.cell * size as f32;
+        mesh.rect(self.left, self.top, extent, extent, WOOD);
+
+        let first = self.cell * 0.5;
+        let last = extent - self.cell * 0.5;
+        for i in 0..size {
+            let along = (i as f32 + 0.5) * self.cell;
+            mesh.segment((self.left + first, self.top + along), (self.left + last, self.top + along), LINE_WIDTH, LINE);
+            mesh.segment((self.left + along, self.top + first), (self.left + along, self.top + last), LINE_WIDTH, LINE);
+        }
+
+        let opaque = |[r, g, b]: [f32; 3]| [r, g, b, 1.0];
+        let last_move = board.last_move();
+        for (&position, &color) in rules.board().get_all_stones() {
+            let Some(cell) = self.cell_of(position) else {
+                continue;
+            };
+            if blind == BlindMode::Hidden && Some(position) != last_move {
+                continue;
+            }
+            let color = match blind {
+                BlindMode::Off => theme.stone_color(color),
+                _ => theme.neutral_stone,
+            };
+            mesh.regular(self.center(cell), self.cell * 0.45, STONE_SIDES, opaque(color));
+        }
+
+        // Last move, then the stone under the cursor, as in the 3D view
+        let markers = [last_move, board.hover()];
+        for (position, color) in markers.into_iter().zip(theme.markers) {
+            if let Some(cell) = position.and_then(|position| self.cell_of(position)) {
+                mesh.regular(self.center(cell), self.cell * 0.12, STONE_SIDES, opaque(color));
+            }
+        }
+
+        // The guide intersection, boxed
+        if let Some(cell) = self.cell_of(self.guide) {
+            let (cx, cy) = self.center(cell);
+            let r = self.cell * 0.5;
+            let corners = [(cx - r, cy - r), (cx + r, cy - r), (cx + r, cy + r), (cx - r, cy + r)];
+            for i in 0..4 {
+                mesh.segment(corners[i], corners[(i + 1) % 4], LINE_WIDTH * 2.0, opaque(theme.guide_dot));
+            }
+        }
+        mesh.into_parts()
+    }
+}
@@ -0,0 +1,21 @@
+// Liberty display mode: a translucent shell over every stone whose group is
+// short of liberties, red in atari and yellow on two, so endangered groups
+// stand out even deep inside the lattice
+use super::{BoardToWorld, Instance};
+use crate::game::Board;
+
+// Shells per level: atari, then two liberties
+pub const LEVELS: usize = 2;
+
+// Shells over the endangered stones of `board`, by level
+pub fn shells(board: &Board, to_world: &BoardToWorld) -> [Vec<Instance>; LEVELS] {
+    let mut shells = [Vec::new(), Vec::new()];
+    for (position, liberties) in board.liberty_counts() {
+        if let Some(level) = liberties.checked_sub(1).filter(|&level| level < LEVELS) {
+            let mut instance = Instance::new(to_world.to_world(position));
+            instance.scale = glam::Vec3::splat(to_world.stone_scale());
+            shells[level].push(instance);
+        }
+    }
+    shells
+}
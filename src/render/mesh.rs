@@ -51,6 +51,13 @@ impl Mesh {
         Self { vertices, indices }
     }
 
+    // Adds `other` to this mesh so both draw in one call
+    pub fn append(&mut self, other: &Mesh) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(&other.vertices);
+        self.indices.extend(other.indices.iter().map(|&index| index + offset));
+    }
+
     pub fn create_cube(size: f32, color: [f32; 3]) -> Self {
         let s = size / 2.0;
         
@@ -151,6 +158,40 @@ impl Mesh {
         Self::new(vertices, indices)
     }
 
+    // Flat-shaded, so each face gets its own three vertices
+    pub fn create_octahedron(radius: f32, color: [f32; 3]) -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for sx in [-1.0f32, 1.0] {
+            for sy in [-1.0f32, 1.0] {
+                for sz in [-1.0f32, 1.0] {
+                    let a = Vec3::new(sx * radius, 0.0, 0.0);
+                    let mut b = Vec3::new(0.0, sy * radius, 0.0);
+                    let mut c = Vec3::new(0.0, 0.0, sz * radius);
+                    // Keep the winding counter-clockwise seen from outside
+                    if sx * sy * sz < 0.0 {
+                        std::mem::swap(&mut b, &mut c);
+                    }
+
+                    let normal = Vec3::new(sx, sy, sz).normalize();
+                    let base = vertices.len() as u32;
+                    for (corner, tex_coords) in [(a, [0.0, 0.0]), (b, [1.0, 0.0]), (c, [0.5, 1.0])] {
+                        vertices.push(Vertex {
+                            position: corner.to_array(),
+                            normal: normal.to_array(),
+                            tex_coords,
+                            color,
+                        });
+                    }
+                    indices.extend([base, base + 1, base + 2]);
+                }
+            }
+        }
+
+        Self::new(vertices, indices)
+    }
+
     pub fn create_line(start: Vec3, end: Vec3, color: [f32; 3]) -> Self {
         let vertices = vec![
             Vertex {
@@ -241,40 +282,4 @@ impl Mesh {
         
         Self::new(vertices, indices)
     }
-
-    pub fn create_guide_plane_xz(size: f32, color: [f32; 3]) -> Self {
-        let s = size / 2.0;
-        
-        let vertices = vec![
-            Vertex { position: [-s, 0.0, -s], normal: [0.0, 1.0, 0.0], tex_coords: [0.0, 0.0], color },
-            Vertex { position: [ s, 0.0, -s], normal: [0.0, 1.0, 0.0], tex_coords: [1.0, 0.0], color },
-            Vertex { position: [ s, 0.0,  s], normal: [0.0, 1.0, 0.0], tex_coords: [1.0, 1.0], color },
-            Vertex { position: [-s, 0.0,  s], normal: [0.0, 1.0, 0.0], tex_coords: [0.0, 1.0], color },
-        ];
-        
-        let indices = vec![
-            0, 1, 2,  0, 2, 3,  // Top
-            0, 3, 2,  0, 2, 1,  // Bottom (double-sided)
-        ];
-        
-        Self::new(vertices, indices)
-    }
-
-    pub fn create_guide_plane_yz(size: f32, color: [f32; 3]) -> Self {
-        let s = size / 2.0;
-        
-        let vertices = vec![
-            Vertex { position: [0.0, -s, -s], normal: [1.0, 0.0, 0.0], tex_coords: [0.0, 0.0], color },
-            Vertex { position: [0.0,  s, -s], normal: [1.0, 0.0, 0.0], tex_coords: [1.0, 0.0], color },
-            Vertex { position: [0.0,  s,  s], normal: [1.0, 0.0, 0.0], tex_coords: [1.0, 1.0], color },
-            Vertex { position: [0.0, -s,  s], normal: [1.0, 0.0, 0.0], tex_coords: [0.0, 1.0], color },
-        ];
-        
-        let indices = vec![
-            0, 1, 2,  0, 2, 3,  // Right
-            0, 3, 2,  0, 2, 1,  // Left (double-sided)
-        ];
-        
-        Self::new(vertices, indices)
-    }
 }
\ No newline at end of file
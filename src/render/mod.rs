@@ -1,19 +1,56 @@
+pub mod animation;
+pub mod annotations;
+pub mod board_to_world;
+pub mod booklet;
+pub mod board_view;
 pub mod camera;
+pub mod clock;
+pub mod gltf;
 pub mod graphics;
+pub mod heatmap;
+pub mod hit_test;
+pub mod influence;
+pub mod layer_board;
+pub mod liberties;
 pub mod mesh;
+pub mod panel_preview;
+pub mod pick_debug;
+pub mod passes;
+pub mod profiler;
+pub mod score_graph;
 pub mod shader;
+pub mod surface;
 pub mod ui;
+pub mod uniforms;
 pub mod text;
+pub mod theme;
+pub mod thumbnail;
 pub mod ui_panels;
+pub mod variation;
+pub mod what_if;
 pub mod guide_system;
 pub mod axis_indicator;
 
-pub use camera::{Camera, CameraController};
+pub use animation::{CaptureAnimations, CaptureStyle, GhostTrails};
+pub use annotations::AnnotationTool;
+pub use board_to_world::BoardToWorld;
+pub use board_view::BoardView;
+pub use camera::{Camera, CameraController, CameraPreset};
+pub use clock::{AnimationClock, Easing, Tween};
 pub use graphics::{Graphics, Instance};
 pub use mesh::{Mesh, Vertex};
+pub use panel_preview::PanelPreviews;
+pub use score_graph::ScoreGraph;
 pub use shader::Shader;
-pub use ui::{UISystem, ViewDirection, SideView};
+pub use surface::FrameStats;
+pub use ui::{PanelLayer, UISystem, ViewDirection, SideView};
 pub use text::{TextRenderer, TextVertex};
-pub use ui_panels::{UIPanels, UIVertex};
+pub use theme::{Light, StoneShape, Theme};
+pub use thumbnail::thumbnail;
+pub use ui_panels::{ScreenMesh, UIPanels, UIVertex};
 pub use guide_system::GuideSystem;
+pub use heatmap::HeatCell;
+pub use influence::InfluenceCell;
+pub use layer_board::LayerBoard;
+pub use hit_test::UiHit;
 pub use axis_indicator::AxisIndicator;
\ No newline at end of file
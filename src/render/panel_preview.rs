@@ -0,0 +1,397 @@
+// Miniature 3D renders for the side panels. Each panel's layer is drawn from
+// its view direction's camera, orthographic so every layer shows at the same
+// size, into a small offscreen texture; the texture is only redrawn when the
+// layer, its stones, the layout or the blind mode change. The UI pass then
+// composites the textures as quads.
+use super::camera::CameraUniform;
+use super::graphics::InstanceRaw;
+use super::passes::{begin_load_pass, mesh_buffers, MeshBuffers};
+use super::uniforms::{UniformRing, ViewUniform};
+use super::{BoardToWorld, Instance, Light, Mesh, PanelLayer, Shader, SideView, TextVertex, Theme, Vertex};
+use crate::game::StoneColor;
+use crate::settings::BlindMode;
+use glam::{Mat4, Vec3};
+use super::profiler;
+
+// Texels per panel pixel, so the renders stay sharp on high-DPI screens
+const TEXELS_PER_PIXEL: u32 = 2;
+// Stone meshes: one per color, then this one for blind training
+const NEUTRAL_MESH: usize = StoneColor::MAX_PLAYERS as usize;
+
+// What a panel's texture was last drawn from
+type DrawnFrom = (Option<PanelLayer>, BoardToWorld, BlindMode);
+
+struct PreviewTarget {
+    color: wgpu::TextureView,
+    depth: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    drawn: Option<DrawnFrom>,
+}
+
+pub struct PanelPreviews {
+    uniforms: UniformRing,
+    stone_pipeline: Shader,
+    box_pipeline: Shader,
+    composite_pipeline: wgpu::RenderPipeline,
+    texture_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    // Black, white and blind-training stones, then the bounding box
+    stone_meshes: [MeshBuffers; NEUTRAL_MESH + 1],
+    box_mesh: MeshBuffers,
+    light: Light,
+    // The surface's format, so colors come out as in the main view
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+    targets: Vec<PreviewTarget>,
+}
+
+impl PanelPreviews {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, panel_width: f32, panel_height: f32, count: usize) -> Self {
+        let uniforms = UniformRing::new(device, count);
+        let stone_pipeline = Shader::create_basic_shader(
+            device,
+            format,
+            &[uniforms.layout()],
+            &[Vertex::desc(), InstanceRaw::desc()],
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+        let box_pipeline = Shader::create_transparent_shader(
+            device,
+            format,
+            &[uniforms.layout()],
+            &[Vertex::desc(), InstanceRaw::desc()],
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("Panel Preview Bind Group Layout"),
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let composite_pipeline = Self::create_composite_pipeline(device, format, &texture_layout);
+
+        let theme = Theme::default();
+        let size = (
+            panel_width as u32 * TEXELS_PER_PIXEL,
+            panel_height as u32 * TEXELS_PER_PIXEL,
+        );
+        let mut previews = Self {
+            uniforms,
+            stone_pipeline,
+            box_pipeline,
+            composite_pipeline,
+            texture_layout,
+            sampler,
+            stone_meshes: Self::create_stone_meshes(device, &theme),
+            box_mesh: mesh_buffers(device, &Mesh::create_transparent_box(1.0, theme.bounding_box)),
+            light: theme.light,
+            format,
+            size,
+            targets: Vec::new(),
+        };
+        previews.targets = (0..count).map(|_| previews.create_target(device)).collect();
+        previews
+    }
+
+    fn create_composite_pipeline(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        texture_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Panel Preview Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/panel_preview.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Panel Preview Pipeline Layout"),
+            bind_group_layouts: &[texture_layout],
+            push_constant_ranges: &[],
+        });
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Panel Preview Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[TextVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn create_stone_meshes(device: &wgpu::Device, theme: &Theme) -> [MeshBuffers; NEUTRAL_MESH + 1] {
+        [
+            mesh_buffers(device, &theme.black_shape.mesh(0.4, theme.black_stone)),
+            mesh_buffers(device, &theme.white_shape.mesh(0.4, theme.white_stone)),
+            mesh_buffers(device, &Mesh::create_sphere(0.4, 20, 20, theme.red_stone)),
+            mesh_buffers(device, &Mesh::create_sphere(0.4, 20, 20, theme.green_stone)),
+            mesh_buffers(device, &Mesh::create_sphere(0.4, 20, 20, theme.neutral_stone)),
+        ]
+    }
+
+    fn create_target(&self, device: &wgpu::Device) -> PreviewTarget {
+        let texture = |label, format, usage| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: self.size.0,
+                        height: self.size.1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+        let color = texture(
+            "Panel Preview Texture",
+            self.format,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let depth = texture(
+            "Panel Preview Depth",
+            wgpu::TextureFormat::Depth32Float,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&color),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+            label: Some("Panel Preview Bind Group"),
+        });
+        PreviewTarget {
+            color,
+            depth,
+            bind_group,
+            drawn: None,
+        }
+    }
+
+    // Stone colors and shapes are baked into the meshes; every panel redraws
+    pub fn set_theme(&mut self, device: &wgpu::Device, theme: &Theme) {
+        self.stone_meshes = Self::create_stone_meshes(device, theme);
+        self.box_mesh = mesh_buffers(device, &Mesh::create_transparent_box(1.0, theme.bounding_box));
+        self.light = theme.light;
+        for target in &mut self.targets {
+            target.drawn = None;
+        }
+    }
+
+    // Looks at the board from the view's side, framing the whole cube
+    fn view_uniform(&self, side_view: &SideView, to_world: &BoardToWorld) -> ViewUniform {
+        let extent = to_world.extent();
+        let (eye, target, up) = side_view.direction.get_camera_position(extent);
+        let half_height = extent * 0.6;
+        let half_width = half_height * self.size.0 as f32 / self.size.1 as f32;
+        let projection = Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, 0.1, extent * 4.0);
+        let camera = CameraUniform::new(projection * Mat4::look_at_rh(eye, target, up), eye);
+        ViewUniform::new(camera, &self.light)
+    }
+
+    // Redraws the panels whose layer changed since they were last drawn
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        side_views: &[SideView],
+        layers: Vec<Option<PanelLayer>>,
+        to_world: &BoardToWorld,
+        blind: BlindMode,
+    ) {
+        let mut stale = Vec::new();
+        self.uniforms.begin_frame();
+        for (index, (side_view, layer)) in side_views.iter().zip(layers).enumerate() {
+            let drawn_from = (layer, *to_world, blind);
+            if self.targets[index].drawn.as_ref() == Some(&drawn_from) {
+                continue;
+            }
+            let offset = self.uniforms.push(self.view_uniform(side_view, to_world));
+            stale.push((index, offset, drawn_from));
+        }
+        if stale.is_empty() {
+            return;
+        }
+        self.uniforms.upload(device, queue);
+
+        // Box instance first, then the layer's stones by color
+        let box_instance = {
+            let mut instance = Instance::new(Vec3::ZERO);
+            instance.scale = Vec3::splat(to_world.extent());
+            instance.to_raw()
+        };
+        for (index, offset, drawn_from) in stale {
+            let mut instances = vec![box_instance];
+            let mut draws = Vec::new();
+            if let Some(layer) = drawn_from.0.as_ref().filter(|_| blind != BlindMode::Hidden) {
+                for color in StoneColor::ALL {
+                    let start = instances.len() as u32;
+                    instances.extend(
+                        layer
+                            .stones
+                            .iter()
+                            .filter(|&&(_, stone)| stone == color)
+                            .map(|&(position, _)| to_world.stone_instance(position).to_raw()),
+                    );
+                    let mesh = if blind == BlindMode::Off { color.index() } else { NEUTRAL_MESH };
+                    draws.push((mesh, start..instances.len() as u32));
+                }
+            }
+            let instance_buffer = profiler::buffer_init(device, &wgpu::util::BufferInitDescriptor {
+                label: Some("Panel Preview Instance Buffer"),
+                contents: bytemuck::cast_slice(&instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+            let target = &self.targets[index];
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Panel Preview Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.color,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        // The panels' dark gray background
+                        load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.1, g: 0.1, b: 0.1, a: 1.0 }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &target.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_bind_group(0, self.uniforms.bind_group(), &[offset]);
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+
+            render_pass.set_pipeline(&self.stone_pipeline.render_pipeline);
+            for (mesh, range) in draws.into_iter().filter(|(_, range)| !range.is_empty()) {
+                let mesh = &self.stone_meshes[mesh];
+                render_pass.set_vertex_buffer(0, mesh.0.slice(..));
+                render_pass.set_index_buffer(mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.2, 0, range);
+            }
+
+            // The box blends over the stones inside it
+            render_pass.set_pipeline(&self.box_pipeline.render_pipeline);
+            render_pass.set_vertex_buffer(0, self.box_mesh.0.slice(..));
+            render_pass.set_index_buffer(self.box_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..self.box_mesh.2, 0, 0..1);
+            drop(render_pass);
+
+            self.targets[index].drawn = Some(drawn_from);
+        }
+    }
+
+    // Draws each panel's texture over its rectangle, given in pixels
+    pub fn composite(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        rects: &[(f32, f32, f32, f32)],
+        (screen_width, screen_height): (f32, f32),
+    ) {
+        if rects.is_empty() {
+            return;
+        }
+        let ndc = |x: f32, y: f32| [(x / screen_width) * 2.0 - 1.0, 1.0 - (y / screen_height) * 2.0];
+        let mut vertices = Vec::new();
+        for &(x, y, width, height) in rects {
+            vertices.extend_from_slice(&[
+                TextVertex { position: ndc(x, y), tex_coords: [0.0, 0.0] },
+                TextVertex { position: ndc(x + width, y), tex_coords: [1.0, 0.0] },
+                TextVertex { position: ndc(x + width, y + height), tex_coords: [1.0, 1.0] },
+                TextVertex { position: ndc(x, y + height), tex_coords: [0.0, 1.0] },
+            ]);
+        }
+        let vertex_buffer = profiler::buffer_init(device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Panel Preview Quad Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Panel Preview Quad Index Buffer"),
+            contents: bytemuck::cast_slice(&[0u16, 1, 2, 0, 2, 3]),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut render_pass = begin_load_pass(encoder, target, "Panel Preview Composite Pass");
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        for (index, target) in self.targets.iter().enumerate().take(rects.len()) {
+            render_pass.set_bind_group(0, &target.bind_group, &[]);
+            render_pass.draw_indexed(0..6, index as i32 * 4, 0..1);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,92 @@
+// The split view's flat layer board in the right half of the window, with its
+// layer label. Draws nothing unless the split view is on.
+use super::{begin_load_pass, Frame, RenderPass};
+use crate::render::layer_board::LayerBoard;
+use crate::render::{BoardToWorld, GuideSystem, TextRenderer, Theme, UIPanels};
+use crate::render::profiler;
+
+pub struct LayerBoardPass {
+    // Only its pipeline is used
+    ui_panels: UIPanels,
+    text_renderer: TextRenderer,
+    theme: Theme,
+    // A copy of the scene's guide, taken each frame
+    guide: GuideSystem,
+    enabled: bool,
+}
+
+impl LayerBoardPass {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
+        Self {
+            ui_panels: UIPanels::new(device, format),
+            text_renderer: TextRenderer::new(device, queue, format),
+            theme: Theme::default(),
+            guide: GuideSystem::new(3),
+            enabled: false,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn set_guide(&mut self, guide: &GuideSystem) {
+        self.guide = guide.clone();
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+}
+
+impl RenderPass for LayerBoardPass {
+    fn record(&mut self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        if !self.enabled {
+            return;
+        }
+        let to_world = BoardToWorld::new(frame.rules.board().size(), &frame.layout);
+        let board = LayerBoard::new(to_world, &self.guide, frame.width, frame.height, frame.ui_scale);
+        let screen = (frame.width, frame.height);
+        let (vertices, indices) = board.geometry(frame.rules, frame.board, &self.theme, frame.blind, screen);
+        let (label, x, y, size) = board.label();
+        let (text_vertices, text_indices) =
+            self.text_renderer.create_text_quad(&label, x, y, size, frame.width, frame.height);
+
+        let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Layer Board Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Layer Board Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let text_vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Layer Board Label Buffer"),
+            contents: bytemuck::cast_slice(&text_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let text_index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Layer Board Label Index Buffer"),
+            contents: bytemuck::cast_slice(&text_indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut render_pass = begin_load_pass(encoder, frame.target, "Layer Board Render Pass");
+        render_pass.set_pipeline(&self.ui_panels.pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+
+        render_pass.set_pipeline(&self.text_renderer.pipeline);
+        render_pass.set_bind_group(0, &self.text_renderer.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, text_vertex_buffer.slice(..));
+        render_pass.set_index_buffer(text_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..text_indices.len() as u32, 0, 0..1);
+    }
+}
@@ -0,0 +1,130 @@
+// The frame is drawn by a few render passes, each owning its pipelines and
+// buffers: the 3D scene, the side panels, the split view's flat layer board,
+// and the overlays (score graph, annotations). Graphics runs them in the order a small frame graph works out
+// from what each pass has to draw over.
+pub mod layer_board;
+pub mod overlay;
+pub mod scene;
+pub mod ui;
+
+pub use layer_board::LayerBoardPass;
+pub use overlay::OverlayPass;
+pub use scene::ScenePass;
+pub use ui::UiPass;
+
+use super::{AnimationClock, BoardView, Camera, Mesh};
+use crate::game::GameRules;
+use crate::settings::{BlindMode, LayoutSettings};
+use crate::render::profiler;
+
+// Vertex buffer, index buffer, index count
+pub type MeshBuffers = (wgpu::Buffer, wgpu::Buffer, u32);
+
+pub fn mesh_buffers(device: &wgpu::Device, mesh: &Mesh) -> MeshBuffers {
+    let vertex_buffer = profiler::buffer_init(device, &wgpu::util::BufferInitDescriptor {
+        label: Some("Vertex Buffer"),
+        contents: bytemuck::cast_slice(&mesh.vertices),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+
+    let index_buffer = profiler::buffer_init(device, &wgpu::util::BufferInitDescriptor {
+        label: Some("Index Buffer"),
+        contents: bytemuck::cast_slice(&mesh.indices),
+        usage: wgpu::BufferUsages::INDEX,
+    });
+
+    (vertex_buffer, index_buffer, mesh.indices.len() as u32)
+}
+
+// Everything a pass may read while recording one frame
+pub struct Frame<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub target: &'a wgpu::TextureView,
+    pub width: f32,
+    pub height: f32,
+    // How much of the width, from the left, the 3D view and its panels get:
+    // all of it, or half in the split view
+    pub scene_width: f32,
+    pub rules: &'a GameRules,
+    pub camera: &'a Camera,
+    pub layout: LayoutSettings,
+    pub blind: BlindMode,
+    // Multiplies every size in the 2D interface; see settings::UiScale
+    pub ui_scale: f32,
+    pub board: &'a BoardView,
+    pub clock: &'a AnimationClock,
+}
+
+pub trait RenderPass {
+    fn record(&mut self, frame: &Frame, encoder: &mut wgpu::CommandEncoder);
+
+    // Size-dependent attachments follow the surface
+    fn resize(&mut self, _device: &wgpu::Device, _width: u32, _height: u32) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassId {
+    Scene,
+    Ui,
+    LayerBoard,
+    Overlay,
+}
+
+impl PassId {
+    // As it appears in profiling output
+    pub fn name(self) -> &'static str {
+        match self {
+            PassId::Scene => "scene",
+            PassId::Ui => "ui",
+            PassId::LayerBoard => "layer_board",
+            PassId::Overlay => "overlay",
+        }
+    }
+}
+
+// Each pass with the passes it draws over; the run order is worked out once
+pub struct FrameGraph {
+    order: Vec<PassId>,
+}
+
+impl FrameGraph {
+    pub fn new(nodes: &[(PassId, &[PassId])]) -> Self {
+        let mut order = Vec::with_capacity(nodes.len());
+        while order.len() < nodes.len() {
+            let ready = nodes
+                .iter()
+                .find(|(id, after)| !order.contains(id) && after.iter().all(|dependency| order.contains(dependency)))
+                .map(|(id, _)| *id)
+                .expect("render passes depend on each other in a cycle, or on a pass that isn't in the graph");
+            order.push(ready);
+        }
+        Self { order }
+    }
+
+    pub fn order(&self) -> &[PassId] {
+        &self.order
+    }
+}
+
+// A pass that draws over what is already in `target`, without depth
+pub fn begin_load_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    target: &'a wgpu::TextureView,
+    label: &'static str,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
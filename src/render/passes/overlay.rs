@@ -0,0 +1,326 @@
+// Screen-space overlays drawn over the board and panels: the score graph,
+// review annotations with their letters and toolbar, the score rows of
+// games with more than two players, the position info panel, and a banner
+// across the middle.
+use super::{begin_load_pass, Frame, RenderPass};
+use crate::game::Mark;
+use crate::render::{annotations, AnnotationTool, ScoreGraph, ScreenMesh, TextRenderer, UIPanels};
+use glam::Vec3;
+use crate::render::profiler;
+
+pub struct OverlayPass {
+    // Only its pipeline is used; the panel geometry belongs to the UI pass
+    ui_panels: UIPanels,
+    text_renderer: TextRenderer,
+
+    score_graph: Option<ScoreGraph>,
+    // One line per player, top right
+    score_rows: Vec<String>,
+    // The position summary, bottom right; empty when it's off
+    stats_rows: Vec<String>,
+
+    // Review markup of the current node, and the toolbar while annotating
+    annotation_marks: Vec<(Vec3, Mark)>,
+    annotation_tool: Option<AnnotationTool>,
+
+    // A line of text on a dark strip, centred in the window
+    banner: Option<String>,
+}
+
+impl OverlayPass {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
+        Self {
+            ui_panels: UIPanels::new(device, format),
+            text_renderer: TextRenderer::new(device, queue, format),
+            score_graph: None,
+            score_rows: Vec::new(),
+            stats_rows: Vec::new(),
+            annotation_marks: Vec::new(),
+            annotation_tool: None,
+            banner: None,
+        }
+    }
+
+    pub fn set_score_graph(&mut self, graph: Option<ScoreGraph>) {
+        self.score_graph = graph;
+    }
+
+    pub fn set_score_rows(&mut self, rows: Vec<String>) {
+        self.score_rows = rows;
+    }
+
+    pub fn set_stats_rows(&mut self, rows: Vec<String>) {
+        self.stats_rows = rows;
+    }
+
+    // World position and mark of each annotation to show
+    pub fn set_annotations(&mut self, marks: Vec<(Vec3, Mark)>) {
+        self.annotation_marks = marks;
+    }
+
+    // Shows the toolbar with `tool` selected, or hides it
+    pub fn set_annotation_tool(&mut self, tool: Option<AnnotationTool>) {
+        self.annotation_tool = tool;
+    }
+
+    pub fn annotation_tool(&self) -> Option<AnnotationTool> {
+        self.annotation_tool
+    }
+
+    pub fn set_banner(&mut self, banner: Option<String>) {
+        self.banner = banner;
+    }
+
+    fn render_score_graph(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        let Some(graph) = &self.score_graph else {
+            return;
+        };
+        let (vertices, indices) = graph.geometry(frame.width, frame.height, frame.ui_scale);
+
+        let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Score Graph Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Score Graph Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut render_pass = begin_load_pass(encoder, frame.target, "Score Graph Render Pass");
+        render_pass.set_pipeline(&self.ui_panels.pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+    }
+
+    fn render_score_rows(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        const SIZE: f32 = 16.0;
+        const MARGIN: f32 = 12.0;
+        let (size, margin) = (SIZE * frame.ui_scale, MARGIN * frame.ui_scale);
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for (row, text) in self.score_rows.iter().enumerate() {
+            let x = frame.scene_width - margin - text.chars().count() as f32 * size;
+            let y = margin + row as f32 * size * 1.5;
+            let (quad_vertices, quad_indices) =
+                self.text_renderer.create_text_quad(text, x, y, size, frame.width, frame.height);
+            let offset = vertices.len() as u16;
+            vertices.extend(quad_vertices);
+            indices.extend(quad_indices.iter().map(|&index| index + offset));
+        }
+        if indices.is_empty() {
+            return;
+        }
+
+        let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Score Row Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Score Row Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut render_pass = begin_load_pass(encoder, frame.target, "Score Row Render Pass");
+        render_pass.set_pipeline(&self.text_renderer.pipeline);
+        render_pass.set_bind_group(0, &self.text_renderer.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+    }
+
+    // The rows on a dark backing in the bottom-right corner of the scene
+    fn render_stats_panel(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        const SIZE: f32 = 12.0;
+        const MARGIN: f32 = 12.0;
+        const PADDING: f32 = 8.0;
+        const BACKING: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+        if self.stats_rows.is_empty() {
+            return;
+        }
+        let (width, height) = (frame.width, frame.height);
+        let (size, margin, padding) = (SIZE * frame.ui_scale, MARGIN * frame.ui_scale, PADDING * frame.ui_scale);
+        let columns = self.stats_rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        let line = size * 1.5;
+        let panel_width = columns as f32 * size + 2.0 * padding;
+        let panel_height = self.stats_rows.len() as f32 * line - (line - size) + 2.0 * padding;
+        let left = frame.scene_width - margin - panel_width;
+        let top = height - margin - panel_height;
+        let mut backing = ScreenMesh::new(width, height);
+        backing.rect(left, top, panel_width, panel_height, BACKING);
+        let (vertices, indices) = backing.into_parts();
+
+        let mut text_vertices = Vec::new();
+        let mut text_indices = Vec::new();
+        for (row, text) in self.stats_rows.iter().enumerate() {
+            let y = top + padding + row as f32 * line;
+            let (quad_vertices, quad_indices) =
+                self.text_renderer.create_text_quad(text, left + padding, y, size, width, height);
+            let offset = text_vertices.len() as u16;
+            text_vertices.extend(quad_vertices);
+            text_indices.extend(quad_indices.iter().map(|&index| index + offset));
+        }
+
+        let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Stats Panel Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Stats Panel Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let text_vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Stats Panel Text Buffer"),
+            contents: bytemuck::cast_slice(&text_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let text_index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Stats Panel Text Index Buffer"),
+            contents: bytemuck::cast_slice(&text_indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut render_pass = begin_load_pass(encoder, frame.target, "Stats Panel Render Pass");
+        render_pass.set_pipeline(&self.ui_panels.pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+
+        render_pass.set_pipeline(&self.text_renderer.pipeline);
+        render_pass.set_bind_group(0, &self.text_renderer.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, text_vertex_buffer.slice(..));
+        render_pass.set_index_buffer(text_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..text_indices.len() as u32, 0, 0..1);
+    }
+
+    fn render_annotations(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        let (width, height, scale) = (frame.width, frame.height, frame.ui_scale);
+        let view_proj = frame.camera.build_view_projection_matrix();
+        let (mut vertices, mut indices, mut labels) =
+            annotations::billboards(&self.annotation_marks, &view_proj, frame.scene_width, width, height, scale);
+        if let Some(tool) = self.annotation_tool {
+            let (toolbar_vertices, toolbar_indices, toolbar_labels) = annotations::toolbar(tool, width, height, scale);
+            let offset = vertices.len() as u16;
+            vertices.extend(toolbar_vertices);
+            indices.extend(toolbar_indices.iter().map(|&index| index + offset));
+            labels.extend(toolbar_labels);
+        }
+        if indices.is_empty() {
+            return;
+        }
+
+        let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Annotation Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Annotation Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        // Letters go in the same pass, after the shapes they sit on
+        let mut text_vertices = Vec::new();
+        let mut text_indices = Vec::new();
+        for (letter, x, y) in labels {
+            let (quad_vertices, quad_indices) =
+                self.text_renderer.create_text_quad(&letter.to_string(), x, y, annotations::TEXT_SIZE * scale, width, height);
+            let offset = text_vertices.len() as u16;
+            text_vertices.extend(quad_vertices);
+            text_indices.extend(quad_indices.iter().map(|&index| index + offset));
+        }
+        let text_buffers = (!text_indices.is_empty()).then(|| {
+            let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+                label: Some("Annotation Text Buffer"),
+                contents: bytemuck::cast_slice(&text_vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+                label: Some("Annotation Text Index Buffer"),
+                contents: bytemuck::cast_slice(&text_indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+            (vertex_buffer, index_buffer)
+        });
+
+        let mut render_pass = begin_load_pass(encoder, frame.target, "Annotation Render Pass");
+        render_pass.set_pipeline(&self.ui_panels.pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+
+        if let Some((vertex_buffer, index_buffer)) = &text_buffers {
+            render_pass.set_pipeline(&self.text_renderer.pipeline);
+            render_pass.set_bind_group(0, &self.text_renderer.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..text_indices.len() as u32, 0, 0..1);
+        }
+    }
+
+    fn render_banner(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        const SIZE: f32 = 24.0;
+        const STRIP: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+        let Some(text) = &self.banner else {
+            return;
+        };
+        let (width, height) = (frame.width, frame.height);
+        let size = SIZE * frame.ui_scale;
+        let mut strip = ScreenMesh::new(width, height);
+        strip.rect(0.0, (height - size) / 2.0 - size, width, size * 3.0, STRIP);
+        let (vertices, indices) = strip.into_parts();
+        let x = (width - text.chars().count() as f32 * size) / 2.0;
+        let (text_vertices, text_indices) =
+            self.text_renderer.create_text_quad(text, x, (height - size) / 2.0, size, width, height);
+
+        let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Banner Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Banner Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let text_vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Banner Text Buffer"),
+            contents: bytemuck::cast_slice(&text_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let text_index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("Banner Text Index Buffer"),
+            contents: bytemuck::cast_slice(&text_indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut render_pass = begin_load_pass(encoder, frame.target, "Banner Render Pass");
+        render_pass.set_pipeline(&self.ui_panels.pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+
+        render_pass.set_pipeline(&self.text_renderer.pipeline);
+        render_pass.set_bind_group(0, &self.text_renderer.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, text_vertex_buffer.slice(..));
+        render_pass.set_index_buffer(text_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..text_indices.len() as u32, 0, 0..1);
+    }
+}
+
+impl RenderPass for OverlayPass {
+    fn record(&mut self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        self.render_score_graph(frame, encoder);
+        self.render_score_rows(frame, encoder);
+        self.render_stats_panel(frame, encoder);
+        self.render_annotations(frame, encoder);
+        self.render_banner(frame, encoder);
+    }
+}
@@ -0,0 +1,959 @@
+// The 3D board: bounding box, guide planes or crosshair, stones, analysis
+// overlays and the axis indicator, drawn once per eye when stereo output is on.
+use super::{mesh_buffers, Frame, MeshBuffers, RenderPass};
+use crate::render::graphics::InstanceRaw;
+use crate::render::uniforms::{UniformRing, ViewUniform};
+use crate::render::{
+    heatmap, liberties, what_if, AxisIndicator, BoardToWorld, GuideSystem, HeatCell, InfluenceCell, Instance, Mesh, Shader, Theme, Vertex,
+};
+use crate::game::StoneColor;
+use crate::settings::{BlindMode, GuideStyle, StereoMode, StereoSettings};
+use glam::Vec3;
+use crate::render::profiler;
+
+// Pipelines that only write one eye's color channels (anaglyph stereo)
+struct EyePipelines {
+    sphere: Shader,
+    transparent: Shader,
+}
+
+impl EyePipelines {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat, view_layout: &wgpu::BindGroupLayout, write_mask: wgpu::ColorWrites) -> Self {
+        Self {
+            sphere: Shader::create_basic_shader_masked(
+                device,
+                format,
+                &[view_layout],
+                &[Vertex::desc(), InstanceRaw::desc()],
+                wgpu::PrimitiveTopology::TriangleList,
+                write_mask,
+            ),
+            transparent: Shader::create_transparent_shader_masked(
+                device,
+                format,
+                &[view_layout],
+                &[Vertex::desc(), InstanceRaw::desc()],
+                wgpu::PrimitiveTopology::TriangleList,
+                write_mask,
+            ),
+        }
+    }
+}
+
+// Per-frame instance buffers, drawn once per eye
+struct SceneBuffers {
+    black_stones: Option<(wgpu::Buffer, u32)>,
+    white_stones: Option<(wgpu::Buffer, u32)>,
+    // (mesh, instances, count) for Red and Green in games of more than two
+    extra_stones: Vec<(usize, wgpu::Buffer, u32)>,
+    // (color band, instances, count) for each non-empty heatmap band
+    heatmap: Vec<(usize, wgpu::Buffer, u32)>,
+    // (side, instances, count) for each side with influence to show
+    influence: Vec<(usize, wgpu::Buffer, u32)>,
+    // (level, instances, count) for the liberty shells
+    liberties: Vec<(usize, wgpu::Buffer, u32)>,
+    // (kind, instances, count) for the what-if preview shells
+    what_if: Vec<(usize, wgpu::Buffer, u32)>,
+    // (color, instances, count) for the ghost trails
+    ghosts: Vec<(usize, wgpu::Buffer, u32)>,
+    // (color, instance) for the staged pre-move
+    premove: Option<(usize, wgpu::Buffer)>,
+    pick_ray: Option<(wgpu::Buffer, u32)>,
+    pick_cell: Option<(wgpu::Buffer, u32)>,
+    legal_moves: Option<(wgpu::Buffer, u32)>,
+    holes: Option<(wgpu::Buffer, u32)>,
+    contested: Option<(wgpu::Buffer, u32)>,
+}
+
+// An instance buffer kept across frames and rewritten in place, growing
+// only when more instances are written than it has room for
+struct DynamicInstances {
+    label: &'static str,
+    buffer: wgpu::Buffer,
+    capacity: usize,
+}
+
+impl DynamicInstances {
+    fn new(device: &wgpu::Device, label: &'static str, capacity: usize) -> Self {
+        Self {
+            label,
+            buffer: Self::allocate(device, label, capacity),
+            capacity,
+        }
+    }
+
+    fn allocate(device: &wgpu::Device, label: &'static str, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: (capacity * std::mem::size_of::<InstanceRaw>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, instances: &[InstanceRaw]) {
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            self.buffer = Self::allocate(device, self.label, self.capacity);
+        }
+        profiler::write_buffer(queue, &self.buffer, 0, bytemuck::cast_slice(instances));
+    }
+}
+
+// Slots in the auxiliary instance buffers
+const BOX_INSTANCE: u32 = 0;
+const PLANE_INSTANCES: std::ops::Range<u32> = 1..4;
+const DOT_INSTANCE: u32 = 0;
+const AXES_INSTANCE: u32 = 1;
+
+fn instance_buffer(device: &wgpu::Device, label: &'static str, instances: &[InstanceRaw]) -> wgpu::Buffer {
+    profiler::buffer_init(device, &wgpu::util::BufferInitDescriptor {
+        label: Some(label),
+        contents: bytemuck::cast_slice(instances),
+        usage: wgpu::BufferUsages::VERTEX,
+    })
+}
+
+fn stone_buffer(device: &wgpu::Device, label: &'static str, stones: &[Instance]) -> Option<(wgpu::Buffer, u32)> {
+    if stones.is_empty() {
+        return None;
+    }
+    let stone_data: Vec<InstanceRaw> = stones.iter().map(|i| i.to_raw()).collect();
+    Some((instance_buffer(device, label, &stone_data), stones.len() as u32))
+}
+
+fn depth_target(device: &wgpu::Device, width: u32, height: u32) -> (wgpu::Texture, wgpu::TextureView) {
+    let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Depth Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Depth32Float,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    (depth_texture, view)
+}
+
+pub struct ScenePass {
+    // Camera and light for each view drawn this frame
+    uniforms: UniformRing,
+
+    // Stereo output: a second view for the right eye
+    stereo: StereoSettings,
+    anaglyph_left: EyePipelines,
+    anaglyph_right: EyePipelines,
+
+    depth: (wgpu::Texture, wgpu::TextureView),
+
+    // Stone and overlay meshes carry the theme's colors and shapes
+    theme: Theme,
+    black_sphere_mesh: MeshBuffers,
+    white_sphere_mesh: MeshBuffers,
+    // Red, then Green
+    extra_stone_meshes: [MeshBuffers; 2],
+    // Blind training draws both colors with this mesh
+    neutral_sphere_mesh: MeshBuffers,
+    transparent_box_mesh: MeshBuffers,
+
+    // Guide system meshes; one plane mesh is turned per instance for all three
+    // planes, one cube stretched per instance for all three crosshair lines
+    guide_plane_mesh: MeshBuffers,
+    guide_line_mesh: MeshBuffers,
+    guide_dot_mesh: MeshBuffers,
+    guide_style: GuideStyle,
+
+    // Box, guide planes or lines and board markers, then guide dot and axis indicator:
+    // the small per-frame geometry, batched into one buffer per pipeline
+    transparent_aux: DynamicInstances,
+    opaque_aux: DynamicInstances,
+
+    // Last move, hover and selection boxes, and which of them this frame
+    // draws: (marker, instance in transparent_aux)
+    marker_meshes: Vec<MeshBuffers>,
+    marker_draws: Vec<(usize, u32)>,
+
+    // Analysis heatmap: one cube mesh per color band
+    heatmap_meshes: Vec<MeshBuffers>,
+    heatmap_instances: Vec<Vec<InstanceRaw>>,
+
+    // Influence cloud: a cube mesh per side
+    influence_meshes: [MeshBuffers; 2],
+    influence_instances: [Vec<InstanceRaw>; 2],
+
+    // Liberty display mode: a slightly larger sphere per level over each stone short of liberties
+    liberty_meshes: [MeshBuffers; liberties::LEVELS],
+    liberty_instances: [Vec<InstanceRaw>; liberties::LEVELS],
+
+    // What-if preview: shells a little wider than the liberty shells, so both show
+    what_if_meshes: [MeshBuffers; what_if::KINDS],
+    what_if_instances: [Vec<InstanceRaw>; what_if::KINDS],
+
+    // Ghost trails while scrubbing: a translucent stone per color
+    ghost_meshes: [MeshBuffers; StoneColor::MAX_PLAYERS as usize],
+    ghost_instances: [Vec<InstanceRaw>; StoneColor::MAX_PLAYERS as usize],
+    // A pre-move waiting for its turn, as a ghost of its color
+    premove: Option<(usize, InstanceRaw)>,
+
+    // Legal-move hints: faint dots on every point the side to move may play
+    legal_move_mesh: MeshBuffers,
+    legal_move_instances: Vec<InstanceRaw>,
+
+    // Picking debug view: the last pick ray and the cell it found, when shown
+    pick_ray_mesh: MeshBuffers,
+    pick_cell_mesh: MeshBuffers,
+    pick_ray_instances: Vec<InstanceRaw>,
+    pick_cell_instances: Vec<InstanceRaw>,
+
+    // Voids: a dark cube on each point cut out of the board
+    hole_mesh: MeshBuffers,
+    // Faint boxes around the points a victory condition is fought over
+    contested_mesh: MeshBuffers,
+
+    sphere_shader: Shader,
+    transparent_shader: Shader,
+
+    guide_system: GuideSystem,
+    axis_indicator: AxisIndicator,
+}
+
+impl ScenePass {
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+        // One view, or two with stereo output
+        let uniforms = UniformRing::new(device, 2);
+        let view_layout = uniforms.layout();
+
+        log::warn!("🔍 Creating DEPTH texture with sample_count=1");
+        let depth = depth_target(device, config.width, config.height);
+        log::warn!("✅ DEPTH texture created successfully");
+
+        let theme = Theme::default();
+        let black_sphere_mesh = mesh_buffers(device, &theme.black_shape.mesh(0.4, theme.black_stone));
+        let white_sphere_mesh = mesh_buffers(device, &theme.white_shape.mesh(0.4, theme.white_stone));
+        let extra_stone_meshes = Self::create_extra_stone_meshes(device, &theme);
+        let neutral_sphere_mesh = mesh_buffers(device, &Mesh::create_sphere(0.4, 20, 20, theme.neutral_stone));
+
+        let transparent_box_data = Mesh::create_transparent_box(1.0, theme.bounding_box);  // Unit cube, will scale based on board
+        let transparent_box_mesh = mesh_buffers(device, &transparent_box_data);
+
+        // Create guide plane meshes (very faint yellow)
+        let guide_plane_mesh = mesh_buffers(device, &Mesh::create_guide_plane_xy(1.0, theme.guide_plane));
+        // Crosshair lines take the dot's color, so the two read as one guide
+        let guide_line_mesh = mesh_buffers(device, &Mesh::create_cube(1.0, theme.guide_dot));
+
+        let heatmap_meshes = Self::create_heatmap_meshes(device, &theme);
+        let influence_meshes = Self::create_influence_meshes(device, &theme);
+        let liberty_meshes = Self::create_liberty_meshes(device, &theme);
+        let what_if_meshes = Self::create_what_if_meshes(device, &theme);
+        let ghost_meshes = Self::create_ghost_meshes(device, &theme);
+        let marker_meshes = Self::create_marker_meshes(device, &theme);
+        let legal_move_mesh = mesh_buffers(device, &Mesh::create_sphere(0.08, 8, 8, theme.legal_move));
+        let hole_mesh = mesh_buffers(device, &Mesh::create_cube(1.0, theme.hole));
+        let pick_ray_mesh = mesh_buffers(device, &Mesh::create_cube(1.0, theme.pick_ray));
+        let pick_cell_mesh = mesh_buffers(device, &Mesh::create_transparent_box(1.0, theme.pick_ray));
+        let contested_mesh = mesh_buffers(device, &Mesh::create_transparent_box(1.0, theme.contested));
+
+        // Create guide dot mesh (blue, 1/8 size)
+        let guide_dot_mesh = mesh_buffers(device, &Mesh::create_sphere(0.05, 10, 10, theme.guide_dot));
+
+        let sphere_shader = Shader::create_basic_shader(
+            device,
+            config.format,
+            &[view_layout],
+            &[Vertex::desc(), InstanceRaw::desc()],
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+
+        let transparent_shader = Shader::create_transparent_shader(
+            device,
+            config.format,
+            &[view_layout],
+            &[Vertex::desc(), InstanceRaw::desc()],
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+
+        let anaglyph_left = EyePipelines::new(device, config.format, view_layout, wgpu::ColorWrites::RED);
+        let anaglyph_right = EyePipelines::new(
+            device,
+            config.format,
+            view_layout,
+            wgpu::ColorWrites::GREEN | wgpu::ColorWrites::BLUE,
+        );
+
+        Self {
+            uniforms,
+            stereo: StereoSettings::default(),
+            anaglyph_left,
+            anaglyph_right,
+            depth,
+            theme,
+            black_sphere_mesh,
+            white_sphere_mesh,
+            extra_stone_meshes,
+            neutral_sphere_mesh,
+            transparent_box_mesh,
+            guide_plane_mesh,
+            guide_line_mesh,
+            guide_dot_mesh,
+            guide_style: GuideStyle::default(),
+            // Box, planes or lines and up to three markers
+            transparent_aux: DynamicInstances::new(device, "Transparent Aux Buffer", PLANE_INSTANCES.end as usize + 3),
+            opaque_aux: DynamicInstances::new(device, "Opaque Aux Buffer", AXES_INSTANCE as usize + 1),
+            marker_meshes,
+            marker_draws: Vec::new(),
+            heatmap_meshes,
+            heatmap_instances: vec![Vec::new(); heatmap::LEVELS],
+            influence_meshes,
+            influence_instances: [Vec::new(), Vec::new()],
+            liberty_meshes,
+            liberty_instances: [Vec::new(), Vec::new()],
+            what_if_meshes,
+            what_if_instances: [Vec::new(), Vec::new()],
+            ghost_meshes,
+            ghost_instances: Default::default(),
+            premove: None,
+            legal_move_mesh,
+            legal_move_instances: Vec::new(),
+            pick_ray_mesh,
+            pick_cell_mesh,
+            pick_ray_instances: Vec::new(),
+            pick_cell_instances: Vec::new(),
+            hole_mesh,
+            contested_mesh,
+            sphere_shader,
+            transparent_shader,
+            guide_system: GuideSystem::new(3),  // 3x3x3 board
+            axis_indicator: AxisIndicator::new(device),
+        }
+    }
+
+    fn create_extra_stone_meshes(device: &wgpu::Device, theme: &Theme) -> [MeshBuffers; 2] {
+        [theme.red_stone, theme.green_stone].map(|color| mesh_buffers(device, &Mesh::create_sphere(0.4, 20, 20, color)))
+    }
+
+    fn create_heatmap_meshes(device: &wgpu::Device, theme: &Theme) -> Vec<MeshBuffers> {
+        theme
+            .heatmap
+            .iter()
+            .map(|&color| mesh_buffers(device, &Mesh::create_transparent_box(1.0, color)))
+            .collect()
+    }
+
+    fn create_influence_meshes(device: &wgpu::Device, theme: &Theme) -> [MeshBuffers; 2] {
+        theme.influence.map(|color| mesh_buffers(device, &Mesh::create_transparent_box(1.0, color)))
+    }
+
+    fn create_liberty_meshes(device: &wgpu::Device, theme: &Theme) -> [MeshBuffers; liberties::LEVELS] {
+        theme.liberties.map(|color| mesh_buffers(device, &Mesh::create_sphere(0.46, 20, 20, color)))
+    }
+
+    fn create_what_if_meshes(device: &wgpu::Device, theme: &Theme) -> [MeshBuffers; what_if::KINDS] {
+        theme.what_if.map(|color| mesh_buffers(device, &Mesh::create_sphere(0.5, 20, 20, color)))
+    }
+
+    fn create_ghost_meshes(device: &wgpu::Device, theme: &Theme) -> [MeshBuffers; StoneColor::MAX_PLAYERS as usize] {
+        StoneColor::ALL.map(|color| mesh_buffers(device, &Mesh::create_sphere(0.4, 16, 16, theme.stone_color(color))))
+    }
+
+    fn create_marker_meshes(device: &wgpu::Device, theme: &Theme) -> Vec<MeshBuffers> {
+        theme
+            .markers
+            .iter()
+            .map(|&color| mesh_buffers(device, &Mesh::create_transparent_box(1.0, color)))
+            .collect()
+    }
+
+    pub fn guide_style(&self) -> GuideStyle {
+        self.guide_style
+    }
+
+    pub fn set_guide_style(&mut self, style: GuideStyle) {
+        self.guide_style = style;
+    }
+
+    pub fn guide_system(&self) -> &GuideSystem {
+        &self.guide_system
+    }
+
+    pub fn guide_system_mut(&mut self) -> &mut GuideSystem {
+        &mut self.guide_system
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    // Colors are baked into the vertices, so the affected meshes are rebuilt
+    pub fn set_theme(&mut self, device: &wgpu::Device, theme: Theme) {
+        self.theme = theme;
+        self.black_sphere_mesh = mesh_buffers(device, &theme.black_shape.mesh(0.4, theme.black_stone));
+        self.white_sphere_mesh = mesh_buffers(device, &theme.white_shape.mesh(0.4, theme.white_stone));
+        self.extra_stone_meshes = Self::create_extra_stone_meshes(device, &theme);
+        self.neutral_sphere_mesh = mesh_buffers(device, &Mesh::create_sphere(0.4, 20, 20, theme.neutral_stone));
+        self.transparent_box_mesh = mesh_buffers(device, &Mesh::create_transparent_box(1.0, theme.bounding_box));
+        self.guide_plane_mesh = mesh_buffers(device, &Mesh::create_guide_plane_xy(1.0, theme.guide_plane));
+        self.guide_line_mesh = mesh_buffers(device, &Mesh::create_cube(1.0, theme.guide_dot));
+        self.guide_dot_mesh = mesh_buffers(device, &Mesh::create_sphere(0.05, 10, 10, theme.guide_dot));
+        self.heatmap_meshes = Self::create_heatmap_meshes(device, &theme);
+        self.influence_meshes = Self::create_influence_meshes(device, &theme);
+        self.liberty_meshes = Self::create_liberty_meshes(device, &theme);
+        self.what_if_meshes = Self::create_what_if_meshes(device, &theme);
+        self.ghost_meshes = Self::create_ghost_meshes(device, &theme);
+        self.marker_meshes = Self::create_marker_meshes(device, &theme);
+        self.legal_move_mesh = mesh_buffers(device, &Mesh::create_sphere(0.08, 8, 8, theme.legal_move));
+        self.hole_mesh = mesh_buffers(device, &Mesh::create_cube(1.0, theme.hole));
+        self.pick_ray_mesh = mesh_buffers(device, &Mesh::create_cube(1.0, theme.pick_ray));
+        self.pick_cell_mesh = mesh_buffers(device, &Mesh::create_transparent_box(1.0, theme.pick_ray));
+        self.contested_mesh = mesh_buffers(device, &Mesh::create_transparent_box(1.0, theme.contested));
+    }
+
+    // Replaces the analysis overlay; an empty slice hides it
+    pub fn set_heatmap(&mut self, cells: &[HeatCell], pitch: f32) {
+        for band in &mut self.heatmap_instances {
+            band.clear();
+        }
+        for cell in cells {
+            let mut instance = Instance::new(cell.position);
+            instance.scale = Vec3::splat(cell.scale() * pitch);
+            self.heatmap_instances[cell.level()].push(instance.to_raw());
+        }
+    }
+
+    // Replaces the influence cloud; an empty slice hides it
+    pub fn set_influence(&mut self, cells: &[InfluenceCell], pitch: f32) {
+        for side in &mut self.influence_instances {
+            side.clear();
+        }
+        for cell in cells {
+            let mut instance = Instance::new(cell.position);
+            instance.scale = Vec3::splat(cell.scale() * pitch);
+            self.influence_instances[cell.side].push(instance.to_raw());
+        }
+    }
+
+    // Replaces the liberty shells; empty lists hide them
+    pub fn set_liberty_shells(&mut self, shells: &[Vec<Instance>; liberties::LEVELS]) {
+        for (level, instances) in shells.iter().enumerate() {
+            self.liberty_instances[level] = instances.iter().map(Instance::to_raw).collect();
+        }
+    }
+
+    // Replaces the what-if preview shells; empty lists hide them
+    pub fn set_what_if(&mut self, shells: &[Vec<Instance>; what_if::KINDS]) {
+        for (kind, instances) in shells.iter().enumerate() {
+            self.what_if_instances[kind] = instances.iter().map(Instance::to_raw).collect();
+        }
+    }
+
+    // Replaces the ghost trails, by color; empty lists hide them
+    pub fn set_ghosts(&mut self, ghosts: &[Vec<Instance>; StoneColor::MAX_PLAYERS as usize]) {
+        for (color, instances) in ghosts.iter().enumerate() {
+            self.ghost_instances[color] = instances.iter().map(Instance::to_raw).collect();
+        }
+    }
+
+    // The staged pre-move and the color it's for; None hides it
+    pub fn set_premove(&mut self, premove: Option<(Instance, StoneColor)>) {
+        self.premove = premove.map(|(instance, color)| (color.index(), instance.to_raw()));
+    }
+
+    // The pick ray debug view; None hides it
+    pub fn set_pick_debug(&mut self, ray: Option<Instance>, cell: Option<Instance>) {
+        self.pick_ray_instances = ray.iter().map(Instance::to_raw).collect();
+        self.pick_cell_instances = cell.iter().map(Instance::to_raw).collect();
+    }
+
+    // World positions of the legal-move dots; an empty slice hides them
+    pub fn set_legal_moves(&mut self, positions: &[Vec3], pitch: f32) {
+        self.legal_move_instances = positions
+            .iter()
+            .map(|&position| {
+                let mut instance = Instance::new(position);
+                instance.scale = Vec3::splat(pitch);
+                instance.to_raw()
+            })
+            .collect();
+    }
+
+    pub fn stereo(&self) -> StereoSettings {
+        self.stereo
+    }
+
+    pub fn set_stereo(&mut self, stereo: StereoSettings) {
+        self.stereo = stereo;
+    }
+
+    fn stone_meshes(&self, blind: BlindMode) -> (&MeshBuffers, &MeshBuffers) {
+        match blind {
+            BlindMode::Off => (&self.black_sphere_mesh, &self.white_sphere_mesh),
+            BlindMode::OneColor | BlindMode::Hidden => (&self.neutral_sphere_mesh, &self.neutral_sphere_mesh),
+        }
+    }
+
+    fn extra_stone_mesh(&self, index: usize, blind: BlindMode) -> &MeshBuffers {
+        match blind {
+            BlindMode::Off => &self.extra_stone_meshes[index],
+            BlindMode::OneColor | BlindMode::Hidden => &self.neutral_sphere_mesh,
+        }
+    }
+
+    fn buffers(&self, frame: &Frame) -> SceneBuffers {
+        let device = frame.device;
+
+        let heatmap = self
+            .heatmap_instances
+            .iter()
+            .enumerate()
+            .filter(|(_, band)| !band.is_empty())
+            .map(|(level, band)| (level, instance_buffer(device, "Heatmap Buffer", band), band.len() as u32))
+            .collect();
+
+        let influence = self
+            .influence_instances
+            .iter()
+            .enumerate()
+            .filter(|(_, side)| !side.is_empty())
+            .map(|(index, side)| (index, instance_buffer(device, "Influence Buffer", side), side.len() as u32))
+            .collect();
+
+        let liberties = self
+            .liberty_instances
+            .iter()
+            .enumerate()
+            .filter(|(_, level)| !level.is_empty())
+            .map(|(index, level)| (index, instance_buffer(device, "Liberty Shell Buffer", level), level.len() as u32))
+            .collect();
+
+        let what_if = self
+            .what_if_instances
+            .iter()
+            .enumerate()
+            .filter(|(_, kind)| !kind.is_empty())
+            .map(|(index, kind)| (index, instance_buffer(device, "What-If Buffer", kind), kind.len() as u32))
+            .collect();
+
+        let ghosts = self
+            .ghost_instances
+            .iter()
+            .enumerate()
+            .filter(|(_, color)| !color.is_empty())
+            .map(|(index, color)| (index, instance_buffer(device, "Ghost Buffer", color), color.len() as u32))
+            .collect();
+
+        let premove = self
+            .premove
+            .map(|(color, instance)| (color, instance_buffer(device, "Pre-move Buffer", &[instance])));
+
+        let pick_ray = (!self.pick_ray_instances.is_empty())
+            .then(|| (instance_buffer(device, "Pick Ray Buffer", &self.pick_ray_instances), 1));
+        let pick_cell = (!self.pick_cell_instances.is_empty())
+            .then(|| (instance_buffer(device, "Pick Cell Buffer", &self.pick_cell_instances), 1));
+
+        let legal_moves = (!self.legal_move_instances.is_empty()).then(|| {
+            let buffer = instance_buffer(device, "Legal Move Buffer", &self.legal_move_instances);
+            (buffer, self.legal_move_instances.len() as u32)
+        });
+
+        let board = frame.rules.board();
+        let to_world = BoardToWorld::new(board.size(), &frame.layout);
+        let holes: Vec<InstanceRaw> = board
+            .holes()
+            .map(|position| {
+                let mut instance = Instance::new(to_world.to_world(position));
+                instance.scale = Vec3::splat(to_world.stone_radius() * 0.5);
+                instance.to_raw()
+            })
+            .collect();
+        let holes = (!holes.is_empty()).then(|| (instance_buffer(device, "Hole Buffer", &holes), holes.len() as u32));
+
+        let contested: Vec<InstanceRaw> = frame
+            .rules
+            .contested_points()
+            .iter()
+            .map(|&position| {
+                let mut instance = Instance::new(to_world.to_world(position));
+                instance.scale = Vec3::splat(to_world.stone_radius() * 2.6);
+                instance.to_raw()
+            })
+            .collect();
+        let contested = (!contested.is_empty())
+            .then(|| (instance_buffer(device, "Contested Buffer", &contested), contested.len() as u32));
+
+        SceneBuffers {
+            black_stones: stone_buffer(device, "Black Stone Buffer", frame.board.stones(StoneColor::Black)),
+            white_stones: stone_buffer(device, "White Stone Buffer", frame.board.stones(StoneColor::White)),
+            extra_stones: [StoneColor::Red, StoneColor::Green]
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, color)| {
+                    let (buffer, count) = stone_buffer(device, "Extra Stone Buffer", frame.board.stones(color))?;
+                    Some((index, buffer, count))
+                })
+                .collect(),
+            heatmap,
+            influence,
+            liberties,
+            what_if,
+            ghosts,
+            premove,
+            pick_ray,
+            pick_cell,
+            legal_moves,
+            holes,
+            contested,
+        }
+    }
+
+    // Rewrites the box, guide and axis instances in their persistent buffers
+    fn write_aux_instances(&mut self, frame: &Frame) {
+        // Transparent box scaled to the board
+        let to_world = BoardToWorld::new(frame.rules.board().size(), &frame.layout);
+        let mut box_instance = Instance::new(Vec3::new(0.0, 0.0, 0.0));
+        box_instance.scale = Vec3::splat(to_world.extent());
+
+        let mut transparent = vec![box_instance.to_raw()];
+        let guides = match self.guide_style {
+            GuideStyle::Planes => self.guide_system.get_plane_instances(&to_world),
+            GuideStyle::Crosshair => self.guide_system.get_line_instances(&to_world),
+        };
+        transparent.extend(guides.iter().map(Instance::to_raw));
+
+        // Boxes a little wider than a stone, so they show around it
+        let board = frame.board;
+        self.marker_draws.clear();
+        for (marker, position) in [board.last_move(), board.hover(), board.selection()].into_iter().enumerate() {
+            let Some(position) = position else {
+                continue;
+            };
+            let mut instance = Instance::new(to_world.to_world(position));
+            instance.scale = Vec3::splat(to_world.stone_radius() * 2.4);
+            self.marker_draws.push((marker, transparent.len() as u32));
+            transparent.push(instance.to_raw());
+        }
+        self.transparent_aux.write(frame.device, frame.queue, &transparent);
+
+        let view_matrix = frame.camera.build_view_matrix();
+        let opaque = [
+            self.guide_system.get_dot_instance(&to_world).to_raw(),
+            self.axis_indicator.get_instance(&view_matrix).to_raw(),
+        ];
+        self.opaque_aux.write(frame.device, frame.queue, &opaque);
+    }
+
+    fn begin_scene_pass<'a>(
+        encoder: &'a mut wgpu::CommandEncoder,
+        view: &'a wgpu::TextureView,
+        depth_view: &'a wgpu::TextureView,
+        clear_color: bool,
+    ) -> wgpu::RenderPass<'a> {
+        let load = if clear_color {
+            wgpu::LoadOp::Clear(wgpu::Color {
+                r: 0.0,  // Black background
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            })
+        } else {
+            wgpu::LoadOp::Load
+        };
+
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    // Draws the board, stones, guides and axis indicator from one camera
+    fn draw_scene<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        buffers: &'a SceneBuffers,
+        blind: BlindMode,
+        view_offset: u32,
+        sphere_shader: &'a Shader,
+        transparent_shader: &'a Shader,
+    ) {
+        render_pass.set_bind_group(0, self.uniforms.bind_group(), &[view_offset]);
+
+        // Render transparent boundary box, then the three guide planes (very faint)
+        // in one draw, or the three solid crosshair lines in their slots
+        render_pass.set_pipeline(&transparent_shader.render_pipeline);
+        render_pass.set_vertex_buffer(1, self.transparent_aux.buffer.slice(..));
+        render_pass.set_vertex_buffer(0, self.transparent_box_mesh.0.slice(..));
+        render_pass.set_index_buffer(self.transparent_box_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.transparent_box_mesh.2, 0, BOX_INSTANCE..BOX_INSTANCE + 1);
+
+        let guide_mesh = match self.guide_style {
+            GuideStyle::Planes => &self.guide_plane_mesh,
+            GuideStyle::Crosshair => {
+                render_pass.set_pipeline(&sphere_shader.render_pipeline);
+                &self.guide_line_mesh
+            }
+        };
+        render_pass.set_vertex_buffer(0, guide_mesh.0.slice(..));
+        render_pass.set_index_buffer(guide_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..guide_mesh.2, 0, PLANE_INSTANCES);
+
+        render_pass.set_pipeline(&sphere_shader.render_pipeline);
+
+        let (black_mesh, white_mesh) = self.stone_meshes(blind);
+        if let Some((ref buffer, count)) = buffers.black_stones {
+            render_pass.set_vertex_buffer(0, black_mesh.0.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(black_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..black_mesh.2, 0, 0..count);
+        }
+
+        if let Some((ref buffer, count)) = buffers.white_stones {
+            render_pass.set_vertex_buffer(0, white_mesh.0.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(white_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..white_mesh.2, 0, 0..count);
+        }
+
+        for (index, buffer, count) in &buffers.extra_stones {
+            let mesh = self.extra_stone_mesh(*index, blind);
+            render_pass.set_vertex_buffer(0, mesh.0.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.2, 0, 0..*count);
+        }
+
+        if let Some((ref buffer, count)) = buffers.holes {
+            render_pass.set_vertex_buffer(0, self.hole_mesh.0.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(self.hole_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..self.hole_mesh.2, 0, 0..count);
+        }
+
+        if let Some((ref buffer, count)) = buffers.pick_ray {
+            render_pass.set_vertex_buffer(0, self.pick_ray_mesh.0.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(self.pick_ray_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..self.pick_ray_mesh.2, 0, 0..count);
+        }
+
+        // Liberty and what-if shells tint the stones they enclose
+        if !buffers.liberties.is_empty() || !buffers.what_if.is_empty() {
+            render_pass.set_pipeline(&transparent_shader.render_pipeline);
+            let liberty_draws = buffers.liberties.iter().map(|(level, buffer, count)| (&self.liberty_meshes[*level], buffer, count));
+            let what_if_draws = buffers.what_if.iter().map(|(kind, buffer, count)| (&self.what_if_meshes[*kind], buffer, count));
+            for (mesh, buffer, count) in liberty_draws.chain(what_if_draws) {
+                render_pass.set_vertex_buffer(0, mesh.0.slice(..));
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.set_index_buffer(mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.2, 0, 0..*count);
+            }
+            render_pass.set_pipeline(&sphere_shader.render_pipeline);
+        }
+
+        // Ghosts blend over the stones they're leaving or closing in on; a
+        // pre-move is drawn as one too
+        if !buffers.ghosts.is_empty() || buffers.premove.is_some() {
+            render_pass.set_pipeline(&transparent_shader.render_pipeline);
+            let ghosts = buffers.ghosts.iter().map(|(color, buffer, count)| (*color, buffer, *count));
+            let premove = buffers.premove.iter().map(|(color, buffer)| (*color, buffer, 1));
+            for (color, buffer, count) in ghosts.chain(premove) {
+                let mesh = &self.ghost_meshes[color];
+                render_pass.set_vertex_buffer(0, mesh.0.slice(..));
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.set_index_buffer(mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.2, 0, 0..count);
+            }
+            render_pass.set_pipeline(&sphere_shader.render_pipeline);
+        }
+
+        // Heatmap cubes blend over the stones, so they come after them
+        if !buffers.heatmap.is_empty() {
+            render_pass.set_pipeline(&transparent_shader.render_pipeline);
+            for (level, buffer, count) in &buffers.heatmap {
+                let mesh = &self.heatmap_meshes[*level];
+                render_pass.set_vertex_buffer(0, mesh.0.slice(..));
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.set_index_buffer(mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.2, 0, 0..*count);
+            }
+            render_pass.set_pipeline(&sphere_shader.render_pipeline);
+        }
+
+        // The influence cloud blends the same way
+        if !buffers.influence.is_empty() {
+            render_pass.set_pipeline(&transparent_shader.render_pipeline);
+            for (side, buffer, count) in &buffers.influence {
+                let mesh = &self.influence_meshes[*side];
+                render_pass.set_vertex_buffer(0, mesh.0.slice(..));
+                render_pass.set_vertex_buffer(1, buffer.slice(..));
+                render_pass.set_index_buffer(mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.2, 0, 0..*count);
+            }
+            render_pass.set_pipeline(&sphere_shader.render_pipeline);
+        }
+
+        if let Some((ref buffer, count)) = buffers.pick_cell {
+            render_pass.set_pipeline(&transparent_shader.render_pipeline);
+            render_pass.set_vertex_buffer(0, self.pick_cell_mesh.0.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(self.pick_cell_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..self.pick_cell_mesh.2, 0, 0..count);
+            render_pass.set_pipeline(&sphere_shader.render_pipeline);
+        }
+
+        // Legal-move dots are faint, so they blend like the heatmap
+        if let Some((ref buffer, count)) = buffers.legal_moves {
+            render_pass.set_pipeline(&transparent_shader.render_pipeline);
+            render_pass.set_vertex_buffer(0, self.legal_move_mesh.0.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(self.legal_move_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..self.legal_move_mesh.2, 0, 0..count);
+            render_pass.set_pipeline(&sphere_shader.render_pipeline);
+        }
+
+        if let Some((ref buffer, count)) = buffers.contested {
+            render_pass.set_pipeline(&transparent_shader.render_pipeline);
+            render_pass.set_vertex_buffer(0, self.contested_mesh.0.slice(..));
+            render_pass.set_vertex_buffer(1, buffer.slice(..));
+            render_pass.set_index_buffer(self.contested_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..self.contested_mesh.2, 0, 0..count);
+            render_pass.set_pipeline(&sphere_shader.render_pipeline);
+        }
+
+        // Board markers blend over the stones too
+        if !self.marker_draws.is_empty() {
+            render_pass.set_pipeline(&transparent_shader.render_pipeline);
+            render_pass.set_vertex_buffer(1, self.transparent_aux.buffer.slice(..));
+            for &(marker, instance) in &self.marker_draws {
+                let mesh = &self.marker_meshes[marker];
+                render_pass.set_vertex_buffer(0, mesh.0.slice(..));
+                render_pass.set_index_buffer(mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.2, 0, instance..instance + 1);
+            }
+            render_pass.set_pipeline(&sphere_shader.render_pipeline);
+        }
+
+        // Render guide dot and 3D axis indicator (always on top)
+        render_pass.set_vertex_buffer(1, self.opaque_aux.buffer.slice(..));
+        render_pass.set_vertex_buffer(0, self.guide_dot_mesh.0.slice(..));
+        render_pass.set_index_buffer(self.guide_dot_mesh.1.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.guide_dot_mesh.2, 0, DOT_INSTANCE..DOT_INSTANCE + 1);
+
+        let axes = &self.axis_indicator.mesh;
+        render_pass.set_vertex_buffer(0, axes.0.slice(..));
+        render_pass.set_index_buffer(axes.1.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..axes.2, 0, AXES_INSTANCE..AXES_INSTANCE + 1);
+    }
+}
+
+impl RenderPass for ScenePass {
+    fn record(&mut self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        self.write_aux_instances(frame);
+        let buffers = self.buffers(frame);
+        let view = frame.target;
+        let light = self.theme.light;
+
+        self.uniforms.begin_frame();
+        match self.stereo.mode {
+            StereoMode::Off => {
+                let main_view = self.uniforms.push(ViewUniform::new(frame.camera.get_uniform(), &light));
+                self.uniforms.upload(frame.device, frame.queue);
+
+                log::warn!("🔥 STARTING MAIN RENDER PASS - surface sample_count should be 1");
+                let mut render_pass = Self::begin_scene_pass(encoder, view, &self.depth.1, true);
+                render_pass.set_viewport(0.0, 0.0, frame.scene_width, frame.height, 0.0, 1.0);
+                self.draw_scene(
+                    &mut render_pass,
+                    &buffers,
+                    frame.blind,
+                    main_view,
+                    &self.sphere_shader,
+                    &self.transparent_shader,
+                );
+            }
+            StereoMode::SideBySide => {
+                // Each eye keeps its natural aspect in its half of the window
+                let half_width = frame.scene_width / 2.0;
+                let height = frame.height;
+                let (left, right) = frame.camera.stereo_uniforms(self.stereo.eye_separation, half_width / height);
+                let left_view = self.uniforms.push(ViewUniform::new(left, &light));
+                let right_view = self.uniforms.push(ViewUniform::new(right, &light));
+                self.uniforms.upload(frame.device, frame.queue);
+
+                let mut render_pass = Self::begin_scene_pass(encoder, view, &self.depth.1, true);
+                render_pass.set_viewport(0.0, 0.0, half_width, height, 0.0, 1.0);
+                self.draw_scene(
+                    &mut render_pass,
+                    &buffers,
+                    frame.blind,
+                    left_view,
+                    &self.sphere_shader,
+                    &self.transparent_shader,
+                );
+                render_pass.set_viewport(half_width, 0.0, half_width, height, 0.0, 1.0);
+                self.draw_scene(
+                    &mut render_pass,
+                    &buffers,
+                    frame.blind,
+                    right_view,
+                    &self.sphere_shader,
+                    &self.transparent_shader,
+                );
+            }
+            StereoMode::Anaglyph => {
+                let (left, right) = frame.camera.stereo_uniforms(self.stereo.eye_separation, frame.camera.aspect);
+                let left_view = self.uniforms.push(ViewUniform::new(left, &light));
+                let right_view = self.uniforms.push(ViewUniform::new(right, &light));
+                self.uniforms.upload(frame.device, frame.queue);
+
+                // Left eye writes red only, right eye green and blue over a fresh depth buffer
+                {
+                    let mut render_pass = Self::begin_scene_pass(encoder, view, &self.depth.1, true);
+                    render_pass.set_viewport(0.0, 0.0, frame.scene_width, frame.height, 0.0, 1.0);
+                    self.draw_scene(
+                        &mut render_pass,
+                        &buffers,
+                        frame.blind,
+                        left_view,
+                        &self.anaglyph_left.sphere,
+                        &self.anaglyph_left.transparent,
+                    );
+                }
+                let mut render_pass = Self::begin_scene_pass(encoder, view, &self.depth.1, false);
+                render_pass.set_viewport(0.0, 0.0, frame.scene_width, frame.height, 0.0, 1.0);
+                self.draw_scene(
+                    &mut render_pass,
+                    &buffers,
+                    frame.blind,
+                    right_view,
+                    &self.anaglyph_right.sphere,
+                    &self.anaglyph_right.transparent,
+                );
+            }
+        }
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.depth = depth_target(device, width, height);
+    }
+}
@@ -0,0 +1,176 @@
+// The six side-view panels down the right edge, each showing a miniature
+// render of one layer of the board as its view direction sees it, labelled
+// with the layer's index.
+use super::{begin_load_pass, Frame, RenderPass};
+use crate::render::ui::{panel_rect, PANEL_HEIGHT, PANEL_WIDTH};
+use crate::render::{BoardToWorld, PanelPreviews, SideView, TextRenderer, Theme, UISystem, UIPanels, UIVertex};
+use crate::render::profiler;
+
+// Inset of the label from the panel's edges, at a UI scale of 1
+const PADDING: f32 = 4.0;
+const LABEL_SIZE: f32 = 8.0;
+
+pub struct UiPass {
+    ui_system: UISystem,
+    ui_panels: UIPanels,
+    previews: PanelPreviews,
+    text_renderer: TextRenderer,
+    // For the pinned panels' border color
+    theme: Theme,
+    visible: bool,
+}
+
+impl UiPass {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
+        let ui_system = UISystem::new();
+        let previews = PanelPreviews::new(device, format, PANEL_WIDTH, PANEL_HEIGHT, ui_system.side_views.len());
+        Self {
+            ui_system,
+            ui_panels: UIPanels::new(device, format),
+            previews,
+            text_renderer: TextRenderer::new(device, queue, format),
+            theme: Theme::default(),
+            visible: true,
+        }
+    }
+
+    pub fn set_theme(&mut self, device: &wgpu::Device, theme: Theme) {
+        self.theme = theme;
+        self.previews.set_theme(device, &theme);
+    }
+
+    fn draw(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder, label: &'static str, vertices: &[UIVertex], indices: &[u16]) {
+        if vertices.is_empty() {
+            return;
+        }
+        let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut ui_render_pass = begin_load_pass(encoder, frame.target, label);
+        ui_render_pass.set_pipeline(&self.ui_panels.pipeline);
+        ui_render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        ui_render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        ui_render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+    }
+
+    fn draw_labels(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder, labels: &[(String, f32, f32)]) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for (text, x, y) in labels {
+            let (quad_vertices, quad_indices) =
+                self.text_renderer.create_text_quad(text, *x, *y, LABEL_SIZE * frame.ui_scale, frame.width, frame.height);
+            let offset = vertices.len() as u16;
+            vertices.extend(quad_vertices);
+            indices.extend(quad_indices.iter().map(|&index| index + offset));
+        }
+        if indices.is_empty() {
+            return;
+        }
+
+        let vertex_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("UI Panel Label Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = profiler::buffer_init(frame.device, &wgpu::util::BufferInitDescriptor {
+            label: Some("UI Panel Label Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let mut render_pass = begin_load_pass(encoder, frame.target, "UI Panel Label Render Pass");
+        render_pass.set_pipeline(&self.text_renderer.pipeline);
+        render_pass.set_bind_group(0, &self.text_renderer.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn side_view_count(&self) -> usize {
+        self.ui_system.side_views.len()
+    }
+
+    pub fn side_view_mut(&mut self, index: usize) -> Option<&mut SideView> {
+        self.ui_system.side_views.get_mut(index)
+    }
+}
+
+impl RenderPass for UiPass {
+    fn record(&mut self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        if !self.visible {
+            return;
+        }
+        let board_size = frame.rules.board().size();
+        let to_world = BoardToWorld::new(board_size, &frame.layout);
+
+        let layers: Vec<_> = self
+            .ui_system
+            .side_views
+            .iter_mut()
+            .map(|side_view| side_view.get_visible_stones(frame.rules, &to_world, frame.clock))
+            .collect();
+
+        // Layers are numbered from 1 for people
+        let mut labels = Vec::new();
+        let mut rects = Vec::new();
+        for (i, (side_view, layer)) in self.ui_system.side_views.iter().zip(&layers).enumerate() {
+            let rect = panel_rect(i, frame.scene_width, frame.ui_scale);
+            let (panel_x, panel_y, _, _) = rect;
+            let label = match layer {
+                Some(layer) => format!("{} {}/{}", side_view.direction.label(), layer.layer + 1, layer.layer_count),
+                None => side_view.direction.label().to_string(),
+            };
+            let padding = PADDING * frame.ui_scale;
+            labels.push((label, panel_x + padding, panel_y + padding));
+            rects.push(rect);
+        }
+
+        self.previews.update(
+            frame.device,
+            frame.queue,
+            encoder,
+            &self.ui_system.side_views,
+            layers,
+            &to_world,
+            frame.blind,
+        );
+        self.previews.composite(frame.device, encoder, frame.target, &rects, (frame.width, frame.height));
+
+        // The borders go over everything; pinned panels take the selection color
+        let mut all_vertices = Vec::new();
+        let mut all_indices = Vec::new();
+        let mut current_index_offset = 0u16;
+        let [r, g, b] = self.theme.markers[2];
+        for (i, side_view) in self.ui_system.side_views.iter().enumerate() {
+            let (panel_x, panel_y, width, height) = panel_rect(i, frame.scene_width, frame.ui_scale);
+            let color = if side_view.is_pinned() { [r, g, b, 1.0] } else { [1.0, 1.0, 1.0, 1.0] };
+            let (vertices, indices) = self.ui_panels.create_panel_border(
+                panel_x, panel_y, width, height,
+                frame.width, frame.height, color
+            );
+
+            let vertex_count = vertices.len() as u16;
+            all_vertices.extend(vertices);
+            all_indices.extend(indices.iter().map(|&idx| idx + current_index_offset));
+            current_index_offset += vertex_count.max(20);
+        }
+        self.draw(frame, encoder, "UI Panel Border Render Pass", &all_vertices, &all_indices);
+        self.draw_labels(frame, encoder, &labels);
+    }
+}
@@ -0,0 +1,23 @@
+// Picking debug view: the last pick ray as a thin rod from the camera it was
+// cast from, and a box around the cell it resolved to
+use super::{BoardToWorld, Instance};
+use glam::{Quat, Vec3};
+
+// Rod thickness, relative to the lattice pitch
+const THICKNESS: f32 = 0.03;
+
+// A rod from `origin` along `direction` for `length`
+pub fn ray(origin: Vec3, direction: Vec3, length: f32, to_world: &BoardToWorld) -> Instance {
+    let mut instance = Instance::new(origin + direction * (length / 2.0));
+    instance.rotation = Quat::from_rotation_arc(Vec3::Z, direction);
+    let thickness = THICKNESS * to_world.pitch();
+    instance.scale = Vec3::new(thickness, thickness, length);
+    instance
+}
+
+// A box filling the cell of `position`
+pub fn cell(position: (u8, u8, u8), to_world: &BoardToWorld) -> Instance {
+    let mut instance = Instance::new(to_world.to_world(position));
+    instance.scale = Vec3::splat(to_world.pitch() * 0.9);
+    instance
+}
@@ -0,0 +1,230 @@
+// Render profiling for --profile-render: how long each pass of the frame graph
+// takes on the GPU, from timestamp queries where the adapter has them, and how
+// many bytes it uploads, as CSV with one row per pass per frame. Uploads are
+// counted wherever render code fills a buffer, through buffer_init and
+// write_buffer; those made between frames, when the game hands over new
+// instances, get an "updates" row of their own. Timestamps are read back a
+// few frames late so the CPU never waits on the GPU for them.
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use wgpu::util::DeviceExt;
+
+static UPLOADED: AtomicU64 = AtomicU64::new(0);
+
+// Frames whose timestamps can be in flight at once
+const SLOTS: usize = 3;
+// How a readback's mapping went
+const MAPPING: u8 = 0;
+const MAPPED: u8 = 1;
+const FAILED: u8 = 2;
+const TIMING: wgpu::Features = wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+// Bytes uploaded by render code since launch
+pub fn uploaded() -> u64 {
+    UPLOADED.load(Ordering::Relaxed)
+}
+
+// device.create_buffer_init(), counted
+pub fn buffer_init(device: &wgpu::Device, descriptor: &wgpu::util::BufferInitDescriptor) -> wgpu::Buffer {
+    UPLOADED.fetch_add(descriptor.contents.len() as u64, Ordering::Relaxed);
+    device.create_buffer_init(descriptor)
+}
+
+// queue.write_buffer(), counted
+pub fn write_buffer(queue: &wgpu::Queue, buffer: &wgpu::Buffer, offset: wgpu::BufferAddress, data: &[u8]) {
+    UPLOADED.fetch_add(data.len() as u64, Ordering::Relaxed);
+    queue.write_buffer(buffer, offset, data);
+}
+
+// The device features that let passes be timed, if the adapter has them all
+pub fn timing_features(adapter: &wgpu::Adapter) -> wgpu::Features {
+    if adapter.features().contains(TIMING) {
+        TIMING
+    } else {
+        wgpu::Features::empty()
+    }
+}
+
+// A frame's figures waiting on its timestamps
+struct Pending {
+    frame: u64,
+    updates: u64,
+    uploads: Vec<u64>,
+}
+
+struct Readback {
+    buffer: wgpu::Buffer,
+    state: Arc<AtomicU8>,
+    pending: Option<Pending>,
+}
+
+struct Timestamps {
+    queries: wgpu::QuerySet,
+    resolve: wgpu::Buffer,
+    readbacks: Vec<Readback>,
+    // Nanoseconds per tick
+    period: f32,
+}
+
+pub struct RenderProfiler {
+    out: Box<dyn Write>,
+    passes: Vec<&'static str>,
+    timestamps: Option<Timestamps>,
+    frame: u64,
+    // The upload count at the end of the last frame, then at the start of
+    // this one and after each of its passes
+    last_uploaded: u64,
+    marks: Vec<u64>,
+    // The readback slot taking this frame's timestamps, if one was free
+    slot: Option<usize>,
+}
+
+impl RenderProfiler {
+    // Starts the CSV with its header; passes are named in the order they run
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, passes: Vec<&'static str>, mut out: Box<dyn Write>) -> io::Result<Self> {
+        writeln!(out, "frame,pass,gpu_ms,upload_bytes")?;
+        let timestamps = device.features().contains(TIMING).then(|| {
+            // A timestamp before the first pass and after each one
+            let count = passes.len() as u32 + 1;
+            let size = count as wgpu::BufferAddress * wgpu::QUERY_SIZE as wgpu::BufferAddress;
+            let readback = |_| Readback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Profiler Readback Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                state: Arc::new(AtomicU8::new(MAPPING)),
+                pending: None,
+            };
+            Timestamps {
+                queries: device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("Profiler Timestamps"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count,
+                }),
+                resolve: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Profiler Resolve Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                }),
+                readbacks: (0..SLOTS).map(readback).collect(),
+                period: queue.get_timestamp_period(),
+            }
+        });
+        Ok(Self {
+            out,
+            passes,
+            timestamps,
+            frame: 0,
+            last_uploaded: uploaded(),
+            marks: Vec::new(),
+            slot: None,
+        })
+    }
+
+    // Whether the GPU column is filled in; without timestamp queries it's left empty
+    pub fn has_gpu_timing(&self) -> bool {
+        self.timestamps.is_some()
+    }
+
+    // Before the first pass is recorded
+    pub fn begin_frame(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        self.marks.clear();
+        self.marks.push(uploaded());
+        self.slot = None;
+        if let Some(timestamps) = &self.timestamps {
+            self.slot = timestamps.readbacks.iter().position(|readback| readback.pending.is_none());
+            if self.slot.is_some() {
+                encoder.write_timestamp(&timestamps.queries, 0);
+            }
+        }
+    }
+
+    // After the pass at `index` in the run order is recorded
+    pub fn end_pass(&mut self, encoder: &mut wgpu::CommandEncoder, index: usize) {
+        self.marks.push(uploaded());
+        if let (Some(timestamps), Some(_)) = (&self.timestamps, self.slot) {
+            encoder.write_timestamp(&timestamps.queries, index as u32 + 1);
+        }
+    }
+
+    // Before the encoder is finished: copies the timestamps out for reading
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let (Some(timestamps), Some(slot)) = (&self.timestamps, self.slot) {
+            let count = self.passes.len() as u32 + 1;
+            encoder.resolve_query_set(&timestamps.queries, 0..count, &timestamps.resolve, 0);
+            let readback = &timestamps.readbacks[slot].buffer;
+            encoder.copy_buffer_to_buffer(&timestamps.resolve, 0, readback, 0, readback.size());
+        }
+    }
+
+    // After the frame is submitted: writes the rows of every frame whose
+    // timestamps have come back, and this one's straight away if it has none
+    pub fn end_frame(&mut self, device: &wgpu::Device) {
+        let updates = self.marks[0] - self.last_uploaded;
+        self.last_uploaded = *self.marks.last().expect("begin_frame marks the start");
+        let pending = Pending {
+            frame: self.frame,
+            updates,
+            uploads: self.marks.windows(2).map(|pair| pair[1] - pair[0]).collect(),
+        };
+        self.frame += 1;
+
+        let mut rows = Vec::new();
+        match (&mut self.timestamps, self.slot) {
+            (Some(timestamps), Some(slot)) => {
+                let readback = &mut timestamps.readbacks[slot];
+                let state = readback.state.clone();
+                state.store(MAPPING, Ordering::Relaxed);
+                readback.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+                    state.store(if result.is_ok() { MAPPED } else { FAILED }, Ordering::Release);
+                });
+                readback.pending = Some(pending);
+            }
+            _ => rows.push((pending, None)),
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+        if let Some(timestamps) = &mut self.timestamps {
+            for readback in &mut timestamps.readbacks {
+                match readback.state.swap(MAPPING, Ordering::Acquire) {
+                    MAPPED => {}
+                    // The frame's figures are lost, but the slot is free again
+                    FAILED => {
+                        readback.pending = None;
+                        continue;
+                    }
+                    _ => continue,
+                }
+                let ticks: Vec<u64> = bytemuck::cast_slice(&readback.buffer.slice(..).get_mapped_range()).to_vec();
+                readback.buffer.unmap();
+                let milliseconds = ticks
+                    .windows(2)
+                    .map(|pair| pair[1].saturating_sub(pair[0]) as f64 * timestamps.period as f64 / 1e6)
+                    .collect();
+                if let Some(pending) = readback.pending.take() {
+                    rows.push((pending, Some(milliseconds)));
+                }
+            }
+        }
+        rows.sort_by_key(|(pending, _)| pending.frame);
+        if let Err(error) = self.write_rows(&rows) {
+            log::warn!("render profiling stopped: {}", error);
+            self.out = Box::new(io::sink());
+        }
+    }
+
+    fn write_rows(&mut self, rows: &[(Pending, Option<Vec<f64>>)]) -> io::Result<()> {
+        for (pending, milliseconds) in rows {
+            writeln!(self.out, "{},updates,,{}", pending.frame, pending.updates)?;
+            for (index, (pass, uploads)) in self.passes.iter().zip(&pending.uploads).enumerate() {
+                let gpu = milliseconds.as_ref().map_or(String::new(), |times| format!("{:.4}", times[index]));
+                writeln!(self.out, "{},{},{},{}", pending.frame, pass, gpu, uploads)?;
+            }
+        }
+        self.out.flush()
+    }
+}
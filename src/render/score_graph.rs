@@ -0,0 +1,78 @@
+// Score graph panel for review: Black's estimated lead per move, with markers
+// on the analyzer's blunders and a cursor at the move being shown
+use super::{ScreenMesh, UIVertex};
+use crate::ai::analysis::GameAnalysis;
+
+const WIDTH: f32 = 320.0;
+const HEIGHT: f32 = 120.0;
+const MARGIN: f32 = 20.0;
+const LINE_WIDTH: f32 = 2.0;
+const MARKER_SIZE: f32 = 7.0;
+// Long games are thinned out so the vertices fit 16-bit indices
+const MAX_POINTS: usize = 1000;
+
+const BACKGROUND: [f32; 4] = [0.1, 0.1, 0.1, 0.8];
+const AXIS: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+const LINE: [f32; 4] = [0.95, 0.95, 0.95, 1.0];
+const BLUNDER: [f32; 4] = [0.84, 0.37, 0.0, 1.0];
+const CURSOR: [f32; 4] = [0.9, 0.8, 0.2, 0.8];
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScoreGraph {
+    pub analysis: GameAnalysis,
+    pub current_move: usize,
+}
+
+impl ScoreGraph {
+    pub fn new(analysis: GameAnalysis, current_move: usize) -> Self {
+        Self {
+            analysis,
+            current_move,
+        }
+    }
+
+    // Triangles for the bottom-left panel, in NDC for a screen of the given
+    // size, the panel sized by the UI scale
+    pub fn geometry(&self, screen_width: f32, screen_height: f32, scale: f32) -> (Vec<UIVertex>, Vec<u16>) {
+        let mut mesh = ScreenMesh::new(screen_width, screen_height);
+
+        let (width, height) = (WIDTH * scale, HEIGHT * scale);
+        let left = MARGIN * scale;
+        let top = screen_height - left - height;
+        mesh.rect(left, top, width, height, BACKGROUND);
+
+        let scores = &self.analysis.scores;
+        let moves = scores.len().saturating_sub(1).max(1) as f32;
+        // Symmetric around zero so the middle line is always an even game
+        let range = scores.iter().fold(5.0f32, |range, score| range.max(score.abs()));
+        let x_at = |move_number: usize| left + width * move_number as f32 / moves;
+        let y_at = |score: f32| top + height * 0.5 - (height * 0.45) * score / range;
+
+        mesh.rect(left, top + height * 0.5 - 0.5, width, 1.0, AXIS);
+
+        let step = scores.len().div_ceil(MAX_POINTS).max(1);
+        let mut sampled: Vec<usize> = (0..scores.len()).step_by(step).collect();
+        if let Some(&last) = sampled.last() {
+            if last + 1 != scores.len() {
+                sampled.push(scores.len() - 1);
+            }
+        }
+        for pair in sampled.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            mesh.segment((x_at(a), y_at(scores[a])), (x_at(b), y_at(scores[b])), LINE_WIDTH * scale, LINE);
+        }
+
+        for blunder in &self.analysis.blunders {
+            if let Some(&score) = scores.get(blunder.move_number) {
+                let (x, y) = (x_at(blunder.move_number), y_at(score));
+                let marker = MARKER_SIZE * scale;
+                mesh.rect(x - marker * 0.5, y - marker * 0.5, marker, marker, BLUNDER);
+            }
+        }
+
+        let cursor = x_at(self.current_move.min(scores.len().saturating_sub(1)));
+        mesh.rect(cursor - 0.5, top, 1.0, height, CURSOR);
+
+        mesh.into_parts()
+    }
+}
\ No newline at end of file
@@ -9,6 +9,18 @@ impl Shader {
         bind_group_layouts: &[&wgpu::BindGroupLayout],
         vertex_buffer_layouts: &[wgpu::VertexBufferLayout],
         topology: wgpu::PrimitiveTopology,
+    ) -> Self {
+        Self::create_basic_shader_masked(device, format, bind_group_layouts, vertex_buffer_layouts, topology, wgpu::ColorWrites::ALL)
+    }
+
+    // Same pipeline restricted to some color channels (anaglyph stereo eyes)
+    pub fn create_basic_shader_masked(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        vertex_buffer_layouts: &[wgpu::VertexBufferLayout],
+        topology: wgpu::PrimitiveTopology,
+        write_mask: wgpu::ColorWrites,
     ) -> Self {
         log::warn!("🔍 Creating BASIC SHADER pipeline");
         let shader_source = include_str!("shaders/basic.wgsl");
@@ -30,15 +42,17 @@ impl Shader {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
+                compilation_options: Default::default(),
                 buffers: vertex_buffer_layouts,
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
+                compilation_options: Default::default(),
                 targets: &[Some(wgpu::ColorTargetState {
                     format,
                     blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
+                    write_mask,
                 })],
             }),
             primitive: wgpu::PrimitiveState {
@@ -63,6 +77,7 @@ impl Shader {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
         log::warn!("✅ BASIC render pipeline created successfully with sample_count=1");
 
@@ -75,6 +90,17 @@ impl Shader {
         bind_group_layouts: &[&wgpu::BindGroupLayout],
         vertex_buffer_layouts: &[wgpu::VertexBufferLayout],
         topology: wgpu::PrimitiveTopology,
+    ) -> Self {
+        Self::create_transparent_shader_masked(device, format, bind_group_layouts, vertex_buffer_layouts, topology, wgpu::ColorWrites::ALL)
+    }
+
+    pub fn create_transparent_shader_masked(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        vertex_buffer_layouts: &[wgpu::VertexBufferLayout],
+        topology: wgpu::PrimitiveTopology,
+        write_mask: wgpu::ColorWrites,
     ) -> Self {
         log::warn!("🔍 Creating TRANSPARENT SHADER pipeline");
         let shader_source = include_str!("shaders/transparent.wgsl");
@@ -96,11 +122,13 @@ impl Shader {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
+                compilation_options: Default::default(),
                 buffers: vertex_buffer_layouts,
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
+                compilation_options: Default::default(),
                 targets: &[Some(wgpu::ColorTargetState {
                     format,
                     blend: Some(wgpu::BlendState {
@@ -115,7 +143,7 @@ impl Shader {
                             operation: wgpu::BlendOperation::Add,
                         },
                     }),
-                    write_mask: wgpu::ColorWrites::ALL,
+                    write_mask,
                 })],
             }),
             primitive: wgpu::PrimitiveState {
@@ -140,6 +168,7 @@ impl Shader {
                 alpha_to_coverage_enabled: true,
             },
             multiview: None,
+            cache: None,
         });
         log::warn!("✅ TRANSPARENT render pipeline created successfully with sample_count=1");
 
@@ -1,9 +1,12 @@
-struct CameraUniform {
+struct ViewUniform {
     view_proj: mat4x4<f32>,
     view_pos: vec4<f32>,
+    light_direction: vec4<f32>,
+    light_color: vec4<f32>,
+    ambient: vec4<f32>,
 }
 @group(0) @binding(0)
-var<uniform> camera: CameraUniform;
+var<uniform> view: ViewUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -17,6 +20,7 @@ struct InstanceInput {
     @location(5) model_matrix_1: vec4<f32>,
     @location(6) model_matrix_2: vec4<f32>,
     @location(7) model_matrix_3: vec4<f32>,
+    @location(8) seed: f32,
 }
 
 struct VertexOutput {
@@ -25,6 +29,9 @@ struct VertexOutput {
     @location(1) world_normal: vec3<f32>,
     @location(2) tex_coords: vec2<f32>,
     @location(3) color: vec3<f32>,
+    // Mesh-space position and variation seed, for the white stone shell bands
+    @location(4) local_position: vec3<f32>,
+    @location(5) seed: f32,
 }
 
 @vertex
@@ -47,28 +54,40 @@ fn vs_main(
     out.world_normal = world_normal;
     out.tex_coords = model.tex_coords;
     out.color = model.color;
-    out.clip_position = camera.view_proj * world_position;
+    out.local_position = model.position;
+    out.seed = instance.seed;
+    out.clip_position = view.view_proj * world_position;
     
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let light_dir = normalize(vec3<f32>(0.8, 1.0, 0.6));
-    let light_color = vec3<f32>(1.0, 0.95, 0.85);
-    let ambient = vec3<f32>(0.15, 0.15, 0.2);
+    let light_dir = normalize(view.light_direction.xyz);
+    let light_color = view.light_color.rgb;
+    let ambient = view.ambient.rgb;
     
     // Add simple procedural texture based on world position
     let texture_scale = 8.0;
     let noise = sin(in.world_position.x * texture_scale) * 
                 sin(in.world_position.y * texture_scale) * 
                 sin(in.world_position.z * texture_scale);
-    let texture_factor = 0.05 * noise + 0.95;
+    var texture_factor = 0.05 * noise + 0.95;
+
+    // Clamshell white stones show fine growth bands across the shell; each
+    // stone gets its own spacing and phase from its seed
+    let brightness = dot(in.color, vec3<f32>(0.299, 0.587, 0.114));
+    if (in.seed > 0.0 && brightness > 0.6) {
+        let frequency = 45.0 + in.seed * 25.0;
+        let wobble = sin(in.local_position.x * 9.0 + in.seed * 40.0) * 0.15;
+        let bands = sin((in.local_position.y + wobble) * frequency + in.seed * 6.2831);
+        texture_factor = texture_factor * (0.97 + 0.03 * bands);
+    }
     
     let diffuse_strength = max(dot(in.world_normal, light_dir), 0.0);
     let diffuse = diffuse_strength * light_color;
     
-    let view_dir = normalize(camera.view_pos.xyz - in.world_position);
+    let view_dir = normalize(view.view_pos.xyz - in.world_position);
     let reflect_dir = reflect(-light_dir, in.world_normal);
     let spec_strength = pow(max(dot(view_dir, reflect_dir), 0.0), 64.0);
     let specular = spec_strength * light_color * 0.6;
@@ -78,7 +97,7 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let rim_light = pow(rim_factor, 2.0) * 0.2;
     
     // Calculate Z-depth darkening (stones further back appear darker)
-    let view_space_z = (camera.view_proj * vec4<f32>(in.world_position, 1.0)).z;
+    let view_space_z = (view.view_proj * vec4<f32>(in.world_position, 1.0)).z;
     let depth_factor = 1.0 - (view_space_z * 0.015); // Subtle darkening based on depth
     let depth_factor_clamped = clamp(depth_factor, 0.7, 1.0); // Don't darken too much
     
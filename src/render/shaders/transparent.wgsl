@@ -1,9 +1,12 @@
-struct CameraUniform {
+struct ViewUniform {
     view_proj: mat4x4<f32>,
     view_pos: vec4<f32>,
+    light_direction: vec4<f32>,
+    light_color: vec4<f32>,
+    ambient: vec4<f32>,
 }
 @group(0) @binding(0)
-var<uniform> camera: CameraUniform;
+var<uniform> view: ViewUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -47,21 +50,21 @@ fn vs_main(
     out.world_normal = world_normal;
     out.tex_coords = model.tex_coords;
     out.color = model.color;
-    out.clip_position = camera.view_proj * world_position;
+    out.clip_position = view.view_proj * world_position;
     
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let light_dir = normalize(vec3<f32>(1.0, 1.0, 1.0));
-    let light_color = vec3<f32>(0.8, 0.9, 1.0);
-    let ambient = vec3<f32>(0.1, 0.15, 0.2);
+    let light_dir = normalize(view.light_direction.xyz);
+    let light_color = view.light_color.rgb;
+    let ambient = view.ambient.rgb;
     
     let diffuse_strength = max(dot(in.world_normal, light_dir), 0.0);
     let diffuse = diffuse_strength * light_color * 0.3;
     
-    let view_dir = normalize(camera.view_pos.xyz - in.world_position);
+    let view_dir = normalize(view.view_pos.xyz - in.world_position);
     let reflect_dir = reflect(-light_dir, in.world_normal);
     let spec_strength = pow(max(dot(view_dir, reflect_dir), 0.0), 16.0);
     let specular = spec_strength * light_color * 0.1;
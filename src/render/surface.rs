@@ -0,0 +1,91 @@
+// Swapchain acquisition that never lets a stalled or lost surface hold up the
+// event loop. A frame without a texture is skipped and counted; after a
+// failure the surface is left alone for a while, twice as long each time it
+// keeps failing, and a lost or outdated one is reconfigured once per attempt
+// rather than on every error.
+use instant::Instant;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    pub presented: u64,
+    // Frames dropped because no surface texture could be had
+    pub skipped: u64,
+    pub reconfigures: u64,
+}
+
+pub struct SurfaceRetry {
+    stats: FrameStats,
+    // Don't touch the surface before this after a failure
+    retry_at: Option<Instant>,
+    delay: Duration,
+    needs_configure: bool,
+}
+
+impl Default for SurfaceRetry {
+    fn default() -> Self {
+        Self {
+            stats: FrameStats::default(),
+            retry_at: None,
+            delay: Self::FIRST_DELAY,
+            needs_configure: false,
+        }
+    }
+}
+
+impl SurfaceRetry {
+    const FIRST_DELAY: Duration = Duration::from_millis(16);
+    const MAX_DELAY: Duration = Duration::from_secs(2);
+
+    pub fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    // The surface was just configured from outside, e.g. on resize
+    pub fn configured(&mut self) {
+        self.retry_at = None;
+        self.delay = Self::FIRST_DELAY;
+        self.needs_configure = false;
+    }
+
+    pub fn presented(&mut self) {
+        self.stats.presented += 1;
+    }
+
+    // The next surface texture, or None when this frame should be skipped;
+    // only running out of memory is an error
+    pub fn acquire(
+        &mut self,
+        surface: &wgpu::Surface,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> Result<Option<wgpu::SurfaceTexture>, wgpu::SurfaceError> {
+        let now = Instant::now();
+        if self.retry_at.is_some_and(|at| now < at) {
+            self.stats.skipped += 1;
+            return Ok(None);
+        }
+        if self.needs_configure {
+            surface.configure(device, config);
+            self.stats.reconfigures += 1;
+            self.needs_configure = false;
+        }
+
+        match surface.get_current_texture() {
+            Ok(frame) => {
+                self.retry_at = None;
+                self.delay = Self::FIRST_DELAY;
+                Ok(Some(frame))
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => Err(wgpu::SurfaceError::OutOfMemory),
+            Err(error) => {
+                self.needs_configure = matches!(error, wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated);
+                self.stats.skipped += 1;
+                self.retry_at = Some(now + self.delay);
+                log::warn!("skipping frames for {:?}: {}", self.delay, error);
+                self.delay = (self.delay * 2).min(Self::MAX_DELAY);
+                Ok(None)
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,4 +1,3 @@
-use wgpu::util::DeviceExt;
 use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
@@ -143,11 +142,13 @@ impl TextRenderer {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
+                compilation_options: Default::default(),
                 buffers: &[TextVertex::desc()],
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
+                compilation_options: Default::default(),
                 targets: &[Some(wgpu::ColorTargetState {
                     format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -158,7 +159,8 @@ impl TextRenderer {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                // Glyph quads are wound clockwise; there is no back to cull in 2D
+                cull_mode: None,
                 unclipped_depth: false,
                 polygon_mode: wgpu::PolygonMode::Fill,
                 conservative: false,
@@ -170,6 +172,7 @@ impl TextRenderer {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
 
         Self {
@@ -217,6 +220,54 @@ impl TextRenderer {
             [0x7C, 0x66, 0x66, 0x7C, 0x6C, 0x66, 0x66, 0x00],
             // T (84)
             [0x7E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00],
+            // The rest of the alphabet, for annotation labels
+            // D (68)
+            [0x78, 0x6C, 0x66, 0x66, 0x66, 0x6C, 0x78, 0x00],
+            // J (74)
+            [0x1E, 0x0C, 0x0C, 0x0C, 0x6C, 0x6C, 0x38, 0x00],
+            // M (77)
+            [0x63, 0x77, 0x7F, 0x6B, 0x63, 0x63, 0x63, 0x00],
+            // N (78)
+            [0x66, 0x76, 0x7E, 0x7E, 0x6E, 0x66, 0x66, 0x00],
+            // Q (81)
+            [0x3C, 0x66, 0x66, 0x66, 0x6A, 0x6C, 0x36, 0x00],
+            // S (83)
+            [0x3C, 0x66, 0x60, 0x3C, 0x06, 0x66, 0x3C, 0x00],
+            // U (85)
+            [0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x00],
+            // V (86)
+            [0x66, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x18, 0x00],
+            // W (87)
+            [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00],
+            // X (88)
+            [0x66, 0x66, 0x3C, 0x18, 0x3C, 0x66, 0x66, 0x00],
+            // Y (89)
+            [0x66, 0x66, 0x66, 0x3C, 0x18, 0x18, 0x18, 0x00],
+            // Z (90)
+            [0x7E, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x7E, 0x00],
+            // Digits and the slash, for the side panels' layer labels
+            // 0 (48)
+            [0x3C, 0x66, 0x6E, 0x76, 0x66, 0x66, 0x3C, 0x00],
+            // 1 (49)
+            [0x18, 0x38, 0x18, 0x18, 0x18, 0x18, 0x7E, 0x00],
+            // 2 (50)
+            [0x3C, 0x66, 0x06, 0x0C, 0x30, 0x60, 0x7E, 0x00],
+            // 3 (51)
+            [0x3C, 0x66, 0x06, 0x1C, 0x06, 0x66, 0x3C, 0x00],
+            // 4 (52)
+            [0x0C, 0x1C, 0x3C, 0x6C, 0x7E, 0x0C, 0x0C, 0x00],
+            // 5 (53)
+            [0x7E, 0x60, 0x7C, 0x06, 0x06, 0x66, 0x3C, 0x00],
+            // 6 (54)
+            [0x3C, 0x66, 0x60, 0x7C, 0x66, 0x66, 0x3C, 0x00],
+            // 7 (55)
+            [0x7E, 0x66, 0x0C, 0x18, 0x18, 0x18, 0x18, 0x00],
+            // 8 (56)
+            [0x3C, 0x66, 0x66, 0x3C, 0x66, 0x66, 0x3C, 0x00],
+            // 9 (57)
+            [0x3C, 0x66, 0x66, 0x3E, 0x06, 0x66, 0x3C, 0x00],
+            // / (47)
+            [0x00, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x00, 0x00],
         ];
         
         // Map characters to positions
@@ -236,6 +287,29 @@ impl TextRenderer {
             (80, 12), // P
             (82, 13), // R
             (84, 14), // T
+            (68, 15), // D
+            (74, 16), // J
+            (77, 17), // M
+            (78, 18), // N
+            (81, 19), // Q
+            (83, 20), // S
+            (85, 21), // U
+            (86, 22), // V
+            (87, 23), // W
+            (88, 24), // X
+            (89, 25), // Y
+            (90, 26), // Z
+            (48, 27), // 0
+            (49, 28), // 1
+            (50, 29), // 2
+            (51, 30), // 3
+            (52, 31), // 4
+            (53, 32), // 5
+            (54, 33), // 6
+            (55, 34), // 7
+            (56, 35), // 8
+            (57, 36), // 9
+            (47, 37), // /
         ];
 
         for (ascii_code, pattern_idx) in char_positions {
@@ -244,8 +318,7 @@ impl TextRenderer {
                 let char_x = (ascii_code % 16) * 8;
                 let char_y = (ascii_code / 16) * 8;
                 
-                for row in 0..8 {
-                    let byte = pattern[row];
+                for (row, &byte) in pattern.iter().enumerate() {
                     for col in 0..8 {
                         if (byte >> (7 - col)) & 1 != 0 {
                             let x = char_x + col;
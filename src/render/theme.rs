@@ -0,0 +1,166 @@
+use super::{heatmap, Mesh};
+use crate::game::StoneColor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoneShape {
+    Sphere,
+    Octahedron,
+}
+
+impl StoneShape {
+    pub fn mesh(self, radius: f32, color: [f32; 3]) -> Mesh {
+        match self {
+            StoneShape::Sphere => Mesh::create_sphere(radius, 20, 20, color),
+            StoneShape::Octahedron => Mesh::create_octahedron(radius, color),
+        }
+    }
+}
+
+// The scene's single directional light, passed to the shaders per view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    // Towards the light, in world space
+    pub direction: [f32; 3],
+    pub color: [f32; 3],
+    pub ambient: [f32; 3],
+}
+
+// Colors and shapes baked into the scene meshes, and the light they are seen in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub black_stone: [f32; 3],
+    pub white_stone: [f32; 3],
+    pub black_shape: StoneShape,
+    pub white_shape: StoneShape,
+    // The third and fourth players' stones
+    pub red_stone: [f32; 3],
+    pub green_stone: [f32; 3],
+    // Both colors' stones in blind training mode
+    pub neutral_stone: [f32; 3],
+    pub bounding_box: [f32; 3],
+    pub guide_plane: [f32; 3],
+    pub guide_dot: [f32; 3],
+    // Pick ray debug view: the ray and the cell it found
+    pub pick_ray: [f32; 3],
+    // Ghost dots on the legal points for the side to move
+    pub legal_move: [f32; 3],
+    // Small cubes marking the points a holes board is missing
+    pub hole: [f32; 3],
+    // Boxes around the points a king-of-the-hill game is fought over
+    pub contested: [f32; 3],
+    // Analysis heatmap, coldest band first
+    pub heatmap: [[f32; 3]; heatmap::LEVELS],
+    // Influence cloud in analysis mode: Black's side, then White's
+    pub influence: [[f32; 3]; 2],
+    // Shells over stones in atari and on two liberties
+    pub liberties: [[f32; 3]; 2],
+    // What-if preview shells: stones a move would capture, then its group left in atari
+    pub what_if: [[f32; 3]; 2],
+    // Boxes around the last move, the stone under the cursor and the selected stone
+    pub markers: [[f32; 3]; 3],
+    pub light: Light,
+}
+
+impl Theme {
+    pub fn classic() -> Self {
+        Self {
+            black_stone: [0.1, 0.1, 0.1],
+            white_stone: [0.9, 0.9, 0.9],
+            black_shape: StoneShape::Sphere,
+            white_shape: StoneShape::Sphere,
+            red_stone: [0.8, 0.15, 0.1],
+            green_stone: [0.1, 0.6, 0.2],
+            neutral_stone: [0.55, 0.5, 0.45],
+            bounding_box: [0.3, 0.5, 0.8],
+            guide_plane: [1.0, 1.0, 1.0],
+            guide_dot: [0.2, 0.4, 0.9],
+            pick_ray: [1.0, 0.2, 0.8],
+            legal_move: [0.6, 0.9, 0.6],
+            hole: [0.35, 0.2, 0.2],
+            contested: [1.0, 0.75, 0.2],
+            heatmap: [
+                [0.2, 0.3, 0.9],
+                [0.2, 0.8, 0.9],
+                [0.3, 0.9, 0.3],
+                [1.0, 0.8, 0.1],
+                [1.0, 0.2, 0.1],
+            ],
+            influence: [[0.35, 0.25, 0.6], [0.95, 0.85, 0.6]],
+            liberties: [[1.0, 0.15, 0.1], [1.0, 0.85, 0.1]],
+            what_if: [[0.2, 0.9, 0.4], [1.0, 0.15, 0.1]],
+            markers: [[1.0, 0.5, 0.2], [0.9, 0.9, 0.5], [0.3, 0.8, 1.0]],
+            // Warm key light with a cool ambient fill
+            light: Light {
+                direction: [0.8, 1.0, 0.6],
+                color: [1.0, 0.95, 0.85],
+                ambient: [0.15, 0.15, 0.2],
+            },
+        }
+    }
+
+    // Stones differ in shape as well as brightness, and the overlays use the
+    // Okabe-Ito palette, which stays distinguishable under all common forms
+    // of color blindness.
+    pub fn color_blind() -> Self {
+        Self {
+            black_stone: [0.05, 0.05, 0.05],
+            white_stone: [0.95, 0.95, 0.95],
+            black_shape: StoneShape::Sphere,
+            white_shape: StoneShape::Octahedron,
+            // Okabe-Ito vermillion and bluish green, apart in lightness too
+            red_stone: [0.84, 0.37, 0.0],
+            green_stone: [0.0, 0.62, 0.45],
+            neutral_stone: [0.5, 0.5, 0.5],
+            bounding_box: [0.0, 0.45, 0.70],
+            guide_plane: [0.34, 0.71, 0.91],
+            guide_dot: [0.90, 0.62, 0.0],
+            // Okabe-Ito reddish purple
+            pick_ray: [0.8, 0.47, 0.65],
+            legal_move: [0.0, 0.62, 0.45],
+            // Okabe-Ito reddish purple
+            hole: [0.8, 0.47, 0.65],
+            // Okabe-Ito orange
+            contested: [0.90, 0.62, 0.0],
+            // Viridis, which is ordered by lightness as well as hue
+            heatmap: [
+                [0.27, 0.00, 0.33],
+                [0.23, 0.32, 0.55],
+                [0.13, 0.57, 0.55],
+                [0.37, 0.79, 0.38],
+                [0.99, 0.91, 0.14],
+            ],
+            // Okabe-Ito blue and yellow
+            influence: [[0.0, 0.45, 0.70], [0.94, 0.89, 0.26]],
+            // Okabe-Ito vermillion and yellow
+            liberties: [[0.84, 0.37, 0.0], [0.94, 0.89, 0.26]],
+            // Okabe-Ito bluish green and vermillion
+            what_if: [[0.0, 0.62, 0.45], [0.84, 0.37, 0.0]],
+            // Okabe-Ito vermillion, yellow and sky blue
+            markers: [[0.84, 0.37, 0.0], [0.94, 0.89, 0.26], [0.34, 0.71, 0.91]],
+            // White light, so the palette isn't tinted away from its tested hues
+            light: Light {
+                direction: [0.8, 1.0, 0.6],
+                color: [1.0, 1.0, 1.0],
+                ambient: [0.17, 0.17, 0.17],
+            },
+        }
+    }
+}
+
+impl Theme {
+    // Stone color for flat drawings, like the split view's layer board
+    pub fn stone_color(&self, color: StoneColor) -> [f32; 3] {
+        match color {
+            StoneColor::Black => self.black_stone,
+            StoneColor::White => self.white_stone,
+            StoneColor::Red => self.red_stone,
+            StoneColor::Green => self.green_stone,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,94 @@
+// Board thumbnails drawn on the CPU, for lists of games and for builds
+// without a GPU: the lattice seen from a fixed corner above it, Z up, each
+// stone a shaded disc in the classic theme's colors and each empty point a
+// faint dot, so the board's shape shows before anything is played. Points
+// are drawn back to front, nearer ones a little larger, so nearer stones
+// cover those behind them.
+use super::Theme;
+use crate::game::{Board, StoneColor};
+use glam::Vec3;
+use image::{Rgba, RgbaImage};
+
+// Dark enough for white stones to stand out and light enough for black ones
+const BACKGROUND: [u8; 3] = [64, 70, 80];
+const EMPTY: [f32; 3] = [0.75, 0.75, 0.8];
+// Turned about the up axis, then tilted to look down, in radians
+const YAW: f32 = 0.35;
+const PITCH: f32 = 0.4;
+// As shares of the lattice pitch
+const STONE_RADIUS: f32 = 0.4;
+const DOT_RADIUS: f32 = 0.08;
+// How much bigger the nearest point is drawn than one at the centre
+const PERSPECTIVE: f32 = 0.2;
+
+// A `size` by `size` picture of the board
+pub fn thumbnail(board: &Board, size: u32) -> RgbaImage {
+    let [r, g, b] = BACKGROUND;
+    let mut image = RgbaImage::from_pixel(size, size, Rgba([r, g, b, 255]));
+    let theme = Theme::classic();
+    let half = (board.size() as f32 - 1.0) / 2.0;
+    // From the centre to the lattice's farthest corner, from any side
+    let corner = half * 3f32.sqrt();
+    let scale = size as f32 / 2.0 / (corner + STONE_RADIUS * (1.0 + PERSPECTIVE));
+
+    let mut points: Vec<(Vec3, Option<StoneColor>)> = board
+        .positions()
+        .map(|position| (project(position, half), board.get_stone(position)))
+        .collect();
+    points.sort_by(|a, b| a.0.z.total_cmp(&b.0.z));
+    for (point, stone) in points {
+        let center = (size as f32 / 2.0 + point.x * scale, size as f32 / 2.0 - point.y * scale);
+        let nearness = 1.0 + PERSPECTIVE * point.z / corner.max(1.0);
+        match stone {
+            Some(color) => disc(&mut image, center, STONE_RADIUS * scale * nearness, stone_color(&theme, color), true),
+            None => disc(&mut image, center, DOT_RADIUS * scale * nearness, EMPTY, false),
+        }
+    }
+    image
+}
+
+fn stone_color(theme: &Theme, color: StoneColor) -> [f32; 3] {
+    [theme.black_stone, theme.white_stone, theme.red_stone, theme.green_stone][color.index()]
+}
+
+// A point of the board as (across, up, towards the viewer), in lattice
+// pitches from the board's centre
+fn project((x, y, z): (u8, u8, u8), half: f32) -> Vec3 {
+    let (x, y, z) = (x as f32 - half, y as f32 - half, z as f32 - half);
+    let (sin_yaw, cos_yaw) = YAW.sin_cos();
+    let (sin_pitch, cos_pitch) = PITCH.sin_cos();
+    let across = x * cos_yaw - y * sin_yaw;
+    let towards = x * sin_yaw + y * cos_yaw;
+    Vec3::new(across, z * cos_pitch - towards * sin_pitch, towards * cos_pitch + z * sin_pitch)
+}
+
+// A circle with a smoothed edge; a shaded one brightens towards the upper
+// left like the rendered stones' highlight
+fn disc(image: &mut RgbaImage, (cx, cy): (f32, f32), radius: f32, color: [f32; 3], shaded: bool) {
+    let span = |center: f32, limit: u32| {
+        let low = (center - radius - 1.0).floor().max(0.0) as u32;
+        let high = ((center + radius + 1.0).ceil().max(0.0) as u32).min(limit);
+        low..high
+    };
+    for py in span(cy, image.height()) {
+        for px in span(cx, image.width()) {
+            let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+            let distance = ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+            let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+            if coverage == 0.0 {
+                continue;
+            }
+            let lit = if shaded {
+                let offset = radius * 0.35;
+                (1.0 - ((x - cx + offset).powi(2) + (y - cy + offset).powi(2)).sqrt() / radius).max(0.0)
+            } else {
+                0.0
+            };
+            let pixel = image.get_pixel_mut(px, py);
+            for (channel, &base) in pixel.0.iter_mut().zip(&color) {
+                let value = (base * 255.0 * (0.75 + 0.25 * lit) + 60.0 * lit).min(255.0);
+                *channel = (*channel as f32 * (1.0 - coverage) + value * coverage).round() as u8;
+            }
+        }
+    }
+}
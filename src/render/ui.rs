@@ -1,7 +1,6 @@
-use super::{Camera, Graphics, Instance};
+use super::{AnimationClock, BoardToWorld};
 use crate::game::{GameRules, StoneColor};
-use glam::{Vec3, Mat4};
-use instant::Instant;
+use glam::Vec3;
 
 #[derive(Debug, Clone, Copy)]
 pub enum ViewDirection {
@@ -75,11 +74,37 @@ impl ViewDirection {
     }
 }
 
+// One layer of the board as a side panel draws it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanelLayer {
+    pub layer: usize,
+    pub layer_count: usize,
+    // Board positions, sorted so equal layers compare equal
+    pub stones: Vec<((u8, u8, u8), StoneColor)>,
+}
+
+// Where the side-view panels sit down the right edge of the screen, at a UI
+// scale of 1
+pub const PANEL_WIDTH: f32 = 120.0;
+pub const PANEL_HEIGHT: f32 = 80.0;
+const PANEL_SPACING: f32 = 90.0;
+const RIGHT_MARGIN: f32 = 20.0;
+const START_Y: f32 = 20.0;
+
+// Left, top, width and height of panel `index`, in pixels
+pub fn panel_rect(index: usize, screen_width: f32, scale: f32) -> (f32, f32, f32, f32) {
+    let (width, height) = (PANEL_WIDTH * scale, PANEL_HEIGHT * scale);
+    let left = screen_width - width - RIGHT_MARGIN * scale;
+    (left, (START_Y + index as f32 * PANEL_SPACING) * scale, width, height)
+}
+
 pub struct SideView {
     pub direction: ViewDirection,
+    // The layer shown last frame
     pub current_layer: usize,
-    pub animation_time: f32,
     pub layer_cycle_speed: f32,
+    // Holds the view on one layer instead of cycling
+    pinned: Option<usize>,
 }
 
 impl SideView {
@@ -87,44 +112,109 @@ impl SideView {
         Self {
             direction,
             current_layer: 0,
-            animation_time: 0.0,
             layer_cycle_speed: 0.3, // layers per second (slower for better visibility)
+            pinned: None,
         }
     }
 
-    pub fn update(&mut self, dt: f32, _board_size: usize) {
-        self.animation_time += dt;
-        
-        // Each view cycles through layers at different offsets for visual variety
-        let offset = match self.direction {
+    pub fn is_pinned(&self) -> bool {
+        self.pinned.is_some()
+    }
+
+    // Pins the layer on show, or resumes cycling if already pinned; returns
+    // whether the view is now pinned
+    pub fn toggle_pin(&mut self) -> bool {
+        self.pinned = match self.pinned {
+            Some(_) => None,
+            None => Some(self.current_layer),
+        };
+        self.pinned.is_some()
+    }
+
+    // Pins the view `steps` layers on from the one on show; kept on the
+    // board when next drawn
+    pub fn step_layer(&mut self, steps: isize) {
+        let from = self.pinned.unwrap_or(self.current_layer);
+        self.pinned = Some(from.saturating_add_signed(steps));
+    }
+
+    // Each view starts its cycle a little further along, for visual variety
+    fn phase(&self) -> f32 {
+        match self.direction {
             ViewDirection::Top => 0.0,
             ViewDirection::Left => 0.2,
             ViewDirection::Right => 0.4,
             ViewDirection::Back => 0.6,
             ViewDirection::Front => 0.8,
             ViewDirection::Bottom => 1.0,
+        }
+    }
+
+    // Where a board position lands in this view: its layer, counted from the
+    // side the view looks from, and its column and row on the panel, as the
+    // view's camera (right-handed, like the main one) would see them. So
+    // opposite views are mirror images and run their layers in reverse.
+    pub fn project(&self, to_world: &BoardToWorld, (x, y, z): (u8, u8, u8)) -> (usize, usize, usize) {
+        let board_size = to_world.board_size();
+        let (eye, target, up) = self.direction.get_camera_position(1.0);
+        let forward = (target - eye).normalize();
+        let right = forward.cross(up);
+        let board = [x as usize, y as usize, z as usize];
+
+        // Index along a world axis direction, counted from its negative end
+        let along = |direction: Vec3| {
+            let world_axis = (0..3)
+                .max_by(|&a, &b| direction[a].abs().total_cmp(&direction[b].abs()))
+                .unwrap_or(0);
+            let index = board[to_world.board_axis(world_axis)];
+            if direction[world_axis] > 0.0 { index } else { board_size - 1 - index }
         };
-        
-        self.animation_time += offset * dt; // Apply offset to the time increment
+        // Rows count down from the top of the panel
+        (along(forward), along(right), board_size - 1 - along(up))
+    }
+
+    // The layer to show and its stones: the pinned layer, else the one the
+    // cycle is on, or None when cycling an empty board
+    pub fn get_visible_stones(&mut self, game_rules: &GameRules, to_world: &BoardToWorld, clock: &AnimationClock) -> Option<PanelLayer> {
+        let board_size = game_rules.board().size();
+
+        let current_layer = match self.pinned {
+            Some(layer) => {
+                let layer = layer.min(board_size.saturating_sub(1));
+                self.pinned = Some(layer);
+                layer
+            }
+            None => self.cycled_layer(game_rules, to_world, clock)?,
+        };
+        self.current_layer = current_layer;
+
+        let mut stones: Vec<_> = game_rules
+            .board()
+            .get_all_stones()
+            .filter(|&(&position, _)| self.project(to_world, position).0 == current_layer)
+            .map(|(&position, &color)| (position, color))
+            .collect();
+        stones.sort_by_key(|&(position, _)| position);
+
+        Some(PanelLayer {
+            layer: current_layer,
+            layer_count: board_size,
+            stones,
+        })
     }
 
-    pub fn get_visible_stones(&self, game_rules: &GameRules, max_layers: usize) -> (Vec<Instance>, Vec<Instance>) {
-        let mut black_stones = Vec::new();
-        let mut white_stones = Vec::new();
+    // The occupied layer the animation is on, cycling from the nearest to the
+    // furthest one
+    fn cycled_layer(&self, game_rules: &GameRules, to_world: &BoardToWorld, clock: &AnimationClock) -> Option<usize> {
         let board_size = game_rules.board().size();
-        let half_size = board_size as f32 * 0.5;
 
         // First, find the min and max occupied layers for this view direction
         let mut min_layer = board_size;
         let mut max_layer = 0;
         let mut has_stones = false;
         
-        for ((x, y, z), _color) in game_rules.board().get_all_stones() {
-            let stone_layer = match self.direction {
-                ViewDirection::Top | ViewDirection::Bottom => *y as usize,
-                ViewDirection::Left | ViewDirection::Right => *x as usize,
-                ViewDirection::Front | ViewDirection::Back => *z as usize,
-            };
+        for (&position, _color) in game_rules.board().get_all_stones() {
+            let (stone_layer, _, _) = self.project(to_world, position);
             
             if stone_layer < min_layer {
                 min_layer = stone_layer;
@@ -135,60 +225,40 @@ impl SideView {
             has_stones = true;
         }
 
-        // If no stones, return empty
+        // If no stones, there's no layer to show
         if !has_stones {
-            return (black_stones, white_stones);
+            return None;
         }
 
         // Calculate the range of layers to cycle through (min to max inclusive)
         let layer_range = max_layer - min_layer + 1;
         
         // Calculate which layer in the range to show based on animation
-        let cycle_position = (self.animation_time * self.layer_cycle_speed) % layer_range as f32;
+        let cycle_position = (clock.time() as f32 * self.layer_cycle_speed + self.phase()) % layer_range as f32;
         let current_layer = min_layer + cycle_position as usize;
         
         // Debug output - only print occasionally to avoid spam
         static mut DEBUG_COUNTER: usize = 0;
         unsafe {
             DEBUG_COUNTER += 1;
-            if DEBUG_COUNTER % 300 == 0 {  // Print every ~5 seconds at 60fps
+            if DEBUG_COUNTER.is_multiple_of(300) {  // Print every ~5 seconds at 60fps
                 println!("[{:?}] Layer {}/{} (range: {}-{})", 
                     self.direction, current_layer, layer_range, min_layer, max_layer);
             }
         }
 
-        // Show only stones from the current animated layer
-        for ((x, y, z), color) in game_rules.board().get_all_stones() {
-            let stone_layer = match self.direction {
-                ViewDirection::Top | ViewDirection::Bottom => *y as usize,
-                ViewDirection::Left | ViewDirection::Right => *x as usize,
-                ViewDirection::Front | ViewDirection::Back => *z as usize,
-            };
-
-            if stone_layer == current_layer {
-                let pos = Vec3::new(
-                    *x as f32 - half_size + 0.5,
-                    *z as f32 - half_size + 0.5,
-                    *y as f32 - half_size + 0.5,
-                );
-                
-                let mut instance = Instance::new(pos);
-                instance.scale = Vec3::splat(0.8); // Smaller for side views
-                
-                match color {
-                    StoneColor::Black => black_stones.push(instance),
-                    StoneColor::White => white_stones.push(instance),
-                }
-            }
-        }
-
-        (black_stones, white_stones)
+        Some(current_layer)
     }
 }
 
 pub struct UISystem {
     pub side_views: [SideView; 6],
-    pub last_update_time: Instant,
+}
+
+impl Default for UISystem {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl UISystem {
@@ -203,19 +273,6 @@ impl UISystem {
             SideView::new(directions[5]),
         ];
 
-        Self {
-            side_views,
-            last_update_time: Instant::now(),
-        }
-    }
-
-    pub fn update(&mut self, board_size: usize) {
-        let now = Instant::now();
-        let dt = now.duration_since(self.last_update_time).as_secs_f32();
-        self.last_update_time = now;
-
-        for view in &mut self.side_views {
-            view.update(dt, board_size);
-        }
+        Self { side_views }
     }
 }
\ No newline at end of file
@@ -1,4 +1,3 @@
-use wgpu::util::DeviceExt;
 use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
@@ -52,11 +51,13 @@ impl UIPanels {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
+                compilation_options: Default::default(),
                 buffers: &[UIVertex::desc()],
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
                 entry_point: "fs_main",
+                compilation_options: Default::default(),
                 targets: &[Some(wgpu::ColorTargetState {
                     format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -79,19 +80,20 @@ impl UIPanels {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+            cache: None,
         });
 
         Self { pipeline }
     }
 
-    pub fn create_panel_border(&self, x: f32, y: f32, width: f32, height: f32, screen_width: f32, screen_height: f32) -> (Vec<UIVertex>, Vec<u16>) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_panel_border(&self, x: f32, y: f32, width: f32, height: f32, screen_width: f32, screen_height: f32, border_color: [f32; 4]) -> (Vec<UIVertex>, Vec<u16>) {
         // Convert screen coordinates to NDC
         let ndc_x = (x / screen_width) * 2.0 - 1.0;
         let ndc_y = 1.0 - (y / screen_height) * 2.0;
         let ndc_w = (width / screen_width) * 2.0;
         let ndc_h = (height / screen_height) * 2.0;
 
-        let border_color = [1.0, 1.0, 1.0, 1.0]; // White border
         let border_width = 2.0 / screen_width; // 1px converted to NDC
         let border_height = 2.0 / screen_height;
 
@@ -142,59 +144,7 @@ impl UIPanels {
         (vertices, indices)
     }
 
-    pub fn create_panel_with_stones(&self, x: f32, y: f32, width: f32, height: f32, screen_width: f32, screen_height: f32, black_count: usize, white_count: usize) -> (Vec<UIVertex>, Vec<u16>) {
-        // Convert screen coordinates to NDC
-        let ndc_x = (x / screen_width) * 2.0 - 1.0;
-        let ndc_y = 1.0 - (y / screen_height) * 2.0;
-        let ndc_w = (width / screen_width) * 2.0;
-        let ndc_h = (height / screen_height) * 2.0;
-
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
-        let mut index_offset = 0u16;
-
-        // Background (dark gray to show stones better)
-        let bg_color = [0.1, 0.1, 0.1, 1.0];
-        vertices.extend_from_slice(&[
-            UIVertex { position: [ndc_x + 0.01, ndc_y - 0.01], color: bg_color },
-            UIVertex { position: [ndc_x + ndc_w - 0.01, ndc_y - 0.01], color: bg_color },
-            UIVertex { position: [ndc_x + ndc_w - 0.01, ndc_y - ndc_h + 0.01], color: bg_color },
-            UIVertex { position: [ndc_x + 0.01, ndc_y - ndc_h + 0.01], color: bg_color },
-        ]);
-        indices.extend_from_slice(&[index_offset, index_offset + 1, index_offset + 2, index_offset, index_offset + 2, index_offset + 3]);
-        index_offset += 4;
-
-        // Simple stone representation - show colored squares for black/white stones
-        let stone_size = (ndc_w.min(ndc_h)) * 0.1; // Small squares
-        let stones_per_row = 4;
-        let total_stones = black_count + white_count;
-        
-        for i in 0..total_stones.min(12) { // Max 12 stones to fit in panel
-            let row = i / stones_per_row;
-            let col = i % stones_per_row;
-            
-            let stone_x = ndc_x + 0.02 + col as f32 * stone_size * 1.2;
-            let stone_y = ndc_y - 0.02 - row as f32 * stone_size * 1.2;
-            
-            let color = if i < black_count {
-                [0.1, 0.1, 0.1, 1.0] // Dark for black stones
-            } else {
-                [0.9, 0.9, 0.9, 1.0] // Light for white stones
-            };
-            
-            vertices.extend_from_slice(&[
-                UIVertex { position: [stone_x, stone_y], color },
-                UIVertex { position: [stone_x + stone_size, stone_y], color },
-                UIVertex { position: [stone_x + stone_size, stone_y - stone_size], color },
-                UIVertex { position: [stone_x, stone_y - stone_size], color },
-            ]);
-            indices.extend_from_slice(&[index_offset, index_offset + 1, index_offset + 2, index_offset, index_offset + 2, index_offset + 3]);
-            index_offset += 4;
-        }
-
-        (vertices, indices)
-    }
-
+    #[allow(clippy::too_many_arguments)]
     pub fn create_panel_vertices(&self, x: f32, y: f32, width: f32, height: f32, screen_width: f32, screen_height: f32, panel_id: u32) -> (Vec<UIVertex>, Vec<u16>) {
         // Convert screen coordinates to NDC
         let ndc_x = (x / screen_width) * 2.0 - 1.0;
@@ -274,4 +224,76 @@ impl UIPanels {
 
         (vertices, indices)
     }
+}
+
+// Accumulates 2D shapes given in screen pixels, for the UI panel pipeline
+pub struct ScreenMesh {
+    vertices: Vec<UIVertex>,
+    indices: Vec<u16>,
+    screen: (f32, f32),
+}
+
+impl ScreenMesh {
+    pub fn new(screen_width: f32, screen_height: f32) -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            screen: (screen_width, screen_height),
+        }
+    }
+
+    pub fn into_parts(self) -> (Vec<UIVertex>, Vec<u16>) {
+        (self.vertices, self.indices)
+    }
+
+    fn ndc(&self, x: f32, y: f32) -> [f32; 2] {
+        [(x / self.screen.0) * 2.0 - 1.0, 1.0 - (y / self.screen.1) * 2.0]
+    }
+
+    // A convex polygon as a triangle fan
+    pub fn fan(&mut self, corners: &[(f32, f32)], color: [f32; 4]) {
+        let base = self.vertices.len() as u16;
+        for &(x, y) in corners {
+            let position = self.ndc(x, y);
+            self.vertices.push(UIVertex { position, color });
+        }
+        for i in 1..corners.len().saturating_sub(1) as u16 {
+            self.indices.extend([base, base + i, base + i + 1]);
+        }
+    }
+
+    pub fn quad(&mut self, corners: [(f32, f32); 4], color: [f32; 4]) {
+        self.fan(&corners, color);
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: [f32; 4]) {
+        self.quad([(x, y), (x + width, y), (x + width, y + height), (x, y + height)], color);
+    }
+
+    // Regular polygon around (x, y); enough sides make a disc
+    pub fn regular(&mut self, (x, y): (f32, f32), radius: f32, sides: usize, color: [f32; 4]) {
+        let corners: Vec<(f32, f32)> = (0..sides)
+            .map(|i| {
+                // Start at the top so triangles point up
+                let angle = std::f32::consts::TAU * i as f32 / sides as f32 - std::f32::consts::FRAC_PI_2;
+                (x + radius * angle.cos(), y + radius * angle.sin())
+            })
+            .collect();
+        self.fan(&corners, color);
+    }
+
+    pub fn segment(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: [f32; 4]) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        let (nx, ny) = (-dy / length * width * 0.5, dx / length * width * 0.5);
+        self.quad(
+            [
+                (from.0 + nx, from.1 + ny),
+                (to.0 + nx, to.1 + ny),
+                (to.0 - nx, to.1 - ny),
+                (from.0 - nx, from.1 - ny),
+            ],
+            color,
+        );
+    }
 }
\ No newline at end of file
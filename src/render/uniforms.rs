@@ -0,0 +1,138 @@
+// Per-view shader uniforms. Every view drawn in a frame (the main camera, each
+// stereo eye, later side panels or offscreen exports) pushes its own slot into
+// a CPU staging area; the whole frame is then uploaded with one write and each
+// draw binds its slot by dynamic offset, so no view overwrites another's
+// camera before the GPU has read it.
+use super::camera::CameraUniform;
+use super::profiler;
+use super::theme::Light;
+use bytemuck::{Pod, Zeroable};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct ViewUniform {
+    camera: CameraUniform,
+    light_direction: [f32; 4],
+    light_color: [f32; 4],
+    ambient: [f32; 4],
+}
+
+impl ViewUniform {
+    pub fn new(camera: CameraUniform, light: &Light) -> Self {
+        let [x, y, z] = light.direction;
+        let [r, g, b] = light.color;
+        let [ar, ag, ab] = light.ambient;
+        Self {
+            camera,
+            light_direction: [x, y, z, 0.0],
+            light_color: [r, g, b, 1.0],
+            ambient: [ar, ag, ab, 1.0],
+        }
+    }
+}
+
+pub struct UniformRing {
+    layout: wgpu::BindGroupLayout,
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    // Slot size rounded up to the device's dynamic offset alignment
+    stride: usize,
+    capacity: usize,
+    staging: Vec<u8>,
+}
+
+impl UniformRing {
+    pub fn new(device: &wgpu::Device, capacity: usize) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<ViewUniform>() as u64),
+                    },
+                    count: None,
+                }
+            ],
+            label: Some("view_uniform_layout"),
+        });
+
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as usize;
+        let stride = std::mem::size_of::<ViewUniform>().next_multiple_of(alignment);
+        let (buffer, bind_group) = Self::allocate(device, &layout, stride, capacity);
+        Self {
+            layout,
+            buffer,
+            bind_group,
+            stride,
+            capacity,
+            staging: Vec::with_capacity(stride * capacity),
+        }
+    }
+
+    fn allocate(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        stride: usize,
+        capacity: usize,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("View Uniform Ring"),
+            size: (stride * capacity) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<ViewUniform>() as u64),
+                    }),
+                }
+            ],
+            label: Some("view_uniform_bind_group"),
+        });
+        (buffer, bind_group)
+    }
+
+    // Pipelines drawing with these uniforms take this as their group 0 layout
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    // Forgets last frame's views; call before pushing this frame's
+    pub fn begin_frame(&mut self) {
+        self.staging.clear();
+    }
+
+    // Stages one view and returns the dynamic offset to bind it with
+    pub fn push(&mut self, uniform: ViewUniform) -> u32 {
+        let offset = self.staging.len();
+        self.staging.extend_from_slice(bytemuck::bytes_of(&uniform));
+        self.staging.resize(offset + self.stride, 0);
+        offset as u32
+    }
+
+    // Writes the staged views, growing the ring first if this frame had more
+    // views than it has slots
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let needed = self.staging.len() / self.stride;
+        if needed > self.capacity {
+            self.capacity = needed.next_power_of_two();
+            (self.buffer, self.bind_group) = Self::allocate(device, &self.layout, self.stride, self.capacity);
+        }
+        if !self.staging.is_empty() {
+            profiler::write_buffer(queue, &self.buffer, 0, &self.staging);
+        }
+    }
+}
\ No newline at end of file
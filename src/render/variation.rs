@@ -0,0 +1,52 @@
+// Per-stone variation so a full board looks less synthetic: a slight radius
+// jitter, a random spin and a seed the stone shader turns into the shell
+// banding of clamshell white stones. Everything derives from the board
+// position, so a stone keeps its look whenever the instances are rebuilt.
+use super::Instance;
+use glam::Quat;
+
+type Position = (u8, u8, u8);
+
+// Largest relative change to the stone radius
+const RADIUS_JITTER: f32 = 0.03;
+// Largest tilt away from the mesh's own axis, in radians
+const MAX_TILT: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StoneLook {
+    // In (0, 1]; zero is left for instances without variation
+    pub seed: f32,
+    pub radius_factor: f32,
+    pub rotation: Quat,
+}
+
+impl StoneLook {
+    pub fn at((x, y, z): Position) -> Self {
+        let hash = mix(u32::from(x) | u32::from(y) << 8 | u32::from(z) << 16);
+        // Three independent values in [0, 1) from one hash
+        let unit = |shift: u32| ((hash >> shift) & 0x3ff) as f32 / 1024.0;
+        let (a, b, c) = (unit(0), unit(10), unit(20));
+
+        Self {
+            seed: 1.0 - a,
+            radius_factor: 1.0 + (b * 2.0 - 1.0) * RADIUS_JITTER,
+            rotation: Quat::from_rotation_y(c * std::f32::consts::TAU)
+                * Quat::from_rotation_x((a * 2.0 - 1.0) * MAX_TILT),
+        }
+    }
+
+    pub fn apply(&self, instance: &mut Instance) {
+        instance.scale *= self.radius_factor;
+        instance.rotation = self.rotation;
+        instance.seed = self.seed;
+    }
+}
+
+// Murmur3's finalizer: cheap and spreads neighbouring points far apart
+fn mix(mut h: u32) -> u32 {
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2_ae35);
+    h ^ (h >> 16)
+}
\ No newline at end of file
@@ -0,0 +1,17 @@
+// The what-if preview: shells over the stones a move would capture and, when
+// it would leave its own group in atari, over that group and the point itself
+use super::{BoardToWorld, Instance};
+use crate::game::MovePreview;
+
+// Shell kinds: stones captured, then the mover's group left in atari
+pub const KINDS: usize = 2;
+
+pub fn shells(preview: &MovePreview, to_world: &BoardToWorld) -> [Vec<Instance>; KINDS] {
+    let shell = |&position: &(u8, u8, u8)| {
+        let mut instance = Instance::new(to_world.to_world(position));
+        instance.scale = glam::Vec3::splat(to_world.stone_scale());
+        instance
+    };
+    let atari = if preview.in_atari() { preview.group.iter().map(shell).collect() } else { Vec::new() };
+    [preview.captured.iter().map(shell).collect(), atari]
+}
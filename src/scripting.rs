@@ -0,0 +1,290 @@
+// Community-made scripts that follow a game and act on it, e.g. tutorials that
+// comment on each move or problems that mark the answer. Scripts are Rhai and
+// may define any of these hooks:
+//
+//   fn on_start()                       once, after the script has loaded
+//   fn on_move(color, point, captured)  "black"/"white", "d4-2", stones taken
+//   fn on_pass(color)
+//   fn on_undo()
+//   fn on_reset()
+//
+// and call back into the game with size(), to_move(), move_number(),
+// stone(point), is_legal(point), mark(point, mark), unmark(point),
+// propose(point) and say(text). Marks are the teaching words: a letter,
+// triangle, square or a marker color. Script functions can't see the script's
+// variables, so remember(key, value) and recall(key) keep state between hooks.
+// Running scripts needs the `scripting` feature.
+use crate::game::{GameRules, Mark, SessionEvent};
+use std::fmt;
+use std::path::Path;
+
+type Point = (u8, u8, u8);
+
+// What a hook asked the frontend to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptCommand {
+    // Markup on the current position, as a teacher would draw it
+    Mark { point: Point, mark: Option<Mark> },
+    // A move the script suggests; shown, never played for the user
+    Propose(Point),
+    Say(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    Unsupported,
+    Unreadable { path: String, reason: String },
+    Compile(String),
+    Runtime { hook: &'static str, reason: String },
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Unsupported => f.write_str("running scripts needs the `scripting` feature"),
+            ScriptError::Unreadable { path, reason } => write!(f, "cannot read script {}: {}", path, reason),
+            ScriptError::Compile(reason) => write!(f, "script doesn't compile: {}", reason),
+            ScriptError::Runtime { hook, reason } => write!(f, "script failed in {}: {}", hook, reason),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+fn color_word(color: crate::game::StoneColor) -> &'static str {
+    match color {
+        crate::game::StoneColor::Black => "black",
+        crate::game::StoneColor::White => "white",
+        crate::game::StoneColor::Red => "red",
+        crate::game::StoneColor::Green => "green",
+    }
+}
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use super::{color_word, Point, ScriptCommand, ScriptError};
+    use crate::accessibility::{format_point, parse_point};
+    use crate::game::GameRules;
+    use crate::teaching::parse_mark;
+    use rhai::{CallFnOptions, Dynamic, EvalAltResult, FuncArgs, Map, Scope, AST};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // A runaway loop in a hook stops here instead of freezing the frame
+    const MAX_OPERATIONS: u64 = 1_000_000;
+
+    // What the registered functions see: the position the hook runs against
+    // and what it has asked for so far
+    struct Context {
+        rules: GameRules,
+        commands: Vec<ScriptCommand>,
+        memory: Map,
+    }
+
+    impl Context {
+        fn point(&self, text: &str) -> Result<Point, Box<EvalAltResult>> {
+            parse_point(text, self.rules.board().size()).map_err(|e| e.to_string().into())
+        }
+    }
+
+    pub struct ScriptHost {
+        engine: rhai::Engine,
+        ast: AST,
+        scope: Scope<'static>,
+        context: Rc<RefCell<Context>>,
+    }
+
+    impl ScriptHost {
+        pub fn new(source: &str) -> Result<Self, ScriptError> {
+            let context = Rc::new(RefCell::new(Context {
+                rules: GameRules::new(3),
+                commands: Vec::new(),
+                memory: Map::new(),
+            }));
+            let mut engine = rhai::Engine::new();
+            engine.set_max_operations(MAX_OPERATIONS);
+            register(&mut engine, &context);
+
+            let ast = engine.compile(source).map_err(|e| ScriptError::Compile(e.to_string()))?;
+            Ok(Self {
+                engine,
+                ast,
+                scope: Scope::new(),
+                context,
+            })
+        }
+
+        // Runs the script's top level, then on_start
+        pub fn start(&mut self, rules: &GameRules) -> Result<Vec<ScriptCommand>, ScriptError> {
+            self.context.borrow_mut().rules = rules.clone();
+            self.engine
+                .run_ast_with_scope(&mut self.scope, &self.ast)
+                .map_err(|e| ScriptError::Runtime {
+                    hook: "the top level",
+                    reason: e.to_string(),
+                })?;
+            self.call("on_start", 0, (), rules)
+        }
+
+        pub fn call(
+            &mut self,
+            hook: &'static str,
+            arity: usize,
+            args: impl FuncArgs,
+            rules: &GameRules,
+        ) -> Result<Vec<ScriptCommand>, ScriptError> {
+            let defined = self
+                .ast
+                .iter_functions()
+                .any(|function| function.name == hook && function.params.len() == arity);
+            if !defined {
+                return Ok(Vec::new());
+            }
+
+            self.context.borrow_mut().rules = rules.clone();
+            // The top level already ran in start
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self
+                .engine
+                .call_fn_with_options::<Dynamic>(options, &mut self.scope, &self.ast, hook, args);
+            let commands = std::mem::take(&mut self.context.borrow_mut().commands);
+            result.map(|_| commands).map_err(|e| ScriptError::Runtime {
+                hook,
+                reason: e.to_string(),
+            })
+        }
+    }
+
+    fn register(engine: &mut rhai::Engine, context: &Rc<RefCell<Context>>) {
+        let ctx = context.clone();
+        engine.register_fn("size", move || ctx.borrow().rules.board().size() as i64);
+        let ctx = context.clone();
+        engine.register_fn("to_move", move || color_word(ctx.borrow().rules.current_player()).to_string());
+        let ctx = context.clone();
+        engine.register_fn("move_number", move || ctx.borrow().rules.moves().len() as i64);
+        let ctx = context.clone();
+        engine.register_fn("stone", move |point: &str| -> Result<String, Box<EvalAltResult>> {
+            let context = ctx.borrow();
+            let stone = context.rules.board().get_stone(context.point(point)?);
+            Ok(stone.map_or("", color_word).to_string())
+        });
+        let ctx = context.clone();
+        engine.register_fn("is_legal", move |point: &str| -> Result<bool, Box<EvalAltResult>> {
+            let context = ctx.borrow();
+            let (x, y, z) = context.point(point)?;
+            Ok(context.rules.is_legal_move(x, y, z))
+        });
+
+        let ctx = context.clone();
+        engine.register_fn("mark", move |point: &str, mark: &str| -> Result<(), Box<EvalAltResult>> {
+            let mut context = ctx.borrow_mut();
+            let point = context.point(point)?;
+            let mark = parse_mark(mark).ok_or_else(|| format!("unknown mark '{}'", mark))?;
+            context.commands.push(ScriptCommand::Mark { point, mark: Some(mark) });
+            Ok(())
+        });
+        let ctx = context.clone();
+        engine.register_fn("unmark", move |point: &str| -> Result<(), Box<EvalAltResult>> {
+            let mut context = ctx.borrow_mut();
+            let point = context.point(point)?;
+            context.commands.push(ScriptCommand::Mark { point, mark: None });
+            Ok(())
+        });
+        let ctx = context.clone();
+        engine.register_fn("propose", move |point: &str| -> Result<(), Box<EvalAltResult>> {
+            let mut context = ctx.borrow_mut();
+            let point = context.point(point)?;
+            if !context.rules.is_legal_move(point.0, point.1, point.2) {
+                return Err(format!("{} is not a legal move", format_point(point)).into());
+            }
+            context.commands.push(ScriptCommand::Propose(point));
+            Ok(())
+        });
+        let ctx = context.clone();
+        engine.register_fn("say", move |text: &str| {
+            ctx.borrow_mut().commands.push(ScriptCommand::Say(text.to_string()));
+        });
+
+        let ctx = context.clone();
+        engine.register_fn("remember", move |key: &str, value: Dynamic| {
+            ctx.borrow_mut().memory.insert(key.into(), value);
+        });
+        let ctx = context.clone();
+        engine.register_fn("recall", move |key: &str| {
+            ctx.borrow().memory.get(key).cloned().unwrap_or(Dynamic::UNIT)
+        });
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+mod engine {
+    use super::{ScriptCommand, ScriptError};
+    use crate::game::GameRules;
+
+    pub struct ScriptHost;
+
+    impl ScriptHost {
+        pub fn new(_source: &str) -> Result<Self, ScriptError> {
+            Err(ScriptError::Unsupported)
+        }
+
+        pub fn start(&mut self, _rules: &GameRules) -> Result<Vec<ScriptCommand>, ScriptError> {
+            Ok(Vec::new())
+        }
+
+        pub fn call<A>(
+            &mut self,
+            _hook: &'static str,
+            _arity: usize,
+            _args: A,
+            _rules: &GameRules,
+        ) -> Result<Vec<ScriptCommand>, ScriptError> {
+            Ok(Vec::new())
+        }
+    }
+}
+
+// A loaded script and the game events it has hooks for
+pub struct Script {
+    host: engine::ScriptHost,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self, ScriptError> {
+        if cfg!(not(feature = "scripting")) {
+            return Err(ScriptError::Unsupported);
+        }
+        let source = std::fs::read_to_string(path).map_err(|e| ScriptError::Unreadable {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })?;
+        Self::from_source(&source)
+    }
+
+    pub fn from_source(source: &str) -> Result<Self, ScriptError> {
+        engine::ScriptHost::new(source).map(|host| Self { host })
+    }
+
+    pub fn start(&mut self, rules: &GameRules) -> Result<Vec<ScriptCommand>, ScriptError> {
+        self.host.start(rules)
+    }
+
+    // Generic Changed events aren't passed on: the script's own markup sends
+    // them, and reacting to those would loop
+    pub fn handle_event(&mut self, event: &SessionEvent, rules: &GameRules) -> Result<Vec<ScriptCommand>, ScriptError> {
+        match *event {
+            SessionEvent::MovePlayed { color, position, captured } => {
+                let args = (
+                    color_word(color).to_string(),
+                    crate::accessibility::format_point(position),
+                    captured as i64,
+                );
+                self.host.call("on_move", 3, args, rules)
+            }
+            SessionEvent::Passed { color } => self.host.call("on_pass", 1, (color_word(color).to_string(),), rules),
+            SessionEvent::Undone => self.host.call("on_undo", 0, (), rules),
+            SessionEvent::Reset => self.host.call("on_reset", 0, (), rules),
+            SessionEvent::Changed => Ok(Vec::new()),
+        }
+    }
+}
\ No newline at end of file
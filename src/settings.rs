@@ -0,0 +1,574 @@
+use crate::ai::{Engine, GreedyEngine, MctsEngine, RandomEngine};
+use crate::game::{GameRules, StoneColor, Variant};
+use crate::power;
+use crate::render::Theme;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoringRule {
+    // Enclosed empty points plus prisoners taken
+    #[default]
+    Territory,
+    // Enclosed empty points plus stones on the board
+    Area,
+}
+
+impl FromStr for ScoringRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "territory" | "japanese" => Ok(ScoringRule::Territory),
+            "area" | "chinese" => Ok(ScoringRule::Area),
+            other => Err(format!("unknown rule set '{}' (expected territory or area)", other)),
+        }
+    }
+}
+
+impl fmt::Display for ScoringRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoringRule::Territory => f.write_str("territory"),
+            ScoringRule::Area => f.write_str("area"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSettings {
+    pub board_size: usize,
+    pub scoring: ScoringRule,
+    pub komi: f32,
+    pub variant: Variant,
+    // Colors taking turns, 2 to 4
+    pub players: u8,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            board_size: 3,
+            scoring: ScoringRule::Territory,
+            komi: 0.5,
+            variant: Variant::Standard,
+            players: 2,
+        }
+    }
+}
+
+impl GameSettings {
+    // Board edge lengths a game can be set up with
+    pub const MIN_BOARD_SIZE: usize = 2;
+    pub const MAX_BOARD_SIZE: usize = 19;
+
+    pub fn new_game(&self) -> GameRules {
+        let mut rules = self.variant.new_game(self.board_size);
+        rules.set_players(self.players);
+        rules
+    }
+
+    // Final (black, white) score; komi is added to White
+    pub fn score(&self, rules: &GameRules) -> (f32, f32) {
+        let (black_territory, white_territory) = rules.get_territory_score();
+        let board = rules.board();
+
+        let (black_bonus, white_bonus) = match self.scoring {
+            ScoringRule::Territory => (
+                board.get_captured(StoneColor::White),
+                board.get_captured(StoneColor::Black),
+            ),
+            ScoringRule::Area => {
+                let black_stones = board.stones_of(StoneColor::Black).count();
+                (black_stones, board.stones_of(StoneColor::White).count())
+            }
+        };
+
+        (
+            (black_territory + black_bonus) as f32,
+            (white_territory + white_bonus) as f32 + self.komi,
+        )
+    }
+
+    // Final score of every player, in turn order. Games of three or four are
+    // always area scored without komi: a prisoner can't be credited to
+    // whoever took it once several colors share in a capture
+    pub fn scores(&self, rules: &GameRules) -> Vec<(StoneColor, f32)> {
+        if rules.players() == 2 {
+            let (black, white) = self.score(rules);
+            return vec![(StoneColor::Black, black), (StoneColor::White, white)];
+        }
+        let territory = rules.territory_by_color();
+        StoneColor::players(rules.players())
+            .iter()
+            .map(|&color| {
+                let stones = rules.board().stones_of(color).count();
+                (color, (stones + territory[color.index()]) as f32)
+            })
+            .collect()
+    }
+}
+
+// How much of the asked-for search the MCTS level spends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AiBudget {
+    // Scaled down on the web or on battery power
+    #[default]
+    Auto,
+    // Always all of it
+    Full,
+    // Always scaled down
+    Saver,
+}
+
+impl FromStr for AiBudget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(AiBudget::Auto),
+            "full" => Ok(AiBudget::Full),
+            "saver" => Ok(AiBudget::Saver),
+            other => Err(format!("unknown AI budget '{}' (expected auto, full or saver)", other)),
+        }
+    }
+}
+
+impl fmt::Display for AiBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AiBudget::Auto => f.write_str("auto"),
+            AiBudget::Full => f.write_str("full"),
+            AiBudget::Saver => f.write_str("saver"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AiSettings {
+    pub enabled: bool,
+    pub level: u8,
+    pub color: StoneColor,
+    // Search playouts and threads for the MCTS level; 0 threads means one
+    // per core
+    pub playouts: usize,
+    pub threads: usize,
+    pub budget: AiBudget,
+    // Where the engine's random choices start; None for fresh ones each game
+    pub seed: Option<u64>,
+}
+
+impl Default for AiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level: 1,
+            color: StoneColor::White,
+            playouts: MctsEngine::DEFAULT_PLAYOUTS,
+            threads: 0,
+            budget: AiBudget::Auto,
+            seed: None,
+        }
+    }
+}
+
+impl AiSettings {
+    pub const MAX_LEVEL: u8 = 2;
+    // The saver budget: a quarter of the playouts, though never fewer than
+    // SAVER_MIN_PLAYOUTS unless fewer were asked for, on at most two threads
+    const SAVER_DIVISOR: usize = 4;
+    const SAVER_MIN_PLAYOUTS: usize = 100;
+    const SAVER_THREADS: usize = 2;
+
+    // Whether the search is scaled down, checking the power supply now for
+    // the auto budget
+    pub fn saving(&self) -> bool {
+        match self.budget {
+            AiBudget::Auto => power::constrained(),
+            AiBudget::Full => false,
+            AiBudget::Saver => true,
+        }
+    }
+
+    // The playouts and threads the MCTS level searches with
+    pub fn search(&self) -> (usize, usize) {
+        if !self.saving() {
+            return (self.playouts, self.threads);
+        }
+        let playouts = (self.playouts / Self::SAVER_DIVISOR).max(self.playouts.min(Self::SAVER_MIN_PLAYOUTS));
+        let threads = match self.threads {
+            0 => Self::SAVER_THREADS,
+            threads => threads.min(Self::SAVER_THREADS),
+        };
+        (playouts, threads)
+    }
+
+    // An engine for games played under `game`, whose komi and scoring rule
+    // the search judges positions by
+    pub fn create_engine(&self, game: &GameSettings) -> Box<dyn Engine> {
+        let mut engine: Box<dyn Engine> = match self.level {
+            0 => Box::new(RandomEngine::new()),
+            1 => Box::new(GreedyEngine::new()),
+            _ => {
+                let (playouts, threads) = self.search();
+                Box::new(MctsEngine::new(playouts, threads, game.clone()))
+            }
+        };
+        if let Some(seed) = self.seed {
+            engine.reseed(seed);
+        }
+        engine
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioSettings {
+    pub volume: f32, // 0.0..=1.0
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            volume: 0.8,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub const VOLUME_STEP: f32 = 0.1;
+
+    pub fn adjust_volume(&mut self, delta: f32) {
+        self.volume = (self.volume + delta).clamp(0.0, 1.0);
+    }
+
+    // Volume actually applied to samples
+    pub fn gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StereoMode {
+    #[default]
+    Off,
+    // Red/cyan glasses, both eyes composited into one image
+    Anaglyph,
+    // Left eye in the left half of the window, right eye in the right half
+    SideBySide,
+}
+
+impl StereoMode {
+    pub fn next(self) -> Self {
+        match self {
+            StereoMode::Off => StereoMode::Anaglyph,
+            StereoMode::Anaglyph => StereoMode::SideBySide,
+            StereoMode::SideBySide => StereoMode::Off,
+        }
+    }
+}
+
+impl fmt::Display for StereoMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StereoMode::Off => f.write_str("off"),
+            StereoMode::Anaglyph => f.write_str("anaglyph"),
+            StereoMode::SideBySide => f.write_str("side-by-side"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StereoSettings {
+    pub mode: StereoMode,
+    pub eye_separation: f32, // world units, one unit is the stone spacing
+}
+
+impl Default for StereoSettings {
+    fn default() -> Self {
+        Self {
+            mode: StereoMode::Off,
+            eye_separation: 0.4,
+        }
+    }
+}
+
+impl StereoSettings {
+    pub const SEPARATION_STEP: f32 = 0.05;
+    pub const MAX_SEPARATION: f32 = 2.0;
+
+    pub fn adjust_separation(&mut self, delta: f32) {
+        self.eye_separation = (self.eye_separation + delta).clamp(0.0, Self::MAX_SEPARATION);
+    }
+}
+
+// Visualization training: stones lose their colors, or vanish once played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlindMode {
+    #[default]
+    Off,
+    // Every stone in the theme's neutral color
+    OneColor,
+    // Only the most recent stone is drawn, also in the neutral color
+    Hidden,
+}
+
+impl BlindMode {
+    pub fn next(self) -> Self {
+        match self {
+            BlindMode::Off => BlindMode::OneColor,
+            BlindMode::OneColor => BlindMode::Hidden,
+            BlindMode::Hidden => BlindMode::Off,
+        }
+    }
+}
+
+impl FromStr for BlindMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(BlindMode::Off),
+            "one-color" | "one-colour" | "onecolor" => Ok(BlindMode::OneColor),
+            "hidden" => Ok(BlindMode::Hidden),
+            other => Err(format!("unknown blind mode '{}' (expected off, one-color or hidden)", other)),
+        }
+    }
+}
+
+impl fmt::Display for BlindMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlindMode::Off => f.write_str("off"),
+            BlindMode::OneColor => f.write_str("one-color"),
+            BlindMode::Hidden => f.write_str("hidden"),
+        }
+    }
+}
+
+// Which board axis points up on screen; see render::BoardToWorld
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpAxis {
+    // Board z is the height, so layers stack vertically
+    #[default]
+    Z,
+    Y,
+}
+
+impl FromStr for UpAxis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "z" | "z-up" => Ok(UpAxis::Z),
+            "y" | "y-up" => Ok(UpAxis::Y),
+            other => Err(format!("unknown up axis '{}' (expected z or y)", other)),
+        }
+    }
+}
+
+impl fmt::Display for UpAxis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpAxis::Z => f.write_str("z"),
+            UpAxis::Y => f.write_str("y"),
+        }
+    }
+}
+
+// Spacing of the rendered lattice, independent of the board size: a larger
+// pitch spreads a dense board out, the stone radius stays as chosen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutSettings {
+    pub pitch: f32,        // world units between neighbouring points
+    pub stone_radius: f32, // world units
+    pub up: UpAxis,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            pitch: 1.0,
+            stone_radius: 0.48,
+            up: UpAxis::Z,
+        }
+    }
+}
+
+impl LayoutSettings {
+    pub const PITCH_STEP: f32 = 0.1;
+    pub const MIN_PITCH: f32 = 0.5;
+    pub const MAX_PITCH: f32 = 4.0;
+    pub const MIN_STONE_RADIUS: f32 = 0.1;
+    pub const MAX_STONE_RADIUS: f32 = 1.5;
+    // Radius of the stone meshes before instance scaling
+    pub const MESH_RADIUS: f32 = 0.4;
+
+    pub fn adjust_pitch(&mut self, delta: f32) {
+        self.pitch = (self.pitch + delta).clamp(Self::MIN_PITCH, Self::MAX_PITCH);
+    }
+
+    // World coordinate of lattice index `index` along one axis, centred on the origin
+    pub fn coordinate(&self, index: u8, board_size: usize) -> f32 {
+        (index as f32 - board_size as f32 * 0.5 + 0.5) * self.pitch
+    }
+
+    // Inverse of `coordinate`, unrounded
+    pub fn index(&self, coordinate: f32, board_size: usize) -> f32 {
+        coordinate / self.pitch + board_size as f32 * 0.5 - 0.5
+    }
+
+    // Edge length of the bounding box
+    pub fn extent(&self, board_size: usize) -> f32 {
+        board_size as f32 * self.pitch
+    }
+
+    pub fn stone_scale(&self) -> f32 {
+        self.stone_radius / Self::MESH_RADIUS
+    }
+}
+
+// Scene color scheme, see render::Theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThemeKind {
+    #[default]
+    Classic,
+    // Shape-coded stones and a color-blind safe overlay palette
+    ColorBlind,
+}
+
+impl ThemeKind {
+    pub fn next(self) -> Self {
+        match self {
+            ThemeKind::Classic => ThemeKind::ColorBlind,
+            ThemeKind::ColorBlind => ThemeKind::Classic,
+        }
+    }
+
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemeKind::Classic => Theme::classic(),
+            ThemeKind::ColorBlind => Theme::color_blind(),
+        }
+    }
+}
+
+impl FromStr for ThemeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "classic" => Ok(ThemeKind::Classic),
+            "color-blind" | "colorblind" | "colour-blind" => Ok(ThemeKind::ColorBlind),
+            other => Err(format!("unknown theme '{}' (expected classic or color-blind)", other)),
+        }
+    }
+}
+
+impl fmt::Display for ThemeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeKind::Classic => f.write_str("classic"),
+            ThemeKind::ColorBlind => f.write_str("color-blind"),
+        }
+    }
+}
+
+// How the guide shows the point a stone would go: three faint planes, or
+// only the three lines where they cross, for less clutter on big boards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GuideStyle {
+    #[default]
+    Planes,
+    Crosshair,
+}
+
+impl GuideStyle {
+    pub fn next(self) -> Self {
+        match self {
+            GuideStyle::Planes => GuideStyle::Crosshair,
+            GuideStyle::Crosshair => GuideStyle::Planes,
+        }
+    }
+}
+
+impl FromStr for GuideStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "planes" => Ok(GuideStyle::Planes),
+            "crosshair" | "lines" => Ok(GuideStyle::Crosshair),
+            other => Err(format!("unknown guide style '{}' (expected planes or crosshair)", other)),
+        }
+    }
+}
+
+impl fmt::Display for GuideStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuideStyle::Planes => f.write_str("planes"),
+            GuideStyle::Crosshair => f.write_str("crosshair"),
+        }
+    }
+}
+
+// Size of the 2D interface (side panels, overlay text, the annotation toolbar
+// and labels) as a multiple of its design size, so it reads the same on a 4K
+// monitor and a small laptop screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiScale(f32);
+
+impl UiScale {
+    pub const MIN: f32 = 0.75;
+    pub const MAX: f32 = 2.0;
+    pub const STEP: f32 = 0.25;
+
+    // Clamped into range; anything that isn't a number is the design size
+    pub fn new(factor: f32) -> Self {
+        Self(if factor.is_finite() { factor.clamp(Self::MIN, Self::MAX) } else { 1.0 })
+    }
+
+    pub fn factor(self) -> f32 {
+        self.0
+    }
+
+    // `steps` notches larger (or smaller, if negative), on the STEP grid
+    pub fn stepped(self, steps: i32) -> Self {
+        Self::new(((self.0 / Self::STEP).round() + steps as f32) * Self::STEP)
+    }
+}
+
+impl Default for UiScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+// A factor ("1.5") or a percentage ("150%")
+impl FromStr for UiScale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let factor = match s.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f32>().map(|percent| percent / 100.0),
+            None => s.parse::<f32>(),
+        };
+        match factor {
+            Ok(factor) if (Self::MIN..=Self::MAX).contains(&factor) => Ok(Self(factor)),
+            _ => Err(format!("UI scale must be between {} and {}, or {}% and {}%", Self::MIN, Self::MAX, Self::MIN * 100.0, Self::MAX * 100.0)),
+        }
+    }
+}
+
+impl fmt::Display for UiScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,193 @@
+// Simultaneous play between two machines. Each turn both players first send a
+// commitment to their move, and only reveal the move once the other's
+// commitment has arrived, so neither can answer the other's choice, e.g.
+//
+//   commit 3f9a0c1e55d2b7a4     reveal d4-2 8172635     reveal pass 99127
+//
+// A reveal has to match the commitment before it. Once both moves are known,
+// each end plays Black's and then White's into its own GameSession, where the
+// simultaneous variant holds Black's back until White's lands with it. The
+// digest is a salted mix, not a cryptographic hash: it catches a reveal that
+// differs from the commitment, not a client built to forge one. As with
+// teaching games there is no network transport yet to carry these lines.
+use crate::accessibility::{format_point, parse_point, CommandError};
+use crate::game::zobrist::mix;
+use crate::game::{GameSession, MoveError, StoneColor};
+use std::fmt;
+
+type Point = (u8, u8, u8);
+// None for a pass
+type Play = Option<Point>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevealMessage {
+    Commit(u64),
+    Reveal { play: Play, salt: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimultaneousError {
+    Unreadable(CommandError),
+    AlreadyCommitted,
+    // A reveal with no commitment before it
+    NotCommitted,
+    // A reveal that doesn't match its commitment
+    Mismatch,
+    Illegal(MoveError),
+}
+
+impl fmt::Display for SimultaneousError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimultaneousError::Unreadable(error) => write!(f, "{}", error),
+            SimultaneousError::AlreadyCommitted => f.write_str("a move has already been committed this turn"),
+            SimultaneousError::NotCommitted => f.write_str("move revealed without a commitment"),
+            SimultaneousError::Mismatch => f.write_str("revealed move doesn't match its commitment"),
+            SimultaneousError::Illegal(error) => write!(f, "illegal move: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SimultaneousError {}
+
+fn digest(play: Play, salt: u64) -> u64 {
+    let play = match play {
+        Some((x, y, z)) => u64::from(x) | u64::from(y) << 8 | u64::from(z) << 16,
+        None => 1 << 24,
+    };
+    mix(mix(salt) ^ play)
+}
+
+impl RevealMessage {
+    pub fn parse(line: &str, board_size: usize) -> Result<Self, SimultaneousError> {
+        let unknown = || SimultaneousError::Unreadable(CommandError::Unknown(line.trim().to_string()));
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["commit", digest] => u64::from_str_radix(digest, 16)
+                .map(RevealMessage::Commit)
+                .map_err(|_| unknown()),
+            ["reveal", play, salt] => {
+                let play = match *play {
+                    "pass" => None,
+                    point => Some(parse_point(point, board_size).map_err(SimultaneousError::Unreadable)?),
+                };
+                let salt = salt.parse().map_err(|_| unknown())?;
+                Ok(RevealMessage::Reveal { play, salt })
+            }
+            _ => Err(unknown()),
+        }
+    }
+}
+
+impl fmt::Display for RevealMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RevealMessage::Commit(digest) => write!(f, "commit {:016x}", digest),
+            RevealMessage::Reveal { play: Some(point), salt } => write!(f, "reveal {} {}", format_point(point), salt),
+            RevealMessage::Reveal { play: None, salt } => write!(f, "reveal pass {}", salt),
+        }
+    }
+}
+
+// One player's side of the exchange for the turn being played
+#[derive(Debug, Clone)]
+pub struct SimultaneousTurn {
+    color: StoneColor,
+    own: Option<(Play, u64)>,
+    their_commitment: Option<u64>,
+    their_play: Option<Play>,
+}
+
+impl SimultaneousTurn {
+    pub fn new(color: StoneColor) -> Self {
+        Self {
+            color,
+            own: None,
+            their_commitment: None,
+            their_play: None,
+        }
+    }
+
+    pub fn color(&self) -> StoneColor {
+        self.color
+    }
+
+    pub fn has_committed(&self) -> bool {
+        self.own.is_some()
+    }
+
+    // Commits to `play` and returns the lines to send: the commitment, and
+    // the reveal as well if the opponent has already committed
+    pub fn commit(&mut self, play: Play, session: &GameSession) -> Result<Vec<RevealMessage>, SimultaneousError> {
+        if self.own.is_some() {
+            return Err(SimultaneousError::AlreadyCommitted);
+        }
+        check_play(session, self.color, play)?;
+
+        let salt = rand::random();
+        self.own = Some((play, salt));
+        let mut replies = vec![RevealMessage::Commit(digest(play, salt))];
+        if self.their_commitment.is_some() {
+            replies.push(RevealMessage::Reveal { play, salt });
+        }
+        Ok(replies)
+    }
+
+    // Takes in the opponent's line and returns any lines to send back. The
+    // reveal that completes the turn plays both moves into `session`.
+    pub fn receive(&mut self, message: RevealMessage, session: &GameSession) -> Result<Vec<RevealMessage>, SimultaneousError> {
+        match message {
+            RevealMessage::Commit(digest) => {
+                if self.their_commitment.is_some() {
+                    return Err(SimultaneousError::AlreadyCommitted);
+                }
+                self.their_commitment = Some(digest);
+                Ok(self.own.map(|(play, salt)| RevealMessage::Reveal { play, salt }).into_iter().collect())
+            }
+            RevealMessage::Reveal { play, salt } => {
+                let commitment = self.their_commitment.ok_or(SimultaneousError::NotCommitted)?;
+                if digest(play, salt) != commitment {
+                    return Err(SimultaneousError::Mismatch);
+                }
+                check_play(session, self.color.opposite(), play)?;
+                self.their_play = Some(play);
+                self.finish(session);
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    // Plays the turn once both moves are known, Black's first. White's was
+    // checked without knowing Black's, and if the two together would repeat
+    // an earlier position it's played as a pass instead, at both ends alike.
+    fn finish(&mut self, session: &GameSession) {
+        let (Some((own, _)), Some(theirs)) = (self.own, self.their_play) else {
+            return;
+        };
+        let (black, white) = if self.color == StoneColor::Black {
+            (own, theirs)
+        } else {
+            (theirs, own)
+        };
+        for play in [black, white] {
+            match play {
+                Some((x, y, z)) if session.make_move(x, y, z) => {}
+                _ => session.pass(),
+            }
+        }
+        *self = Self::new(self.color);
+    }
+}
+
+// Checked against the position the turn started from, as the rules will
+fn check_play(session: &GameSession, color: StoneColor, play: Play) -> Result<(), SimultaneousError> {
+    let Some((x, y, z)) = play else {
+        return Ok(());
+    };
+    let mut rules = session.snapshot();
+    if rules.current_player() != color {
+        rules.pass();
+    }
+    rules.check_move(x, y, z).map_err(SimultaneousError::Illegal)
+}
\ No newline at end of file
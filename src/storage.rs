@@ -0,0 +1,420 @@
+// Persistent save slots. Each slot holds one game as SGF-3D text; native builds
+// keep them as files, the browser build in localStorage. Player profiles use
+// the same backends in a separate namespace.
+use crate::game::{sgf, GameRecord};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum StorageError {
+    Unavailable(String),
+    Io(String),
+    Corrupt { slot: String, reason: String },
+    Version { slot: String, error: VersionError },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Unavailable(reason) => write!(f, "storage unavailable: {}", reason),
+            StorageError::Io(reason) => write!(f, "storage error: {}", reason),
+            StorageError::Corrupt { slot, reason } => {
+                write!(f, "save slot '{}' is corrupt: {}", slot, reason)
+            }
+            StorageError::Version { slot, error } => write!(f, "save slot '{}' can't be read: {}", slot, error),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+// Slot names are short identifiers chosen by the frontend, e.g. "slot1"
+pub trait Backend {
+    fn list(&self) -> Result<Vec<String>, StorageError>;
+    fn load(&self, slot: &str) -> Result<Option<String>, StorageError>;
+    fn save(&mut self, slot: &str, data: &str) -> Result<(), StorageError>;
+    fn delete(&mut self, slot: &str) -> Result<(), StorageError>;
+}
+
+// The stores kept as lines of text (profiles, preferences, engine
+// configurations and calibration) start with a `version=N` line, as game
+// records give theirs in FV. Text without one was written before versioning
+// and counts as version 1. Each store migrates older text itself, one version
+// at a time, and refuses text newer than it reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionError {
+    Invalid(String),
+    Newer { found: u32, current: u32 },
+}
+
+impl fmt::Display for VersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionError::Invalid(value) => write!(f, "invalid format version '{}'", value),
+            VersionError::Newer { found, current } => {
+                write!(f, "format version {} is newer than this build reads ({})", found, current)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+pub fn version_line(version: u32) -> String {
+    format!("version={}", version)
+}
+
+// The version `data` gives and its lines with the version line blanked. The
+// version line is the first that isn't blank or a '#' comment.
+pub fn split_version(data: &str, current: u32) -> Result<(u32, Vec<String>), VersionError> {
+    let mut lines: Vec<String> = data.lines().map(str::to_string).collect();
+    let first = lines.iter().position(|line| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    });
+    let Some((index, value)) = first.and_then(|index| {
+        let (key, value) = lines[index].split_once('=')?;
+        (key.trim() == "version").then(|| (index, value.trim().to_string()))
+    }) else {
+        return Ok((1, lines));
+    };
+    let version = value
+        .parse()
+        .ok()
+        .filter(|&version| version >= 1)
+        .ok_or(VersionError::Invalid(value))?;
+    if version > current {
+        return Err(VersionError::Newer { found: version, current });
+    }
+    // Blanked rather than removed, so line numbers in errors still match
+    lines[index].clear();
+    Ok((version, lines))
+}
+
+// Fallback when nothing persistent is available (e.g. private browsing)
+#[derive(Default)]
+pub struct MemoryBackend {
+    slots: BTreeMap<String, String>,
+}
+
+impl Backend for MemoryBackend {
+    fn list(&self) -> Result<Vec<String>, StorageError> {
+        Ok(self.slots.keys().cloned().collect())
+    }
+
+    fn load(&self, slot: &str) -> Result<Option<String>, StorageError> {
+        Ok(self.slots.get(slot).cloned())
+    }
+
+    fn save(&mut self, slot: &str, data: &str) -> Result<(), StorageError> {
+        self.slots.insert(slot.to_string(), data.to_string());
+        Ok(())
+    }
+
+    fn delete(&mut self, slot: &str) -> Result<(), StorageError> {
+        self.slots.remove(slot);
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::FileBackend;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod file {
+    use super::{Backend, StorageError};
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+
+    pub struct FileBackend {
+        dir: PathBuf,
+        extension: &'static str,
+    }
+
+    impl FileBackend {
+        pub fn new(dir: impl Into<PathBuf>) -> Self {
+            Self {
+                dir: dir.into(),
+                extension: "sgf",
+            }
+        }
+
+        pub fn with_extension(mut self, extension: &'static str) -> Self {
+            self.extension = extension;
+            self
+        }
+
+        // <data dir>/go3d/saves, e.g. ~/.local/share/go3d/saves on Linux
+        pub fn in_data_dir() -> Result<Self, StorageError> {
+            Self::in_data_subdir("saves")
+        }
+
+        pub fn in_data_subdir(name: &str) -> Result<Self, StorageError> {
+            dirs::data_dir()
+                .map(|dir| Self::new(dir.join("go3d").join(name)))
+                .ok_or_else(|| StorageError::Unavailable("no data directory".to_string()))
+        }
+
+        fn path(&self, slot: &str) -> PathBuf {
+            self.dir.join(slot).with_extension(self.extension)
+        }
+    }
+
+    fn io_error(error: std::io::Error) -> StorageError {
+        StorageError::Io(error.to_string())
+    }
+
+    impl Backend for FileBackend {
+        fn list(&self) -> Result<Vec<String>, StorageError> {
+            let entries = match fs::read_dir(&self.dir) {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(error) => return Err(io_error(error)),
+            };
+
+            let mut slots: Vec<String> = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == self.extension))
+                .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                .collect();
+            slots.sort();
+            Ok(slots)
+        }
+
+        fn load(&self, slot: &str) -> Result<Option<String>, StorageError> {
+            match fs::read_to_string(self.path(slot)) {
+                Ok(data) => Ok(Some(data)),
+                Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(io_error(error)),
+            }
+        }
+
+        fn save(&mut self, slot: &str, data: &str) -> Result<(), StorageError> {
+            fs::create_dir_all(&self.dir).map_err(io_error)?;
+            // Write then rename so a crash never leaves a half-written save
+            let path = self.path(slot);
+            let temp = path.with_extension("tmp");
+            fs::write(&temp, data).map_err(io_error)?;
+            fs::rename(&temp, &path).map_err(io_error)
+        }
+
+        fn delete(&mut self, slot: &str) -> Result<(), StorageError> {
+            match fs::remove_file(self.path(slot)) {
+                Err(error) if error.kind() != ErrorKind::NotFound => Err(io_error(error)),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use local::LocalStorageBackend;
+
+#[cfg(target_arch = "wasm32")]
+mod local {
+    use super::{Backend, StorageError};
+
+    // localStorage rather than IndexedDB: saves are small and the Backend
+    // trait is synchronous, which IndexedDB cannot be
+    pub struct LocalStorageBackend {
+        storage: web_sys::Storage,
+        prefix: &'static str,
+    }
+
+    fn js_error(error: wasm_bindgen::JsValue) -> StorageError {
+        StorageError::Io(format!("{:?}", error))
+    }
+
+    impl LocalStorageBackend {
+        pub fn new() -> Result<Self, StorageError> {
+            Self::with_prefix("go3d.save.")
+        }
+
+        pub fn with_prefix(prefix: &'static str) -> Result<Self, StorageError> {
+            web_sys::window()
+                .and_then(|win| win.local_storage().ok().flatten())
+                .map(|storage| Self { storage, prefix })
+                .ok_or_else(|| StorageError::Unavailable("localStorage is disabled".to_string()))
+        }
+    }
+
+    impl Backend for LocalStorageBackend {
+        fn list(&self) -> Result<Vec<String>, StorageError> {
+            let len = self.storage.length().map_err(js_error)?;
+            let mut slots: Vec<String> = (0..len)
+                .filter_map(|index| self.storage.key(index).ok().flatten())
+                .filter_map(|key| key.strip_prefix(self.prefix).map(str::to_string))
+                .collect();
+            slots.sort();
+            Ok(slots)
+        }
+
+        fn load(&self, slot: &str) -> Result<Option<String>, StorageError> {
+            self.storage
+                .get_item(&format!("{}{}", self.prefix, slot))
+                .map_err(js_error)
+        }
+
+        fn save(&mut self, slot: &str, data: &str) -> Result<(), StorageError> {
+            self.storage
+                .set_item(&format!("{}{}", self.prefix, slot), data)
+                .map_err(js_error)
+        }
+
+        fn delete(&mut self, slot: &str) -> Result<(), StorageError> {
+            self.storage
+                .remove_item(&format!("{}{}", self.prefix, slot))
+                .map_err(js_error)
+        }
+    }
+}
+
+// The platform's persistent backend, or memory if it can't be opened
+pub fn default_backend() -> Box<dyn Backend> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            let backend = LocalStorageBackend::new();
+        } else {
+            let backend = FileBackend::in_data_dir();
+        }
+    }
+    or_memory(backend, "saves")
+}
+
+// Kept apart from the save slots so profiles never show up as games
+pub fn profile_backend() -> Box<dyn Backend> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            let backend = LocalStorageBackend::with_prefix("go3d.profiles.");
+        } else {
+            let backend = FileBackend::in_data_subdir("profiles").map(|backend| backend.with_extension("txt"));
+        }
+    }
+    or_memory(backend, "profiles")
+}
+
+// Finished games for the archive screen, apart from the quick-save slots
+pub fn archive_backend() -> Box<dyn Backend> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            let backend = LocalStorageBackend::with_prefix("go3d.archive.");
+        } else {
+            let backend = FileBackend::in_data_subdir("archive");
+        }
+    }
+    or_memory(backend, "archived games")
+}
+
+// Named AI configurations, one `key=value` file each
+pub fn engine_backend() -> Box<dyn Backend> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            let backend = LocalStorageBackend::with_prefix("go3d.engines.");
+        } else {
+            let backend = FileBackend::in_data_subdir("engines").map(|backend| backend.with_extension("txt"));
+        }
+    }
+    or_memory(backend, "saved engines")
+}
+
+// Crash reports from native builds, for bug reports
+#[cfg(not(target_arch = "wasm32"))]
+pub fn crash_backend() -> Box<dyn Backend> {
+    or_memory(FileBackend::in_data_subdir("crashes").map(|backend| backend.with_extension("txt")), "crash reports")
+}
+
+// Window geometry and view state carried from one session to the next
+pub fn preferences_backend() -> Box<dyn Backend> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            let backend = LocalStorageBackend::with_prefix("go3d.preferences.");
+        } else {
+            let backend = FileBackend::in_data_subdir("preferences").map(|backend| backend.with_extension("txt"));
+        }
+    }
+    or_memory(backend, "preferences")
+}
+
+fn or_memory<B: Backend + 'static>(backend: Result<B, StorageError>, what: &str) -> Box<dyn Backend> {
+    match backend {
+        Ok(backend) => Box::new(backend),
+        Err(error) => {
+            log::warn!("{}; {} will not persist", error, what);
+            Box::new(MemoryBackend::default())
+        }
+    }
+}
+
+// Numbered quick-save slots as shown in the UI (F1-F4 select, F5 save, F9 load)
+pub struct SaveSlots {
+    backend: Box<dyn Backend>,
+    selected: usize,
+}
+
+impl SaveSlots {
+    pub const COUNT: usize = 4;
+
+    pub fn new(backend: Box<dyn Backend>) -> Self {
+        Self {
+            backend,
+            selected: 0,
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(Self::COUNT - 1);
+    }
+
+    fn slot_name(index: usize) -> String {
+        format!("slot{}", index + 1)
+    }
+
+    pub fn save(&mut self, record: &GameRecord) -> Result<(), StorageError> {
+        self.backend
+            .save(&Self::slot_name(self.selected), &sgf::write(record))
+    }
+
+    // The record has been checked to replay cleanly
+    pub fn load(&self) -> Result<Option<GameRecord>, StorageError> {
+        let slot = Self::slot_name(self.selected);
+        let Some(data) = self.backend.load(&slot)? else {
+            return Ok(None);
+        };
+
+        let corrupt = |reason: String| StorageError::Corrupt {
+            slot: slot.clone(),
+            reason,
+        };
+        let record = sgf::parse(&data).map_err(|e| corrupt(e.to_string()))?;
+        record.replay().map_err(|e| corrupt(e.to_string()))?;
+        Ok(Some(record))
+    }
+
+    // One line per slot for the slot list, e.g. "> 2: 3³ board, 14 moves"
+    pub fn describe(&self) -> Vec<String> {
+        (0..Self::COUNT)
+            .map(|index| {
+                let marker = if index == self.selected { '>' } else { ' ' };
+                let contents = match self.backend.load(&Self::slot_name(index)) {
+                    Ok(Some(data)) => match sgf::parse(&data) {
+                        Ok(record) => format!(
+                            "{}³ board, {} moves",
+                            record.board_size,
+                            record.moves.len()
+                        ),
+                        Err(_) => "unreadable".to_string(),
+                    },
+                    Ok(None) => "empty".to_string(),
+                    Err(error) => error.to_string(),
+                };
+                format!("{} {}: {}", marker, index + 1, contents)
+            })
+            .collect()
+    }
+}
\ No newline at end of file
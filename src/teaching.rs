@@ -0,0 +1,170 @@
+// Teaching games: one designated teacher may place stones for either color,
+// rewind the game and mark up the board, while students only play their own
+// color on their turn. Messages are single text lines so they can travel over
+// any line-based link, e.g.
+//
+//   place W d4-2     rewind 3     mark c3-1 triangle     unmark c3-1
+//
+// Both ends apply the same messages to their own GameSession, so the marks a
+// teacher draws appear on the students' boards as they are made. This build
+// has no network transport yet (`--connect` reports that), so nothing sends
+// these lines between machines so far.
+use crate::accessibility::{color_name, format_point, parse_point, CommandError};
+use crate::game::{GameSession, Mark, MarkerColor, MoveError, StoneColor};
+use std::fmt;
+
+type Point = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Teacher,
+    Student(StoneColor),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeachingMessage {
+    Place { color: StoneColor, point: Point },
+    // Take back this many moves
+    Rewind(usize),
+    // The mark now on the point in the current position, None to clear it
+    Mark { point: Point, mark: Option<Mark> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TeachingError {
+    Unreadable(CommandError),
+    TeacherOnly,
+    WrongColor(StoneColor),
+    // A color with no seat in this game
+    NotPlaying(StoneColor),
+    // Passing round to the color would end the game first
+    EndsGame,
+    Illegal(MoveError),
+    NothingToRewind,
+}
+
+impl fmt::Display for TeachingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TeachingError::Unreadable(error) => write!(f, "{}", error),
+            TeachingError::TeacherOnly => f.write_str("only the teacher can do that"),
+            TeachingError::WrongColor(color) => write!(f, "students may only play {} on their turn", color_name(*color)),
+            TeachingError::NotPlaying(color) => write!(f, "{} isn't playing in this game", color_name(*color)),
+            TeachingError::EndsGame => f.write_str("passing round to that color would end the game"),
+            TeachingError::Illegal(error) => write!(f, "illegal move: {}", error),
+            TeachingError::NothingToRewind => f.write_str("not that many moves to rewind"),
+        }
+    }
+}
+
+impl std::error::Error for TeachingError {}
+
+fn mark_name(mark: Mark) -> String {
+    match mark {
+        Mark::Label(letter) => letter.to_string(),
+        Mark::Triangle => "triangle".to_string(),
+        Mark::Square => "square".to_string(),
+        Mark::Marker(color) => color.to_string(),
+    }
+}
+
+pub(crate) fn parse_mark(word: &str) -> Option<Mark> {
+    match word {
+        "triangle" => Some(Mark::Triangle),
+        "square" => Some(Mark::Square),
+        _ => {
+            let mut chars = word.chars();
+            match (chars.next(), chars.next()) {
+                (Some(letter), None) if letter.is_ascii_uppercase() => Some(Mark::Label(letter)),
+                _ => word.parse::<MarkerColor>().ok().map(Mark::Marker),
+            }
+        }
+    }
+}
+
+impl TeachingMessage {
+    pub fn parse(line: &str, board_size: usize) -> Result<Self, TeachingError> {
+        let unknown = || TeachingError::Unreadable(CommandError::Unknown(line.trim().to_string()));
+        let point = |text: &str| parse_point(text, board_size).map_err(TeachingError::Unreadable);
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["place", color, at] => {
+                let color = StoneColor::from_letter(color).ok_or_else(unknown)?;
+                Ok(TeachingMessage::Place { color, point: point(at)? })
+            }
+            ["rewind", count] => count.parse().map(TeachingMessage::Rewind).map_err(|_| unknown()),
+            ["mark", at, mark] => Ok(TeachingMessage::Mark {
+                point: point(at)?,
+                mark: Some(parse_mark(mark).ok_or_else(unknown)?),
+            }),
+            ["unmark", at] => Ok(TeachingMessage::Mark { point: point(at)?, mark: None }),
+            _ => Err(unknown()),
+        }
+    }
+
+    pub fn apply(&self, role: Role, session: &GameSession) -> Result<(), TeachingError> {
+        match (*self, role) {
+            (TeachingMessage::Place { color, point }, Role::Student(student)) => {
+                if color != student || session.read().current_player() != student {
+                    return Err(TeachingError::WrongColor(student));
+                }
+                place(session, color, point)
+            }
+            (TeachingMessage::Place { color, point }, Role::Teacher) => place(session, color, point),
+            (_, Role::Student(_)) => Err(TeachingError::TeacherOnly),
+            (TeachingMessage::Rewind(count), Role::Teacher) => {
+                if session.read().moves().len() < count {
+                    return Err(TeachingError::NothingToRewind);
+                }
+                for _ in 0..count {
+                    session.undo();
+                }
+                Ok(())
+            }
+            (TeachingMessage::Mark { point, mark }, Role::Teacher) => {
+                session.set_mark(point, mark);
+                Ok(())
+            }
+        }
+    }
+}
+
+// Out of turn, every player due before `color` passes first so the record
+// stays in turn order; passes that would end the game are refused
+fn place(session: &GameSession, color: StoneColor, (x, y, z): Point) -> Result<(), TeachingError> {
+    let mut rules = session.snapshot();
+    if !StoneColor::players(rules.players()).contains(&color) {
+        return Err(TeachingError::NotPlaying(color));
+    }
+    let mut passes = 0;
+    while rules.current_player() != color {
+        rules.pass();
+        passes += 1;
+        if rules.is_over() {
+            return Err(TeachingError::EndsGame);
+        }
+    }
+    rules.check_move(x, y, z).map_err(TeachingError::Illegal)?;
+
+    for _ in 0..passes {
+        session.pass();
+    }
+    session.make_move(x, y, z);
+    Ok(())
+}
+
+impl fmt::Display for TeachingMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TeachingMessage::Place { color, point } => {
+                write!(f, "place {} {}", color.letter(), format_point(point))
+            }
+            TeachingMessage::Rewind(count) => write!(f, "rewind {}", count),
+            TeachingMessage::Mark { point, mark: Some(mark) } => {
+                write!(f, "mark {} {}", format_point(point), mark_name(mark))
+            }
+            TeachingMessage::Mark { point, mark: None } => write!(f, "unmark {}", format_point(point)),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,397 @@
+// Engine-vs-engine play without a window. play_match is the match runner
+// behind --headless self-play; a tournament pairs several engine
+// configurations over it, round-robin or Swiss-style, keeps the standings and
+// can archive every game as an SGF-3D record. Networked players can't enter
+// yet, as this build has no network transport (see --connect).
+use crate::ai::Engine;
+use crate::api::{Game, Move};
+use crate::game::{sgf, GameRecord, GameRules, StoneColor};
+use crate::profiles::Outcome;
+use crate::settings::{AiSettings, GameSettings};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub struct MatchResult {
+    pub record: GameRecord,
+    // (black, white) under the game's scoring rule
+    pub score: (f32, f32),
+    pub outcome: Outcome,
+}
+
+// Plays one game until two passes in a row, or twice as many moves as the
+// board has points; `on_move` sees each move as it is played. Black's engine
+// is seeded with `seed` and White's with the number after it; the record
+// keeps the seed, so the same engines play the game again from it.
+pub fn play_match(
+    black: &mut dyn Engine,
+    white: &mut dyn Engine,
+    settings: &GameSettings,
+    seed: u64,
+    on_move: impl FnMut(usize, StoneColor, Move),
+) -> MatchResult {
+    play_match_from(settings.new_game(), black, white, settings, seed, on_move)
+}
+
+// The same from a position already set up, e.g. with handicap stones
+pub fn play_match_from(
+    start: GameRules,
+    black: &mut dyn Engine,
+    white: &mut dyn Engine,
+    settings: &GameSettings,
+    seed: u64,
+    mut on_move: impl FnMut(usize, StoneColor, Move),
+) -> MatchResult {
+    black.reseed(seed);
+    white.reseed(seed.wrapping_add(1));
+    let first_move = start.moves().len() + 1;
+    let mut game = Game::from(start);
+    let move_limit = settings.board_size.pow(3) * 2;
+    for move_number in first_move..=move_limit {
+        // Tournament games are always two-player
+        let color = game.to_move();
+        let mv = match color {
+            StoneColor::Black => black.select_move(&game),
+            _ => white.select_move(&game),
+        };
+        game.play(mv).expect("engine chose an illegal move");
+        on_move(move_number, color, mv);
+        if game.is_over() {
+            break;
+        }
+    }
+
+    let rules = GameRules::from(game);
+    let score = settings.score(&rules);
+    MatchResult {
+        record: GameRecord {
+            seed: Some(seed),
+            ..GameRecord::from_rules(&rules, settings.komi)
+        },
+        score,
+        outcome: rules.winner().map_or(Outcome::from_score(score), Outcome::Win),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pairing {
+    // Everyone meets everyone once
+    RoundRobin,
+    // Each round pairs entrants on equal points who haven't met yet
+    Swiss,
+}
+
+impl FromStr for Pairing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "round-robin" | "roundrobin" => Ok(Pairing::RoundRobin),
+            "swiss" => Ok(Pairing::Swiss),
+            other => Err(format!("unknown pairing '{}' (expected round-robin or swiss)", other)),
+        }
+    }
+}
+
+impl fmt::Display for Pairing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pairing::RoundRobin => f.write_str("round-robin"),
+            Pairing::Swiss => f.write_str("swiss"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entrant {
+    pub name: String,
+    pub ai: AiSettings,
+}
+
+impl Entrant {
+    // One entrant per entry, named as given or else after its engine, and
+    // numbered when a name enters more than once
+    pub fn lineup(entries: Vec<(Option<String>, AiSettings)>) -> Vec<Entrant> {
+        let mut bases: Vec<String> = Vec::new();
+        let mut entrants = Vec::new();
+        for (name, ai) in entries {
+            let base = name.unwrap_or_else(|| ai.create_engine(&GameSettings::default()).name().to_string());
+            let taken = bases.iter().filter(|&taken| *taken == base).count();
+            let name = if taken == 0 { base.clone() } else { format!("{}-{}", base, taken + 1) };
+            bases.push(base);
+            entrants.push(Entrant { name, ai });
+        }
+        entrants
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TournamentError {
+    TooFewEntrants,
+    Archive { path: String, reason: String },
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TournamentError::TooFewEntrants => f.write_str("a tournament needs at least two entrants"),
+            TournamentError::Archive { path, reason } => write!(f, "cannot archive to {}: {}", path, reason),
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {}
+
+#[derive(Debug, Clone, Default)]
+struct Standing {
+    // A win or a Swiss bye is worth a point, a draw half
+    points: f32,
+    wins: usize,
+    draws: usize,
+    losses: usize,
+    had_bye: bool,
+    blacks: usize,
+    opponents: Vec<usize>,
+}
+
+// A finished game of the tournament, entrants given by index
+pub struct TournamentGame {
+    pub round: usize,
+    pub black: usize,
+    pub white: usize,
+    pub result: MatchResult,
+    pub archived: Option<PathBuf>,
+}
+
+pub struct Tournament {
+    entrants: Vec<Entrant>,
+    settings: GameSettings,
+    pairing: Pairing,
+    rounds: usize,
+    archive: Option<PathBuf>,
+    standings: Vec<Standing>,
+    played: HashSet<(usize, usize)>,
+    // For the next game; each takes two, one per side
+    next_seed: u64,
+}
+
+impl Tournament {
+    // `rounds` only applies to Swiss pairing; by default it plays enough
+    // rounds for one entrant to be left winning every game. The games are
+    // seeded in turn from `seed`, so the same one plays the tournament again.
+    pub fn new(
+        entrants: Vec<Entrant>,
+        settings: GameSettings,
+        pairing: Pairing,
+        rounds: Option<usize>,
+        archive: Option<PathBuf>,
+        seed: u64,
+    ) -> Result<Self, TournamentError> {
+        let count = entrants.len();
+        if count < 2 {
+            return Err(TournamentError::TooFewEntrants);
+        }
+        let rounds = match pairing {
+            Pairing::RoundRobin => count.next_multiple_of(2) - 1,
+            Pairing::Swiss => rounds.unwrap_or(count.next_power_of_two().trailing_zeros() as usize).max(1),
+        };
+        Ok(Self {
+            entrants,
+            settings,
+            pairing,
+            rounds,
+            archive,
+            standings: vec![Standing::default(); count],
+            played: HashSet::new(),
+            next_seed: seed,
+        })
+    }
+
+    pub fn entrants(&self) -> &[Entrant] {
+        &self.entrants
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    // Plays every round, handing each game to `on_game` as it finishes
+    pub fn run(&mut self, mut on_game: impl FnMut(&TournamentGame)) -> Result<(), TournamentError> {
+        if let Some(dir) = &self.archive {
+            std::fs::create_dir_all(dir).map_err(|e| archive_error(dir, e))?;
+        }
+        for round in 1..=self.rounds {
+            let (pairs, bye) = match self.pairing {
+                Pairing::RoundRobin => self.round_robin_pairs(round),
+                Pairing::Swiss => self.swiss_pairs(),
+            };
+            // Everyone sits out once in an odd round-robin, so only Swiss byes score
+            if let Some(entrant) = bye.filter(|_| self.pairing == Pairing::Swiss) {
+                self.standings[entrant].points += 1.0;
+                self.standings[entrant].had_bye = true;
+            }
+            for (black, white) in pairs {
+                let game = self.play(round, black, white)?;
+                on_game(&game);
+            }
+        }
+        Ok(())
+    }
+
+    fn play(&mut self, round: usize, black: usize, white: usize) -> Result<TournamentGame, TournamentError> {
+        let mut black_engine = self.entrants[black].ai.create_engine(&self.settings);
+        let mut white_engine = self.entrants[white].ai.create_engine(&self.settings);
+        let seed = self.next_seed;
+        self.next_seed = seed.wrapping_add(2);
+        let result = play_match(&mut *black_engine, &mut *white_engine, &self.settings, seed, |_, _, _| {});
+
+        let archived = match &self.archive {
+            Some(dir) => {
+                let name = format!(
+                    "round{:02}-{}-vs-{}.sgf",
+                    round, self.entrants[black].name, self.entrants[white].name
+                );
+                let path = dir.join(name);
+                std::fs::write(&path, sgf::write(&result.record)).map_err(|e| archive_error(&path, e))?;
+                Some(path)
+            }
+            None => None,
+        };
+
+        let winner = match result.outcome {
+            Outcome::Win(StoneColor::Black) => Some(black),
+            Outcome::Win(_) => Some(white),
+            Outcome::Draw => None,
+        };
+        for (entrant, opponent) in [(black, white), (white, black)] {
+            let standing = &mut self.standings[entrant];
+            match winner {
+                Some(winner) if winner == entrant => {
+                    standing.points += 1.0;
+                    standing.wins += 1;
+                }
+                Some(_) => standing.losses += 1,
+                None => {
+                    standing.points += 0.5;
+                    standing.draws += 1;
+                }
+            }
+            standing.opponents.push(opponent);
+        }
+        self.standings[black].blacks += 1;
+        self.played.insert((black.min(white), black.max(white)));
+
+        Ok(TournamentGame {
+            round,
+            black,
+            white,
+            result,
+            archived,
+        })
+    }
+
+    // Circle method: the first entrant stays put while the rest rotate, with
+    // an empty seat for a bye when the count is odd. Colors alternate by round.
+    fn round_robin_pairs(&self, round: usize) -> (Vec<(usize, usize)>, Option<usize>) {
+        let seats = self.entrants.len().next_multiple_of(2);
+        let mut others: Vec<usize> = (1..seats).collect();
+        others.rotate_right(round - 1);
+        let circle: Vec<usize> = std::iter::once(0).chain(others).collect();
+
+        let mut pairs = Vec::new();
+        let mut bye = None;
+        for i in 0..seats / 2 {
+            let (a, b) = (circle[i], circle[seats - 1 - i]);
+            if b >= self.entrants.len() {
+                bye = Some(a);
+            } else if a >= self.entrants.len() {
+                bye = Some(b);
+            } else if (round + i).is_multiple_of(2) {
+                pairs.push((a, b));
+            } else {
+                pairs.push((b, a));
+            }
+        }
+        (pairs, bye)
+    }
+
+    // Highest points first; each takes the best-placed entrant it hasn't met
+    // (or anyone, once it has met them all). The lowest-placed entrant without
+    // a bye so far sits out an odd round. Whoever has had Black less takes it.
+    fn swiss_pairs(&self) -> (Vec<(usize, usize)>, Option<usize>) {
+        let mut order = self.ranking();
+        let bye = if order.len() % 2 == 1 {
+            let index = order
+                .iter()
+                .rposition(|&entrant| !self.standings[entrant].had_bye)
+                .unwrap_or(order.len() - 1);
+            Some(order.remove(index))
+        } else {
+            None
+        };
+
+        let mut pairs = Vec::new();
+        while !order.is_empty() {
+            let a = order.remove(0);
+            let index = order
+                .iter()
+                .position(|&b| !self.played.contains(&(a.min(b), a.max(b))))
+                .unwrap_or(0);
+            let b = order.remove(index);
+            if self.standings[a].blacks <= self.standings[b].blacks {
+                pairs.push((a, b));
+            } else {
+                pairs.push((b, a));
+            }
+        }
+        (pairs, bye)
+    }
+
+    // Sum of the opponents' points, to split entrants on equal points
+    fn buchholz(&self, entrant: usize) -> f32 {
+        self.standings[entrant]
+            .opponents
+            .iter()
+            .map(|&opponent| self.standings[opponent].points)
+            .sum()
+    }
+
+    // Entrant indices by points, then Buchholz, then entry order
+    fn ranking(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entrants.len()).collect();
+        order.sort_by(|&a, &b| {
+            let key = |entrant: usize| (self.standings[entrant].points, self.buchholz(entrant));
+            key(b).partial_cmp(&key(a)).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        order
+    }
+
+    pub fn standings_table(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{:>4} {:<20} {:>6} {:>5} {:>5} {:>5} {:>8}",
+            "Rank", "Entrant", "Points", "Won", "Lost", "Drawn", "Buchholz"
+        )];
+        for (rank, entrant) in self.ranking().into_iter().enumerate() {
+            let standing = &self.standings[entrant];
+            lines.push(format!(
+                "{:>4} {:<20} {:>6.1} {:>5} {:>5} {:>5} {:>8.1}",
+                rank + 1,
+                self.entrants[entrant].name,
+                standing.points,
+                standing.wins,
+                standing.losses,
+                standing.draws,
+                self.buchholz(entrant)
+            ));
+        }
+        lines
+    }
+}
+
+fn archive_error(path: &Path, error: std::io::Error) -> TournamentError {
+    TournamentError::Archive {
+        path: path.display().to_string(),
+        reason: error.to_string(),
+    }
+}
\ No newline at end of file
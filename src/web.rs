@@ -0,0 +1,416 @@
+// JavaScript API for pages embedding the wasm build. Calls go through the same
+// GameSession the render loop draws from, so scripted moves show up on the next frame.
+use crate::accessibility::Command;
+use crate::attract;
+use crate::game::{share, GameRecord, GameRules, GameSession, SessionEvent, StoneColor};
+use crate::settings::GameSettings;
+use std::cell::RefCell;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+use web_sys::{Element, HtmlCanvasElement, ResizeObserver};
+use winit::{dpi::LogicalSize, event_loop::ActiveEventLoop, window::Window};
+
+const DEFAULT_ELEMENT_ID: &str = "wasm-example";
+
+struct WebState {
+    session: GameSession,
+    // Of the shared game the page opened, else the default
+    komi: f32,
+    events: Receiver<SessionEvent>,
+    move_callbacks: Vec<js_sys::Function>,
+    pending_resize: Option<(u32, u32)>,
+    init_options: Option<InitOptions>,
+    resize_observer: Option<ResizeObserver>,
+    // From the InitOptions, once the window is built
+    attract_after: Option<Duration>,
+    // Set by calls that play or change the game, which count as someone
+    // being there
+    scripted: bool,
+}
+
+// How the game is mounted in the page. Sizes are CSS pixels; winit scales the
+// canvas backing store by devicePixelRatio so rendering stays sharp on HiDPI.
+#[wasm_bindgen]
+pub struct InitOptions {
+    element_id: String,
+    canvas: Option<HtmlCanvasElement>,
+    width: Option<u32>,
+    height: Option<u32>,
+    auto_resize: bool,
+    attract_after: Option<Duration>,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self {
+            element_id: DEFAULT_ELEMENT_ID.to_string(),
+            canvas: None,
+            width: None,
+            height: None,
+            auto_resize: true,
+            attract_after: Some(attract::DEFAULT_IDLE),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl InitOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Container the game appends its own canvas to
+    #[wasm_bindgen(setter)]
+    pub fn set_element_id(&mut self, element_id: String) {
+        self.element_id = element_id;
+    }
+
+    // Render into an existing canvas instead; its parent is used for sizing
+    #[wasm_bindgen(setter)]
+    pub fn set_canvas(&mut self, canvas: HtmlCanvasElement) {
+        self.canvas = Some(canvas);
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_width(&mut self, width: u32) {
+        self.width = Some(width);
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_height(&mut self, height: u32) {
+        self.height = Some(height);
+    }
+
+    // Follow the container's size as the page layout changes (default on)
+    #[wasm_bindgen(setter)]
+    pub fn set_auto_resize(&mut self, auto_resize: bool) {
+        self.auto_resize = auto_resize;
+    }
+
+    // Seconds without input before a demo game starts playing under a
+    // "click to play" banner; 0 turns it off (default 60)
+    #[wasm_bindgen(setter)]
+    pub fn set_attract_seconds(&mut self, seconds: u32) {
+        self.attract_after = (seconds > 0).then(|| Duration::from_secs(u64::from(seconds)));
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<Option<WebState>> = const { RefCell::new(None) };
+}
+
+fn with_state<R>(f: impl FnOnce(&mut WebState) -> R) -> R {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let state = state.get_or_insert_with(|| {
+            let (rules, komi) = initial_game();
+            let session = GameSession::new(rules);
+            let events = session.subscribe();
+            WebState {
+                session,
+                komi,
+                events,
+                move_callbacks: Vec::new(),
+                pending_resize: None,
+                init_options: None,
+                resize_observer: None,
+                attract_after: None,
+                scripted: false,
+            }
+        });
+        f(state)
+    })
+}
+
+// A #game= or #g= fragment in the page URL opens the shared game; otherwise the demo position
+fn initial_game() -> (GameRules, f32) {
+    let demo = || (GameRules::new_with_dodecahedron(3), GameSettings::default().komi);
+    let fragment = web_sys::window()
+        .and_then(|win| win.location().hash().ok())
+        .unwrap_or_default();
+    if !share::is_game_link(&fragment) {
+        return demo();
+    }
+
+    let game = share::from_fragment(&fragment)
+        .map_err(|error| error.to_string())
+        .and_then(|record| record.replay().map(|rules| (rules, record.komi)).map_err(|error| error.to_string()));
+    match game {
+        Ok(game) => game,
+        Err(error) => {
+            log::warn!("ignoring shared game: {}", error);
+            demo()
+        }
+    }
+}
+
+// The session shared between the JS API and the render loop
+pub fn session() -> GameSession {
+    with_state(|state| state.session.clone())
+}
+
+// Called once per frame by the render loop
+pub fn dispatch_events() {
+    let (events, callbacks) = with_state(|state| {
+        let events: Vec<_> = state.events.try_iter().collect();
+        (events, state.move_callbacks.clone())
+    });
+
+    // Callbacks run outside the borrow so they may call back into the API
+    for event in events {
+        let json = match event {
+            SessionEvent::MovePlayed { color, position: (x, y, z), captured } => format!(
+                r#"{{"type":"move","color":"{}","x":{},"y":{},"z":{},"captured":{}}}"#,
+                color_name(color), x, y, z, captured
+            ),
+            SessionEvent::Passed { color } => {
+                format!(r#"{{"type":"pass","color":"{}"}}"#, color_name(color))
+            }
+            _ => continue,
+        };
+        let Ok(value) = js_sys::JSON::parse(&json) else {
+            continue;
+        };
+        for callback in &callbacks {
+            if let Err(error) = callback.call1(&JsValue::NULL, &value) {
+                log::warn!("on_move callback failed: {:?}", error);
+            }
+        }
+    }
+}
+
+// Creates the game window inside the page as described by the InitOptions
+// passed to mount(), or the defaults when the game was launched with run()
+pub fn build_window(event_loop: &ActiveEventLoop) -> Window {
+    use winit::platform::web::{WindowAttributesExtWebSys, WindowExtWebSys};
+
+    let options = with_state(|state| state.init_options.take()).unwrap_or_default();
+    with_state(|state| state.attract_after = options.attract_after);
+    let document = web_sys::window()
+        .and_then(|win| win.document())
+        .expect("no document to mount the game in");
+
+    let attributes = Window::default_attributes()
+        .with_title("3D Go")
+        .with_canvas(options.canvas.clone());
+    let window = event_loop.create_window(attributes).expect("couldn't create the game canvas");
+    let canvas = window.canvas().expect("the game window has a canvas");
+
+    let container = match options.canvas {
+        Some(_) => canvas.parent_element(),
+        None => {
+            let container = document
+                .get_element_by_id(&options.element_id)
+                .unwrap_or_else(|| panic!("no element with id '{}'", options.element_id));
+            container
+                .append_child(&canvas)
+                .expect("couldn't append canvas to its container");
+            Some(container)
+        }
+    };
+    let sizing_element = container.unwrap_or_else(|| Element::from(canvas));
+
+    let (width, height) = element_size(&sizing_element);
+    let _ = window.request_inner_size(LogicalSize::new(
+        options.width.unwrap_or(width),
+        options.height.unwrap_or(height),
+    ));
+
+    if options.auto_resize {
+        observe_size(sizing_element);
+    }
+
+    window
+}
+
+fn element_size(element: &Element) -> (u32, u32) {
+    (element.client_width().max(1) as u32, element.client_height().max(1) as u32)
+}
+
+fn observe_size(element: Element) {
+    let target = element.clone();
+    let on_resize = Closure::<dyn FnMut()>::new(move || {
+        let size = element_size(&target);
+        with_state(|state| state.pending_resize = Some(size));
+    });
+
+    match ResizeObserver::new(on_resize.as_ref().unchecked_ref()) {
+        Ok(observer) => {
+            observer.observe(&element);
+            // Both live for the rest of the page
+            on_resize.forget();
+            with_state(|state| state.resize_observer = Some(observer));
+        }
+        Err(error) => log::warn!("ResizeObserver unavailable, auto-resize disabled: {:?}", error),
+    }
+}
+
+pub fn take_pending_resize() -> Option<(u32, u32)> {
+    with_state(|state| state.pending_resize.take())
+}
+
+// How long the render loop waits for input before attract mode starts
+pub fn attract_after() -> Option<Duration> {
+    with_state(|state| state.attract_after)
+}
+
+// Whether the page has played or changed the game since last asked
+pub fn take_scripted() -> bool {
+    with_state(|state| std::mem::take(&mut state.scripted))
+}
+
+// Drops pending notifications unseen, so the page isn't told about the
+// demo game's moves
+pub fn discard_events() {
+    with_state(|state| state.events.try_iter().for_each(drop));
+}
+
+fn scripted() -> GameSession {
+    with_state(|state| {
+        state.scripted = true;
+        state.session.clone()
+    })
+}
+
+fn color_name(color: StoneColor) -> &'static str {
+    match color {
+        StoneColor::Black => "black",
+        StoneColor::White => "white",
+        StoneColor::Red => "red",
+        StoneColor::Green => "green",
+    }
+}
+
+// Alternative to run() for pages that need to control where and how the game mounts
+#[wasm_bindgen]
+pub async fn mount(options: InitOptions) {
+    with_state(|state| state.init_options = Some(options));
+    crate::run().await;
+}
+
+#[wasm_bindgen]
+pub fn new_game(size: usize) -> Result<(), JsValue> {
+    if !(GameSettings::MIN_BOARD_SIZE..=GameSettings::MAX_BOARD_SIZE).contains(&size) {
+        let message = format!("board size must be between {} and {}", GameSettings::MIN_BOARD_SIZE, GameSettings::MAX_BOARD_SIZE);
+        return Err(JsValue::from_str(&message));
+    }
+    with_state(|state| state.komi = GameSettings::default().komi);
+    scripted().update(|rules| rules.replace(GameRules::new(size)));
+    Ok(())
+}
+
+// Plays for the side to move; rejects illegal moves with a readable reason
+#[wasm_bindgen]
+pub fn play(x: u8, y: u8, z: u8) -> Result<(), JsValue> {
+    let session = scripted();
+    session
+        .read()
+        .check_move(x, y, z)
+        .map_err(|error| JsValue::from_str(&error.to_string()))?;
+    session.make_move(x, y, z);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn pass() {
+    scripted().pass();
+}
+
+#[wasm_bindgen]
+pub fn undo() -> bool {
+    scripted().undo()
+}
+
+// Text command such as "play B d4-2", "look c3-1" or "status"; returns the
+// reply to queries, moves are announced through the live region
+#[wasm_bindgen]
+pub fn command(line: &str) -> Result<String, JsValue> {
+    let session = scripted();
+    let board_size = session.read().board().size();
+    Command::parse(line, board_size)
+        .and_then(|command| command.execute(&session))
+        .map(Option::unwrap_or_default)
+        .map_err(|error| JsValue::from_str(&error.to_string()))
+}
+
+#[wasm_bindgen]
+pub fn get_board_json() -> String {
+    let session = session();
+    let rules = session.read();
+    let board = rules.board();
+
+    let mut stones: Vec<_> = board.get_all_stones().collect();
+    stones.sort_by_key(|&(&position, _)| position);
+    let stones: Vec<String> = stones
+        .into_iter()
+        .map(|(&(x, y, z), &color)| {
+            format!(r#"{{"x":{},"y":{},"z":{},"color":"{}"}}"#, x, y, z, color_name(color))
+        })
+        .collect();
+
+    format!(
+        r#"{{"size":{},"toMove":"{}","captured":{{"black":{},"white":{}}},"stones":[{}]}}"#,
+        board.size(),
+        color_name(rules.current_player()),
+        board.get_captured(StoneColor::Black),
+        board.get_captured(StoneColor::White),
+        stones.join(",")
+    )
+}
+
+// What the last move changed: {"placed":[{x,y,z,color}],"removed":[...]};
+// both lists are empty after a pass
+#[wasm_bindgen]
+pub fn last_move_diff_json() -> String {
+    let diff = session().read().diff_last_move();
+    let stones = |stones: &[((u8, u8, u8), StoneColor)]| -> String {
+        stones
+            .iter()
+            .map(|&((x, y, z), color)| {
+                format!(r#"{{"x":{},"y":{},"z":{},"color":"{}"}}"#, x, y, z, color_name(color))
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(r#"{{"placed":[{}],"removed":[{}]}}"#, stones(&diff.placed), stones(&diff.removed))
+}
+
+// URL of this page that reopens the current game
+#[wasm_bindgen]
+pub fn share_link() -> String {
+    let (session, komi) = with_state(|state| (state.session.clone(), state.komi));
+    let record = GameRecord::from_rules(&session.read(), komi);
+    let href = web_sys::window()
+        .and_then(|win| win.location().href().ok())
+        .unwrap_or_default();
+    let base = href.split('#').next().unwrap_or_default();
+    format!("{}{}", base, share::to_fragment(&record))
+}
+
+// Copies share_link() to the clipboard; the promise settles when the copy does
+#[wasm_bindgen]
+pub fn copy_share_link() -> Result<js_sys::Promise, JsValue> {
+    // Looked up dynamically: the async Clipboard API is missing on insecure origins
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let navigator = js_sys::Reflect::get(&window, &JsValue::from_str("navigator"))?;
+    let clipboard = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"))?;
+    let write_text: js_sys::Function =
+        js_sys::Reflect::get(&clipboard, &JsValue::from_str("writeText"))?.dyn_into()?;
+    write_text
+        .call1(&clipboard, &JsValue::from_str(&share_link()))?
+        .dyn_into()
+}
+
+// `callback` receives {type: "move"|"pass", color, x, y, z, captured} after every turn
+#[wasm_bindgen]
+pub fn on_move(callback: js_sys::Function) {
+    with_state(|state| state.move_callbacks.push(callback));
+}
+
+// Resizes the canvas, in CSS pixels
+#[wasm_bindgen]
+pub fn resize(width: u32, height: u32) {
+    with_state(|state| state.pending_resize = Some((width, height)));
+}
\ No newline at end of file
@@ -0,0 +1,105 @@
+// Desktop window chrome shared by the frontends: icon, status title, toasts,
+// fullscreen
+use crate::game::{GameRules, ViewState};
+use instant::Instant;
+use std::collections::VecDeque;
+use winit::window::{Fullscreen, Icon, Window};
+
+const ICON_SIZE: u32 = 32;
+
+// A black and a white stone overlapping, drawn procedurally so no asset is needed
+pub fn icon() -> Option<Icon> {
+    let stones = [
+        (11.0, 12.0, 9.5, [25, 25, 25]),
+        (20.0, 20.0, 9.5, [235, 235, 235]),
+    ];
+    let mut rgba = vec![0u8; (ICON_SIZE * ICON_SIZE * 4) as usize];
+
+    for (i, pixel) in rgba.chunks_exact_mut(4).enumerate() {
+        let x = (i as u32 % ICON_SIZE) as f32 + 0.5;
+        let y = (i as u32 / ICON_SIZE) as f32 + 0.5;
+
+        // Later stones are drawn on top
+        for &(cx, cy, radius, color) in &stones {
+            let distance = ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+            if distance > radius {
+                continue;
+            }
+            // Brighten towards the upper left like the rendered stones' highlight
+            let highlight = (1.0 - ((x - cx + 3.0).powi(2) + (y - cy + 3.0).powi(2)).sqrt() / radius)
+                .max(0.0)
+                * 60.0;
+            let alpha = ((radius - distance).min(1.0) * 255.0) as u8;
+            for (channel, &base) in pixel.iter_mut().zip(&color) {
+                *channel = (base as f32 + highlight).min(255.0) as u8;
+            }
+            pixel[3] = alpha.max(pixel[3]);
+        }
+    }
+
+    Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE)
+        .map_err(|error| log::warn!("couldn't create window icon: {}", error))
+        .ok()
+}
+
+// e.g. "3D Go — Black to move, move 37"; while reviewing it says so, with
+// the keys that lead back
+pub fn status_title(rules: &GameRules, view: ViewState) -> String {
+    let to_move = rules.current_player().name();
+    match view {
+        ViewState::Live => format!("3D Go — {} to move, move {}", to_move, rules.moves().len() + 1),
+        ViewState::Reviewing { move_number, of } => format!(
+            "3D Go — Reviewing move {} of {} (PgUp/PgDn step, End returns to the game)",
+            move_number, of
+        ),
+        ViewState::Variation { from } => format!(
+            "3D Go — Variation from move {}, {} to move (End returns to the game, Insert plays on from here)",
+            from, to_move
+        ),
+    }
+}
+
+// Short notices shown one at a time in place of the status title
+#[derive(Default)]
+pub struct Toasts {
+    queue: VecDeque<String>,
+    current: Option<(String, Instant)>,
+}
+
+impl Toasts {
+    const SECONDS: f32 = 4.0;
+
+    pub fn push(&mut self, text: String) {
+        self.queue.push_back(text);
+    }
+
+    // Expires and advances the queue; true when the title needs redrawing
+    pub fn tick(&mut self) -> bool {
+        let expired = self
+            .current
+            .as_ref()
+            .is_some_and(|(_, shown)| shown.elapsed().as_secs_f32() >= Self::SECONDS);
+        if !expired && self.current.is_some() {
+            return false;
+        }
+        let next = self.queue.pop_front().map(|text| (text, Instant::now()));
+        let changed = expired || next.is_some();
+        self.current = next;
+        changed
+    }
+
+    pub fn title_or(&self, status: String) -> String {
+        match &self.current {
+            Some((text, _)) => format!("3D Go — {}", text),
+            None => status,
+        }
+    }
+}
+
+pub fn toggle_fullscreen(window: &Window) {
+    let fullscreen = match window.fullscreen() {
+        Some(_) => None,
+        None => Some(Fullscreen::Borderless(None)),
+    };
+    window.set_fullscreen(fullscreen);
+}
\ No newline at end of file
@@ -0,0 +1,54 @@
+// The archive of finished games
+mod common;
+
+use common::*;
+use go3d::archive::{Archive, ArchiveFilter, ResultFilter};
+use go3d::game::{GameRecord, GameResult, GameRules, StoneColor};
+use go3d::storage::MemoryBackend;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // Finished games come back from the archive as they went in, players,
+    // result and all, newest first and only where the filter lets them through
+    #[test]
+    fn archived_games_come_back_through_their_filters(
+        games in prop::collection::vec(
+            (2usize..=4, prop::collection::vec(step(), 0..40), "[a-z\\]\\\\ ]{1,8}", any::<bool>(), 0.0f32..20.0),
+            1..6,
+        ),
+        size in 2usize..=4,
+    ) {
+        let mut archive = Archive::new(Box::<MemoryBackend>::default());
+        let mut records = Vec::new();
+        for (board_size, steps, name, black_won, margin) in games {
+            let mut rules = GameRules::new(board_size);
+            play_random_game(&mut rules, &steps)?;
+            let mut record = GameRecord::from_rules(&rules, 6.5);
+            record.black_player = Some(name);
+            record.white_player = Some("AI level 1".to_string());
+            let color = if black_won { StoneColor::Black } else { StoneColor::White };
+            record.result = Some(GameResult::Win { color, margin: Some(margin) });
+            archive.add(&record).expect("memory storage cannot fail");
+            records.push(record);
+        }
+        records.reverse();
+
+        let everything = archive.entries(&ArchiveFilter::default()).expect("memory storage cannot fail");
+        let listed: Vec<&GameRecord> = everything.iter().map(|entry| &entry.record).collect();
+        prop_assert_eq!(listed, records.iter().collect::<Vec<_>>());
+
+        let filter = ArchiveFilter {
+            player: Some("ai LEVEL 1".to_string()),
+            board_size: Some(size),
+            result: Some(ResultFilter::Won(StoneColor::White)),
+        };
+        let filtered = archive.entries(&filter).expect("memory storage cannot fail");
+        let expected: Vec<&GameRecord> = records
+            .iter()
+            .filter(|record| record.board_size == size && record.result.and_then(GameResult::winner) == Some(StoneColor::White))
+            .collect();
+        prop_assert_eq!(filtered.iter().map(|entry| &entry.record).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,45 @@
+// The demo game played after a spell of no input
+mod common;
+
+use common::*;
+use go3d::attract::AttractMode;
+use go3d::game::{GameRules, GameSession};
+use go3d::render::CameraPreset;
+use glam::Vec3;
+use std::time::Duration;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // However far the demo game got, the first input puts back the game it
+    // interrupted, camera included
+    #[test]
+    fn attract_mode_returns_to_the_live_game(
+        size in 2usize..=4,
+        steps in prop::collection::vec(step(), 0..60),
+        frames in 1usize..200,
+    ) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let before = position_hash(&rules);
+        let session = GameSession::new(rules);
+        let camera = CameraPreset { distance: 20.0, angle_x: 0.3, angle_y: 0.4, center: Vec3::ZERO };
+
+        let mut attract = AttractMode::new(Some(Duration::from_secs(1)));
+        let mut view = camera;
+        for _ in 0..frames {
+            if let Some(turned) = attract.update(0.5, &session, view, Vec3::ONE) {
+                view = turned;
+            }
+        }
+        prop_assert_eq!(attract.is_playing(), frames >= 2);
+
+        let restored = attract.input(&session);
+        prop_assert!(!attract.is_playing());
+        if frames >= 2 {
+            prop_assert_eq!(restored, Some(camera));
+        }
+        prop_assert_eq!(position_hash(&session.snapshot()), before);
+    }
+}
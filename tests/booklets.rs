@@ -0,0 +1,35 @@
+// Printable PDF booklets of a game
+mod common;
+
+use common::*;
+use go3d::game::{GameRecord, GameRules};
+use go3d::render::{booklet, BoardToWorld};
+use go3d::settings::LayoutSettings;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // A booklet has a page for every few moves, and its cross-reference
+    // table points at each object, so readers find them without repairing it
+    #[test]
+    fn booklets_page_through_the_game(size in 2usize..=4, steps in prop::collection::vec(step(), 0..80), every in 1usize..12) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let mut record = GameRecord::from_rules(&rules, 6.5);
+        record.black_player = Some("Ann (\\ü)".to_string());
+        let to_world = BoardToWorld::new(size, &LayoutSettings::default());
+        let pdf = booklet::export_booklet(&record, &to_world, every).expect("a played game must replay");
+
+        prop_assert!(pdf.is_ascii() && pdf.starts_with("%PDF-") && pdf.ends_with("%%EOF\n"));
+        prop_assert_eq!(pdf.matches("/Type /Page ").count(), record.moves.len().div_ceil(every).max(1));
+        let (_, xref) = pdf.rsplit_once("startxref\n").expect("a booklet ends with its xref offset");
+        let xref: usize = xref.trim_end_matches("%%EOF\n").trim().parse().expect("the xref offset is a number");
+        prop_assert!(pdf[xref..].starts_with("xref\n"));
+        for (index, line) in pdf[xref..].lines().skip(3).take_while(|line| line.ends_with(" n ")).enumerate() {
+            let offset: usize = line[..10].parse().expect("offsets are ten digits");
+            let header = format!("{} 0 obj\n", index + 1);
+            prop_assert!(pdf[offset..].starts_with(&header));
+        }
+    }
+}
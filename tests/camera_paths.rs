@@ -0,0 +1,33 @@
+// Camera fly-arounds recorded over a replay
+use go3d::game::{sgf, CameraKeyframe, CameraView, GameRecord};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // Camera paths survive saving, and pass through their keyframes when played
+    #[test]
+    fn camera_paths_save_and_play_through_their_keyframes(
+        views in prop::collection::vec((0.1f32..5.0, 5.0f32..100.0, -10.0f32..10.0, -1.4f32..1.4, -10.0f32..10.0), 1..20),
+    ) {
+        let mut record = GameRecord::new(5, 6.5);
+        let mut time = 0.0;
+        for &(gap, distance, angle_x, angle_y, center) in &views {
+            time += gap;
+            record.camera_path.push(CameraKeyframe {
+                time,
+                view: CameraView { distance, angle_x, angle_y, center: [center, 2.0, -center] },
+            });
+        }
+
+        let parsed = sgf::parse(&sgf::write(&record)).expect("written SGF must parse");
+        prop_assert_eq!(&parsed, &record);
+
+        let path = &record.camera_path;
+        for keyframe in path.keyframes() {
+            prop_assert_eq!(path.sample(keyframe.time), Some(keyframe.view));
+        }
+        let last = path.keyframes().last().expect("at least one keyframe");
+        prop_assert_eq!(path.sample(last.time + 1.0), Some(last.view));
+    }
+}
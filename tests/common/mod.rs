@@ -0,0 +1,83 @@
+// Random games for the property tests: each step picks a legal move by
+// index, or passes, and the rules' invariants are checked after every one.
+// Each test file uses only some of these.
+#![allow(dead_code)]
+
+use go3d::game::{GameRules, StoneColor};
+use proptest::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    Play(usize),
+    Pass,
+}
+
+pub fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        9 => any::<usize>().prop_map(Step::Play),
+        1 => Just(Step::Pass),
+    ]
+}
+
+pub fn position_hash(rules: &GameRules) -> u64 {
+    let board = rules.board();
+    let mut stones: Vec<_> = board.get_all_stones().map(|(&pos, &color)| (pos, color)).collect();
+    stones.sort_by_key(|&(pos, _)| pos);
+
+    let mut hasher = DefaultHasher::new();
+    stones.hash(&mut hasher);
+    board.get_captured(StoneColor::Black).hash(&mut hasher);
+    board.get_captured(StoneColor::White).hash(&mut hasher);
+    rules.current_player().hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn assert_every_group_has_liberties(rules: &GameRules) -> Result<(), TestCaseError> {
+    let board = rules.board();
+    for (&pos, _) in board.get_all_stones() {
+        let group = board.get_group(pos).expect("stone without a group");
+        prop_assert!(
+            !board.get_liberties(&group).is_empty(),
+            "group at {:?} has no liberties",
+            pos
+        );
+    }
+    Ok(())
+}
+
+// Plays `steps` and returns every intermediate position hash, oldest first
+pub fn play_random_game(rules: &mut GameRules, steps: &[Step]) -> Result<Vec<u64>, TestCaseError> {
+    let mut hashes = vec![position_hash(rules)];
+
+    for &step in steps {
+        let color = rules.current_player();
+        let opponent = color.opposite();
+        let stones_before = rules.board().get_all_stones().count();
+        let captured_before = rules.board().get_captured(opponent);
+        let own_captured_before = rules.board().get_captured(color);
+
+        let legal = rules.legal_moves();
+        match step {
+            Step::Play(index) if !legal.is_empty() => {
+                let (x, y, z) = legal[index % legal.len()];
+                prop_assert!(rules.make_move(x, y, z), "legal move {:?} was rejected", (x, y, z));
+
+                let removed = stones_before + 1 - rules.board().get_all_stones().count();
+                let captured = rules.board().get_captured(opponent) - captured_before;
+                prop_assert_eq!(removed, captured);
+                // A legal move never captures the mover's own stones
+                prop_assert_eq!(rules.board().get_captured(color), own_captured_before);
+                prop_assert_eq!(rules.board().get_stone((x, y, z)), Some(color));
+            }
+            _ => rules.pass(),
+        }
+
+        prop_assert_eq!(rules.current_player(), opponent);
+        assert_every_group_has_liberties(rules)?;
+        hashes.push(position_hash(rules));
+    }
+
+    Ok(hashes)
+}
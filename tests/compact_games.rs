@@ -0,0 +1,45 @@
+// The compact binary game encoding behind share links
+use go3d::game::{codec, GameRecord, MoveRecord, StoneColor};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // Compact games come back as they went in, for any number of players;
+    // arbitrary bytes may be rejected but must never panic
+    #[test]
+    fn compact_games_round_trip(
+        size in 2usize..=19,
+        players in 2u8..=4,
+        positions in prop::collection::vec(prop::option::weighted(0.9, (0u8..19, 0u8..19, 0u8..19)), 0..200),
+        noise in prop::collection::vec(any::<u8>(), 0..64),
+    ) {
+        let mut record = GameRecord::new(size, 6.5);
+        record.players = players;
+        for (index, position) in positions.into_iter().enumerate() {
+            let color = StoneColor::players(players)[index % players as usize];
+            record.moves.push(match position.filter(|&(x, y, z)| [x, y, z].iter().all(|&c| (c as usize) < size)) {
+                Some(position) => MoveRecord::place(color, position),
+                None => MoveRecord::pass(color),
+            });
+        }
+        let bytes = codec::encode(&record).expect("moves in turn have a compact form");
+        prop_assert_eq!(codec::decode(&bytes).expect("encoded games decode"), record);
+        let _ = codec::decode(&noise);
+    }
+}
+
+// Two bytes a move up to 25³, three for the far points of 26³
+#[test]
+fn moves_take_two_bytes_up_to_25_cubed() {
+    let far_corner = |size: usize| {
+        let mut record = GameRecord::new(size, 6.5);
+        let edge = size as u8 - 1;
+        record.moves.push(MoveRecord::place(StoneColor::Black, (edge, edge, edge)));
+        let header = codec::encode(&GameRecord::new(size, 6.5)).expect("an empty game encodes").len();
+        codec::encode(&record).expect("moves in turn have a compact form").len() - header
+    };
+    assert_eq!(far_corner(19), 2);
+    assert_eq!(far_corner(25), 2);
+    assert_eq!(far_corner(26), 3);
+}
@@ -0,0 +1,19 @@
+// Crash reports saved on panic
+use go3d::crash;
+use go3d::storage::{Backend, MemoryBackend};
+
+const REPORT: &str = "go3d 0.1.0 crash report (linux x86_64)\n\npanic at src/game/rules.rs:12:5: out of liberties\n";
+
+// A crash report is offered on the next launch, once, and kept afterwards
+#[test]
+fn crash_reports_are_offered_once() {
+    let mut backend = MemoryBackend::default();
+    assert_eq!(crash::take_pending(&mut backend).expect("memory storage cannot fail"), None);
+
+    let slot = crash::save_report(&mut backend, REPORT).expect("memory storage cannot fail");
+    assert!(slot.starts_with("crash-"));
+    let pending = crash::take_pending(&mut backend).expect("memory storage cannot fail");
+    assert_eq!(pending, Some((slot.clone(), REPORT.to_string())));
+    assert_eq!(crash::take_pending(&mut backend).expect("memory storage cannot fail"), None);
+    assert_eq!(backend.load(&slot).expect("memory storage cannot fail").as_deref(), Some(REPORT));
+}
@@ -0,0 +1,31 @@
+// Named engine configurations
+use go3d::engine_configs::{EngineConfig, EngineConfigs};
+use go3d::settings::AiSettings;
+use go3d::storage::MemoryBackend;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // A saved engine configuration exports, imports under another name and
+    // plays with exactly the settings it was saved with
+    #[test]
+    fn engine_configs_survive_export_and_import(
+        name in "[A-Za-z0-9_-]{1,32}",
+        level in 0..=AiSettings::MAX_LEVEL,
+        playouts in 1usize..100_000,
+        threads in 0usize..64,
+    ) {
+        let ai = AiSettings { enabled: true, level, playouts, threads, ..AiSettings::default() };
+        let config = EngineConfig::new(&name, &ai).expect("the name is valid");
+        let mut engines = EngineConfigs::new(Box::<MemoryBackend>::default());
+        engines.save(&config).expect("memory storage cannot fail");
+        prop_assert_eq!(engines.get(&name).expect("just saved").ai_settings(), ai);
+
+        let text = engines.export(&name).expect("just saved");
+        let mut elsewhere = EngineConfigs::new(Box::<MemoryBackend>::default());
+        let imported = elsewhere.import(&text, "ignored").expect("exported text imports");
+        prop_assert_eq!(&imported, &config);
+        prop_assert_eq!(elsewhere.list().expect("memory storage cannot fail"), vec![config]);
+    }
+}
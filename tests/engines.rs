@@ -0,0 +1,99 @@
+// The AI engines: pattern matching, search budgets and seeded play
+mod common;
+
+use common::*;
+use go3d::ai::patterns::PatternLibrary;
+use go3d::game::{Axis, GameRecord, GameRules, Symmetry};
+use go3d::ai::{Engine, GreedyEngine};
+use go3d::api::{Game, Move};
+use go3d::settings::{AiBudget, AiSettings, GameSettings, ScoringRule};
+use go3d::tournament;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // Patterns are matched in every orientation, so turning the board can't change what's found
+    #[test]
+    fn patterns_match_whichever_way_the_board_is_turned(
+        size in 3usize..=5,
+        steps in prop::collection::vec(step(), 0..30),
+        axis in prop_oneof![Just(Axis::X), Just(Axis::Y), Just(Axis::Z)],
+    ) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let record = GameRecord::from_rules(&rules, 6.5);
+        let library = PatternLibrary::builtin();
+
+        let names = |rules: &GameRules| {
+            let mut names: Vec<_> = library.matches(rules).into_iter().map(|found| found.name).collect();
+            names.dedup();
+            names
+        };
+        for symmetry in [Symmetry::Rotate(axis), Symmetry::Reflect(axis)] {
+            let turned = record.transformed(symmetry).replay().expect("a turned game must replay");
+            prop_assert_eq!(names(&turned), names(&rules));
+        }
+    }
+
+    // Saving power never searches more than was asked for, nor less than the
+    // floor; the full budget searches exactly what was asked for
+    #[test]
+    fn ai_budgets_scale_the_search_down_only(playouts in 1usize..100_000, threads in 0usize..64) {
+        let ai = AiSettings { level: AiSettings::MAX_LEVEL, playouts, threads, ..AiSettings::default() };
+        let full = AiSettings { budget: AiBudget::Full, ..ai.clone() };
+        prop_assert_eq!(full.search(), (playouts, threads));
+
+        let (saver_playouts, saver_threads) = AiSettings { budget: AiBudget::Saver, ..ai }.search();
+        prop_assert!(saver_playouts <= playouts && saver_playouts >= playouts.min(100));
+        prop_assert!((1..=2).contains(&saver_threads) && (threads == 0 || saver_threads <= threads));
+    }
+
+    // Engines seeded alike choose the same moves and so play the same game,
+    // which keeps the seed in its record; the search repeats itself on one
+    // thread. Komi and the scoring rule are the game's own, not the defaults.
+    #[test]
+    fn seeded_matches_play_again(level in 0..=AiSettings::MAX_LEVEL, seed in any::<u64>(), komi in -8.0f32..8.0, area in any::<bool>()) {
+        let ai = AiSettings { level, playouts: 8, threads: 1, ..AiSettings::default() };
+        let scoring = if area { ScoringRule::Area } else { ScoringRule::Territory };
+        let settings = GameSettings { board_size: 2, komi, scoring, ..GameSettings::default() };
+        let play = || {
+            let mut chosen = Vec::new();
+            let result = tournament::play_match(&mut *ai.create_engine(&settings), &mut *ai.create_engine(&settings), &settings, seed, |number, color, mv| {
+                chosen.push((number, color, mv));
+            });
+            (chosen, result)
+        };
+        let (first_moves, first) = play();
+        let (again_moves, again) = play();
+        prop_assert_eq!(again_moves, first_moves);
+        prop_assert_eq!(first.record.seed, Some(seed));
+        prop_assert_eq!(first.record.komi, komi);
+        prop_assert_eq!(again.record, first.record);
+        prop_assert_eq!(again.score, first.score);
+
+        // Reseeding puts an engine back where it started
+        let game = Game::new(2);
+        let mut engine = ai.create_engine(&settings);
+        engine.reseed(seed);
+        let chosen = engine.select_move(&game);
+        engine.reseed(seed);
+        prop_assert_eq!(engine.select_move(&game), chosen);
+    }
+}
+
+// In a three-player game Red's stones are worth taking too, not only White's
+#[test]
+fn greedy_play_captures_any_other_color() {
+    let mut rules = GameRules::new(2);
+    rules.set_players(3);
+    for (x, y, z) in [(1, 0, 0), (0, 1, 0), (0, 0, 0)] {
+        assert!(rules.make_move(x, y, z));
+    }
+    let game = Game::from(rules);
+    let mut engine = GreedyEngine::new();
+    for seed in 0..16 {
+        engine.reseed(seed);
+        assert_eq!(engine.select_move(&game), Move::Place((0, 0, 1).into()));
+    }
+}
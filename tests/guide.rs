@@ -0,0 +1,106 @@
+// The guide: its planes and crosshair, bookmarks and the pulse on followed moves
+use go3d::render::{BoardToWorld, GuideSystem};
+use go3d::settings::{LayoutSettings, UpAxis};
+use glam::Vec3;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // Each bookmark brings the guide back to the point last stored under it;
+    // an empty one leaves the guide where it is
+    #[test]
+    fn guide_bookmarks_recall_what_was_stored(
+        size in 2usize..=9,
+        steps in prop::collection::vec((0usize..GuideSystem::BOOKMARKS, any::<bool>(), (0u8..9, 0u8..9, 0u8..9)), 0..40),
+    ) {
+        let mut guide = GuideSystem::new(size);
+        let mut stored = [None; GuideSystem::BOOKMARKS];
+        for (slot, store, point) in steps {
+            if store {
+                guide.set_intersection(point);
+                stored[slot] = Some(guide.store_bookmark(slot));
+            } else {
+                let before = guide.get_intersection_position();
+                prop_assert_eq!(guide.recall_bookmark(slot), stored[slot]);
+                prop_assert_eq!(guide.get_intersection_position(), stored[slot].unwrap_or(before));
+            }
+        }
+    }
+
+    // Whatever the layout and however the guide was moved, each plane faces
+    // its own world axis and all three pass through the guide point as drawn;
+    // the dot stands on the two upright planes, straight above the point. The
+    // crosshair lines run along the same axes, through the same point, across
+    // the whole board
+    #[test]
+    fn guide_planes_meet_at_the_guide_point(
+        size in 2usize..=9,
+        point in (0u8..9, 0u8..9, 0u8..9),
+        moves in prop::collection::vec((0usize..3, -3i32..=3), 0..10),
+        up_y in any::<bool>(),
+        pitch in 0.5f32..4.0,
+    ) {
+        let layout = LayoutSettings {
+            pitch,
+            up: if up_y { UpAxis::Y } else { UpAxis::Z },
+            ..LayoutSettings::default()
+        };
+        let to_world = BoardToWorld::new(size, &layout);
+        let mut guide = GuideSystem::new(size);
+        guide.set_intersection(point);
+        for (board_axis, delta) in moves {
+            guide.move_along(board_axis, delta);
+        }
+
+        let target = to_world.to_world(guide.get_intersection_position());
+        let dot = guide.get_dot_instance(&to_world).position;
+        // The plane mesh lies in XY, facing Z
+        for (world_axis, plane) in guide.get_plane_instances(&to_world).iter().enumerate() {
+            let normal = plane.rotation * Vec3::Z;
+            prop_assert!(normal.abs().dot([Vec3::X, Vec3::Y, Vec3::Z][world_axis]) > 0.999);
+            prop_assert!((target - plane.position).dot(normal).abs() < 1e-3);
+            if world_axis != 1 {
+                prop_assert!((dot - plane.position).dot(normal).abs() < 1e-3);
+            }
+        }
+        prop_assert!(dot.y > target.y);
+
+        for (world_axis, line) in guide.get_line_instances(&to_world).iter().enumerate() {
+            prop_assert!((line.scale[world_axis] - to_world.extent()).abs() < 1e-3);
+            for other in (0..3).filter(|&other| other != world_axis) {
+                prop_assert!(line.scale[other] < line.scale[world_axis]);
+                prop_assert!((line.position[other] - target[other]).abs() < 1e-3);
+            }
+            prop_assert!(line.position[world_axis].abs() < 1e-3);
+        }
+    }
+
+    // Following a move puts the guide on it and pulses it, and the pulse
+    // dies away within its length however the frames fall
+    #[test]
+    fn followed_moves_pulse_briefly(
+        size in 2usize..=9,
+        point in (0u8..9, 0u8..9, 0u8..9),
+        frames in prop::collection::vec(0.0f32..0.25, 0..40),
+    ) {
+        let mut guide = GuideSystem::new(size);
+        guide.follow_move(point);
+        let clamp = |n: u8| n.min(size as u8 - 1);
+        prop_assert_eq!(guide.get_intersection_position(), (clamp(point.0), clamp(point.1), clamp(point.2)));
+        prop_assert!(guide.is_pulsing());
+        let mut elapsed = 0.0;
+        for dt in frames {
+            guide.tick(dt);
+            elapsed += dt;
+            // Allowing for rounding in the sum
+            if elapsed < GuideSystem::PULSE_SECONDS - 1e-3 {
+                prop_assert!(guide.is_pulsing());
+            } else if elapsed > GuideSystem::PULSE_SECONDS + 1e-3 {
+                prop_assert!(!guide.is_pulsing());
+            }
+        }
+        guide.tick(GuideSystem::PULSE_SECONDS);
+        prop_assert!(!guide.is_pulsing());
+    }
+}
@@ -0,0 +1,36 @@
+// King of the hill, whose hold on the centre the rules count move by move
+mod common;
+
+use common::*;
+use go3d::game::{GameRules, Hill, StoneColor, Variant, VictoryCondition};
+use proptest::prelude::*;
+
+// Who holds the hill and for how many moves, counted back over every board
+fn held_by_looking_back(hill: &Hill, rules: &GameRules) -> Option<(StoneColor, usize)> {
+    let color = hill.controller(rules.board())?;
+    let held = rules.previous_boards().take_while(|&board| hill.controller(board) == Some(color)).count();
+    // The start position isn't after a move
+    Some((color, (held + 1).min(rules.moves().len())))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // The count kept as moves are made and taken back is the one a look back
+    // over the whole game gives
+    #[test]
+    fn hill_control_is_counted_move_by_move(size in 2usize..=5, turns in 1u8..=6, steps in prop::collection::vec(step(), 0..60), undos in 0usize..20) {
+        let mut rules = GameRules::new(size);
+        rules.set_variant(Variant::Hill { turns });
+        let hill = Hill::new(rules.board(), turns);
+        play_random_game(&mut rules, &steps)?;
+        prop_assert_eq!(rules.control(), held_by_looking_back(&hill, &rules));
+        let winner = held_by_looking_back(&hill, &rules).filter(|&(_, moves)| moves >= usize::from(turns)).map(|(color, _)| color);
+        prop_assert_eq!(rules.winner(), winner);
+
+        for _ in 0..undos {
+            rules.undo();
+            prop_assert_eq!(rules.control(), held_by_looking_back(&hill, &rules));
+        }
+    }
+}
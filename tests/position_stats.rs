@@ -0,0 +1,35 @@
+// The position info panel's counts
+mod common;
+
+use common::*;
+use go3d::game::{GameRules, PositionStats, StoneColor};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // The info panel's summary agrees with counting the board point by point
+    #[test]
+    fn position_stats_count_the_board(size in 2usize..=4, steps in prop::collection::vec(step(), 0..120)) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let board = rules.board();
+        let stats = PositionStats::of(board);
+
+        for color in StoneColor::ALL {
+            prop_assert_eq!(stats.stones[color.index()], board.stones_of(color).count());
+        }
+        prop_assert_eq!(stats.stones.iter().sum::<usize>() + stats.empty, board.positions().count());
+        let mut groups = Vec::new();
+        for (&position, _) in board.get_all_stones() {
+            let group = board.get_group(position).expect("a stone is in a group");
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+        prop_assert_eq!(stats.groups, groups.len());
+        let in_atari = groups.iter().filter(|group| board.get_liberties(group).len() == 1).count();
+        prop_assert_eq!(stats.groups_in_atari, in_atari);
+        prop_assert_eq!(stats.largest_group, groups.iter().map(|group| group.len()).max().unwrap_or(0));
+    }
+}
@@ -0,0 +1,25 @@
+// Pre-moves staged while the AI is to move
+mod common;
+
+use common::*;
+use go3d::game::{GameRules, Premove};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // A pre-move waits out the other player's reply, then goes by the rules
+    // as they stand after it
+    #[test]
+    fn premoves_wait_for_their_turn(size in 2usize..=4, steps in prop::collection::vec(step(), 0..80), reply in step(), point in any::<usize>()) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let positions: Vec<_> = rules.board().positions().collect();
+        let premove = Premove::new(positions[point % positions.len()], rules.current_player().opposite());
+        prop_assert_eq!(premove.due(&rules), None);
+
+        play_random_game(&mut rules, &[reply])?;
+        let (x, y, z) = premove.position;
+        prop_assert_eq!(premove.due(&rules), Some(rules.check_move(x, y, z).map(|()| premove.position)));
+    }
+}
@@ -0,0 +1,119 @@
+// Records written by earlier builds must keep loading as the format moves on:
+// one fixture for each shape the writer has had
+use go3d::game::{sgf, Annotation, GameRecord, GameResult, Mark, MoveRecord, StoneColor, Variant};
+use std::time::Duration;
+
+fn parse(input: &str) -> GameRecord {
+    let record = sgf::parse(input).unwrap_or_else(|error| panic!("{}: {}", input, error));
+    record.replay().unwrap_or_else(|error| panic!("{}: {}", input, error));
+    record
+}
+
+// Version 1, as first written: size, komi and moves
+#[test]
+fn version_1_moves() {
+    let record = parse("(;GM[3DGO]FF[4]SZ[3]KM[0.5];B[bbb];W[aaa];B[])");
+    assert_eq!((record.board_size, record.komi, record.players), (3, 0.5, 2));
+    assert_eq!(record.variant, Variant::Standard);
+    assert_eq!(
+        record.moves,
+        vec![
+            MoveRecord::place(StoneColor::Black, (1, 1, 1)),
+            MoveRecord::place(StoneColor::White, (0, 0, 0)),
+            MoveRecord::pass(StoneColor::Black),
+        ]
+    );
+}
+
+// Version 1 with review markup
+#[test]
+fn version_1_markup() {
+    let record = parse("(;GM[3DGO]FF[4]SZ[3]KM[0.5]TR[aaa];B[bbb]LB[ccc:A]SQ[aab]MK[cca:red];W[aaa])");
+    let marks = |move_number| record.annotations.at(move_number).cloned().collect::<Vec<Annotation>>();
+    assert_eq!(marks(0).len(), 1);
+    assert_eq!(marks(0)[0].mark, Mark::Triangle);
+    let after_first: Vec<Mark> = marks(1).iter().map(|annotation| annotation.mark).collect();
+    assert!(after_first.contains(&Mark::Label('A')));
+    assert!(after_first.contains(&Mark::Square));
+    assert_eq!(after_first.len(), 3);
+}
+
+// Version 1 with thinking times
+#[test]
+fn version_1_thinking_times() {
+    let record = parse("(;GM[3DGO]FF[4]SZ[3]KM[0.5];B[bbb]TS[4.215];W[aaa]TS[0.000])");
+    let times: Vec<Option<Duration>> = record.moves.iter().map(|mv| mv.thinking).collect();
+    assert_eq!(times, vec![Some(Duration::from_millis(4215)), Some(Duration::ZERO)]);
+}
+
+// Version 1 with a rule variant
+#[test]
+fn version_1_variants() {
+    for (value, variant) in [
+        ("gravity", Variant::Gravity),
+        ("stack:2", Variant::Stack { levels: 2 }),
+        ("holes:7:10", Variant::Holes { seed: 7, percent: 10 }),
+        ("hill:10", Variant::Hill { turns: 10 }),
+    ] {
+        let record = parse(&format!("(;GM[3DGO]FF[4]SZ[3]KM[0.5]VR[{}])", value));
+        assert_eq!(record.variant, variant);
+    }
+}
+
+// Version 1 with three players
+#[test]
+fn version_1_players() {
+    let record = parse("(;GM[3DGO]FF[4]SZ[3]KM[0]NP[3];B[aaa];W[ccc];R[bbb])");
+    assert_eq!(record.players, 3);
+    assert_eq!(record.moves[2], MoveRecord::place(StoneColor::Red, (1, 1, 1)));
+}
+
+// Version 1 with a camera path
+#[test]
+fn version_1_camera_path() {
+    let record = parse("(;GM[3DGO]FF[4]SZ[3]KM[0.5]CP[0:30:0.5:0.6:1:1:1][4.25:25:1.1:0.6:1:1:1];B[bbb])");
+    let times: Vec<f32> = record.camera_path.keyframes().iter().map(|keyframe| keyframe.time).collect();
+    assert_eq!(times, vec![0.0, 4.25]);
+}
+
+// Version 1 as last written, with players and result
+#[test]
+fn version_1_players_and_result() {
+    let record = parse("(;GM[3DGO]FF[4]SZ[3]KM[0.5]PB[Ann \\] B]PW[AI level 2]RE[W+4.5];B[bbb])");
+    assert_eq!(record.black_player.as_deref(), Some("Ann ] B"));
+    assert_eq!(record.white_player.as_deref(), Some("AI level 2"));
+    assert_eq!(
+        record.result,
+        Some(GameResult::Win {
+            color: StoneColor::White,
+            margin: Some(4.5)
+        })
+    );
+}
+
+// Version 2 names its version in the root node
+#[test]
+fn version_2_is_written_and_read() {
+    let mut record = GameRecord::new(3, 0.5);
+    record.moves.push(MoveRecord::place(StoneColor::Black, (1, 1, 1)));
+    let text = sgf::write(&record);
+    assert_eq!(text, "(;GM[3DGO]FF[4]FV[2]SZ[3]KM[0.5];B[bbb])");
+    assert_eq!(sgf::FORMAT_VERSION, 2);
+    assert_eq!(parse(&text), record);
+}
+
+// Version 2 with the seed an engine game was played from
+#[test]
+fn version_2_seed() {
+    let record = parse("(;GM[3DGO]FF[4]FV[2]SZ[3]KM[0.5]RS[8731640071];B[bbb];W[])");
+    assert_eq!(record.seed, Some(8731640071));
+    assert_eq!(parse(&sgf::write(&record)), record);
+}
+
+#[test]
+fn newer_versions_are_refused() {
+    let error = sgf::parse("(;GM[3DGO]FF[4]FV[3]SZ[3]KM[0.5])").unwrap_err();
+    assert_eq!(error, sgf::SgfError::NewerVersion(3));
+    let error = sgf::parse("(;GM[3DGO]FF[4]FV[0]SZ[3]KM[0.5])").unwrap_err();
+    assert_eq!(error, sgf::SgfError::InvalidVersion("0".to_string()));
+}
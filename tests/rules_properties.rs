@@ -0,0 +1,102 @@
+// Property tests: long random games must keep the rules engine's invariants
+mod common;
+
+use common::*;
+use go3d::game::{what_if, Axis, BoardDiff, GameRecord, GameRules, Symmetry};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn random_games_keep_invariants(size in 2usize..=4, steps in prop::collection::vec(step(), 0..150)) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        prop_assert_eq!(rules.moves().len(), steps.len());
+    }
+
+    #[test]
+    fn undo_restores_every_position(size in 2usize..=4, steps in prop::collection::vec(step(), 0..120)) {
+        let mut rules = GameRules::new(size);
+        let mut hashes = play_random_game(&mut rules, &steps)?;
+
+        hashes.pop();
+        while let Some(expected) = hashes.pop() {
+            prop_assert!(rules.undo());
+            prop_assert_eq!(position_hash(&rules), expected);
+        }
+        prop_assert!(!rules.undo());
+    }
+
+    // A what-if preview agrees with the rules on every point, and the stones
+    // it says a move would capture are the ones playing it does
+    #[test]
+    fn move_previews_match_the_moves(size in 2usize..=4, steps in prop::collection::vec(step(), 0..80)) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+
+        for position in rules.board().positions().collect::<Vec<_>>() {
+            let (x, y, z) = position;
+            let preview = what_if::preview(&rules, position);
+            prop_assert_eq!(preview.as_ref().err().copied(), rules.check_move(x, y, z).err());
+            let Ok(preview) = preview else {
+                continue;
+            };
+            let mut played = rules.clone();
+            prop_assert!(played.make_move(x, y, z));
+            let captured: Vec<_> = played.diff_last_move().removed.iter().map(|&(stone, _)| stone).collect();
+            prop_assert_eq!(&preview.captured, &captured);
+            let group = played.board().get_group(position).expect("a stone was just played there");
+            prop_assert_eq!(preview.liberties, played.board().get_liberties(&group).len());
+            let mut group: Vec<_> = group.into_iter().collect();
+            group.sort_unstable();
+            prop_assert_eq!(&preview.group, &group);
+        }
+    }
+
+    // Review steps of any length are diffed as one move that takes either board to the other
+    #[test]
+    fn diffs_between_positions_apply_and_revert(
+        size in 2usize..=4,
+        steps in prop::collection::vec(step(), 0..100),
+        from in any::<prop::sample::Index>(),
+        to in any::<prop::sample::Index>(),
+    ) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let record = GameRecord::from_rules(&rules, 6.5);
+        let moves = record.moves.len() + 1;
+        let before = record.replay_to(from.index(moves)).expect("a played game must replay");
+        let after = record.replay_to(to.index(moves)).expect("a played game must replay");
+
+        let diff = BoardDiff::between(before.board(), after.board());
+        let mut board = before.board().clone();
+        diff.apply(&mut board);
+        prop_assert_eq!(board.get_all_stones().collect::<std::collections::HashMap<_, _>>(), after.board().get_all_stones().collect());
+        diff.revert(&mut board);
+        prop_assert_eq!(board.get_all_stones().collect::<std::collections::HashMap<_, _>>(), before.board().get_all_stones().collect());
+    }
+
+    #[test]
+    fn turned_games_replay_and_turn_back(
+        size in 2usize..=4,
+        steps in prop::collection::vec(step(), 0..100),
+        axis in prop_oneof![Just(Axis::X), Just(Axis::Y), Just(Axis::Z)],
+    ) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let record = GameRecord::from_rules(&rules, 6.5);
+
+        let mirrored = record.transformed(Symmetry::Reflect(axis));
+        let replayed = mirrored.replay().expect("a mirrored game must replay");
+        prop_assert_eq!(replayed.board().get_all_stones().count(), rules.board().get_all_stones().count());
+        prop_assert_eq!(&mirrored.transformed(Symmetry::Reflect(axis)), &record);
+
+        let mut turned = record.clone();
+        for _ in 0..4 {
+            turned = turned.transformed(Symmetry::Rotate(axis));
+            prop_assert!(turned.replay().is_ok(), "a turned game must replay");
+        }
+        prop_assert_eq!(turned, record);
+    }
+}
@@ -0,0 +1,69 @@
+// Observers on a game session, which are called with the session unlocked
+use go3d::game::{GameRules, GameSession, MoveRecord, RulesObserver};
+use std::sync::{Arc, Mutex};
+
+type Position = (u8, u8, u8);
+
+// Reads the session back on every callback, which would deadlock if the
+// session were still locked
+struct Reader {
+    session: GameSession,
+    seen: Mutex<Vec<(usize, usize)>>,
+    ended: Mutex<bool>,
+}
+
+impl RulesObserver for Reader {
+    fn on_move(&self, rules: &GameRules, _record: &MoveRecord, _captured: &[Position]) {
+        let live = self.session.read().moves().len();
+        self.seen.lock().unwrap().push((rules.moves().len(), live));
+    }
+
+    fn on_game_end(&self, _rules: &GameRules) {
+        *self.ended.lock().unwrap() = self.session.read().is_over();
+    }
+}
+
+#[test]
+fn observers_can_read_the_session() {
+    let session = GameSession::new(GameRules::new(3));
+    let reader = Arc::new(Reader {
+        session: session.clone(),
+        seen: Mutex::new(Vec::new()),
+        ended: Mutex::new(false),
+    });
+    let id = session.add_observer(reader.clone());
+    let events = session.subscribe();
+
+    assert!(session.make_move(1, 1, 1));
+    session.update(|rules| rules.make_move(0, 0, 0));
+    session.pass();
+    session.pass();
+    assert_eq!(*reader.seen.lock().unwrap(), vec![(1, 1), (2, 2), (3, 3), (4, 4)]);
+    assert!(*reader.ended.lock().unwrap());
+    assert_eq!(events.try_iter().count(), 5, "four moves and the update's Changed");
+
+    assert!(session.remove_observer(id));
+    assert!(session.make_move(2, 2, 2));
+    assert_eq!(reader.seen.lock().unwrap().len(), 4);
+}
+
+// An observer may even play on the session it's observing
+struct Replier(GameSession);
+
+impl RulesObserver for Replier {
+    fn on_move(&self, _rules: &GameRules, record: &MoveRecord, _captured: &[Position]) {
+        if record.position.is_some() {
+            self.0.pass();
+        }
+    }
+}
+
+#[test]
+fn observers_can_play_on_the_session() {
+    let session = GameSession::new(GameRules::new(3));
+    session.add_observer(Arc::new(Replier(session.clone())));
+    assert!(session.make_move(1, 1, 1));
+    let rules = session.read();
+    assert_eq!(rules.moves().len(), 2);
+    assert_eq!(rules.moves()[1].position, None);
+}
@@ -0,0 +1,58 @@
+// Session recordings are sent in with bug reports and played back on another
+// machine, so what's written has to read back as the same session
+use go3d::api::{Move, Position};
+use go3d::game::sgf;
+use go3d::input::{SessionEntry, SessionInput, SessionPlayback, SessionRecording};
+use winit::keyboard::{KeyCode, ModifiersState};
+
+fn recording() -> SessionRecording {
+    SessionRecording {
+        args: vec!["--size".to_string(), "5".to_string(), "--player".to_string(), "Ann Lee".to_string()],
+        preferences: "panels=on\nguide=crosshair".to_string(),
+        game: Some(sgf::parse("(;GM[3DGO]FF[4]SZ[3]KM[0.5];B[bbb];W[aaa])").unwrap()),
+        entries: vec![
+            SessionEntry::Input(SessionInput::Resized { width: 1024, height: 768 }),
+            SessionEntry::Frame(0.0),
+            SessionEntry::Input(SessionInput::Modifiers(ModifiersState::SHIFT | ModifiersState::ALT)),
+            SessionEntry::Input(SessionInput::Key { key: KeyCode::Digit3, pressed: true }),
+            SessionEntry::Input(SessionInput::Pointer { x: 412.37, y: 0.1 }),
+            SessionEntry::Frame(0.011_111),
+            SessionEntry::Input(SessionInput::Button { pressed: true }),
+            SessionEntry::Input(SessionInput::Drag { dx: -3.25, dy: 1e-3 }),
+            SessionEntry::Input(SessionInput::Button { pressed: false }),
+            SessionEntry::Input(SessionInput::Wheel(-0.3)),
+            SessionEntry::Frame(0.25),
+            SessionEntry::Ai(Move::Place(Position::new(0, 2, 1))),
+            SessionEntry::Input(SessionInput::Modifiers(ModifiersState::empty())),
+            SessionEntry::Ai(Move::Pass),
+            SessionEntry::Input(SessionInput::Key { key: KeyCode::Escape, pressed: true }),
+        ],
+    }
+}
+
+#[test]
+fn recordings_read_back_exactly() {
+    let recording = recording();
+    let text = recording.to_text();
+    assert_eq!(SessionRecording::parse(&text), Ok(recording), "{}", text);
+}
+
+#[test]
+fn playback_hands_out_inputs_frame_by_frame() {
+    let recording = recording();
+    assert_eq!(recording.ai_moves(), [Move::Place(Position::new(0, 2, 1)), Move::Pass]);
+
+    let mut playback = SessionPlayback::new(&recording);
+    let frames: Vec<(usize, f32)> = std::iter::from_fn(|| playback.next_frame())
+        .map(|(inputs, seconds)| (inputs.len(), seconds))
+        .collect();
+    // The inputs after the last frame still get one, of no length
+    assert_eq!(frames, [(1, 0.0), (3, 0.011_111), (4, 0.25), (2, 0.0)]);
+}
+
+#[test]
+fn unreadable_lines_are_reported() {
+    assert!(SessionRecording::parse("frame 0.1\n").is_err());
+    let error = SessionRecording::parse("3dgo-session 1\nframe 0.1\nkey Launch down\n").unwrap_err();
+    assert_eq!(error.to_string(), "line 3: cannot read 'key Launch down'");
+}
@@ -0,0 +1,98 @@
+// SGF-3D records: what's written reads back as the same game, and the parser
+// turns away anything else without panicking
+mod common;
+
+use common::*;
+use go3d::game::{sgf, share, GameRecord, GameRules, MoveRecord, StoneColor};
+use proptest::prelude::*;
+use std::time::Duration;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn records_replay_to_the_same_position(size in 2usize..=4, steps in prop::collection::vec(step(), 0..100)) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+
+        let record = GameRecord::from_rules(&rules, 6.5);
+        let replayed = record.replay().expect("a played game must replay");
+        prop_assert_eq!(position_hash(&replayed), position_hash(&rules));
+
+        let parsed = sgf::parse(&sgf::write(&record)).expect("written SGF must parse");
+        prop_assert_eq!(&parsed, &record);
+
+        let link = share::to_fragment(&record);
+        prop_assert!(link.starts_with(share::COMPACT_PREFIX));
+        let shared = share::from_fragment(&link).expect("share link must decode");
+        prop_assert_eq!(&shared, &record);
+
+        // Anything beyond the moves keeps the link in SGF-3D
+        let named = GameRecord { black_player: Some("Ann".to_string()), ..record };
+        let link = share::to_fragment(&named);
+        prop_assert!(link.starts_with(share::FRAGMENT_PREFIX));
+        prop_assert_eq!(share::from_fragment(&link).expect("share link must decode"), named);
+    }
+
+    // Parser fuzzing: arbitrary input may be rejected but must never panic
+    #[test]
+    fn sgf_parser_never_panics(input in ".{0,256}") {
+        let _ = sgf::parse(&input);
+    }
+
+    #[test]
+    fn sgf_parser_never_panics_on_near_valid_input(
+        size in 1usize..=30,
+        moves in prop::collection::vec("[a-z]{0,4}", 0..20),
+        cut in any::<prop::sample::Index>(),
+    ) {
+        let mut input = format!("(;GM[3DGO]FF[4]SZ[{}]", size);
+        for (i, coord) in moves.iter().enumerate() {
+            input.push_str(&format!(";{}[{}]", if i % 2 == 0 { 'B' } else { 'W' }, coord));
+        }
+        input.push(')');
+
+        if let Ok(record) = sgf::parse(&input) {
+            let in_bounds = |mv: &MoveRecord| {
+                mv.position.is_none_or(|(x, y, z)| {
+                    [x, y, z].iter().all(|&c| (c as usize) < record.board_size)
+                })
+            };
+            prop_assert!(record.moves.iter().all(in_bounds));
+        }
+        // Truncated records must fail cleanly too
+        let _ = sgf::parse(&input[..cut.index(input.len() + 1)]);
+    }
+}
+
+// Every color's moves keep their thinking times, not just Black's and White's
+#[test]
+fn thinking_times_survive_for_every_color() {
+    let mut record = GameRecord::new(4, 0.5);
+    record.players = 4;
+    for (index, &color) in StoneColor::players(4).iter().enumerate() {
+        let mut mv = MoveRecord::place(color, (index as u8, 0, 0));
+        mv.thinking = Some(Duration::from_millis(1500 + index as u64));
+        record.moves.push(mv);
+    }
+    let text = sgf::write(&record);
+    assert!(text.contains(";R[caa]TS[1.502]") && text.contains(";G[daa]TS[1.503]"), "{}", text);
+    assert_eq!(sgf::parse(&text), Ok(record));
+
+    let three = sgf::parse("(;GM[3DGO]FF[4]SZ[3]NP[3];B[aaa]TS[1.500];W[ccc]TS[1.500];R[bbb]TS[1.500])").unwrap();
+    assert!(three.moves.iter().all(|mv| mv.thinking == Some(Duration::from_millis(1500))));
+}
+
+// Records that would read back differently once written are refused: a komi
+// that isn't a number, and a size given after the moves it would cut off
+#[test]
+fn records_that_cannot_round_trip_are_refused() {
+    let error = sgf::parse("(;GM[3DGO]FF[4]SZ[5]KM[NaN];B[ddd])").unwrap_err();
+    assert_eq!(error, sgf::SgfError::InvalidKomi("NaN".to_string()));
+    assert!(sgf::parse("(;GM[3DGO]FF[4]SZ[5]KM[inf])").is_err());
+
+    for input in ["(;GM[3DGO]SZ[5];B[ddd];SZ[3])", "(;GM[3DGO]SZ[5]B[ddd]SZ[3])", "(;GM[3DGO]SZ[5];B[aaa];SZ[5])"] {
+        assert_eq!(sgf::parse(input), Err(sgf::SgfError::MisplacedSize), "{}", input);
+    }
+    assert!(sgf::parse("(;GM[3DGO]SZ[3];B[ddd])").is_err());
+}
@@ -0,0 +1,174 @@
+// The simultaneous variant, where Black's and White's stones land together
+use go3d::game::{GameRules, GameSession, MoveError, StoneColor, Variant};
+use go3d::simultaneous::{RevealMessage, SimultaneousError, SimultaneousTurn};
+
+fn simultaneous(turns: &[[(u8, u8, u8); 2]]) -> GameRules {
+    let mut rules = GameRules::new(2);
+    rules.set_variant(Variant::Simultaneous);
+    for &[(bx, by, bz), (wx, wy, wz)] in turns {
+        assert!(rules.make_move(bx, by, bz));
+        assert!(rules.make_move(wx, wy, wz));
+    }
+    rules
+}
+
+// Each stone is fine on its own, but together they fill the board, every
+// stone is captured and the empty start position comes back
+#[test]
+fn the_board_after_both_stones_must_not_repeat() {
+    let mut rules = simultaneous(&[[(0, 1, 0), (1, 1, 0)], [(0, 0, 1), (1, 0, 1)], [(0, 1, 1), (1, 1, 1)]]);
+    assert_eq!(rules.check_move(0, 0, 0), Ok(()));
+    assert!(rules.make_move(0, 0, 0));
+    assert_eq!(rules.check_move(1, 0, 0), Err(MoveError::Ko));
+    assert!(!rules.make_move(1, 0, 0));
+
+    // Passing instead lands Black's stone alone, with the last point free
+    rules.pass();
+    assert_eq!(rules.board().stones_of(StoneColor::Black).count(), 4);
+    assert_eq!(rules.board().stones_of(StoneColor::White).count(), 3);
+}
+
+#[test]
+fn colliding_stones_repeat_nothing() {
+    let mut rules = simultaneous(&[[(0, 0, 0), (1, 1, 1)]]);
+    assert!(rules.make_move(0, 1, 0));
+    assert_eq!(rules.check_move(0, 1, 0), Ok(()));
+    let before = rules.board().hash();
+    assert!(rules.make_move(0, 1, 0));
+    assert_eq!(rules.board().hash(), before);
+    assert!(rules.diff_last_move().is_empty());
+}
+
+// The diff covers the whole turn, and nothing while Black's stone waits
+#[test]
+fn diffs_cover_both_stones_of_a_turn() {
+    let mut rules = simultaneous(&[[(0, 1, 0), (1, 1, 0)]]);
+    let diff = rules.diff_last_move();
+    assert_eq!(diff.placed, vec![((0, 1, 0), StoneColor::Black), ((1, 1, 0), StoneColor::White)]);
+    assert!(diff.removed.is_empty());
+
+    let before = rules.board().clone();
+    assert!(rules.make_move(0, 0, 0));
+    assert!(rules.diff_last_move().is_empty());
+    assert!(rules.make_move(1, 1, 1));
+    let mut replayed = before.clone();
+    rules.diff_last_move().apply(&mut replayed);
+    assert_eq!(replayed.hash(), rules.board().hash());
+    rules.diff_last_move().revert(&mut replayed);
+    assert_eq!(replayed.hash(), before.hash());
+}
+
+// Each player's end of the commit and reveal exchange, every line sent as text
+struct End {
+    turn: SimultaneousTurn,
+    session: GameSession,
+}
+
+impl End {
+    fn new(color: StoneColor, rules: GameRules) -> Self {
+        End { turn: SimultaneousTurn::new(color), session: GameSession::new(rules) }
+    }
+
+    fn commit(&mut self, play: Option<(u8, u8, u8)>) -> Vec<RevealMessage> {
+        self.turn.commit(play, &self.session).expect("a legal play")
+    }
+
+    fn receive(&mut self, lines: Vec<RevealMessage>) -> Vec<RevealMessage> {
+        let size = self.session.read().board().size();
+        let mut replies = Vec::new();
+        for line in lines {
+            let message = RevealMessage::parse(&line.to_string(), size).expect("sent lines read back");
+            assert_eq!(message, line);
+            replies.extend(self.turn.receive(message, &self.session).expect("an honest opponent"));
+        }
+        replies
+    }
+}
+
+// Black commits first, White answers with its commitment and reveal, and each
+// end plays the turn once the other's reveal is in
+fn play_turn(black: &mut End, white: &mut End, plays: [Option<(u8, u8, u8)>; 2]) {
+    let lines = black.commit(plays[0]);
+    assert!(white.receive(lines).is_empty());
+    let lines = white.commit(plays[1]);
+    assert_eq!(lines.len(), 2);
+    let lines = black.receive(lines);
+    assert_eq!(lines.len(), 1);
+    assert!(white.receive(lines).is_empty());
+    assert!(!black.turn.has_committed() && !white.turn.has_committed());
+}
+
+#[test]
+fn both_ends_play_the_revealed_moves() {
+    let mut black = End::new(StoneColor::Black, simultaneous(&[]));
+    let mut white = End::new(StoneColor::White, simultaneous(&[]));
+    play_turn(&mut black, &mut white, [Some((0, 1, 0)), Some((1, 1, 0))]);
+    play_turn(&mut black, &mut white, [None, Some((1, 0, 1))]);
+    let expected = simultaneous(&[[(0, 1, 0), (1, 1, 0)]]);
+    for end in [&black, &white] {
+        let rules = end.session.read();
+        assert_eq!(rules.moves().len(), 4);
+        assert!(rules.moves()[2].is_pass());
+        assert_eq!(rules.board().get_stone((1, 0, 1)), Some(StoneColor::White));
+        assert_eq!(rules.previous_boards().nth(1).map(|board| board.hash()), Some(expected.board().hash()));
+    }
+}
+
+// The whole turn must not repeat a position: both ends find White's stone
+// would, and play it as a pass
+#[test]
+fn a_turn_repeating_a_position_plays_white_as_a_pass() {
+    let turns = [[(0, 1, 0), (1, 1, 0)], [(0, 0, 1), (1, 0, 1)], [(0, 1, 1), (1, 1, 1)]];
+    let mut black = End::new(StoneColor::Black, simultaneous(&turns));
+    let mut white = End::new(StoneColor::White, simultaneous(&turns));
+    play_turn(&mut black, &mut white, [Some((0, 0, 0)), Some((1, 0, 0))]);
+    for end in [&black, &white] {
+        let rules = end.session.read();
+        assert!(rules.moves().last().expect("the turn was played").is_pass());
+        assert_eq!(rules.board().stones_of(StoneColor::Black).count(), 4);
+        assert_eq!(rules.board().stones_of(StoneColor::White).count(), 3);
+    }
+}
+
+#[test]
+fn reveals_must_match_their_commitment() {
+    let mut black = End::new(StoneColor::Black, simultaneous(&[]));
+    let mut white = End::new(StoneColor::White, simultaneous(&[]));
+    let commitment = black.commit(Some((0, 0, 0)));
+    white.receive(commitment);
+    let lines = white.commit(Some((1, 1, 1)));
+    let reveal = black.receive(lines)[0];
+
+    let RevealMessage::Reveal { play, salt } = reveal else {
+        panic!("a reveal was sent");
+    };
+    let forged = RevealMessage::Reveal { play: Some((1, 0, 0)), salt };
+    assert_eq!(white.turn.receive(forged, &white.session), Err(SimultaneousError::Mismatch));
+    let resalted = RevealMessage::Reveal { play, salt: salt.wrapping_add(1) };
+    assert_eq!(white.turn.receive(resalted, &white.session), Err(SimultaneousError::Mismatch));
+    assert!(white.session.read().moves().is_empty());
+    assert_eq!(white.turn.receive(reveal, &white.session), Ok(Vec::new()));
+    assert_eq!(white.session.read().moves().len(), 2);
+}
+
+#[test]
+fn each_side_commits_and_reveals_once_a_turn() {
+    let mut black = End::new(StoneColor::Black, simultaneous(&[]));
+    let mut white = End::new(StoneColor::White, simultaneous(&[]));
+    let reveal = RevealMessage::Reveal { play: None, salt: 7 };
+    assert_eq!(white.turn.receive(reveal, &white.session), Err(SimultaneousError::NotCommitted));
+
+    let commitment = black.commit(Some((0, 0, 0)));
+    assert_eq!(black.turn.commit(None, &black.session), Err(SimultaneousError::AlreadyCommitted));
+    white.receive(commitment.clone());
+    assert_eq!(white.turn.receive(commitment[0], &white.session), Err(SimultaneousError::AlreadyCommitted));
+}
+
+#[test]
+fn illegal_plays_are_refused_before_committing() {
+    let mut black = End::new(StoneColor::Black, simultaneous(&[[(0, 0, 0), (1, 1, 1)]]));
+    assert_eq!(black.turn.commit(Some((1, 1, 1)), &black.session), Err(SimultaneousError::Illegal(MoveError::Occupied)));
+    assert!(!black.turn.has_committed());
+    assert!(matches!(RevealMessage::parse("reveal c1-1 5", 2), Err(SimultaneousError::Unreadable(_))));
+    assert!(matches!(RevealMessage::parse("commit xyz", 2), Err(SimultaneousError::Unreadable(_))));
+}
@@ -0,0 +1,167 @@
+// Profiles, preferences, engine configurations and calibration written by
+// earlier builds must keep loading as their formats move on: one fixture for
+// each version, and newer text refused
+use go3d::achievements::Achievement;
+use go3d::calibration::{self, Calibration, Rank};
+use go3d::engine_configs::{self, EngineConfig, EngineConfigError};
+use go3d::preferences::{self, Preferences, WindowGeometry};
+use go3d::profiles::{self, Profiles};
+use go3d::settings::{GuideStyle, ThemeKind};
+use go3d::storage::{Backend, MemoryBackend, StorageError, VersionError};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn backend(slot: &str, data: &str) -> MemoryBackend {
+    let mut backend = MemoryBackend::default();
+    backend.save(slot, data).expect("memory storage cannot fail");
+    backend
+}
+
+fn profiles(data: &str) -> Result<Profiles, StorageError> {
+    Profiles::load(Box::new(backend("profiles", data)))
+}
+
+// Version 1, before achievements: six fields a line
+#[test]
+fn profiles_version_1_without_achievements() {
+    let profiles = profiles("Ann\t1516.0\t1\t0\t0\t42").expect("version 1 loads");
+    let ann = profiles.get("Ann").expect("Ann was read");
+    assert_eq!((ann.rating, ann.wins, ann.total_moves), (1516.0, 1, 42));
+    assert!(ann.achievements.is_empty());
+}
+
+// Version 1 once achievements came in, still without a version line
+#[test]
+fn profiles_version_1_with_achievements() {
+    let profiles = profiles("Ann\t1516.0\t1\t0\t0\t42\tfirst-capture,veteran\nBob\t1484.0\t0\t1\t0\t42\t").expect("version 1 loads");
+    let ann = profiles.get("Ann").expect("Ann was read");
+    assert_eq!(ann.achievements.iter().copied().collect::<Vec<_>>(), vec![Achievement::FirstCapture, Achievement::Veteran]);
+    assert!(profiles.get("Bob").expect("Bob was read").achievements.is_empty());
+}
+
+#[test]
+fn profiles_version_2() {
+    let loaded = profiles("version=2\nAnn\t1516.0\t1\t0\t0\t42\tveteran").expect("version 2 loads");
+    assert_eq!(loaded.get("Ann").expect("Ann was read").achievements.len(), 1);
+    // Six fields was only ever version 1
+    let loaded = profiles("version=2\nAnn\t1516.0\t1\t0\t0\t42").expect("version 2 loads");
+    assert!(loaded.get("Ann").is_none());
+}
+
+// Profiles keep their backend, so the test keeps a handle on it too
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<MemoryBackend>>);
+
+impl Backend for Shared {
+    fn list(&self) -> Result<Vec<String>, StorageError> {
+        self.0.borrow().list()
+    }
+
+    fn load(&self, slot: &str) -> Result<Option<String>, StorageError> {
+        self.0.borrow().load(slot)
+    }
+
+    fn save(&mut self, slot: &str, data: &str) -> Result<(), StorageError> {
+        self.0.borrow_mut().save(slot, data)
+    }
+
+    fn delete(&mut self, slot: &str) -> Result<(), StorageError> {
+        self.0.borrow_mut().delete(slot)
+    }
+}
+
+#[test]
+fn profiles_are_saved_at_the_current_version() {
+    let shared = Shared::default();
+    let mut profiles = Profiles::load(Box::new(shared.clone())).expect("nothing saved loads");
+    profiles.unlock("Ann", Achievement::Veteran).expect("a valid name");
+    let text = shared.load("profiles").expect("memory storage cannot fail").expect("just saved");
+    assert_eq!(text, format!("version={}\nAnn\t1500.0\t0\t0\t0\t0\tveteran", profiles::FORMAT_VERSION));
+    let reloaded = Profiles::load(Box::new(shared)).expect("loads");
+    assert_eq!(profiles.get("Ann"), reloaded.get("Ann"));
+}
+
+#[test]
+fn newer_profiles_are_refused() {
+    let error = profiles("version=3\nAnn\t1516.0\t1\t0\t0\t42\t\tgold").err().expect("version 3 is refused");
+    assert!(matches!(error, StorageError::Version { error: VersionError::Newer { found: 3, current: 2 }, .. }));
+    assert!(matches!(profiles("version=two").err(), Some(StorageError::Version { error: VersionError::Invalid(_), .. })));
+}
+
+#[test]
+fn preferences_version_1() {
+    let preferences = Preferences::parse("window=800x600@10,20\npanels=off\nsize=5\ntheme=color-blind\nguide=crosshair\nwheel=2");
+    assert_eq!(preferences.window, Some(WindowGeometry { width: 800, height: 600, position: Some((10, 20)) }));
+    assert!(!preferences.panels_visible);
+    assert_eq!(preferences.board_size, Some(5));
+    assert_eq!(preferences.theme, Some(ThemeKind::ColorBlind));
+    assert_eq!(preferences.guide_style, Some(GuideStyle::Crosshair));
+    assert_eq!(preferences.wheel_sensitivity, Some(2.0));
+}
+
+#[test]
+fn preferences_version_2() {
+    let preferences = Preferences::parse("version=2\npanels=off\nsize=5");
+    assert!(!preferences.panels_visible);
+    assert_eq!(preferences.board_size, Some(5));
+    assert!(Preferences::default().to_text().starts_with(&format!("version={}\n", preferences::FORMAT_VERSION)));
+    assert_eq!(Preferences::parse(&preferences.to_text()), preferences);
+}
+
+#[test]
+fn newer_preferences_give_the_defaults() {
+    assert_eq!(Preferences::parse("version=3\npanels=off\nsize=5"), Preferences::default());
+}
+
+#[test]
+fn engine_configs_version_1() {
+    let config = EngineConfig::parse("# tuned for 5³\nname=deep\nengine=mcts\nplayouts=5000\nthreads=4", "ignored").expect("version 1 reads");
+    assert_eq!((config.name.as_str(), config.engine(), config.playouts, config.threads), ("deep", "mcts", 5000, 4));
+}
+
+#[test]
+fn engine_configs_version_2() {
+    let text = "# tuned for 5³\nversion=2\nname=deep\nengine=greedy\n";
+    let config = EngineConfig::parse(text, "ignored").expect("version 2 reads");
+    assert_eq!((config.name.as_str(), config.engine()), ("deep", "greedy"));
+    assert!(config.to_text().starts_with(&format!("version={}\n", engine_configs::FORMAT_VERSION)));
+    assert_eq!(EngineConfig::parse(&config.to_text(), "ignored"), Ok(config));
+    // Lines are still numbered as in the text
+    assert_eq!(
+        EngineConfig::parse("version=2\nname=deep\nplayouts=lots", "ignored"),
+        Err(EngineConfigError::BadLine(3, "playouts=lots".to_string()))
+    );
+}
+
+#[test]
+fn newer_engine_configs_are_refused() {
+    assert_eq!(
+        EngineConfig::parse("version=3\nname=deep\nengine=mcts\nvalue_net=big", "ignored"),
+        Err(EngineConfigError::Version(VersionError::Newer { found: 3, current: 2 }))
+    );
+}
+
+#[test]
+fn calibration_version_1() {
+    let calibration = Calibration::load(&backend("calibration", "1@5=24k\n2@5=2d"));
+    assert_eq!(calibration.rank(1, 5), Some(("24k".parse::<Rank>().unwrap(), 5)));
+    assert_eq!(calibration.rank(2, 5), Some(("2d".parse::<Rank>().unwrap(), 5)));
+}
+
+#[test]
+fn calibration_version_2() {
+    let calibration = Calibration::load(&backend("calibration", "version=2\n1@5=24k"));
+    assert_eq!(calibration.rank(1, 5), Some(("24k".parse::<Rank>().unwrap(), 5)));
+
+    let mut saved = MemoryBackend::default();
+    calibration.save(&mut saved).expect("memory storage cannot fail");
+    let text = saved.load("calibration").expect("memory storage cannot fail").expect("just saved");
+    assert_eq!(text, format!("version={}\n1@5=24k", calibration::FORMAT_VERSION));
+    assert_eq!(Calibration::load(&saved), calibration);
+}
+
+#[test]
+fn newer_calibration_leaves_the_levels_unranked() {
+    let calibration = Calibration::load(&backend("calibration", "version=3\n1@5=24k"));
+    assert_eq!(calibration, Calibration::default());
+}
@@ -0,0 +1,86 @@
+// Teaching games: the teacher places for anyone, rewinds and marks up, while
+// students play only their own color on their turn
+use go3d::game::{GameRules, GameSession, Mark, MoveError, StoneColor};
+use go3d::teaching::{Role, TeachingError, TeachingMessage};
+
+fn message(line: &str) -> TeachingMessage {
+    TeachingMessage::parse(line, 3).expect("a valid message")
+}
+
+fn colors(session: &GameSession) -> Vec<(StoneColor, bool)> {
+    session.read().moves().iter().map(|mv| (mv.color, mv.is_pass())).collect()
+}
+
+#[test]
+fn messages_survive_being_sent() {
+    for line in ["place W b2-3", "rewind 3", "mark c3-1 triangle", "mark a1-1 B", "unmark c3-1"] {
+        assert_eq!(message(line).to_string(), line);
+    }
+    assert!(matches!(TeachingMessage::parse("place W d4-2", 3), Err(TeachingError::Unreadable(_))));
+    assert!(matches!(TeachingMessage::parse("mark a1-1 circle", 3), Err(TeachingError::Unreadable(_))));
+}
+
+#[test]
+fn students_play_only_their_own_color_on_their_turn() {
+    let session = GameSession::new(GameRules::new(3));
+    let white = Role::Student(StoneColor::White);
+    assert_eq!(message("place W a1-1").apply(white, &session), Err(TeachingError::WrongColor(StoneColor::White)));
+    assert_eq!(message("place B a1-1").apply(white, &session), Err(TeachingError::WrongColor(StoneColor::White)));
+    assert_eq!(message("place B a1-1").apply(Role::Student(StoneColor::Black), &session), Ok(()));
+    assert_eq!(message("place W a1-1").apply(white, &session), Err(TeachingError::Illegal(MoveError::Occupied)));
+    assert_eq!(message("place W b1-1").apply(white, &session), Ok(()));
+
+    assert_eq!(message("rewind 1").apply(white, &session), Err(TeachingError::TeacherOnly));
+    assert_eq!(message("mark a1-1 square").apply(white, &session), Err(TeachingError::TeacherOnly));
+    assert_eq!(session.read().moves().len(), 2);
+    assert!(session.annotations().is_empty());
+}
+
+// Everyone due before the placed color passes, however many players there are
+#[test]
+fn the_teacher_places_out_of_turn_after_passes() {
+    let mut rules = GameRules::new(3);
+    rules.set_players(3);
+    let session = GameSession::new(rules);
+    assert_eq!(message("place R a1-1").apply(Role::Teacher, &session), Ok(()));
+    assert_eq!(colors(&session), vec![(StoneColor::Black, true), (StoneColor::White, true), (StoneColor::Red, false)]);
+    assert_eq!(session.read().board().get_stone((0, 0, 0)), Some(StoneColor::Red));
+    assert_eq!(session.read().current_player(), StoneColor::Black);
+
+    assert_eq!(message("place G b1-1").apply(Role::Teacher, &session), Err(TeachingError::NotPlaying(StoneColor::Green)));
+    assert_eq!(session.read().moves().len(), 3);
+}
+
+// A pass put in after a real one would end a two-player game, so the teacher
+// is refused rather than the game stopped
+#[test]
+fn inserted_passes_never_end_the_game() {
+    let session = GameSession::new(GameRules::new(3));
+    session.pass();
+    assert_eq!(message("place B a1-1").apply(Role::Teacher, &session), Err(TeachingError::EndsGame));
+    assert_eq!(session.read().moves().len(), 1);
+    assert!(!session.read().is_over());
+
+    // An illegal point leaves the game as it was, with no passes put in
+    session.make_move(0, 0, 0);
+    assert_eq!(message("place W a1-1").apply(Role::Teacher, &session), Err(TeachingError::Illegal(MoveError::Occupied)));
+    assert_eq!(session.read().moves().len(), 2);
+    assert_eq!(message("place W b1-1").apply(Role::Teacher, &session), Ok(()));
+    assert_eq!(colors(&session)[2..], [(StoneColor::Black, true), (StoneColor::White, false)]);
+}
+
+#[test]
+fn the_teacher_rewinds_and_marks_up() {
+    let session = GameSession::new(GameRules::new(3));
+    session.make_move(0, 0, 0);
+    session.make_move(1, 0, 0);
+    assert_eq!(message("rewind 3").apply(Role::Teacher, &session), Err(TeachingError::NothingToRewind));
+    assert_eq!(message("rewind 1").apply(Role::Teacher, &session), Ok(()));
+    assert_eq!(session.read().moves().len(), 1);
+
+    assert_eq!(message("mark c3-1 triangle").apply(Role::Teacher, &session), Ok(()));
+    let marks: Vec<_> = session.annotations().at(1).map(|annotation| (annotation.position, annotation.mark)).collect();
+    assert_eq!(marks, vec![((2, 2, 0), Mark::Triangle)]);
+    assert_eq!(message("unmark c3-1").apply(Role::Teacher, &session), Ok(()));
+    assert!(session.annotations().is_empty());
+}
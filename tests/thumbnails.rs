@@ -0,0 +1,23 @@
+// CPU-drawn board thumbnails
+mod common;
+
+use common::*;
+use go3d::game::GameRules;
+use go3d::render::thumbnail;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // Thumbnails come out the size asked for, and show something more than
+    // the empty board once a stone is on it
+    #[test]
+    fn thumbnails_show_the_stones(size in 2usize..=5, pixels in 16u32..96, steps in prop::collection::vec(step(), 0..30)) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let picture = thumbnail(rules.board(), pixels);
+        prop_assert_eq!((picture.width(), picture.height()), (pixels, pixels));
+        let empty = thumbnail(GameRules::new(size).board(), pixels);
+        prop_assert_eq!(picture != empty, rules.board().get_all_stones().next().is_some());
+    }
+}
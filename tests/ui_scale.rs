@@ -0,0 +1,52 @@
+// The adjustable UI and text scale
+use go3d::preferences::Preferences;
+use go3d::settings::UiScale;
+use go3d::storage::MemoryBackend;
+
+#[test]
+fn ui_scales_are_clamped_into_range() {
+    assert_eq!(UiScale::new(0.5).factor(), UiScale::MIN);
+    assert_eq!(UiScale::new(0.75).factor(), 0.75);
+    assert_eq!(UiScale::new(2.0).factor(), 2.0);
+    assert_eq!(UiScale::new(3.0).factor(), UiScale::MAX);
+    assert_eq!(UiScale::new(f32::NAN).factor(), 1.0);
+    assert_eq!(UiScale::new(f32::INFINITY).factor(), 1.0);
+}
+
+// Steps land on the STEP grid and stop at either end
+#[test]
+fn ui_scales_step_on_the_grid() {
+    assert_eq!(UiScale::default().stepped(1).factor(), 1.25);
+    assert_eq!(UiScale::default().stepped(-1).factor(), 0.75);
+    assert_eq!(UiScale::new(1.1).stepped(0).factor(), 1.0);
+    assert_eq!(UiScale::new(1.9).stepped(1).factor(), UiScale::MAX);
+    assert_eq!(UiScale::new(UiScale::MIN).stepped(-1).factor(), UiScale::MIN);
+    assert_eq!(UiScale::new(UiScale::MAX).stepped(12).factor(), UiScale::MAX);
+    assert_eq!(UiScale::new(UiScale::MAX).stepped(-5).factor(), UiScale::MIN);
+}
+
+#[test]
+fn ui_scales_are_read_as_factors_or_percentages() {
+    assert_eq!("0.75".parse::<UiScale>(), Ok(UiScale::new(0.75)));
+    assert_eq!("200%".parse::<UiScale>(), Ok(UiScale::new(2.0)));
+    assert_eq!(" 125 % ".parse::<UiScale>(), Ok(UiScale::new(1.25)));
+    assert!("0.7".parse::<UiScale>().is_err());
+    assert!("201%".parse::<UiScale>().is_err());
+    assert!("large".parse::<UiScale>().is_err());
+    for factor in [UiScale::MIN, 1.0, UiScale::MAX] {
+        let scale = UiScale::new(factor);
+        assert_eq!(scale.to_string().parse::<UiScale>(), Ok(scale));
+    }
+}
+
+// The scale in use comes back from the saved preferences
+#[test]
+fn ui_scales_are_kept_in_the_preferences() {
+    for factor in [UiScale::MIN, 1.5, UiScale::MAX] {
+        let scale = UiScale::new(factor);
+        let mut backend = MemoryBackend::default();
+        let preferences = Preferences { ui_scale: Some(scale), ..Preferences::default() };
+        preferences.save(&mut backend).expect("memory storage cannot fail");
+        assert_eq!(Preferences::load(&backend).ui_scale, Some(scale));
+    }
+}
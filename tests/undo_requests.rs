@@ -0,0 +1,46 @@
+// Taking moves back by agreement
+mod common;
+
+use common::*;
+use go3d::game::{GameRules, GameSession, UndoMessage, UndoRequest};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // A granted undo goes back to just before the asker's last move, and the
+    // request survives being sent as a message
+    #[test]
+    fn undo_requests_take_back_the_askers_move(size in 2usize..=4, steps in prop::collection::vec(step(), 0..80), own in step(), reply in proptest::option::of(step())) {
+        let mut rules = GameRules::new(size);
+        play_random_game(&mut rules, &steps)?;
+        let color = rules.current_player();
+        let before = position_hash(&rules);
+        let moves = rules.moves().len();
+        play_random_game(&mut rules, &[own])?;
+        play_random_game(&mut rules, reply.as_slice())?;
+
+        let request = UndoRequest::new(color, &rules).expect("the asker has moved");
+        prop_assert_eq!(request.moves, rules.moves().len() - moves);
+        let message = UndoMessage::Request(request);
+        prop_assert_eq!(message.to_string().parse::<UndoMessage>(), Ok(message));
+
+        let session = GameSession::new(rules);
+        prop_assert!(request.grant(&session));
+        prop_assert_eq!(session.read().moves().len(), moves);
+        prop_assert_eq!(session.read().current_player(), color);
+        prop_assert_eq!(position_hash(&session.read()), before);
+    }
+}
+
+// A request made before another move no longer fits, and takes nothing back
+#[test]
+fn stale_undo_requests_are_refused() {
+    let session = GameSession::new(GameRules::new(3));
+    let black = session.read().current_player();
+    assert!(session.make_move(0, 0, 0));
+    let request = UndoRequest::new(black, &session.read()).expect("black has moved");
+    assert!(session.make_move(1, 1, 1));
+    assert!(!request.grant(&session));
+    assert_eq!(session.read().moves().len(), 2);
+}